@@ -3,7 +3,7 @@ use std::sync::{Arc, Mutex, atomic::AtomicBool};
 use strata_core::engine::LLMEngine;
 use strata_core::memory::SessionMemory;
 
-use crate::plugin::PluginBackend;
+use crate::plugin::RuntimeBackend;
 
 /// Global application state.
 pub struct AppState {
@@ -13,9 +13,9 @@ pub struct AppState {
     /// Stop flag holder for in-flight generations.
     pub current_stop: Arc<Mutex<Option<Arc<AtomicBool>>>>,
 
-    /// Persisted engine (owns the PluginBackend + llama session / KV).
+    /// Persisted engine (owns the plugin backend + llama session / KV).
     /// We reuse this across prompts to avoid reloading or re-prefilling.
-    pub engine: Arc<Mutex<Option<LLMEngine<PluginBackend>>>>,
+    pub engine: Arc<Mutex<Option<LLMEngine<RuntimeBackend>>>>,
 }
 
 impl AppState {
@@ -10,20 +10,33 @@ mod service;
 
 use crate::app_state::AppState;
 use std::sync::atomic::Ordering;
-use strata_abi::backend::ChatTurn;
+use strata_abi::backend::{ChatTurn, LoraAdapter};
+use strata_abi::error::{ErrorCode, StrataError};
+use strata_abi::session::{KvCacheType, MemoryReport, SessionParams};
+use strata_core::engine::GenerationStats;
 use tauri::{AppHandle, Emitter, State};
 
+pub(crate) use service::DrainedEngine;
 use service::ensure_engine_for_model;
 
 // ---------------------------
 // Public Tauri commands
 // ---------------------------
+// Commands that reach the runtime plugin fail with a `StrataError`
+// (`{ code, message }`) so the UI can tell e.g. a full context from OOM.
 
 #[tauri::command]
 pub async fn load_system_prompt(app: AppHandle) -> Result<String, String> {
     loader::load_system_prompt_impl(app).await
 }
 
+/// `run_llm` result: the reply and how long it took to produce.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LlmReply {
+    pub text: String,
+    pub stats: Option<GenerationStats>,
+}
+
 // Non-streaming inference
 #[tauri::command]
 pub async fn run_llm(
@@ -32,7 +45,7 @@ pub async fn run_llm(
     model_id: Option<String>,
     app: AppHandle,
     state: State<'_, AppState>,
-) -> Result<String, String> {
+) -> Result<LlmReply, StrataError> {
     {
         let mut mem = state.memory.lock().unwrap();
         mem.push_user(prompt.clone());
@@ -47,7 +60,7 @@ pub async fn run_llm(
     };
     let model_id2 = model_id.clone();
 
-    let reply = tauri::async_runtime::spawn_blocking(move || -> Result<String, String> {
+    let reply = tauri::async_runtime::spawn_blocking(move || -> Result<LlmReply, StrataError> {
         ensure_engine_for_model(&app2, &state2, model_id2)?;
 
         let mut guard = state2.engine.lock().unwrap();
@@ -63,16 +76,19 @@ pub async fn run_llm(
             mem.turns().to_vec()
         };
 
-        let out = engine.infer_chat(&turns)?;
+        let text = engine.infer_chat(&turns)?;
         *state2.current_stop.lock().unwrap() = None;
-        Ok(out)
+        Ok(LlmReply {
+            text,
+            stats: engine.last_generation_stats().cloned(),
+        })
     })
     .await
     .map_err(|e| format!("join error: {e}"))??;
 
     {
         let mut mem = state.memory.lock().unwrap();
-        mem.push_assistant(reply.clone());
+        mem.push_assistant(reply.text.clone());
     }
 
     Ok(reply)
@@ -84,9 +100,10 @@ pub async fn run_llm_stream(
     prompt: String,
     _tts: bool,
     model_id: Option<String>,
+    thinking: Option<bool>,
     app: AppHandle,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<(), StrataError> {
     {
         let mut mem = state.memory.lock().unwrap();
         mem.push_user(prompt.clone());
@@ -100,7 +117,7 @@ pub async fn run_llm_stream(
     };
     let model_id2 = model_id.clone();
 
-    tauri::async_runtime::spawn_blocking(move || -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<String, StrataError> {
         ensure_engine_for_model(&app2, &state2, model_id2)?;
 
         let mut guard = state2.engine.lock().unwrap();
@@ -116,19 +133,32 @@ pub async fn run_llm_stream(
             mem.turns().to_vec()
         };
 
-        let final_text = engine.infer_chat_stream(&turns, |delta| {
-            let _ = app2.emit("llm-stream", serde_json::json!({ "delta": delta }));
-        })?;
+        let reply = engine.infer_chat_stream_split(
+            &turns,
+            thinking,
+            |delta| {
+                let _ = app2.emit("llm-reasoning", serde_json::json!({ "delta": delta }));
+            },
+            |delta| {
+                let _ = app2.emit("llm-stream", serde_json::json!({ "delta": delta }));
+            },
+        )?;
 
         *state2.current_stop.lock().unwrap() = None;
 
         {
             let mut mem = state2.memory.lock().unwrap();
-            mem.push_assistant(final_text.clone());
+            mem.push_assistant(reply.history_text(engine.keep_reasoning()));
         }
 
-        let _ = app2.emit("llm-complete", serde_json::json!({ "text": final_text }));
-        Ok(final_text)
+        let _ = app2.emit(
+            "llm-complete",
+            serde_json::json!({ "text": reply.answer, "reasoning": reply.reasoning }),
+        );
+        if let Some(stats) = engine.last_generation_stats() {
+            let _ = app2.emit("llm-stats", stats);
+        }
+        Ok(reply.answer)
     })
     .await
     .map_err(|e| format!("join error: {e}"))??;
@@ -154,7 +184,7 @@ pub fn cancel_generation(state: State<'_, AppState>) -> Result<(), String> {
 /// - Does not change the selected model.
 /// - Runs on a blocking worker so the UI stays snappy.
 #[tauri::command]
-pub async fn preload_engine(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+pub async fn preload_engine(app: AppHandle, state: State<'_, AppState>) -> Result<(), StrataError> {
     let app2 = app.clone();
     let state2 = AppState {
         memory: std::sync::Arc::clone(&state.memory),
@@ -175,6 +205,142 @@ pub async fn preload_engine(app: AppHandle, state: State<'_, AppState>) -> Resul
     Ok(())
 }
 
+// ---------------------------
+// LoRA adapters
+// ---------------------------
+
+/// Adapters loaded for `model_id`, with their scale (empty unless it is the
+/// active model and its engine exists).
+#[tauri::command]
+pub fn get_lora_adapters(
+    state: State<'_, AppState>,
+    model_id: String,
+) -> Result<Vec<LoraAdapter>, String> {
+    if crate::model::get_current_model().as_deref() != Some(model_id.as_str()) {
+        return Ok(Vec::new());
+    }
+    Ok(state
+        .engine
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|engine| engine.lora_adapters())
+        .unwrap_or_default())
+}
+
+/// Attach the adapter at `path` to `model_id` with `scale`, or detach it when
+/// `scale` is null. `model_id` must be the active model. Base weights stay
+/// resident, and the choice is re-applied whenever the engine is rebuilt for
+/// that model; returns the updated adapter list.
+#[tauri::command]
+pub async fn set_lora_adapter(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    model_id: String,
+    path: String,
+    scale: Option<f32>,
+) -> Result<Vec<LoraAdapter>, StrataError> {
+    let active = crate::model::get_current_model();
+    if active.as_deref() != Some(model_id.as_str()) {
+        return Err(StrataError::new(
+            ErrorCode::InvalidArg,
+            format!(
+                "adapters can only be attached to the active model ({})",
+                active.as_deref().unwrap_or("none")
+            ),
+        ));
+    }
+
+    let state2 = AppState {
+        memory: std::sync::Arc::clone(&state.memory),
+        current_stop: std::sync::Arc::clone(&state.current_stop),
+        engine: std::sync::Arc::clone(&state.engine),
+    };
+
+    tauri::async_runtime::spawn_blocking(move || -> Result<Vec<LoraAdapter>, StrataError> {
+        ensure_engine_for_model(&app, &state2, Some(model_id.clone()))?;
+
+        let mut guard = state2.engine.lock().unwrap();
+        let engine = guard.as_mut().expect("engine initialized");
+        let id = engine.load_lora(&path)?;
+        match scale {
+            Some(scale) => engine.set_lora(id, scale)?,
+            None => engine.remove_lora(id)?,
+        }
+        service::remember_lora(&model_id, &path, scale);
+        Ok(engine.lora_adapters())
+    })
+    .await
+    .map_err(|e| format!("join error: {e}"))?
+}
+
+// ---------------------------
+// Session parameters / memory
+// ---------------------------
+
+/// Parameters new sessions are created with.
+#[tauri::command]
+pub fn get_session_params() -> SessionParams {
+    service::session_params()
+}
+
+/// Choose the K/V cache element types (and flash attention) for new sessions,
+/// then rebuild the active engine with them, keeping the conversation,
+/// adapters and control vectors. Rejected combinations leave the current
+/// settings untouched.
+#[tauri::command]
+pub async fn set_kv_cache_type(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    type_k: KvCacheType,
+    type_v: KvCacheType,
+    flash_attn: Option<bool>,
+) -> Result<SessionParams, StrataError> {
+    let mut params = service::session_params();
+    params.type_k = type_k;
+    params.type_v = type_v;
+    params.flash_attn = flash_attn;
+    params
+        .validate()
+        .map_err(|e| StrataError::new(ErrorCode::InvalidArg, e))?;
+
+    let state2 = AppState {
+        memory: std::sync::Arc::clone(&state.memory),
+        current_stop: std::sync::Arc::clone(&state.current_stop),
+        engine: std::sync::Arc::clone(&state.engine),
+    };
+
+    tauri::async_runtime::spawn_blocking(move || -> Result<SessionParams, StrataError> {
+        let previous = service::set_session_params(params.clone());
+        // Same model: keep the conversation and re-apply adapters/steering.
+        let Some(drained) = service::drain_engine(&state2) else {
+            return Ok(params);
+        };
+        if let Err(e) = service::rebuild_engine(&app, &state2, Some(drained.clone())) {
+            service::set_session_params(previous);
+            if let Err(restore) = service::rebuild_engine(&app, &state2, Some(drained)) {
+                log::warn!("failed to restore the engine with the previous params: {restore}");
+            }
+            return Err(e);
+        }
+        Ok(params)
+    })
+    .await
+    .map_err(|e| format!("join error: {e}"))?
+}
+
+/// Bytes held by the active model's weights, KV cache and compute buffers
+/// (null before the engine exists).
+#[tauri::command]
+pub fn get_memory_report(state: State<'_, AppState>) -> Option<MemoryReport> {
+    state
+        .engine
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|engine| engine.memory_report())
+}
+
 // ---------------------------
 // Crate-visible shims
 // ---------------------------
@@ -184,6 +350,26 @@ pub async fn preload_engine(app: AppHandle, state: State<'_, AppState>) -> Resul
 pub(crate) fn reinit_engine_to_current_model(
     app: &tauri::AppHandle,
     state: &crate::app_state::AppState,
-) -> Result<(), String> {
+) -> Result<(), StrataError> {
     service::reinit_engine_to_current_model(app, state)
 }
+
+/// Drop the engine (and its plugin session); see `service::drain_engine`.
+pub(crate) fn drain_engine(state: &crate::app_state::AppState) -> Option<DrainedEngine> {
+    service::drain_engine(state)
+}
+
+/// Re-create the engine for the current model after `drain_engine`,
+/// re-applying what the drained engine carried.
+pub(crate) fn rebuild_engine(
+    app: &tauri::AppHandle,
+    state: &crate::app_state::AppState,
+    drained: Option<DrainedEngine>,
+) -> Result<(), StrataError> {
+    service::rebuild_engine(app, state, drained)
+}
+
+/// Session params the next load will use (for pre-load estimates).
+pub(crate) fn session_params() -> SessionParams {
+    service::session_params()
+}
@@ -1,28 +1,92 @@
 // src-tauri/src/engine/service.rs
 
+use std::collections::HashMap;
+use std::sync::Mutex;
 use std::sync::atomic::Ordering;
 use std::time::{Duration, Instant};
 
 use crate::app_state::AppState;
 use crate::model::{get_model_path, set_current_model};
-use crate::plugin::PluginBackend;
+use crate::plugin::RuntimeBackend;
 
+use once_cell::sync::Lazy;
+use strata_abi::backend::{ControlVector, LLMBackend};
+use strata_abi::error::StrataError;
+use strata_abi::session::SessionParams;
 use strata_core::engine::LLMEngine;
 use tauri::{AppHandle, Emitter};
 
 use super::loader::load_system_prompt_sync;
 
+/// Context parameters (KV cache types etc.) used for every new session.
+static SESSION_PARAMS: Lazy<Mutex<SessionParams>> =
+    Lazy::new(|| Mutex::new(SessionParams::default()));
+
+pub(crate) fn session_params() -> SessionParams {
+    SESSION_PARAMS
+        .lock()
+        .expect("SESSION_PARAMS poisoned")
+        .clone()
+}
+
+/// Replace the session parameters; returns the previous ones.
+pub(crate) fn set_session_params(params: SessionParams) -> SessionParams {
+    let mut slot = SESSION_PARAMS.lock().expect("SESSION_PARAMS poisoned");
+    std::mem::replace(&mut *slot, params)
+}
+
+/// LoRA adapters attached per model id (path, scale), in attach order;
+/// re-applied whenever an engine is built for that model.
+static LORA_ADAPTERS: Lazy<Mutex<HashMap<String, Vec<(String, f32)>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Record that `path` is attached to `model_id` with `scale` (`None`: detached).
+pub(crate) fn remember_lora(model_id: &str, path: &str, scale: Option<f32>) {
+    let mut map = LORA_ADAPTERS.lock().expect("LORA_ADAPTERS poisoned");
+    let adapters = map.entry(model_id.to_string()).or_default();
+    match (adapters.iter_mut().find(|(p, _)| p == path), scale) {
+        (Some(entry), Some(scale)) => entry.1 = scale,
+        (None, Some(scale)) => adapters.push((path.to_string(), scale)),
+        (_, None) => adapters.retain(|(p, _)| p != path),
+    }
+    if adapters.is_empty() {
+        map.remove(model_id);
+    }
+}
+
+/// Attach the adapters remembered for the current model to a fresh engine.
+/// One that no longer loads is logged and skipped.
+fn restore_loras(engine: &mut LLMEngine<RuntimeBackend>) {
+    let Some(id) = crate::model::get_current_model() else {
+        return;
+    };
+    let adapters = LORA_ADAPTERS
+        .lock()
+        .expect("LORA_ADAPTERS poisoned")
+        .get(&id)
+        .cloned()
+        .unwrap_or_default();
+    for (path, scale) in adapters {
+        let attached = engine
+            .load_lora(&path)
+            .and_then(|lora| engine.set_lora(lora, scale));
+        if let Err(e) = attached {
+            log::warn!("failed to restore LoRA adapter {path}: {e}");
+        }
+    }
+}
+
 /// Ensure an engine exists and matches the requested model id.
 /// (kept as-is, used when you pass `model_id` alongside run calls)
 pub(crate) fn ensure_engine_for_model(
     app: &AppHandle,
     state: &AppState,
     requested_model: Option<String>,
-) -> Result<(), String> {
+) -> Result<(), StrataError> {
     if let Some(id) = requested_model.as_ref() {
         if crate::model::get_current_model().as_ref() != Some(id) {
             if let Some(engine) = state.engine.lock().unwrap().as_mut() {
-                eprintln!("🧹 [engine] Model switch detected, clearing KV cache before drop");
+                log::debug!("model switch detected; clearing KV cache before drop");
                 engine.clear_kv_cache();
             }
             *state.engine.lock().unwrap() = None;
@@ -34,9 +98,12 @@ pub(crate) fn ensure_engine_for_model(
     let mut slot = state.engine.lock().unwrap();
     if slot.is_none() {
         let model_path = get_model_path(app)?;
-        let backend = PluginBackend::load(&model_path)?;
+        let mut backend = load_backend(app, &model_path)?;
+        load_projector_if_present(&mut backend, &model_path);
         let system = load_system_prompt_sync(app);
-        let engine = LLMEngine::with_auto(backend, system);
+        let mut engine = LLMEngine::with_auto(backend, system);
+        apply_current_prompt_config(app, &mut engine)?;
+        restore_loras(&mut engine);
         *slot = Some(engine);
     }
     Ok(())
@@ -51,25 +118,9 @@ pub(crate) fn ensure_engine_for_model(
 pub(crate) fn reinit_engine_to_current_model(
     app: &tauri::AppHandle,
     state: &crate::app_state::AppState,
-) -> Result<(), String> {
-    // 1) stop any in-flight gen
-    if let Some(flag) = state.current_stop.lock().unwrap().as_ref() {
-        flag.store(true, std::sync::atomic::Ordering::SeqCst);
-    }
-
-    // 2) check if we had an engine; drop it if so
-    let had_engine = {
-        let mut eng_slot = state.engine.lock().unwrap();
-        let had = eng_slot.is_some();
-        if let Some(engine) = eng_slot.as_mut() {
-            eprintln!("🧹 [engine] Clearing KV before engine drop");
-            engine.clear_kv_cache();
-        }
-        let old = eng_slot.take();
-        drop(eng_slot);
-        drop(old);
-        had
-    };
+) -> Result<(), StrataError> {
+    // 1) stop any in-flight gen, 2) drop the engine if we had one
+    let had_engine = drain_engine(state).is_some();
 
     // 3) reset session memory either way
     {
@@ -78,14 +129,9 @@ pub(crate) fn reinit_engine_to_current_model(
     }
 
     // 4) only build a fresh engine if we previously had one
+    // (the model may have changed, so nothing of the old one carries over)
     if had_engine {
-        let model_path = crate::model::get_model_path(app)?;
-        let backend = crate::plugin::PluginBackend::load(&model_path)?;
-        let system = super::loader::load_system_prompt_sync(app);
-        let engine = strata_core::engine::LLMEngine::with_auto(backend, system);
-
-        let mut eng_slot = state.engine.lock().unwrap();
-        *eng_slot = Some(engine);
+        rebuild_engine(app, state, None)?;
     }
 
     // 5) notify UI either way
@@ -98,3 +144,90 @@ pub(crate) fn reinit_engine_to_current_model(
 
     Ok(())
 }
+
+/// Engine state a rebuild for the same model re-applies (LoRA adapters are
+/// kept per model; see `remember_lora`).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DrainedEngine {
+    control_vectors: Vec<ControlVector>,
+}
+
+/// Stop any in-flight generation and drop the engine (destroying its plugin
+/// session). Returns what it carried, or `None` if there was none.
+pub(crate) fn drain_engine(state: &crate::app_state::AppState) -> Option<DrainedEngine> {
+    if let Some(flag) = state.current_stop.lock().unwrap().as_ref() {
+        flag.store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    let mut eng_slot = state.engine.lock().unwrap();
+    let drained = eng_slot.as_mut().map(|engine| {
+        log::debug!("clearing KV before engine drop");
+        engine.clear_kv_cache();
+        DrainedEngine {
+            control_vectors: engine.control_vectors().to_vec(),
+        }
+    });
+    let old = eng_slot.take();
+    drop(eng_slot);
+    drop(old);
+    drained
+}
+
+/// Build a fresh engine for the current model into the (empty) slot,
+/// re-applying `drained` state from an engine of the same model.
+pub(crate) fn rebuild_engine(
+    app: &tauri::AppHandle,
+    state: &crate::app_state::AppState,
+    drained: Option<DrainedEngine>,
+) -> Result<(), StrataError> {
+    let model_path = crate::model::get_model_path(app)?;
+    let mut backend = load_backend(app, &model_path)?;
+    load_projector_if_present(&mut backend, &model_path);
+    let system = super::loader::load_system_prompt_sync(app);
+    let mut engine = strata_core::engine::LLMEngine::with_auto(backend, system);
+    apply_current_prompt_config(app, &mut engine)?;
+    restore_loras(&mut engine);
+    if let Some(drained) = drained {
+        if !drained.control_vectors.is_empty() {
+            engine.set_control_vectors(drained.control_vectors)?;
+        }
+    }
+
+    let mut eng_slot = state.engine.lock().unwrap();
+    *eng_slot = Some(engine);
+    Ok(())
+}
+
+/// Open a session on the plugin pinned for the current model, else the one
+/// that claims the file.
+fn load_backend(
+    app: &AppHandle,
+    model_path: &std::path::Path,
+) -> Result<RuntimeBackend, StrataError> {
+    let pinned = crate::model::get_current_model()
+        .and_then(|id| crate::model::get_prompt_config(app, &id).ok())
+        .and_then(|cfg| cfg.plugin_id);
+    RuntimeBackend::load_routed(model_path, &session_params(), pinned.as_deref())
+}
+
+/// Attach a sibling mmproj so the model can take images; text chat still works if it fails.
+fn load_projector_if_present(backend: &mut RuntimeBackend, model_path: &std::path::Path) {
+    let Some(projector) = crate::model::find_projector(model_path) else {
+        return;
+    };
+    if let Err(e) = backend.load_projector(&projector) {
+        log::warn!("failed to load projector {}: {e}", projector.display());
+    }
+}
+
+/// Apply the user's per-model prompt settings (formatter / template override), if any.
+fn apply_current_prompt_config(
+    app: &AppHandle,
+    engine: &mut LLMEngine<RuntimeBackend>,
+) -> Result<(), StrataError> {
+    let Some(id) = crate::model::get_current_model() else {
+        return Ok(());
+    };
+    let cfg = crate::model::get_prompt_config(app, &id)?;
+    Ok(crate::model::apply_prompt_config(engine, &cfg)?)
+}
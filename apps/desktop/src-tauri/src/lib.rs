@@ -3,6 +3,7 @@
 
 mod app_state;
 mod engine;
+mod logging;
 mod metadata;
 mod model;
 mod plugin;
@@ -17,6 +18,8 @@ use strata_hwprof::{hwprof_profile_path, validate_or_redetect};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    logging::init();
+
     tauri::Builder::default()
         .manage(AppState::new())
         .manage(MetaIndexer::new())
@@ -25,13 +28,29 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         // setup (intentionally minimal; UI stays snappy)
         .setup(|app| {
+            // GGUF/safetensors/ONNX headers are read in-process; scraping never loads the llama plugin.
+            strata_core::metadata::register_backend_metadata_provider(Box::new(
+                strata_core::metadata::GgufMetadataProvider,
+            ));
+            strata_core::metadata::register_backend_metadata_provider(Box::new(
+                strata_core::metadata::SafetensorsMetadataProvider,
+            ));
+            strata_core::metadata::register_backend_metadata_provider(Box::new(
+                strata_core::metadata::OnnxMetadataProvider,
+            ));
+            // C-ABI metadata plugins slot in by priority; load them before the indexer can run.
+            let plugins = strata_hwprof::plugins_dir();
+            if let Err(e) = strata_core::metadata::load_metadata_plugins(&plugins) {
+                log::error!("loading metadata plugins from {} failed: {e}", plugins.display());
+            }
+
             // ✅ kick off hardware detection/cache in the background
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn_blocking(move || {
                 match validate_or_redetect() {
                     Ok(profile) => {
-                        eprintln!(
-                            "[hwprof] ready: {} | arch={} | threads={} | backends: cpu={} cuda={} rocm={} vulkan={} metal={}",
+                        log::info!(
+                            "hwprof ready: {} | arch={} | threads={} | backends: cpu={} cuda={} rocm={} vulkan={} metal={}",
                             profile.cpu.brand,
                             profile.arch,
                             profile.cpu.threads,
@@ -41,11 +60,11 @@ pub fn run() {
                             profile.backends.vulkan,
                             profile.backends.metal
                         );
-                        eprintln!("[hwprof] cache: {}", hwprof_profile_path().display());
+                        log::info!("hwprof cache: {}", hwprof_profile_path().display());
                         // keep your existing frontend listener happy
                         let _ = app_handle.emit("strata://hwprofile", &profile);
                     }
-                    Err(e) => eprintln!("[hwprof] detection failed: {e:?}"),
+                    Err(e) => log::warn!("hwprof detection failed: {e:?}"),
                 }
             });
 
@@ -61,8 +80,16 @@ pub fn run() {
             model::get_models_root,
             // import
             model::import_model,
+            // per-model prompt settings
+            model::get_model_prompt_config,
+            model::set_model_prompt_kind,
+            model::set_model_template_file,
+            model::set_model_plugin,
+            plugin::list_runtime_plugins,
+            plugin::reload_runtime_plugins,
             // metadata
             metadata::get_model_metadata,
+            metadata::check_model_fit,
             metadata::meta_start_index,
             metadata::meta_status,
             metadata::meta_get_cached,
@@ -73,6 +100,13 @@ pub fn run() {
             engine::cancel_generation,
             // NEW: preload command (safe no-op if engine already exists)
             engine::preload_engine,
+            // LoRA adapters
+            engine::get_lora_adapters,
+            engine::set_lora_adapter,
+            // session params / memory
+            engine::get_session_params,
+            engine::set_kv_cache_type,
+            engine::get_memory_report,
             // installer
             runtime::is_llama_runtime_installed,
             runtime::run_runtime_installer,
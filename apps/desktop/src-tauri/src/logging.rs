@@ -0,0 +1,154 @@
+// src-tauri/src/logging.rs
+//
+// App logger. Records from the app, the engine (`strata-core`) and the
+// runtime plugins (forwarded by `strata_core::logging`) go to a rotating
+// file in `strata_hwprof::logs_dir()`; warnings and errors, and everything
+// in debug builds, are echoed to stderr.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Mutex,
+};
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Log level filter (`error`, `warn`, `info`, `debug`, `trace`, `off`).
+const ENV_LOG: &str = "STRATA_LOG";
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::Info;
+
+const LOG_FILE: &str = "strata.log";
+/// Size at which `strata.log` is rotated to `strata.log.1`.
+const MAX_FILE_BYTES: u64 = 5 << 20;
+/// Rotated files kept (`strata.log.1` ..= `strata.log.N`).
+const KEEP_ROTATED: usize = 3;
+
+/// The open log file and its size so far.
+struct LogFile {
+    path: PathBuf,
+    /// `None` only while rotating.
+    file: Option<File>,
+    len: u64,
+}
+
+impl LogFile {
+    fn open(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(LOG_FILE);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file: Some(file),
+            len,
+        })
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        self.path.with_extension(format!("log.{n}"))
+    }
+
+    /// `strata.log.N-1` → `.N`, …, `strata.log` → `.1`, then start afresh.
+    fn rotate(&mut self) -> io::Result<()> {
+        // Close our handle first: Windows can't rename a file that is open.
+        drop(self.file.take());
+        let _ = fs::remove_file(self.rotated(KEEP_ROTATED));
+        for n in (1..KEEP_ROTATED).rev() {
+            let _ = fs::rename(self.rotated(n), self.rotated(n + 1));
+        }
+        fs::rename(&self.path, self.rotated(1))?;
+        self.file = Some(
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?,
+        );
+        self.len = 0;
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        if self.len > 0 && self.len + line.len() as u64 > MAX_FILE_BYTES {
+            self.rotate()?;
+        }
+        let file = self
+            .file
+            .as_mut()
+            .ok_or_else(|| io::Error::other("log file closed"))?;
+        file.write_all(line.as_bytes())?;
+        self.len += line.len() as u64;
+        Ok(())
+    }
+}
+
+struct AppLogger {
+    level: LevelFilter,
+    /// `None` if the logs dir couldn't be opened; stderr still works.
+    file: Mutex<Option<LogFile>>,
+}
+
+impl Log for AppLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!(
+            "{} {:<5} {}: {}\n",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        if cfg!(debug_assertions) || record.level() <= log::Level::Warn {
+            eprint!("{line}");
+        }
+        if let Ok(mut file) = self.file.lock()
+            && let Some(f) = file.as_mut()
+            && let Err(e) = f.write_line(&line)
+        {
+            eprintln!(
+                "[log] writing {} failed: {e}; file logging off",
+                f.path.display()
+            );
+            *file = None;
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock()
+            && let Some(f) = file.as_mut().and_then(|f| f.file.as_mut())
+        {
+            let _ = f.flush();
+        }
+    }
+}
+
+/// Install the app logger; call once, before plugins are loaded (they pick
+/// up `log::max_level()` when attached).
+pub fn init() {
+    let level = std::env::var(ENV_LOG)
+        .ok()
+        .and_then(|v| LevelFilter::from_str(v.trim()).ok())
+        .unwrap_or(DEFAULT_LEVEL);
+    let dir = strata_hwprof::logs_dir();
+    let file = match LogFile::open(&dir) {
+        Ok(f) => Some(f),
+        Err(e) => {
+            eprintln!("[log] cannot open {}: {e}", dir.join(LOG_FILE).display());
+            None
+        }
+    };
+    let logger = AppLogger {
+        level,
+        file: Mutex::new(file),
+    };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(level);
+    }
+}
@@ -93,7 +93,7 @@ impl MetaIndexer {
 
             for (i, m) in list.into_iter().enumerate() {
                 // 1) try disk cache
-                let meta = if let Some(cached) = cached_read_meta_path(&m.path) {
+                let mut meta = if let Some(cached) = cached_read_meta_path(&m.path) {
                     cached
                 } else {
                     // 2) collect fresh then persist
@@ -114,6 +114,15 @@ impl MetaIndexer {
                     }
                 };
 
+                // Templateless models stay usable once the user picks a formatter/template.
+                crate::model::resolve_needs_template(&app, &m.id, &mut meta);
+                if meta.needs_template {
+                    let _ = app.emit(
+                        "meta-needs-template",
+                        serde_json::json!({ "id": m.id, "name": m.name }),
+                    );
+                }
+
                 {
                     let mut g = me.inner.write().unwrap();
                     g.cache.insert(m.id.clone(), meta);
@@ -208,8 +217,13 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
+/// Bump when `ModelMetaOut` gains fields providers fill in; older caches are dropped.
+const CACHE_VERSION: u32 = 2;
+
 #[derive(Debug, Default, Serialize, Deserialize)]
 struct CacheFile {
+    #[serde(default)]
+    version: u32,
     entries: HashMap<String, CacheEntry>,
 }
 
@@ -231,10 +245,15 @@ fn load_cache() -> CacheFile {
     let path = meta_cache_file();
     if let Ok(bytes) = fs::read(&path) {
         if let Ok(cf) = serde_json::from_slice::<CacheFile>(&bytes) {
-            return cf;
+            if cf.version == CACHE_VERSION {
+                return cf;
+            }
         }
     }
-    CacheFile::default()
+    CacheFile {
+        version: CACHE_VERSION,
+        ..CacheFile::default()
+    }
 }
 
 fn save_cache(cf: &CacheFile) -> Result<(), String> {
@@ -247,15 +266,33 @@ fn save_cache(cf: &CacheFile) -> Result<(), String> {
     fs::rename(&tmp, &path).map_err(|e| format!("rename {}: {e}", path.display()))
 }
 
+/// (size, mtime) of a file; for folder models, the summed size and newest
+/// mtime of the files directly inside it.
 fn fingerprint_for(p: &Path) -> Option<(u64, u128)> {
     let md = fs::metadata(p).ok()?;
-    let size = md.len();
-    let mtime = md.modified().unwrap_or(SystemTime::UNIX_EPOCH);
-    let ns = mtime
+    if !md.is_dir() {
+        return Some((md.len(), mtime_ns(&md)?));
+    }
+    let mut size = 0;
+    let mut ns = mtime_ns(&md)?;
+    for md in fs::read_dir(p)
+        .ok()?
+        .filter_map(|e| e.ok()?.metadata().ok())
+    {
+        if md.is_file() {
+            size += md.len();
+            ns = ns.max(mtime_ns(&md)?);
+        }
+    }
+    Some((size, ns))
+}
+
+fn mtime_ns(md: &fs::Metadata) -> Option<u128> {
+    md.modified()
+        .unwrap_or(SystemTime::UNIX_EPOCH)
         .duration_since(SystemTime::UNIX_EPOCH)
         .ok()
-        .map(|d| d.as_nanos())?;
-    Some((size, ns))
+        .map(|d| d.as_nanos())
 }
 
 /// Public helpers (so mod.rs can reuse cache too)
@@ -5,7 +5,10 @@ mod provider; // retained for now; no longer used by get_model_metadata
 
 use tauri::{AppHandle, State};
 
-use strata_core::metadata::{ModelMetaOut, collect_model_metadata, to_ui_meta};
+use strata_abi::metadata::ModelGeometry;
+use strata_core::metadata::{
+    FitReport, MemoryBudget, ModelMetaOut, check_fit, collect_model_metadata, to_ui_meta,
+};
 
 pub use indexer::{MetaIndexStatus, MetaIndexer, cached_read_meta_path, cached_write_meta_path};
 
@@ -14,9 +17,11 @@ pub use indexer::{MetaIndexStatus, MetaIndexer, cached_read_meta_path, cached_wr
 #[tauri::command]
 pub async fn get_model_metadata(app: AppHandle) -> Result<ModelMetaOut, String> {
     let path = crate::model::get_model_path(&app)?;
+    let id = crate::model::get_current_model().unwrap_or_default();
 
     // Fast path: disk cache
-    if let Some(cached) = cached_read_meta_path(&path) {
+    if let Some(mut cached) = cached_read_meta_path(&path) {
+        crate::model::resolve_needs_template(&app, &id, &mut cached);
         return Ok(cached);
     }
 
@@ -29,11 +34,71 @@ pub async fn get_model_metadata(app: AppHandle) -> Result<ModelMetaOut, String>
             .await
             .map_err(|e| format!("join error: {e}"))??;
 
-    let ui = to_ui_meta(&info);
+    let mut ui = to_ui_meta(&info);
     let _ = cached_write_meta_path(&path, &ui);
+    crate::model::resolve_needs_template(&app, &id, &mut ui);
     Ok(ui)
 }
 
+/// Estimate whether model `id` fits this machine at the current session
+/// params (optionally with a different `n_ctx`), without loading it.
+#[tauri::command]
+pub async fn check_model_fit(
+    app: AppHandle,
+    id: String,
+    n_ctx: Option<u32>,
+) -> Result<FitReport, String> {
+    let path = crate::model::user_models_root(&app)?.join(&id);
+    if !path.exists() {
+        return Err(format!("Model not found: {id}"));
+    }
+
+    let mut params = crate::engine::session_params();
+    if let Some(n) = n_ctx {
+        params.n_ctx = n;
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let geom = model_geometry(&path)?;
+        Ok(check_fit(&geom, &params, memory_budget()))
+    })
+    .await
+    .map_err(|e| format!("join error: {e}"))?
+}
+
+/// Geometry from the cache, re-collecting entries written before it was recorded.
+fn model_geometry(path: &std::path::Path) -> Result<ModelGeometry, String> {
+    if let Some(g) = cached_read_meta_path(path).and_then(|m| m.geometry) {
+        return Ok(g);
+    }
+    let ui = to_ui_meta(&collect_model_metadata(path)?);
+    let _ = cached_write_meta_path(path, &ui);
+    ui.geometry
+        .ok_or_else(|| "Model format does not expose layer/head sizes".to_string())
+}
+
+/// Installed RAM plus the largest dedicated GPU a GPU backend can use.
+fn memory_budget() -> MemoryBudget {
+    let Ok(hw) = strata_hwprof::load_or_detect() else {
+        return MemoryBudget::default();
+    };
+    let b = &hw.backends;
+    let vram_bytes = if b.cuda || b.rocm || b.vulkan || b.metal {
+        hw.gpus
+            .iter()
+            .filter(|g| !g.integrated && !g.software_renderer)
+            .filter_map(|g| g.vram_bytes)
+            .max()
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    MemoryBudget {
+        ram_bytes: hw.ram_gb << 30,
+        vram_bytes,
+    }
+}
+
 #[tauri::command]
 pub async fn meta_start_index(
     app: AppHandle,
@@ -1,6 +1,6 @@
 use std::path::Path;
 
-use crate::plugin::load_plugin_once;
+use crate::plugin::plugin_registry;
 use strata_abi::metadata::ModelCoreInfo;
 
 #[inline]
@@ -24,23 +24,22 @@ unsafe fn take_plugin_string(
     out
 }
 
-/// Read core model metadata from the plugin for a given model path.
+/// Read core model metadata from the plugin that would run a given model path.
 /// Returns the ABI-level core info (UI conversion happens at the caller).
 pub fn collect_model_metadata_via_plugin(path: &Path) -> Result<ModelCoreInfo, String> {
-    let plugin = load_plugin_once()?;
+    let plugin = plugin_registry().plugin_for_model(path, None)?;
     let cpath = make_cstring(path.to_str().ok_or("invalid UTF-8 in path")?)?;
     unsafe {
         let s = (plugin.api.metadata.collect_json)(cpath.as_ptr());
         let js = take_plugin_string(plugin.api.metadata.free_string, s);
         if js.is_empty() {
-            // On error, API returns empty and last_error() has the reason.
-            let err = (plugin.api.llm.last_error)();
-            let msg = take_plugin_string(plugin.api.llm.free_string, err);
-            if msg.is_empty() {
-                Err("plugin returned empty metadata".into())
-            } else {
-                Err(msg)
-            }
+            // On error, API returns null; the plugin has the reason.
+            let err = plugin.api.error(
+                std::ptr::null_mut(),
+                strata_abi::ffi::ERR_FAIL,
+                "collect_json",
+            );
+            Err(err.to_string())
         } else {
             serde_json::from_str::<ModelCoreInfo>(&js)
                 .map_err(|e| format!("bad metadata JSON: {e}"))
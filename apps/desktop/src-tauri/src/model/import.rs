@@ -3,6 +3,8 @@ use std::{
     fs,
     path::{Path, PathBuf},
 };
+use strata_core::metadata::hf_model_dir;
+use strata_gguf::{SplitName, split::split_path};
 use tauri::AppHandle;
 
 pub fn import_into_user_library(
@@ -10,6 +12,9 @@ pub fn import_into_user_library(
     src: &Path,
     family: Option<&str>,
 ) -> Result<ModelEntry, String> {
+    if src.is_dir() {
+        return import_folder(app, src, family);
+    }
     if !src.is_file() {
         return Err(format!("Source is not a file: {}", src.display()));
     }
@@ -22,29 +27,23 @@ pub fn import_into_user_library(
     if !ALLOWED_MODEL_EXTS.contains(&ext.as_str()) {
         return Err(format!("Unsupported extension .{}", ext));
     }
+    if let Some(split) = SplitName::parse(src) {
+        return import_split_set(app, src, &split, family);
+    }
 
     let file_name = src
         .file_name()
         .ok_or_else(|| "Invalid source file name".to_string())?;
 
     let user_root = user_models_root(app)?;
-    let dest_dir = family
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .map(|s| user_root.join(s))
-        .unwrap_or_else(|| user_root.clone());
-
-    if !dest_dir.exists() {
-        fs::create_dir_all(&dest_dir).map_err(|e| format!("mkdir {}: {e}", dest_dir.display()))?;
-    }
+    let dest_dir = family_dir(&user_root, family)?;
 
     let mut dest = dest_dir.join(file_name);
 
     if dest.exists() {
         let stem = dest.file_stem().and_then(|s| s.to_str()).unwrap_or("model");
         let ext = dest.extension().and_then(|e| e.to_str()).unwrap_or("");
-        let salt =
-            format!("{:x}", fxhash::hash64(src.to_string_lossy().as_bytes()))[..8].to_string();
+        let salt = salt_for(src);
         let new_name = if ext.is_empty() {
             format!("{stem}-{salt}")
         } else {
@@ -60,6 +59,142 @@ pub fn import_into_user_library(
         .ok_or_else(|| "Failed to build ModelEntry after import".into())
 }
 
+/// Destination folder for `family` under the library root (created if needed).
+fn family_dir(user_root: &Path, family: Option<&str>) -> Result<PathBuf, String> {
+    let dest_dir = family
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| user_root.join(s))
+        .unwrap_or_else(|| user_root.to_path_buf());
+
+    if !dest_dir.exists() {
+        fs::create_dir_all(&dest_dir).map_err(|e| format!("mkdir {}: {e}", dest_dir.display()))?;
+    }
+    Ok(dest_dir)
+}
+
+/// Copy an HF model folder's config, weights and tokenizer files into the
+/// library as one folder. Files land in a temp folder that is renamed into
+/// place, so a failed copy never leaves a half-imported model behind.
+fn import_folder(app: &AppHandle, src: &Path, family: Option<&str>) -> Result<ModelEntry, String> {
+    let hf = hf_model_dir(src).ok_or_else(|| {
+        format!(
+            "Not a model folder (needs config.json + weights): {}",
+            src.display()
+        )
+    })?;
+    let dir_name = src
+        .file_name()
+        .ok_or_else(|| "Invalid source folder name".to_string())?;
+
+    let user_root = user_models_root(app)?;
+    let dest_dir = family_dir(&user_root, family)?;
+    let mut dest = dest_dir.join(dir_name);
+    if dest.exists() {
+        let salt = salt_for(src);
+        dest = dest_dir.join(format!("{}-{salt}", dir_name.to_string_lossy()));
+    }
+
+    let tmp = dest_dir.join(format!(
+        ".{}.tmpcopy",
+        dest.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    if tmp.exists() {
+        let _ = fs::remove_dir_all(&tmp);
+    }
+    fs::create_dir_all(&tmp).map_err(|e| format!("mkdir {}: {e}", tmp.display()))?;
+    let copied = std::iter::once(&hf.config)
+        .chain(&hf.weights)
+        .chain(&hf.tokenizer_files)
+        .try_for_each(|f| {
+            let name = f.file_name().unwrap_or_default();
+            fs::copy(f, tmp.join(name)).map(drop)
+        })
+        .and_then(|()| fs::rename(&tmp, &dest));
+    if let Err(e) = copied {
+        let _ = fs::remove_dir_all(&tmp);
+        return Err(format!(
+            "Copy failed {} → {}: {e}",
+            src.display(),
+            dest.display()
+        ));
+    }
+
+    ModelEntry::from_dir(&user_root, &dest)
+        .ok_or_else(|| "Failed to build ModelEntry after import".into())
+}
+
+/// Import every file of a split GGUF set (picked via any of its files).
+/// All files are copied to temp names first and only then renamed into place,
+/// so the library never holds a partial set; incomplete sources are refused.
+fn import_split_set(
+    app: &AppHandle,
+    src: &Path,
+    split: &SplitName,
+    family: Option<&str>,
+) -> Result<ModelEntry, String> {
+    let sources = strata_gguf::check_split_set(src)?;
+    let prefix_name = split
+        .prefix
+        .file_name()
+        .ok_or_else(|| "Invalid source file name".to_string())?
+        .to_string_lossy()
+        .into_owned();
+
+    let user_root = user_models_root(app)?;
+    let dest_dir = family_dir(&user_root, family)?;
+    // Every shard name must be free: renaming over a file would clobber it.
+    let set_free =
+        |prefix: &Path| (1..=split.count).all(|i| !split_path(prefix, i, split.count).exists());
+    let mut prefix = dest_dir.join(&prefix_name);
+    if !set_free(&prefix) {
+        // Salt the prefix, not the file name, so llama.cpp can still find the siblings.
+        prefix = dest_dir.join(format!("{prefix_name}-{}", salt_for(src)));
+        if !set_free(&prefix) {
+            return Err(format!(
+                "{} is already in the library",
+                split_path(&prefix, 1, split.count).display()
+            ));
+        }
+    }
+    let dests: Vec<PathBuf> = (1..=split.count)
+        .map(|i| split_path(&prefix, i, split.count))
+        .collect();
+    let temps: Vec<PathBuf> = dests.iter().map(|d| d.with_extension("tmpcopy")).collect();
+
+    let copied = sources
+        .iter()
+        .zip(&temps)
+        .try_for_each(|(s, t)| fs::copy(s, t).map(drop));
+    let mut renamed = 0;
+    let moved = copied.and_then(|()| {
+        temps.iter().zip(&dests).try_for_each(|(t, d)| {
+            fs::rename(t, d)?;
+            renamed += 1;
+            Ok(())
+        })
+    });
+    if let Err(e) = moved {
+        // Only the shards this import put in place; nothing else is ours to remove.
+        for p in temps.iter().chain(&dests[..renamed]) {
+            let _ = fs::remove_file(p);
+        }
+        return Err(format!(
+            "Copy failed {} → {}: {e}",
+            src.display(),
+            dest_dir.display()
+        ));
+    }
+
+    ModelEntry::from_abs_path(&user_root, dests[0].clone())
+        .ok_or_else(|| "Failed to build ModelEntry after import".into())
+}
+
+/// Short hash of the source path, used to de-duplicate destination names.
+fn salt_for(src: &Path) -> String {
+    format!("{:x}", fxhash::hash64(src.to_string_lossy().as_bytes()))[..8].to_string()
+}
+
 fn copy_atomic(src: &Path, dest: &Path) -> std::io::Result<()> {
     let tmp = dest.with_extension("tmpcopy");
     if tmp.exists() {
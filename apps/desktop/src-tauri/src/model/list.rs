@@ -2,6 +2,8 @@ use std::{
     fs,
     path::{Component, Path, PathBuf},
 };
+use strata_core::metadata::hf_model_dir;
+use strata_gguf::SplitName;
 use tauri::{AppHandle, Manager};
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -12,6 +14,29 @@ pub struct ModelEntry {
     pub backend_hint: String,
     pub file_type: String,
     pub family: String,
+    /// Vision projector (mmproj GGUF) found next to the model, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mmproj: Option<PathBuf>,
+    /// LoRA adapter GGUFs found next to the model.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub loras: Vec<PathBuf>,
+    /// `path` is a Hugging Face-style folder rather than a single file.
+    pub is_dir: bool,
+    /// Bytes on disk (weights, config and tokenizer files for folders).
+    pub size_bytes: u64,
+    /// Folder models: weight files inside `path`. Split GGUFs: the files of
+    /// the set present on disk (`path` is the first).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub shards: Vec<PathBuf>,
+    /// Split GGUFs: files of the set that are missing; such entries can't be activated.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing_shards: Vec<PathBuf>,
+    /// Folder models: tokenizer/generation sidecars inside `path`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tokenizer_files: Vec<PathBuf>,
+    /// The model is complete and a loaded runtime plugin will run it
+    /// (see [`ModelEntry::check_runnable`]).
+    pub can_run: bool,
 }
 
 pub const ALLOWED_MODEL_EXTS: &[&str] = &["gguf", "safetensors", "onnx", "bin"];
@@ -27,6 +52,92 @@ fn format_hint(ext: &str) -> &'static str {
     }
 }
 
+/// Projector files (`mmproj-*.gguf`) are not chat models on their own.
+fn is_mmproj(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.to_lowercase().starts_with("mmproj"))
+}
+
+/// LoRA adapter GGUFs can't be chatted with on their own. llama.cpp's
+/// converter marks them with `general.type = "adapter"` and
+/// `adapter.type = "lora"`; files whose header can't be read fall back to
+/// the `*lora*` naming convention.
+fn is_lora(path: &Path) -> bool {
+    let is_gguf = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("gguf"));
+    if !is_gguf {
+        return false;
+    }
+    match strata_gguf::GgufFile::open(path) {
+        Ok(gguf) => {
+            gguf.get_str("general.type") == Some("adapter")
+                || gguf.get_str("adapter.type") == Some("lora")
+        }
+        Err(_) => path
+            .file_stem()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.to_lowercase().contains("lora")),
+    }
+}
+
+/// LoRA adapters in the same folder as `model_path`, sorted by name.
+pub fn find_loras(model_path: &Path) -> Vec<PathBuf> {
+    let Some(dir) = model_path.parent() else {
+        return Vec::new();
+    };
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut loras: Vec<PathBuf> = read_dir
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && is_lora(p))
+        .collect();
+    loras.sort();
+    loras
+}
+
+/// Sibling mmproj file for a GGUF model; with several, the one sharing the
+/// longest name prefix with the model wins.
+pub fn find_projector(model_path: &Path) -> Option<PathBuf> {
+    let model_name = model_path.file_stem()?.to_str()?.to_lowercase();
+    let dir = model_path.parent()?;
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && is_mmproj(p))
+        .filter(|p| {
+            p.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| e.eq_ignore_ascii_case("gguf"))
+        })
+        .max_by_key(|p| {
+            let stem = p
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("")
+                .to_lowercase();
+            let stem = stem
+                .trim_start_matches("mmproj")
+                .trim_start_matches(['-', '_', '.']);
+            stem.chars()
+                .zip(model_name.chars())
+                .take_while(|(a, b)| a == b)
+                .count()
+        })
+}
+
+/// Refuse models that can't be loaded as a whole: a split GGUF with files
+/// missing or mislabeled.
+pub fn check_model_complete(path: &Path) -> Result<(), String> {
+    if SplitName::parse(path).is_some() {
+        strata_gguf::check_split_set(path)?;
+    }
+    Ok(())
+}
+
 pub fn user_models_root(app: &AppHandle) -> Result<PathBuf, String> {
     let mut root = app
         .path()
@@ -64,15 +175,26 @@ impl ModelEntry {
             return None;
         }
         let ext = abs_path.extension()?.to_str().map(|s| s.to_lowercase())?;
-        if !ALLOWED_MODEL_EXTS.contains(&ext.as_str()) {
+        if !ALLOWED_MODEL_EXTS.contains(&ext.as_str()) || is_mmproj(&abs_path) || is_lora(&abs_path)
+        {
             return None;
         }
 
-        let file_stem = abs_path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("unknown")
-            .to_string();
+        // Any file of a split GGUF stands for the whole set, listed under its first file.
+        let split = SplitName::parse(&abs_path);
+        let abs_path = split.as_ref().map_or(abs_path, SplitName::first);
+
+        let file_stem = match &split {
+            Some(s) => s
+                .prefix
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned()),
+            None => abs_path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .map(str::to_string),
+        }
+        .unwrap_or_else(|| "unknown".to_string());
         let family = abs_path
             .parent()
             .and_then(Path::file_name)
@@ -80,16 +202,84 @@ impl ModelEntry {
             .map(|s| s.to_string())
             .unwrap_or_default();
         let id = rel_id(models_root, &abs_path).unwrap_or_else(|| file_stem.clone());
+        let (mmproj, loras) = if ext == "gguf" {
+            (find_projector(&abs_path), find_loras(&abs_path))
+        } else {
+            (None, Vec::new())
+        };
+
+        let (shards, missing_shards): (Vec<PathBuf>, Vec<PathBuf>) = match &split {
+            Some(s) => s.paths().into_iter().partition(|p| p.is_file()),
+            None => (Vec::new(), Vec::new()),
+        };
+        let size_bytes = if split.is_some() {
+            shards
+                .iter()
+                .filter_map(|p| fs::metadata(p).ok())
+                .map(|m| m.len())
+                .sum()
+        } else {
+            fs::metadata(&abs_path).map(|m| m.len()).unwrap_or(0)
+        };
 
         Some(Self {
             id,
             name: file_stem,
+            size_bytes,
             path: abs_path,
             backend_hint: format_hint(&ext).to_string(),
             file_type: ext,
             family,
+            mmproj,
+            loras,
+            is_dir: false,
+            shards,
+            missing_shards,
+            tokenizer_files: Vec::new(),
+            can_run: false,
         })
     }
+
+    /// One entry for an HF model folder (`config.json` + weights), if `dir` is one.
+    pub fn from_dir(models_root: &Path, dir: &Path) -> Option<Self> {
+        let hf = hf_model_dir(dir)?;
+        let name = dir.file_name()?.to_string_lossy().into_owned();
+        let family = dir
+            .parent()
+            .and_then(Path::file_name)
+            .and_then(|n| n.to_str())
+            .map(|s| s.to_string())
+            .unwrap_or_default();
+
+        Some(Self {
+            id: rel_id(models_root, dir).unwrap_or_else(|| name.clone()),
+            name,
+            path: dir.to_path_buf(),
+            backend_hint: format_hint(hf.weight_format).to_string(),
+            file_type: hf.weight_format.to_string(),
+            family,
+            mmproj: None,
+            loras: Vec::new(),
+            is_dir: true,
+            size_bytes: hf.total_size(),
+            shards: hf.weights,
+            missing_shards: Vec::new(),
+            tokenizer_files: hf.tokenizer_files,
+            can_run: false,
+        })
+    }
+
+    /// Set `can_run`: no shard is missing and the plugin pinned for the model,
+    /// else one that claims its path, is loaded.
+    pub fn check_runnable(&mut self, app: &AppHandle) {
+        let pinned = super::get_prompt_config(app, &self.id)
+            .ok()
+            .and_then(|cfg| cfg.plugin_id);
+        self.can_run = self.missing_shards.is_empty()
+            && crate::plugin::plugin_registry()
+                .plugin_for_model(&self.path, pinned.as_deref())
+                .is_ok();
+    }
 }
 
 pub fn list_available_models(app: AppHandle) -> Result<Vec<ModelEntry>, String> {
@@ -102,6 +292,12 @@ pub fn list_available_models(app: AppHandle) -> Result<Vec<ModelEntry>, String>
         &mut entries,
         &mut std::collections::HashSet::new(),
     )?;
+    // Every file of a split set maps to the same entry; keep one.
+    let mut seen = std::collections::HashSet::new();
+    entries.retain(|e| seen.insert(e.id.clone()));
+    for entry in &mut entries {
+        entry.check_runnable(&app);
+    }
 
     entries.sort_by(|a, b| {
         (a.family.to_lowercase(), a.name.to_lowercase())
@@ -125,7 +321,11 @@ fn walk_dir(
         let entry = entry.map_err(|e| format!("entry in {}: {e}", dir.display()))?;
         let path = entry.path();
         if path.is_dir() {
-            walk_dir(models_root, &path, entries, visited)?;
+            // An HF folder is one model; its files are not listed separately.
+            match ModelEntry::from_dir(models_root, &path) {
+                Some(model_entry) => entries.push(model_entry),
+                None => walk_dir(models_root, &path, entries, visited)?,
+            }
         } else if path.is_file() {
             if let Some(model_entry) = ModelEntry::from_abs_path(models_root, path) {
                 entries.push(model_entry);
@@ -1,12 +1,20 @@
 // src-tauri/src/model/mod.rs
 mod import;
 mod list;
+mod prompt;
 mod select;
 
 pub use import::import_into_user_library;
-pub use list::{ModelEntry, list_available_models, resolve_models_root, user_models_root};
+pub use list::{
+    ModelEntry, check_model_complete, find_projector, list_available_models, resolve_models_root,
+    user_models_root,
+};
+pub use prompt::{
+    ModelPromptConfig, apply_prompt_config, get_prompt_config, resolve_needs_template,
+};
 pub use select::{get_current_model, get_model_path, set_current_model};
 
+use strata_core::format::prompt_format::PromptKind;
 use tauri::{AppHandle, Emitter, State};
 
 use crate::app_state::AppState;
@@ -39,7 +47,10 @@ pub async fn import_model(
 ) -> Result<ModelEntry, String> {
     let app2 = app.clone();
     tauri::async_runtime::spawn_blocking(move || {
-        import_into_user_library(&app2, std::path::Path::new(&src_path), family.as_deref())
+        let mut entry =
+            import_into_user_library(&app2, std::path::Path::new(&src_path), family.as_deref())?;
+        entry.check_runnable(&app2);
+        Ok(entry)
     })
     .await
     .map_err(|e| format!("join error: {e}"))?
@@ -52,6 +63,12 @@ pub async fn set_active_model_cmd(
     state: State<'_, AppState>,
     name: String,
 ) -> Result<(), String> {
+    // Refuse incomplete split sets before touching the current selection.
+    let path = user_models_root(&app)?.join(&name);
+    tauri::async_runtime::spawn_blocking(move || check_model_complete(&path))
+        .await
+        .map_err(|e| format!("join error: {e}"))??;
+
     // Persist the selection
     set_current_model(name.clone());
 
@@ -76,3 +93,77 @@ pub async fn set_active_model_cmd(
     let _ = app.emit("strata://model-switched", &name);
     Ok(())
 }
+
+// --- Per-model prompt settings ---
+
+#[tauri::command]
+pub fn get_model_prompt_config(app: AppHandle, id: String) -> Result<ModelPromptConfig, String> {
+    get_prompt_config(&app, &id)
+}
+
+/// Force a generic formatter for `id` (`None` clears it). Applies to the live engine if `id` is loaded.
+#[tauri::command]
+pub fn set_model_prompt_kind(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+    kind: Option<PromptKind>,
+) -> Result<ModelPromptConfig, String> {
+    let cfg = prompt::set_prompt_kind(&app, &id, kind)?;
+    apply_to_live_engine(&state, &id, &cfg)?;
+    Ok(cfg)
+}
+
+/// Store a user template file for `id` (`None` removes it). Applies to the live engine if `id` is loaded.
+#[tauri::command]
+pub fn set_model_template_file(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+    src_path: Option<String>,
+) -> Result<ModelPromptConfig, String> {
+    let cfg = prompt::set_template_file(&app, &id, src_path.as_deref().map(std::path::Path::new))?;
+    apply_to_live_engine(&state, &id, &cfg)?;
+    Ok(cfg)
+}
+
+/// Pin the runtime plugin for `id` (`None` routes by file again). Reloads the
+/// engine if `id` is loaded.
+#[tauri::command]
+pub async fn set_model_plugin(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+    plugin_id: Option<String>,
+) -> Result<ModelPromptConfig, String> {
+    let app2 = app.clone();
+    let state2 = AppState {
+        memory: std::sync::Arc::clone(&state.memory),
+        current_stop: std::sync::Arc::clone(&state.current_stop),
+        engine: std::sync::Arc::clone(&state.engine),
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Some(pid) = plugin_id.as_deref() {
+            if crate::plugin::plugin_registry().get(pid).is_none() {
+                return Err(format!("Runtime plugin '{pid}' is not loaded"));
+            }
+        }
+        let cfg = prompt::set_plugin_id(&app2, &id, plugin_id)?;
+        if get_current_model().as_deref() == Some(id.as_str()) {
+            crate::engine::reinit_engine_to_current_model(&app2, &state2)?;
+        }
+        Ok(cfg)
+    })
+    .await
+    .map_err(|e| format!("join error: {e}"))?
+}
+
+fn apply_to_live_engine(state: &AppState, id: &str, cfg: &ModelPromptConfig) -> Result<(), String> {
+    if get_current_model().as_deref() != Some(id) {
+        return Ok(());
+    }
+    match state.engine.lock().unwrap().as_mut() {
+        Some(engine) => apply_prompt_config(engine, cfg),
+        None => Ok(()),
+    }
+}
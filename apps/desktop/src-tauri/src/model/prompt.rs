@@ -0,0 +1,190 @@
+//! Per-model prompt settings: a forced generic formatter (`PromptKind`) and/or a
+//! user-supplied Jinja template, for models whose GGUF ships no chat template.
+//! The same record pins the runtime plugin a model should run on.
+//!
+//! Stored in `<app_data>/prompt_settings.json`, keyed by model id; template files
+//! are copied into `<app_data>/templates/` so the originals can move freely.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use strata_abi::backend::LLMBackend;
+use strata_core::engine::LLMEngine;
+use strata_core::format::jinja::Template;
+use strata_core::format::prompt_format::PromptKind;
+use strata_core::metadata::ModelMetaOut;
+use tauri::{AppHandle, Manager};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelPromptConfig {
+    /// Generic formatter chosen by the user.
+    pub prompt_kind: Option<PromptKind>,
+    /// Copy of the user's template file (absolute path).
+    pub template_path: Option<PathBuf>,
+    /// Runtime plugin id preferred over routing by `can_handle`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plugin_id: Option<String>,
+}
+
+impl ModelPromptConfig {
+    /// A prompt format (kind or template) is configured.
+    pub fn is_configured(&self) -> bool {
+        self.prompt_kind.is_some() || self.template_path.is_some()
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.is_configured() && self.plugin_id.is_none()
+    }
+}
+
+type SettingsFile = HashMap<String, ModelPromptConfig>;
+
+fn app_data_root(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("resolve app_data_dir: {e}"))
+}
+
+fn settings_file(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app_data_root(app)?.join("prompt_settings.json"))
+}
+
+fn templates_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app_data_root(app)?.join("templates"))
+}
+
+fn load_settings(app: &AppHandle) -> Result<SettingsFile, String> {
+    let path = settings_file(app)?;
+    match fs::read(&path) {
+        Ok(bytes) => {
+            serde_json::from_slice(&bytes).map_err(|e| format!("parse {}: {e}", path.display()))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(SettingsFile::new()),
+        Err(e) => Err(format!("read {}: {e}", path.display())),
+    }
+}
+
+fn save_settings(app: &AppHandle, settings: &SettingsFile) -> Result<(), String> {
+    let path = settings_file(app)?;
+    let root = path.parent().expect("settings file has a parent");
+    fs::create_dir_all(root).map_err(|e| format!("mkdir {}: {e}", root.display()))?;
+    let tmp = root.join("prompt_settings.json.tmp");
+    let bytes = serde_json::to_vec_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(&tmp, &bytes).map_err(|e| format!("write {}: {e}", tmp.display()))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("rename {}: {e}", path.display()))
+}
+
+fn update<F>(app: &AppHandle, model_id: &str, f: F) -> Result<ModelPromptConfig, String>
+where
+    F: FnOnce(&mut ModelPromptConfig) -> Result<(), String>,
+{
+    let mut settings = load_settings(app)?;
+    let mut cfg = settings.remove(model_id).unwrap_or_default();
+    f(&mut cfg)?;
+    if !cfg.is_empty() {
+        settings.insert(model_id.to_string(), cfg.clone());
+    }
+    save_settings(app, &settings)?;
+    Ok(cfg)
+}
+
+/// File name for a model's stored template (`family/model.gguf` → `family_model.gguf.jinja`).
+fn template_file_name(model_id: &str) -> String {
+    let safe: String = model_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{safe}.jinja")
+}
+
+pub fn get_prompt_config(app: &AppHandle, model_id: &str) -> Result<ModelPromptConfig, String> {
+    Ok(load_settings(app)?.remove(model_id).unwrap_or_default())
+}
+
+pub fn set_prompt_kind(
+    app: &AppHandle,
+    model_id: &str,
+    kind: Option<PromptKind>,
+) -> Result<ModelPromptConfig, String> {
+    update(app, model_id, |cfg| {
+        cfg.prompt_kind = kind;
+        Ok(())
+    })
+}
+
+/// Pin the runtime plugin for a model (`None` routes by `can_handle` again).
+pub fn set_plugin_id(
+    app: &AppHandle,
+    model_id: &str,
+    plugin_id: Option<String>,
+) -> Result<ModelPromptConfig, String> {
+    update(app, model_id, |cfg| {
+        cfg.plugin_id = plugin_id;
+        Ok(())
+    })
+}
+
+/// Validate and store `src` as the model's template; `None` removes it.
+pub fn set_template_file(
+    app: &AppHandle,
+    model_id: &str,
+    src: Option<&Path>,
+) -> Result<ModelPromptConfig, String> {
+    let dest = templates_dir(app)?.join(template_file_name(model_id));
+
+    update(app, model_id, |cfg| {
+        match src {
+            Some(src) => {
+                let text =
+                    fs::read_to_string(src).map_err(|e| format!("read {}: {e}", src.display()))?;
+                Template::parse(&text)
+                    .map_err(|e| format!("invalid template {}: {e}", src.display()))?;
+
+                let dir = dest.parent().expect("template path has a parent");
+                fs::create_dir_all(dir).map_err(|e| format!("mkdir {}: {e}", dir.display()))?;
+                fs::write(&dest, text).map_err(|e| format!("write {}: {e}", dest.display()))?;
+                cfg.template_path = Some(dest.clone());
+            }
+            None => {
+                if dest.exists() {
+                    fs::remove_file(&dest)
+                        .map_err(|e| format!("remove {}: {e}", dest.display()))?;
+                }
+                cfg.template_path = None;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Push a model's prompt settings into a live engine.
+pub fn apply_prompt_config<B: LLMBackend>(
+    engine: &mut LLMEngine<B>,
+    cfg: &ModelPromptConfig,
+) -> Result<(), String> {
+    let template = match cfg.template_path.as_ref() {
+        Some(p) => Some(fs::read_to_string(p).map_err(|e| format!("read {}: {e}", p.display()))?),
+        None => None,
+    };
+    engine.set_chat_template_override(template);
+    engine.set_prompt_kind(cfg.prompt_kind.clone());
+    Ok(())
+}
+
+/// A model "needs template" when it has none and the user hasn't configured one.
+pub fn resolve_needs_template(app: &AppHandle, model_id: &str, meta: &mut ModelMetaOut) {
+    let configured = get_prompt_config(app, model_id)
+        .map(|c| c.is_configured())
+        .unwrap_or(false);
+    meta.needs_template = !meta.has_chat_template && !configured;
+}
@@ -1,4 +1,4 @@
-use super::list::user_models_root;
+use super::list::{check_model_complete, user_models_root};
 use once_cell::sync::Lazy;
 use std::{
     path::{Path, PathBuf},
@@ -24,7 +24,9 @@ pub fn get_model_path(app: &AppHandle) -> Result<PathBuf, String> {
     let rel_id = get_current_model().ok_or("No model selected")?;
     let user_root = user_models_root(app)?;
     let abs_user = user_root.join(Path::new(&rel_id));
-    if abs_user.is_file() {
+    // Folder models (HF layout) are selected by their directory.
+    if abs_user.is_file() || abs_user.is_dir() {
+        check_model_complete(&abs_user)?;
         return Ok(abs_user);
     }
     Err(format!("Selected model not found: {}", rel_id))
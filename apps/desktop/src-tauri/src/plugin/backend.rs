@@ -1,18 +1,27 @@
 use core::ffi::c_void;
-use std::{path::Path, slice};
+use std::{path::Path, slice, sync::Arc};
 
-use crate::plugin::loader::load_plugin_once;
+use crate::plugin::loader::{LoadedPlugin, plugin_registry};
 use strata_abi::{
-    backend::{ChatTurn, LLMBackend, PromptFlavor},
+    backend::{ChatTurn, ControlVector, LLMBackend, LoraAdapter, PromptFlavor},
+    error::{ErrorCode, StrataError},
     ffi::*,
     metadata::ModelCoreInfo,
+    session::{MemoryReport, PerfCounters, SessionParams},
 };
 
 pub struct PluginBackend {
-    pub(crate) plugin: &'static super::loader::LoadedPlugin,
+    /// Keeps the plugin library loaded while this session exists.
+    pub(crate) plugin: Arc<LoadedPlugin>,
     pub(crate) session: *mut c_void,
     eos_token_id: i32,
+    bos_token_id: Option<i32>,
     ctx_len_hint: Option<usize>,
+    chat_template: Option<String>,
+    flavor: PromptFlavor,
+    stop_strings: Vec<String>,
+    /// Set once a projector is loaded; `None` means text-only.
+    media_marker: Option<String>,
 }
 
 impl Drop for PluginBackend {
@@ -28,10 +37,15 @@ impl Clone for PluginBackend {
     fn clone(&self) -> Self {
         // Shallow clone — we only ever use one generation at a time.
         Self {
-            plugin: self.plugin,
+            plugin: Arc::clone(&self.plugin),
             session: self.session,
             eos_token_id: self.eos_token_id,
+            bos_token_id: self.bos_token_id,
             ctx_len_hint: self.ctx_len_hint,
+            chat_template: self.chat_template.clone(),
+            flavor: self.flavor,
+            stop_strings: self.stop_strings.clone(),
+            media_marker: self.media_marker.clone(),
         }
     }
 }
@@ -40,12 +54,13 @@ impl Clone for PluginBackend {
 unsafe impl Send for PluginBackend {}
 unsafe impl Sync for PluginBackend {}
 
-fn make_cstring(s: &str) -> Result<std::ffi::CString, String> {
-    std::ffi::CString::new(s).map_err(|_| "string contains interior NUL".to_string())
+fn make_cstring(s: &str) -> Result<std::ffi::CString, StrataError> {
+    std::ffi::CString::new(s)
+        .map_err(|_| StrataError::new(ErrorCode::InvalidArg, "string contains interior NUL"))
 }
 
 unsafe fn take_plugin_string(api_free: FreeStringFn, s: StrataString) -> String {
-    if s.ptr.is_null() || s.len == 0 {
+    if s.ptr.is_null() {
         return String::new();
     }
     // Rust 2024 lint: body is safe by default; wrap unsafe ops.
@@ -58,81 +73,140 @@ unsafe fn take_plugin_string(api_free: FreeStringFn, s: StrataString) -> String
 }
 
 impl PluginBackend {
-    pub fn load<P: AsRef<Path>>(model_path: P) -> Result<Self, String> {
+    pub fn load<P: AsRef<Path>>(model_path: P) -> Result<Self, StrataError> {
         <Self as LLMBackend>::load(model_path)
     }
-}
 
-impl LLMBackend for PluginBackend {
-    fn load<P: AsRef<Path>>(model_path: P) -> Result<Self, String> {
-        let plugin = load_plugin_once()?;
-        let cpath = make_cstring(
-            model_path
-                .as_ref()
-                .to_str()
-                .ok_or("model path not valid UTF-8")?,
-        )?;
-
-        let session = unsafe { (plugin.api.llm.create_session)(cpath.as_ptr()) };
+    /// Load with explicit context/model parameters (normalized by the plugin).
+    pub fn load_with_params<P: AsRef<Path>>(
+        model_path: P,
+        params: &SessionParams,
+    ) -> Result<Self, StrataError> {
+        <Self as LLMBackend>::load_with_params(model_path, params)
+    }
+
+    /// Like `load_with_params`, preferring the plugin with id `pinned`
+    /// over routing by `can_handle`.
+    pub fn load_routed<P: AsRef<Path>>(
+        model_path: P,
+        params: &SessionParams,
+        pinned: Option<&str>,
+    ) -> Result<Self, StrataError> {
+        let plugin = plugin_registry().plugin_for_model(model_path.as_ref(), pinned)?;
+        Self::load_on(plugin, model_path.as_ref(), params)
+    }
+
+    fn load_on(
+        plugin: Arc<LoadedPlugin>,
+        model_path: &Path,
+        params: &SessionParams,
+    ) -> Result<Self, StrataError> {
+        let cpath = make_cstring(model_path.to_str().ok_or("model path not valid UTF-8")?)?;
+        let params_json =
+            serde_json::to_string(params).map_err(|e| format!("serialize SessionParams: {e}"))?;
+        let cparams = make_cstring(&params_json)?;
+
+        let session = if plugin.api.supports(CAP_SESSION_PARAMS) {
+            unsafe { (plugin.api.llm.create_session_with_params)(cpath.as_ptr(), cparams.as_ptr()) }
+        } else {
+            log::warn!("'{}' ignores session parameters", plugin.id);
+            unsafe { (plugin.api.llm.create_session)(cpath.as_ptr()) }
+        };
         if session.is_null() {
-            let msg = unsafe {
-                let s = (plugin.api.llm.last_error)();
-                take_plugin_string(plugin.api.llm.free_string, s)
+            let err = unsafe {
+                plugin
+                    .api
+                    .error(std::ptr::null_mut(), ERR_FAIL, "create_session")
             };
-            return Err(if msg.is_empty() {
-                "create_session failed".into()
-            } else {
-                msg
-            });
+            return Err(err);
         }
 
-        // Pull metadata to get EOS + context length hint
+        // Pull metadata to get BOS/EOS, context length hint and the raw chat template
         let meta_json = unsafe {
             let s = (plugin.api.metadata.collect_json)(cpath.as_ptr());
             take_plugin_string(plugin.api.metadata.free_string, s)
         };
-
-        let (eos, ctx_hint) = if meta_json.is_empty() {
-            (-1, None)
+        let meta = if meta_json.is_empty() {
+            None
         } else {
-            match serde_json::from_str::<ModelCoreInfo>(&meta_json) {
-                Ok(m) => (
-                    m.eos_token_id.unwrap_or(-1),
-                    m.context_length.map(|c| c as usize),
-                ),
-                Err(_) => (-1, None),
-            }
+            serde_json::from_str::<ModelCoreInfo>(&meta_json).ok()
         };
+        let eos = meta.as_ref().and_then(|m| m.eos_token_id).unwrap_or(-1);
+        let bos = meta.as_ref().and_then(|m| m.bos_token_id);
+        // Prefer the session's real window (n_ctx may be below the training length).
+        let session_ctx = unsafe { (plugin.api.llm.context_window_hint)(session) };
+        let ctx_hint = if session_ctx > 0 {
+            Some(session_ctx as usize)
+        } else {
+            meta.as_ref()
+                .and_then(|m| m.context_length)
+                .map(|c| c as usize)
+        };
+        let chat_template = meta.and_then(|m| m.chat_template);
+
+        // Per-model prompt hints (flavor + stop strings) come from the session itself.
+        let flavor = unsafe {
+            let s = (plugin.api.llm.prompt_flavor)(session);
+            take_plugin_string(plugin.api.llm.free_string, s)
+        };
+        let flavor = PromptFlavor::from_name(&flavor).unwrap_or(PromptFlavor::ChatMl);
+
+        let stops_json = unsafe {
+            let s = (plugin.api.llm.stop_strings_json)(session);
+            take_plugin_string(plugin.api.llm.free_string, s)
+        };
+        let stop_strings = serde_json::from_str::<Vec<String>>(&stops_json).unwrap_or_default();
 
         Ok(Self {
             plugin,
             session,
             eos_token_id: eos,
+            bos_token_id: bos,
             ctx_len_hint: ctx_hint,
+            chat_template,
+            flavor,
+            stop_strings,
+            media_marker: None,
         })
     }
 
-    fn tokenize(&self, text: &str) -> Result<Vec<strata_abi::token::Token>, String> {
+    /// Error (with its `ErrorCode`) for the failed call `what`, which
+    /// returned `rc`.
+    fn error(&self, rc: i32, what: &str) -> StrataError {
+        unsafe { self.plugin.api.error(self.session, rc, what) }
+    }
+}
+
+impl LLMBackend for PluginBackend {
+    fn load<P: AsRef<Path>>(model_path: P) -> Result<Self, StrataError> {
+        <Self as LLMBackend>::load_with_params(model_path, &SessionParams::default())
+    }
+
+    fn load_with_params<P: AsRef<Path>>(
+        model_path: P,
+        params: &SessionParams,
+    ) -> Result<Self, StrataError> {
+        Self::load_routed(model_path, params, None)
+    }
+
+    fn tokenize(&self, text: &str) -> Result<Vec<strata_abi::token::Token>, StrataError> {
         let ctext = make_cstring(text)?;
         let arr = unsafe { (self.plugin.api.llm.tokenize_utf8)(self.session, ctext.as_ptr()) };
 
-        if arr.ptr.is_null() || arr.len == 0 {
-            let msg = unsafe {
-                let s = (self.plugin.api.llm.last_error)();
-                take_plugin_string(self.plugin.api.llm.free_string, s)
-            };
-            return if msg.is_empty() {
-                Ok(Vec::new())
-            } else {
-                Err(msg)
-            };
+        // Null is an error; an empty result is a non-null, zero-length array.
+        if arr.ptr.is_null() {
+            return Err(self.error(ERR_FAIL, "tokenize"));
         }
 
         let mut v: Vec<i32> = unsafe { Vec::from_raw_parts(arr.ptr, arr.len, arr.len) };
         Ok(v.drain(..).map(strata_abi::token::Token).collect())
     }
 
-    fn evaluate(&mut self, tokens: &[strata_abi::token::Token], n_past: i32) -> Result<(), String> {
+    fn evaluate(
+        &mut self,
+        tokens: &[strata_abi::token::Token],
+        n_past: i32,
+    ) -> Result<(), StrataError> {
         let tmp: Vec<i32> = tokens.iter().map(|t| t.0).collect();
         let rc = unsafe {
             (self.plugin.api.llm.evaluate)(self.session, tmp.as_ptr(), tmp.len(), n_past)
@@ -140,15 +214,7 @@ impl LLMBackend for PluginBackend {
         if rc == ERR_OK {
             Ok(())
         } else {
-            let msg = unsafe {
-                let s = (self.plugin.api.llm.last_error)();
-                take_plugin_string(self.plugin.api.llm.free_string, s)
-            };
-            Err(if msg.is_empty() {
-                "evaluate failed".into()
-            } else {
-                msg
-            })
+            Err(self.error(rc, "evaluate"))
         }
     }
 
@@ -157,7 +223,7 @@ impl LLMBackend for PluginBackend {
         _n_past: i32,
         params: &strata_abi::sampling::SamplingParams,
         _token_history: &[strata_abi::token::Token],
-    ) -> Result<strata_abi::token::Token, String> {
+    ) -> Result<strata_abi::token::Token, StrataError> {
         let params = params.normalized();
         let js = serde_json::to_string(&params).map_err(|e| e.to_string())?;
         let cjs = make_cstring(&js)?;
@@ -165,47 +231,40 @@ impl LLMBackend for PluginBackend {
         if tok >= 0 {
             Ok(strata_abi::token::Token(tok))
         } else {
-            let msg = unsafe {
-                let s = (self.plugin.api.llm.last_error)();
-                take_plugin_string(self.plugin.api.llm.free_string, s)
-            };
-            Err(if msg.is_empty() {
-                "sample failed".into()
-            } else {
-                msg
-            })
+            Err(self.error(tok, "sample"))
         }
     }
 
-    fn decode_token(&self, token: strata_abi::token::Token) -> Result<String, String> {
+    fn decode_token(&self, token: strata_abi::token::Token) -> Result<String, StrataError> {
         let s = unsafe { (self.plugin.api.llm.decode_token)(self.session, token.0) };
-        let out = unsafe { take_plugin_string(self.plugin.api.llm.free_string, s) };
-
-        if out.is_empty() {
-            let msg = unsafe {
-                let se = (self.plugin.api.llm.last_error)();
-                take_plugin_string(self.plugin.api.llm.free_string, se)
-            };
-            if msg.is_empty() { Ok(out) } else { Err(msg) }
-        } else {
-            Ok(out)
+        if s.ptr.is_null() {
+            return Err(self.error(ERR_FAIL, "decode_token"));
         }
+        Ok(unsafe { take_plugin_string(self.plugin.api.llm.free_string, s) })
     }
 
     fn eos_token(&self) -> strata_abi::token::Token {
         strata_abi::token::Token(self.eos_token_id)
     }
 
+    fn bos_token(&self) -> Option<strata_abi::token::Token> {
+        self.bos_token_id.map(strata_abi::token::Token)
+    }
+
     fn context_window_hint(&self) -> Option<usize> {
         self.ctx_len_hint
     }
 
+    fn chat_template(&self) -> Option<String> {
+        self.chat_template.clone()
+    }
+
     fn prompt_flavor(&self) -> PromptFlavor {
-        PromptFlavor::ChatMl
+        self.flavor
     }
 
-    fn default_stop_strings(&self) -> &'static [&'static str] {
-        &["<|im_end|>"]
+    fn default_stop_strings(&self) -> Vec<String> {
+        self.stop_strings.clone()
     }
 
     fn apply_native_chat_template(&self, turns: &[ChatTurn]) -> Option<String> {
@@ -217,29 +276,23 @@ impl LLMBackend for PluginBackend {
         let js = match serde_json::to_string(turns) {
             Ok(s) => s,
             Err(e) => {
-                eprintln!("[plugin] serialize ChatTurn failed: {e}");
+                log::warn!("serialize ChatTurn failed: {e}");
                 return None;
             }
         };
         let cjs = match std::ffi::CString::new(js) {
             Ok(c) => c,
             Err(e) => {
-                eprintln!("[plugin] CString::new(turns_json) failed: {e}");
+                log::warn!("CString::new(turns_json) failed: {e}");
                 return None;
             }
         };
 
         // Ask plugin to apply its native chat template → returns JSON for FormattedPrompt
         let s = unsafe { (self.plugin.api.llm.format_chat_json)(self.session, cjs.as_ptr(), true) };
-        if s.ptr.is_null() || s.len == 0 {
-            // Pull plugin error (if any) for logging
-            let msg = unsafe {
-                let se = (self.plugin.api.llm.last_error)();
-                take_plugin_string(self.plugin.api.llm.free_string, se)
-            };
-            if !msg.is_empty() {
-                eprintln!("[plugin] format_chat_json failed: {msg}");
-            }
+        if s.ptr.is_null() {
+            // Pull plugin error for logging (also clears it)
+            log::warn!("{}", self.error(ERR_FAIL, "format_chat_json"));
             return None;
         }
 
@@ -259,19 +312,141 @@ impl LLMBackend for PluginBackend {
         match serde_json::from_str::<FormattedPrompt>(&payload) {
             Ok(fp) => Some(fp.text),
             Err(e) => {
-                eprintln!("[plugin] malformed FormattedPrompt JSON: {e}; raw={payload}");
+                log::warn!("malformed FormattedPrompt JSON: {e}; raw={payload}");
                 None
             }
         }
     }
 
+    fn media_marker(&self) -> Option<String> {
+        self.media_marker.clone()
+    }
+
+    fn load_projector(&mut self, path: &Path) -> Result<(), StrataError> {
+        self.plugin.api.require(CAP_VISION, "image input")?;
+        let cpath = make_cstring(path.to_str().ok_or("projector path not valid UTF-8")?)?;
+        let rc = unsafe { (self.plugin.api.llm.load_projector)(self.session, cpath.as_ptr()) };
+        if rc != ERR_OK {
+            return Err(self.error(rc, "load_projector"));
+        }
+        let marker = unsafe {
+            let s = (self.plugin.api.llm.media_marker)(self.session);
+            take_plugin_string(self.plugin.api.llm.free_string, s)
+        };
+        self.media_marker = (!marker.is_empty()).then_some(marker);
+        Ok(())
+    }
+
+    fn evaluate_with_media(
+        &mut self,
+        prompt: &str,
+        images: &[Vec<u8>],
+        n_past: i32,
+    ) -> Result<i32, StrataError> {
+        self.plugin.api.require(CAP_VISION, "image input")?;
+        let cprompt = make_cstring(prompt)?;
+        let slices: Vec<ByteSlice> = images
+            .iter()
+            .map(|img| ByteSlice {
+                ptr: img.as_ptr(),
+                len: img.len(),
+            })
+            .collect();
+        let rc = unsafe {
+            (self.plugin.api.llm.evaluate_media)(
+                self.session,
+                cprompt.as_ptr(),
+                slices.as_ptr(),
+                slices.len(),
+                n_past,
+            )
+        };
+        if rc >= 0 {
+            return Ok(rc);
+        }
+        Err(self.error(rc, "evaluate_media"))
+    }
+
+    fn load_lora(&mut self, path: &Path) -> Result<i32, StrataError> {
+        self.plugin.api.require(CAP_LORA, "LoRA adapters")?;
+        let cpath = make_cstring(path.to_str().ok_or("adapter path not valid UTF-8")?)?;
+        let id = unsafe { (self.plugin.api.llm.load_lora)(self.session, cpath.as_ptr()) };
+        if id >= 0 {
+            return Ok(id);
+        }
+        Err(self.error(id, "load_lora"))
+    }
+
+    fn set_lora(&mut self, id: i32, scale: f32) -> Result<(), StrataError> {
+        self.plugin.api.require(CAP_LORA, "LoRA adapters")?;
+        let rc = unsafe { (self.plugin.api.llm.set_lora)(self.session, id, scale) };
+        if rc == ERR_OK {
+            return Ok(());
+        }
+        Err(self.error(rc, "set_lora"))
+    }
+
+    fn remove_lora(&mut self, id: i32) -> Result<(), StrataError> {
+        self.plugin.api.require(CAP_LORA, "LoRA adapters")?;
+        let rc = unsafe { (self.plugin.api.llm.remove_lora)(self.session, id) };
+        if rc == ERR_OK {
+            return Ok(());
+        }
+        Err(self.error(rc, "remove_lora"))
+    }
+
+    fn lora_adapters(&self) -> Vec<LoraAdapter> {
+        if !self.plugin.api.supports(CAP_LORA) {
+            return Vec::new();
+        }
+        let js = unsafe {
+            let s = (self.plugin.api.llm.lora_list_json)(self.session);
+            take_plugin_string(self.plugin.api.llm.free_string, s)
+        };
+        serde_json::from_str(&js).unwrap_or_default()
+    }
+
+    fn set_control_vectors(&mut self, vectors: &[ControlVector]) -> Result<(), StrataError> {
+        if vectors.is_empty() && !self.plugin.api.supports(CAP_CONTROL_VECTORS) {
+            return Ok(());
+        }
+        self.plugin
+            .api
+            .require(CAP_CONTROL_VECTORS, "control vectors")?;
+        let js = serde_json::to_string(vectors).map_err(|e| format!("serialize: {e}"))?;
+        let cjs = make_cstring(&js)?;
+        let rc =
+            unsafe { (self.plugin.api.llm.set_control_vectors_json)(self.session, cjs.as_ptr()) };
+        if rc == ERR_OK {
+            return Ok(());
+        }
+        Err(self.error(rc, "set_control_vectors"))
+    }
+
+    fn memory_report(&self) -> Option<MemoryReport> {
+        if !self.plugin.api.supports(CAP_MEMORY_REPORT) {
+            return None;
+        }
+        let js = unsafe {
+            let s = (self.plugin.api.llm.memory_report_json)(self.session);
+            take_plugin_string(self.plugin.api.llm.free_string, s)
+        };
+        serde_json::from_str(&js).ok()
+    }
+
+    fn take_perf_counters(&mut self) -> Option<PerfCounters> {
+        let take = self.plugin.api.llm.take_perf_json?;
+        let js = unsafe { take_plugin_string(self.plugin.api.llm.free_string, take(self.session)) };
+        serde_json::from_str(&js).ok()
+    }
+
     fn detokenize_range(
         &self,
         token_history: &[strata_abi::token::Token],
         start: usize,
         remove_special: bool,
         unparse_special: bool,
-    ) -> Result<Vec<u8>, String> {
+    ) -> Result<Vec<u8>, StrataError> {
         let slice = &token_history[start..];
         if slice.is_empty() {
             return Ok(Vec::new());
@@ -1,72 +1,191 @@
-use super::locate::locate_plugin_binary;
-use crate::runtime::{default_runtime_root, runtime_cpu_fallback_path};
+use super::locate::candidate_plugin_binaries;
 use libloading::Library;
-use std::sync::OnceLock;
-use strata_abi::ffi::{PLUGIN_ENTRY_SYMBOL, PluginApi, PluginEntryFn, STRATA_ABI_VERSION};
+use std::{
+    ffi::CStr,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+use strata_abi::ffi::{PLUGIN_ENTRY_SYMBOL, PluginApi, PluginEntryFn, negotiate};
 
+/// A loaded plugin; the library is unloaded when the last `Arc` (registry
+/// entry or live `PluginBackend`) goes away.
 pub(crate) struct LoadedPlugin {
     #[allow(dead_code)]
     _lib: Library,
-    pub(crate) api: &'static PluginApi,
+    /// Negotiated copy of the plugin's tables; the pointers inside are valid
+    /// for as long as `_lib` is loaded, i.e. this value lives.
+    pub(crate) api: PluginApi,
+    /// `PluginInfo::id` ("llama").
+    pub(crate) id: String,
+    pub(crate) semver: String,
+    pub(crate) path: PathBuf,
 }
 
 unsafe impl Send for LoadedPlugin {}
 unsafe impl Sync for LoadedPlugin {}
 
-static PLUGIN: OnceLock<Result<LoadedPlugin, String>> = OnceLock::new();
-
-pub fn load_plugin_once() -> Result<&'static LoadedPlugin, String> {
-    PLUGIN
-        .get_or_init(|| {
-            if let Some(primary) = locate_plugin_binary() {
-                match unsafe { Library::new(&primary) } {
-                    Ok(lib) => return init_loaded(lib),
-                    Err(e) => eprintln!(
-                        "[plugin] failed to load active plugin {}: {e}",
-                        primary.display()
-                    ),
-                }
-            }
+impl LoadedPlugin {
+    /// Ask the plugin whether it can run this model.
+    pub(crate) fn can_handle(&self, model_path: &Path) -> bool {
+        let Some(cpath) = model_path
+            .to_str()
+            .and_then(|s| std::ffi::CString::new(s).ok())
+        else {
+            return false;
+        };
+        unsafe { (self.api.metadata.can_handle)(cpath.as_ptr()) }
+    }
+}
+
+/// Every compatible plugin found, in discovery order
+/// (`STRATA_PLUGIN_PATH`, backend runtimes, then the plugins dir).
+#[derive(Default)]
+pub(crate) struct PluginRegistry {
+    pub(crate) plugins: Vec<Arc<LoadedPlugin>>,
+}
+
+static REGISTRY: OnceLock<RwLock<PluginRegistry>> = OnceLock::new();
+
+fn registry_lock() -> &'static RwLock<PluginRegistry> {
+    REGISTRY.get_or_init(|| {
+        let mut registry = PluginRegistry::default();
+        registry.rescan();
+        RwLock::new(registry)
+    })
+}
+
+/// The registry, scanned on first use.
+pub(crate) fn plugin_registry() -> RwLockReadGuard<'static, PluginRegistry> {
+    registry_lock().read().expect("plugin registry poisoned")
+}
+
+pub(crate) fn plugin_registry_mut() -> RwLockWriteGuard<'static, PluginRegistry> {
+    registry_lock().write().expect("plugin registry poisoned")
+}
 
-            if let Some(root) = default_runtime_root() {
-                if let Some(cpu_path) = runtime_cpu_fallback_path(&root) {
-                    if cpu_path.exists() {
-                        eprintln!("[plugin] attempting CPU fallback: {}", cpu_path.display());
-                        match unsafe { Library::new(&cpu_path) } {
-                            Ok(lib) => return init_loaded(lib),
-                            Err(e) => eprintln!(
-                                "[plugin] CPU fallback load failed {}: {e}",
-                                cpu_path.display()
-                            ),
-                        }
+impl PluginRegistry {
+    /// Load every candidate binary whose plugin id isn't loaded yet.
+    /// Returns the ids newly loaded.
+    pub(crate) fn rescan(&mut self) -> Vec<String> {
+        let mut added = Vec::new();
+        for candidates in candidate_plugin_binaries() {
+            // Alternatives for one runtime (active variant, CPU fallback):
+            // the first that loads wins.
+            for path in candidates {
+                match load_one(&path) {
+                    Ok(p) if self.get(&p.id).is_some() => {
+                        log::info!(
+                            "skipping {}: plugin '{}' already loaded",
+                            path.display(),
+                            p.id
+                        );
+                        break;
                     }
+                    Ok(p) => {
+                        log::info!(
+                            "loaded '{}' {} (ABI {}, caps {:#x}) from {}",
+                            p.id,
+                            p.semver,
+                            p.api.info.abi_version,
+                            p.api.info.capabilities,
+                            path.display()
+                        );
+                        added.push(p.id.clone());
+                        self.plugins.push(Arc::new(p));
+                        break;
+                    }
+                    Err(e) => log::warn!("failed to load {}: {e}", path.display()),
                 }
             }
+        }
+        added
+    }
+
+    /// Remove plugin `id` (or all plugins) and unload the libraries. Fails,
+    /// leaving the registry untouched, while any of them still has sessions.
+    pub(crate) fn unload(&mut self, id: Option<&str>) -> Result<Vec<String>, String> {
+        let selected = |p: &Arc<LoadedPlugin>| id.is_none_or(|id| p.id == id);
+        let busy: Vec<&str> = self
+            .plugins
+            .iter()
+            .filter(|p| selected(p) && Arc::strong_count(p) > 1)
+            .map(|p| p.id.as_str())
+            .collect();
+        if !busy.is_empty() {
+            return Err(format!(
+                "Plugin still has live sessions: {}",
+                busy.join(", ")
+            ));
+        }
+        let mut ids = Vec::new();
+        self.plugins.retain(|p| {
+            let gone = selected(p);
+            if gone {
+                ids.push(p.id.clone());
+            }
+            !gone
+        });
+        Ok(ids)
+    }
+
+    pub(crate) fn get(&self, id: &str) -> Option<&Arc<LoadedPlugin>> {
+        self.plugins.iter().find(|p| p.id == id)
+    }
 
-            Err("plugin not found or failed to load; try installing/repairing the runtime".into())
-        })
-        .as_ref()
-        .map_err(|e| e.clone())
+    /// Pick the plugin that runs `model_path`: the pinned one if it is
+    /// loaded, else the first whose `metadata.can_handle` accepts the file.
+    pub(crate) fn plugin_for_model(
+        &self,
+        model_path: &Path,
+        pinned: Option<&str>,
+    ) -> Result<Arc<LoadedPlugin>, String> {
+        if self.plugins.is_empty() {
+            return Err(
+                "plugin not found or failed to load; try installing/repairing the runtime".into(),
+            );
+        }
+        if let Some(id) = pinned {
+            match self.get(id) {
+                Some(p) => return Ok(Arc::clone(p)),
+                None => log::warn!("pinned plugin '{id}' is not loaded; routing by model"),
+            }
+        }
+        self.plugins
+            .iter()
+            .find(|p| p.can_handle(model_path))
+            .cloned()
+            .ok_or_else(|| {
+                let ids: Vec<&str> = self.plugins.iter().map(|p| p.id.as_str()).collect();
+                format!(
+                    "No runtime plugin can run {} (loaded: {})",
+                    model_path.display(),
+                    ids.join(", ")
+                )
+            })
+    }
 }
 
-fn init_loaded(lib: Library) -> Result<LoadedPlugin, String> {
+fn load_one(path: &Path) -> Result<LoadedPlugin, String> {
+    let lib = unsafe { Library::new(path) }.map_err(|e| e.to_string())?;
     let entry: libloading::Symbol<PluginEntryFn> = unsafe {
         lib.get(PLUGIN_ENTRY_SYMBOL.as_bytes())
             .map_err(|e| format!("missing symbol {}: {e}", PLUGIN_ENTRY_SYMBOL))?
     };
 
-    let api_ptr = unsafe { entry() };
-    if api_ptr.is_null() {
-        return Err("plugin entry returned null".into());
-    }
+    let api = unsafe { negotiate(entry()) }?;
+    strata_core::logging::attach_plugin_logger(&api);
 
-    let api = unsafe { &*api_ptr };
-    if api.info.abi_version != STRATA_ABI_VERSION {
-        return Err(format!(
-            "ABI mismatch: host={} plugin={}",
-            STRATA_ABI_VERSION, api.info.abi_version
-        ));
-    }
+    let c_str = |p: *const std::ffi::c_char| {
+        (!p.is_null()).then(|| unsafe { CStr::from_ptr(p) }.to_string_lossy().into_owned())
+    };
+    let id = c_str(api.info.id).ok_or("plugin has no id")?;
+    let semver = c_str(api.info.semver).unwrap_or_default();
 
-    Ok(LoadedPlugin { _lib: lib, api })
+    Ok(LoadedPlugin {
+        _lib: lib,
+        api,
+        id,
+        semver,
+        path: path.to_path_buf(),
+    })
 }
@@ -6,41 +6,102 @@ use std::{
     env,
     path::{Path, PathBuf},
 };
+use strata_hwprof::{plugins_dir, runtimes_dir};
 
 const ENV_PLUGIN_PATH: &str = "STRATA_PLUGIN_PATH";
 const ENV_RUNTIME_DIR: &str = "STRATA_RUNTIME_DIR";
+const ENV_PLUGIN_HOST: &str = "STRATA_PLUGIN_HOST";
+
+/// Plugin binaries to try, grouped per plugin: each inner list holds
+/// alternatives (a runtime's active variant, then its CPU fallback) of which
+/// the first that loads is used. Order: `STRATA_PLUGIN_PATH`, each backend
+/// runtime (`STRATA_RUNTIME_DIR` or `runtimes/<backend>`), then every dylib
+/// in the plugins dir.
+pub(crate) fn candidate_plugin_binaries() -> Vec<Vec<PathBuf>> {
+    let mut out = Vec::new();
 
-pub(crate) fn locate_plugin_binary() -> Option<PathBuf> {
     if let Ok(p) = env::var(ENV_PLUGIN_PATH) {
         let p = PathBuf::from(p);
         if p.exists() {
-            eprintln!("[plugin] {ENV_PLUGIN_PATH} = {}", p.display());
-            return Some(p);
+            log::info!("{ENV_PLUGIN_PATH} = {}", p.display());
+            out.push(vec![p]);
         } else {
-            eprintln!(
-                "[plugin] {ENV_PLUGIN_PATH} points to missing file: {}",
-                p.display()
-            );
+            log::warn!("{ENV_PLUGIN_PATH} points to missing file: {}", p.display());
         }
     }
 
-    let root = env::var(ENV_RUNTIME_DIR)
+    let mut roots: Vec<PathBuf> = env::var(ENV_RUNTIME_DIR)
         .ok()
         .map(PathBuf::from)
-        .or_else(default_runtime_root)?;
-
-    if let (Some(dir), Some(file)) = (
-        runtime_current_lib_dir(&root),
-        runtime_plugin_filename(&root),
-    ) {
-        let p = dir.join(file);
-        if p.exists() {
-            eprintln!("[plugin] from runtime.json (active): {}", p.display());
-            return Some(p);
+        .or_else(default_runtime_root)
+        .into_iter()
+        .collect();
+    for dir in sorted_entries(&runtimes_dir()).filter(|p| p.is_dir()) {
+        if !roots.contains(&dir) {
+            roots.push(dir);
+        }
+    }
+    for root in roots {
+        let mut group = Vec::new();
+        if let (Some(dir), Some(file)) = (
+            runtime_current_lib_dir(&root),
+            runtime_plugin_filename(&root),
+        ) {
+            group.push(dir.join(file));
+        }
+        group.extend(runtime_cpu_fallback_path(&root));
+        group.retain(|p| p.exists());
+        group.dedup();
+        if !group.is_empty() {
+            out.push(group);
         }
     }
 
-    None
+    out.extend(
+        sorted_entries(&plugins_dir())
+            .filter(|p| is_dylib(p))
+            .map(|p| vec![p]),
+    );
+    out
+}
+
+/// The out-of-process plugin host: `STRATA_PLUGIN_HOST`, else
+/// `strata-plugin-host` next to the app executable.
+pub(crate) fn plugin_host_binary() -> Result<PathBuf, String> {
+    if let Ok(p) = env::var(ENV_PLUGIN_HOST) {
+        return Ok(PathBuf::from(p));
+    }
+    let exe = env::current_exe().map_err(|e| format!("current_exe: {e}"))?;
+    let host = exe
+        .with_file_name("strata-plugin-host")
+        .with_extension(env::consts::EXE_EXTENSION);
+    if host.exists() {
+        Ok(host)
+    } else {
+        Err(format!(
+            "plugin host not found at {} (set {ENV_PLUGIN_HOST})",
+            host.display()
+        ))
+    }
+}
+
+fn sorted_entries(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .collect();
+    paths.sort();
+    paths.into_iter()
+}
+
+fn is_dylib(path: &Path) -> bool {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) if cfg!(target_os = "linux") => ext == "so",
+        Some(ext) if cfg!(target_os = "macos") => ext == "dylib",
+        Some(ext) if cfg!(target_os = "windows") => ext.eq_ignore_ascii_case("dll"),
+        _ => false,
+    }
 }
 
 pub(crate) fn locate_runtime_ll_lib(_plugin_path: &Path) -> Option<PathBuf> {
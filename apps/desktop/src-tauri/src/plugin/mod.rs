@@ -1,6 +1,136 @@
-pub mod locate;
-pub mod loader;
 pub mod backend;
+pub mod loader;
+pub mod locate;
+pub mod remote;
+pub mod runtime_backend;
 
 pub use backend::PluginBackend;
-pub use loader::load_plugin_once;
+pub(crate) use loader::plugin_registry;
+pub use remote::RemotePluginBackend;
+pub use runtime_backend::RuntimeBackend;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::app_state::AppState;
+
+pub const PLUGIN_RELOAD_EVENT: &str = "strata://plugin-reload";
+
+/// A loaded runtime plugin, for the UI's per-model plugin picker.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuntimePluginInfo {
+    pub id: String,
+    pub semver: String,
+    pub path: String,
+}
+
+/// Steps of a plugin reload, emitted on [`PLUGIN_RELOAD_EVENT`].
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginReloadStage {
+    /// Stopping generation and destroying the active session.
+    Draining,
+    Unloading,
+    Loading,
+    /// Re-creating the engine for the active model.
+    Restoring,
+    Done,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginReloadProgress {
+    pub stage: PluginReloadStage,
+    /// Plugin ids affected by the step, or the error for `Failed`.
+    pub detail: Option<String>,
+}
+
+fn loaded_plugins() -> Vec<RuntimePluginInfo> {
+    plugin_registry()
+        .plugins
+        .iter()
+        .map(|p| RuntimePluginInfo {
+            id: p.id.clone(),
+            semver: p.semver.clone(),
+            path: p.path.display().to_string(),
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub async fn list_runtime_plugins() -> Result<Vec<RuntimePluginInfo>, String> {
+    // First call dlopens every plugin; keep it off the main thread.
+    tauri::async_runtime::spawn_blocking(loaded_plugins)
+        .await
+        .map_err(|e| format!("join error: {e}"))
+}
+
+/// Unload plugin `plugin_id` (or all plugins), rescan the runtimes and
+/// plugins dirs, and re-create the engine for the active model if one was
+/// loaded. Use after installing or switching a runtime variant.
+#[tauri::command]
+pub async fn reload_runtime_plugins(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    plugin_id: Option<String>,
+) -> Result<Vec<RuntimePluginInfo>, String> {
+    let state2 = AppState {
+        memory: std::sync::Arc::clone(&state.memory),
+        current_stop: std::sync::Arc::clone(&state.current_stop),
+        engine: std::sync::Arc::clone(&state.engine),
+    };
+    let app2 = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        reload_plugins(&app2, &state2, plugin_id.as_deref())
+    })
+    .await
+    .map_err(|e| format!("join error: {e}"))
+    .and_then(|r| r);
+    if let Err(e) = &result {
+        emit_reload(&app, PluginReloadStage::Failed, Some(e.clone()));
+    }
+    result
+}
+
+fn emit_reload(app: &AppHandle, stage: PluginReloadStage, detail: Option<String>) {
+    let _ = app.emit(PLUGIN_RELOAD_EVENT, PluginReloadProgress { stage, detail });
+}
+
+fn reload_plugins(
+    app: &AppHandle,
+    state: &AppState,
+    plugin_id: Option<&str>,
+) -> Result<Vec<RuntimePluginInfo>, String> {
+    emit_reload(app, PluginReloadStage::Draining, None);
+    let drained = crate::engine::drain_engine(state);
+
+    let detail = plugin_id.map(str::to_string);
+    emit_reload(app, PluginReloadStage::Unloading, detail);
+    // The metadata service dlopens the same files; while it holds them, the
+    // runtime unload would not really release the libraries.
+    let released = strata_core::metadata::unload_metadata_plugins();
+    log::debug!("released {released} metadata plugin(s)");
+    let unloaded = loader::plugin_registry_mut().unload(plugin_id);
+    let added = unloaded.map(|ids| {
+        log::info!("unloaded: {}", ids.join(", "));
+        emit_reload(app, PluginReloadStage::Loading, None);
+        loader::plugin_registry_mut().rescan()
+    });
+    let plugins = strata_hwprof::plugins_dir();
+    if let Err(e) = strata_core::metadata::load_metadata_plugins(&plugins) {
+        log::error!(
+            "reloading metadata plugins from {} failed: {e}",
+            plugins.display()
+        );
+    }
+
+    // Bring the engine back even if unloading failed (the old plugin is still there).
+    if drained.is_some() {
+        emit_reload(app, PluginReloadStage::Restoring, None);
+        crate::engine::rebuild_engine(app, state, drained)?;
+    }
+    let added = added?;
+
+    emit_reload(app, PluginReloadStage::Done, Some(added.join(", ")));
+    Ok(loaded_plugins())
+}
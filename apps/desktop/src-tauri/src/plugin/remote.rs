@@ -0,0 +1,765 @@
+//! `LLMBackend` over a runtime plugin loaded in a `strata-plugin-host`
+//! child process (protocol in `strata_plugin_host`). A crash in the plugin
+//! only kills the host: the next call restarts it, re-creates the session,
+//! restores projector / adapters / control vectors, replays the KV tokens and
+//! retries once.
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    io::{self, BufRead, BufReader, BufWriter},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    process::{Child, ChildStderr, Command, Stdio},
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use strata_abi::{
+    backend::{ChatTurn, ControlVector, LLMBackend, LoraAdapter, PromptFlavor},
+    error::StrataError,
+    metadata::ModelCoreInfo,
+    session::{MemoryReport, PerfCounters, SessionParams},
+    token::Token,
+};
+use strata_plugin_host::{
+    ENV_TOKEN, Hello, LogLine, Reply, Request, Response, SessionId, write_frame,
+};
+
+use super::{loader::plugin_registry, locate::plugin_host_binary};
+
+/// How long a freshly spawned host has to connect and say hello.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Tokens per `evaluate` when replaying KV into a restarted host.
+const REPLAY_CHUNK: usize = 512;
+
+/// A running host process and its connection.
+struct HostProcess {
+    child: Child,
+    reader: BufReader<TcpStream>,
+    writer: BufWriter<TcpStream>,
+}
+
+impl HostProcess {
+    fn spawn(plugin_path: &Path) -> Result<Self, String> {
+        let exe = plugin_host_binary()?;
+        let listener =
+            TcpListener::bind("127.0.0.1:0").map_err(|e| format!("plugin host listen: {e}"))?;
+        let addr = listener.local_addr().map_err(|e| e.to_string())?;
+        let token = handshake_token();
+
+        let mut child = Command::new(&exe)
+            .arg("--plugin")
+            .arg(plugin_path)
+            .arg("--connect")
+            .arg(addr.to_string())
+            .arg("--log-level")
+            .arg(strata_core::logging::filter_to_raw(log::max_level()).to_string())
+            .env(ENV_TOKEN, &token)
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("spawn {}: {e}", exe.display()))?;
+        if let Some(stderr) = child.stderr.take() {
+            forward_host_log(stderr);
+        }
+
+        match Self::accept(&listener, &mut child, &token) {
+            Ok((reader, writer)) => Ok(Self {
+                child,
+                reader,
+                writer,
+            }),
+            Err(e) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                Err(e)
+            }
+        }
+    }
+
+    fn accept(
+        listener: &TcpListener,
+        child: &mut Child,
+        token: &str,
+    ) -> Result<(BufReader<TcpStream>, BufWriter<TcpStream>), String> {
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let deadline = Instant::now() + CONNECT_TIMEOUT;
+        let stream = loop {
+            match listener.accept() {
+                Ok((stream, _)) => break stream,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => return Err(format!("plugin host accept: {e}")),
+            }
+            if let Ok(Some(status)) = child.try_wait() {
+                return Err(format!("plugin host exited before connecting ({status})"));
+            }
+            if Instant::now() >= deadline {
+                return Err("plugin host did not connect in time".into());
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        };
+        stream.set_nonblocking(false).map_err(|e| e.to_string())?;
+        let _ = stream.set_nodelay(true);
+        stream
+            .set_read_timeout(Some(CONNECT_TIMEOUT))
+            .map_err(|e| e.to_string())?;
+
+        let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+        let hello: Hello = strata_plugin_host::recv(&mut reader)
+            .map_err(|e| format!("plugin host handshake: {e}"))?;
+        if hello.token != token {
+            return Err("plugin host handshake: token mismatch".into());
+        }
+        let desc = hello.plugin?;
+        log::info!(
+            "'{}' {} running in host pid {}",
+            desc.id,
+            desc.semver,
+            child.id()
+        );
+        // Calls such as a long prefill may legitimately take a while.
+        stream.set_read_timeout(None).map_err(|e| e.to_string())?;
+        Ok((reader, BufWriter::new(stream)))
+    }
+
+    fn roundtrip(&mut self, req: &Request, images: &[Vec<u8>]) -> io::Result<Response> {
+        strata_plugin_host::send(&mut self.writer, req)?;
+        for img in images {
+            write_frame(&mut self.writer, img)?;
+        }
+        io::Write::flush(&mut self.writer)?;
+        strata_plugin_host::recv(&mut self.reader)
+    }
+
+    /// Ask the host to exit, killing it if it doesn't within a second.
+    fn shutdown(mut self) {
+        let _ = strata_plugin_host::send(&mut self.writer, &Request::Shutdown);
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Re-emit the host's stderr through our logger until it exits: `LogLine`
+/// records as themselves, any other line as a warning.
+fn forward_host_log(stderr: ChildStderr) {
+    let spawned = std::thread::Builder::new()
+        .name("plugin-host-log".into())
+        .spawn(move || {
+            for line in BufReader::new(stderr).lines() {
+                let Ok(line) = line else { break };
+                match serde_json::from_str::<LogLine>(&line) {
+                    Ok(r) => {
+                        strata_core::logging::log_plugin_record(r.level, &r.target, &r.message)
+                    }
+                    Err(_) if line.trim().is_empty() => {}
+                    Err(_) => log::warn!(target: "plugin-host", "{line}"),
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        log::warn!("cannot forward plugin host log: {e}");
+    }
+}
+
+/// Per-spawn secret so only our child can claim the listening port.
+fn handshake_token() -> String {
+    let mut h = RandomState::new().build_hasher();
+    h.write_u32(std::process::id());
+    h.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default(),
+    );
+    let a = h.finish();
+    let b = RandomState::new().build_hasher().finish();
+    format!("{a:016x}{b:016x}")
+}
+
+/// An adapter as the engine knows it; `remote_id` is its id in the current host.
+struct RemoteLora {
+    id: i32,
+    remote_id: i32,
+    path: String,
+    scale: Option<f32>,
+}
+
+/// Connection plus everything needed to rebuild the session after a crash.
+struct Remote {
+    plugin_path: PathBuf,
+    model_path: String,
+    params_json: String,
+    host: Option<HostProcess>,
+    session: SessionId,
+    projector: Option<String>,
+    loras: Vec<RemoteLora>,
+    control_vectors_json: Option<String>,
+    /// Tokens currently in the KV cache, in order.
+    kv_tokens: Vec<i32>,
+    /// False once media was evaluated: image embeddings can't be replayed.
+    kv_replayable: bool,
+}
+
+impl Remote {
+    /// Send one request built for the current session; on a broken
+    /// connection restart the host, restore state and retry once.
+    fn call(
+        &mut self,
+        make: impl Fn(SessionId) -> Request,
+        images: &[Vec<u8>],
+    ) -> Result<Reply, StrataError> {
+        if let Some(host) = self.host.as_mut() {
+            match host.roundtrip(&make(self.session), images) {
+                Ok(resp) => return resp,
+                Err(e) => log::warn!("plugin host connection lost ({e}); restarting"),
+            }
+        }
+        self.restart()?;
+        let host = self.host.as_mut().expect("host just restarted");
+        match host.roundtrip(&make(self.session), images) {
+            Ok(resp) => resp,
+            Err(e) => {
+                self.kill();
+                Err(format!("plugin host failed again after restart: {e}").into())
+            }
+        }
+    }
+
+    /// Send a request without restart (used while restoring a new host).
+    fn call_once(&mut self, req: &Request) -> Result<Reply, StrataError> {
+        let host = self.host.as_mut().ok_or("plugin host not running")?;
+        host.roundtrip(req, &[])
+            .map_err(|e| format!("plugin host: {e}"))?
+    }
+
+    fn kill(&mut self) {
+        if let Some(mut host) = self.host.take() {
+            let _ = host.child.kill();
+            let _ = host.child.wait();
+        }
+    }
+
+    fn restart(&mut self) -> Result<(), StrataError> {
+        self.kill();
+        self.host = Some(HostProcess::spawn(&self.plugin_path)?);
+        let result = self.restore();
+        if result.is_err() {
+            self.kill();
+        }
+        result
+    }
+
+    fn restore(&mut self) -> Result<(), StrataError> {
+        self.session = self.create_session()?;
+        let session = self.session;
+
+        if let Some(path) = self.projector.clone() {
+            self.call_once(&Request::LoadProjector { session, path })?;
+        }
+        for i in 0..self.loras.len() {
+            let path = self.loras[i].path.clone();
+            let rid = self.int(&Request::LoadLora { session, path })?;
+            self.loras[i].remote_id = rid;
+            if let Some(scale) = self.loras[i].scale {
+                self.call_once(&Request::SetLora {
+                    session,
+                    id: rid,
+                    scale,
+                })?;
+            }
+        }
+        if let Some(vectors_json) = self.control_vectors_json.clone() {
+            self.call_once(&Request::SetControlVectorsJson {
+                session,
+                vectors_json,
+            })?;
+        }
+
+        if !self.kv_replayable {
+            // The request being retried assumes the lost KV; fail it. The next
+            // restart (or the engine's cold prefill) starts from an empty cache.
+            self.kv_tokens.clear();
+            self.kv_replayable = true;
+            return Err(
+                "plugin host restarted but the KV cache held image embeddings, \
+                 which can't be replayed; generation stopped"
+                    .into(),
+            );
+        }
+        let tokens = std::mem::take(&mut self.kv_tokens);
+        for (i, chunk) in tokens.chunks(REPLAY_CHUNK).enumerate() {
+            self.call_once(&Request::Evaluate {
+                session,
+                tokens: chunk.to_vec(),
+                n_past: (i * REPLAY_CHUNK) as i32,
+            })
+            .map_err(|e| e.context("KV replay failed"))?;
+        }
+        self.kv_tokens = tokens;
+        Ok(())
+    }
+
+    fn create_session(&mut self) -> Result<SessionId, StrataError> {
+        match self.call_once(&Request::CreateSession {
+            path: self.model_path.clone(),
+            params_json: self.params_json.clone(),
+        })? {
+            Reply::Session(id) => Ok(id),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    fn int(&mut self, req: &Request) -> Result<i32, StrataError> {
+        match self.call_once(req)? {
+            Reply::Int(v) => Ok(v),
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    fn remote_lora_id(&self, id: i32) -> i32 {
+        self.loras
+            .iter()
+            .find(|l| l.id == id)
+            .map_or(id, |l| l.remote_id)
+    }
+}
+
+fn unexpected(reply: &Reply) -> StrataError {
+    format!("plugin host: unexpected reply {reply:?}").into()
+}
+
+fn expect_int(reply: Reply) -> Result<i32, StrataError> {
+    match reply {
+        Reply::Int(v) => Ok(v),
+        other => Err(unexpected(&other)),
+    }
+}
+
+fn expect_text(reply: Reply) -> Result<String, StrataError> {
+    match reply {
+        Reply::Text(s) => Ok(s),
+        other => Err(unexpected(&other)),
+    }
+}
+
+pub struct RemotePluginBackend {
+    remote: Mutex<Remote>,
+    eos_token_id: i32,
+    bos_token_id: Option<i32>,
+    ctx_len_hint: Option<usize>,
+    chat_template: Option<String>,
+    flavor: PromptFlavor,
+    stop_strings: Vec<String>,
+    /// Set once a projector is loaded; `None` means text-only.
+    media_marker: Option<String>,
+}
+
+impl Drop for RemotePluginBackend {
+    fn drop(&mut self) {
+        let remote = self.remote.get_mut().unwrap_or_else(|e| e.into_inner());
+        if let Some(host) = remote.host.take() {
+            host.shutdown();
+        }
+    }
+}
+
+impl RemotePluginBackend {
+    /// Like `PluginBackend::load_routed`, but the chosen plugin runs in a
+    /// `strata-plugin-host` child process.
+    pub fn load_routed<P: AsRef<Path>>(
+        model_path: P,
+        params: &SessionParams,
+        pinned: Option<&str>,
+    ) -> Result<Self, StrataError> {
+        let model_path = model_path.as_ref();
+        let plugin_path = plugin_registry()
+            .plugin_for_model(model_path, pinned)?
+            .path
+            .clone();
+        let mut remote = Remote {
+            plugin_path,
+            model_path: model_path
+                .to_str()
+                .ok_or("model path not valid UTF-8")?
+                .to_string(),
+            params_json: serde_json::to_string(params)
+                .map_err(|e| format!("serialize SessionParams: {e}"))?,
+            host: None,
+            session: 0,
+            projector: None,
+            loras: Vec::new(),
+            control_vectors_json: None,
+            kv_tokens: Vec::new(),
+            kv_replayable: true,
+        };
+        remote.restart()?;
+
+        // Pull metadata to get BOS/EOS, context length hint and the raw chat template
+        let path = remote.model_path.clone();
+        let meta_json =
+            expect_text(remote.call(|_| Request::CollectJson { path: path.clone() }, &[])?)?;
+        let meta = serde_json::from_str::<ModelCoreInfo>(&meta_json).ok();
+        let eos = meta.as_ref().and_then(|m| m.eos_token_id).unwrap_or(-1);
+        let bos = meta.as_ref().and_then(|m| m.bos_token_id);
+        // Prefer the session's real window (n_ctx may be below the training length).
+        let session_ctx =
+            expect_int(remote.call(|session| Request::ContextWindowHint { session }, &[])?)?;
+        let ctx_hint = if session_ctx > 0 {
+            Some(session_ctx as usize)
+        } else {
+            meta.as_ref()
+                .and_then(|m| m.context_length)
+                .map(|c| c as usize)
+        };
+        let chat_template = meta.and_then(|m| m.chat_template);
+
+        let flavor = expect_text(remote.call(|session| Request::PromptFlavor { session }, &[])?)?;
+        let flavor = PromptFlavor::from_name(&flavor).unwrap_or(PromptFlavor::ChatMl);
+        let stops_json =
+            expect_text(remote.call(|session| Request::StopStringsJson { session }, &[])?)?;
+        let stop_strings = serde_json::from_str::<Vec<String>>(&stops_json).unwrap_or_default();
+
+        Ok(Self {
+            remote: Mutex::new(remote),
+            eos_token_id: eos,
+            bos_token_id: bos,
+            ctx_len_hint: ctx_hint,
+            chat_template,
+            flavor,
+            stop_strings,
+            media_marker: None,
+        })
+    }
+
+    fn remote(&self) -> MutexGuard<'_, Remote> {
+        self.remote.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Run a call returning a plugin string.
+    fn text(&self, make: impl Fn(SessionId) -> Request) -> Result<String, StrataError> {
+        expect_text(self.remote().call(make, &[])?)
+    }
+}
+
+impl LLMBackend for RemotePluginBackend {
+    fn load<P: AsRef<Path>>(model_path: P) -> Result<Self, StrataError> {
+        <Self as LLMBackend>::load_with_params(model_path, &SessionParams::default())
+    }
+
+    fn load_with_params<P: AsRef<Path>>(
+        model_path: P,
+        params: &SessionParams,
+    ) -> Result<Self, StrataError> {
+        Self::load_routed(model_path, params, None)
+    }
+
+    fn tokenize(&self, text: &str) -> Result<Vec<Token>, StrataError> {
+        let reply = self.remote().call(
+            |session| Request::Tokenize {
+                session,
+                text: text.to_string(),
+            },
+            &[],
+        )?;
+        let Reply::Ints(v) = reply else {
+            return Err(unexpected(&reply));
+        };
+        Ok(v.into_iter().map(Token).collect())
+    }
+
+    fn evaluate(&mut self, tokens: &[Token], n_past: i32) -> Result<(), StrataError> {
+        let tmp: Vec<i32> = tokens.iter().map(|t| t.0).collect();
+        let remote = self.remote.get_mut().unwrap_or_else(|e| e.into_inner());
+        remote.call(
+            |session| Request::Evaluate {
+                session,
+                tokens: tmp.clone(),
+                n_past,
+            },
+            &[],
+        )?;
+        remote.kv_tokens.truncate(n_past.max(0) as usize);
+        remote.kv_tokens.extend_from_slice(&tmp);
+        Ok(())
+    }
+
+    fn sample(
+        &mut self,
+        _n_past: i32,
+        params: &strata_abi::sampling::SamplingParams,
+        _token_history: &[Token],
+    ) -> Result<Token, StrataError> {
+        let params = params.normalized();
+        let js = serde_json::to_string(&params).map_err(|e| e.to_string())?;
+        let remote = self.remote.get_mut().unwrap_or_else(|e| e.into_inner());
+        let tok = expect_int(remote.call(
+            |session| Request::SampleJson {
+                session,
+                sampling_json: js.clone(),
+            },
+            &[],
+        )?)?;
+        Ok(Token(tok))
+    }
+
+    fn decode_token(&self, token: Token) -> Result<String, StrataError> {
+        self.text(|session| Request::DecodeToken {
+            session,
+            token: token.0,
+        })
+    }
+
+    fn eos_token(&self) -> Token {
+        Token(self.eos_token_id)
+    }
+
+    fn bos_token(&self) -> Option<Token> {
+        self.bos_token_id.map(Token)
+    }
+
+    fn context_window_hint(&self) -> Option<usize> {
+        self.ctx_len_hint
+    }
+
+    fn chat_template(&self) -> Option<String> {
+        self.chat_template.clone()
+    }
+
+    fn prompt_flavor(&self) -> PromptFlavor {
+        self.flavor
+    }
+
+    fn default_stop_strings(&self) -> Vec<String> {
+        self.stop_strings.clone()
+    }
+
+    fn apply_native_chat_template(&self, turns: &[ChatTurn]) -> Option<String> {
+        if turns.is_empty() {
+            return Some(String::new());
+        }
+        let js = match serde_json::to_string(turns) {
+            Ok(s) => s,
+            Err(e) => {
+                log::warn!("serialize ChatTurn failed: {e}");
+                return None;
+            }
+        };
+        let payload = match self
+            .remote()
+            .call(
+                |session| Request::FormatChatJson {
+                    session,
+                    turns_json: js.clone(),
+                    add_assistant: true,
+                },
+                &[],
+            )
+            .and_then(expect_text)
+        {
+            Ok(s) => s,
+            Err(e) => {
+                log::warn!("format_chat_json failed: {e}");
+                return None;
+            }
+        };
+
+        #[derive(serde::Deserialize)]
+        struct FormattedPrompt {
+            text: String,
+        }
+
+        match serde_json::from_str::<FormattedPrompt>(&payload) {
+            Ok(fp) => Some(fp.text),
+            Err(e) => {
+                log::warn!("malformed FormattedPrompt JSON: {e}; raw={payload}");
+                None
+            }
+        }
+    }
+
+    fn media_marker(&self) -> Option<String> {
+        self.media_marker.clone()
+    }
+
+    fn load_projector(&mut self, path: &Path) -> Result<(), StrataError> {
+        let path = path
+            .to_str()
+            .ok_or("projector path not valid UTF-8")?
+            .to_string();
+        let remote = self.remote.get_mut().unwrap_or_else(|e| e.into_inner());
+        remote.call(
+            |session| Request::LoadProjector {
+                session,
+                path: path.clone(),
+            },
+            &[],
+        )?;
+        remote.projector = Some(path);
+        let marker = expect_text(remote.call(|session| Request::MediaMarker { session }, &[])?)?;
+        self.media_marker = (!marker.is_empty()).then_some(marker);
+        Ok(())
+    }
+
+    fn evaluate_with_media(
+        &mut self,
+        prompt: &str,
+        images: &[Vec<u8>],
+        n_past: i32,
+    ) -> Result<i32, StrataError> {
+        let remote = self.remote.get_mut().unwrap_or_else(|e| e.into_inner());
+        let n = expect_int(remote.call(
+            |session| Request::EvaluateMedia {
+                session,
+                prompt: prompt.to_string(),
+                n_images: images.len(),
+                n_past,
+            },
+            images,
+        )?)?;
+        remote.kv_tokens.truncate(n_past.max(0) as usize);
+        remote.kv_replayable = false;
+        Ok(n)
+    }
+
+    fn load_lora(&mut self, path: &Path) -> Result<i32, StrataError> {
+        let path = path
+            .to_str()
+            .ok_or("adapter path not valid UTF-8")?
+            .to_string();
+        let remote = self.remote.get_mut().unwrap_or_else(|e| e.into_inner());
+        let rid = expect_int(remote.call(
+            |session| Request::LoadLora {
+                session,
+                path: path.clone(),
+            },
+            &[],
+        )?)?;
+        if let Some(l) = remote.loras.iter().find(|l| l.remote_id == rid) {
+            return Ok(l.id);
+        }
+        remote.loras.push(RemoteLora {
+            id: rid,
+            remote_id: rid,
+            path,
+            scale: None,
+        });
+        Ok(rid)
+    }
+
+    fn set_lora(&mut self, id: i32, scale: f32) -> Result<(), StrataError> {
+        let remote = self.remote.get_mut().unwrap_or_else(|e| e.into_inner());
+        let rid = remote.remote_lora_id(id);
+        remote.call(
+            |session| Request::SetLora {
+                session,
+                id: rid,
+                scale,
+            },
+            &[],
+        )?;
+        if let Some(l) = remote.loras.iter_mut().find(|l| l.id == id) {
+            l.scale = Some(scale);
+        }
+        Ok(())
+    }
+
+    fn remove_lora(&mut self, id: i32) -> Result<(), StrataError> {
+        let remote = self.remote.get_mut().unwrap_or_else(|e| e.into_inner());
+        let rid = remote.remote_lora_id(id);
+        remote.call(|session| Request::RemoveLora { session, id: rid }, &[])?;
+        if let Some(l) = remote.loras.iter_mut().find(|l| l.id == id) {
+            l.scale = None;
+        }
+        Ok(())
+    }
+
+    fn lora_adapters(&self) -> Vec<LoraAdapter> {
+        let remote = &mut *self.remote();
+        let js = remote
+            .call(|session| Request::LoraListJson { session }, &[])
+            .and_then(expect_text)
+            .unwrap_or_default();
+        let mut list: Vec<LoraAdapter> = serde_json::from_str(&js).unwrap_or_default();
+        // Report the ids the engine was given, not the current host's.
+        for a in &mut list {
+            if let Some(l) = remote.loras.iter().find(|l| l.remote_id == a.id) {
+                a.id = l.id;
+            }
+        }
+        list
+    }
+
+    fn set_control_vectors(&mut self, vectors: &[ControlVector]) -> Result<(), StrataError> {
+        let js = serde_json::to_string(vectors).map_err(|e| format!("serialize: {e}"))?;
+        let remote = self.remote.get_mut().unwrap_or_else(|e| e.into_inner());
+        remote.call(
+            |session| Request::SetControlVectorsJson {
+                session,
+                vectors_json: js.clone(),
+            },
+            &[],
+        )?;
+        remote.control_vectors_json = (!vectors.is_empty()).then_some(js);
+        Ok(())
+    }
+
+    fn clear_kv_cache(&mut self) {
+        let remote = self.remote.get_mut().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = remote.call(|session| Request::ClearKvCache { session }, &[]) {
+            log::warn!("clear_kv_cache failed: {e}");
+        }
+        remote.kv_tokens.clear();
+        remote.kv_replayable = true;
+    }
+
+    fn kv_len_hint(&self) -> Option<usize> {
+        let n = self
+            .remote()
+            .call(|session| Request::KvLenHint { session }, &[])
+            .and_then(expect_int)
+            .ok()?;
+        (n >= 0).then_some(n as usize)
+    }
+
+    fn memory_report(&self) -> Option<MemoryReport> {
+        let js = self
+            .text(|session| Request::MemoryReportJson { session })
+            .ok()?;
+        serde_json::from_str(&js).ok()
+    }
+
+    fn take_perf_counters(&mut self) -> Option<PerfCounters> {
+        let js = self
+            .text(|session| Request::TakePerfJson { session })
+            .ok()?;
+        serde_json::from_str(&js).ok()
+    }
+
+    fn detokenize_range(
+        &self,
+        token_history: &[Token],
+        start: usize,
+        remove_special: bool,
+        unparse_special: bool,
+    ) -> Result<Vec<u8>, StrataError> {
+        let slice = &token_history[start..];
+        if slice.is_empty() {
+            return Ok(Vec::new());
+        }
+        let tokens: Vec<i32> = slice.iter().map(|t| t.0).collect();
+        let text = self.text(|session| Request::Detokenize {
+            session,
+            tokens: tokens.clone(),
+            remove_special,
+            unparse_special,
+        })?;
+        Ok(text.into_bytes())
+    }
+}
@@ -0,0 +1,182 @@
+use std::path::Path;
+
+use strata_abi::{
+    backend::{ChatTurn, ControlVector, LLMBackend, LoraAdapter, PromptFlavor},
+    error::StrataError,
+    sampling::{BackendSamplingCapabilities, SamplingParams},
+    session::{MemoryReport, PerfCounters, SessionParams},
+    token::Token,
+};
+
+use super::{PluginBackend, RemotePluginBackend};
+
+/// Set to `1` to run runtime plugins in a `strata-plugin-host` child process.
+const ENV_SANDBOX: &str = "STRATA_PLUGIN_SANDBOX";
+
+/// The engine's backend: a plugin loaded in process, or one sandboxed in a
+/// host process that is restarted if the plugin crashes.
+pub enum RuntimeBackend {
+    InProcess(PluginBackend),
+    Sandboxed(Box<RemotePluginBackend>),
+}
+
+fn sandbox_enabled() -> bool {
+    std::env::var(ENV_SANDBOX).is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+impl RuntimeBackend {
+    /// Open a session on the pinned (else routed) plugin, sandboxed if
+    /// `STRATA_PLUGIN_SANDBOX` is set.
+    pub fn load_routed<P: AsRef<Path>>(
+        model_path: P,
+        params: &SessionParams,
+        pinned: Option<&str>,
+    ) -> Result<Self, StrataError> {
+        if sandbox_enabled() {
+            RemotePluginBackend::load_routed(model_path, params, pinned)
+                .map(|b| Self::Sandboxed(Box::new(b)))
+        } else {
+            PluginBackend::load_routed(model_path, params, pinned).map(Self::InProcess)
+        }
+    }
+}
+
+macro_rules! delegate {
+    ($self:ident, $b:ident => $call:expr) => {
+        match $self {
+            RuntimeBackend::InProcess($b) => $call,
+            RuntimeBackend::Sandboxed($b) => $call,
+        }
+    };
+}
+
+impl LLMBackend for RuntimeBackend {
+    fn load<P: AsRef<Path>>(model_path: P) -> Result<Self, StrataError> {
+        <Self as LLMBackend>::load_with_params(model_path, &SessionParams::default())
+    }
+
+    fn load_with_params<P: AsRef<Path>>(
+        model_path: P,
+        params: &SessionParams,
+    ) -> Result<Self, StrataError> {
+        Self::load_routed(model_path, params, None)
+    }
+
+    fn tokenize(&self, text: &str) -> Result<Vec<Token>, StrataError> {
+        delegate!(self, b => b.tokenize(text))
+    }
+
+    fn evaluate(&mut self, tokens: &[Token], n_past: i32) -> Result<(), StrataError> {
+        delegate!(self, b => b.evaluate(tokens, n_past))
+    }
+
+    fn sample(
+        &mut self,
+        n_past: i32,
+        params: &SamplingParams,
+        token_history: &[Token],
+    ) -> Result<Token, StrataError> {
+        delegate!(self, b => b.sample(n_past, params, token_history))
+    }
+
+    fn prompt_flavor(&self) -> PromptFlavor {
+        delegate!(self, b => b.prompt_flavor())
+    }
+
+    fn decode_token(&self, token: Token) -> Result<String, StrataError> {
+        delegate!(self, b => b.decode_token(token))
+    }
+
+    fn eos_token(&self) -> Token {
+        delegate!(self, b => b.eos_token())
+    }
+
+    fn context_window_hint(&self) -> Option<usize> {
+        delegate!(self, b => b.context_window_hint())
+    }
+
+    fn apply_native_chat_template(&self, turns: &[ChatTurn]) -> Option<String> {
+        delegate!(self, b => b.apply_native_chat_template(turns))
+    }
+
+    fn chat_template(&self) -> Option<String> {
+        delegate!(self, b => b.chat_template())
+    }
+
+    fn media_marker(&self) -> Option<String> {
+        delegate!(self, b => b.media_marker())
+    }
+
+    fn load_projector(&mut self, projector_path: &Path) -> Result<(), StrataError> {
+        delegate!(self, b => b.load_projector(projector_path))
+    }
+
+    fn evaluate_with_media(
+        &mut self,
+        prompt: &str,
+        images: &[Vec<u8>],
+        n_past: i32,
+    ) -> Result<i32, StrataError> {
+        delegate!(self, b => b.evaluate_with_media(prompt, images, n_past))
+    }
+
+    fn load_lora(&mut self, path: &Path) -> Result<i32, StrataError> {
+        delegate!(self, b => b.load_lora(path))
+    }
+
+    fn set_lora(&mut self, id: i32, scale: f32) -> Result<(), StrataError> {
+        delegate!(self, b => b.set_lora(id, scale))
+    }
+
+    fn remove_lora(&mut self, id: i32) -> Result<(), StrataError> {
+        delegate!(self, b => b.remove_lora(id))
+    }
+
+    fn lora_adapters(&self) -> Vec<LoraAdapter> {
+        delegate!(self, b => b.lora_adapters())
+    }
+
+    fn set_control_vectors(&mut self, vectors: &[ControlVector]) -> Result<(), StrataError> {
+        delegate!(self, b => b.set_control_vectors(vectors))
+    }
+
+    fn bos_token(&self) -> Option<Token> {
+        delegate!(self, b => b.bos_token())
+    }
+
+    fn default_stop_strings(&self) -> Vec<String> {
+        delegate!(self, b => b.default_stop_strings())
+    }
+
+    fn clear_kv_cache(&mut self) {
+        delegate!(self, b => b.clear_kv_cache())
+    }
+
+    fn kv_len_hint(&self) -> Option<usize> {
+        delegate!(self, b => b.kv_len_hint())
+    }
+
+    fn memory_report(&self) -> Option<MemoryReport> {
+        delegate!(self, b => b.memory_report())
+    }
+
+    fn take_perf_counters(&mut self) -> Option<PerfCounters> {
+        delegate!(self, b => b.take_perf_counters())
+    }
+
+    fn sampling_capabilities(&self) -> BackendSamplingCapabilities {
+        delegate!(self, b => b.sampling_capabilities())
+    }
+
+    fn detokenize_range(
+        &self,
+        token_history: &[Token],
+        start: usize,
+        remove_special: bool,
+        unparse_special: bool,
+    ) -> Result<Vec<u8>, StrataError> {
+        delegate!(self, b => {
+            b.detokenize_range(token_history, start, remove_special, unparse_special)
+        })
+    }
+}
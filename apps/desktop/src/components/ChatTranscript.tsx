@@ -1,5 +1,5 @@
 import React from "react";
-import type { Message } from "../types";
+import type { GenerationStats, Message } from "../types";
 
 import ReactMarkdown from "react-markdown";
 import remarkGfm from "remark-gfm";
@@ -40,6 +40,16 @@ function extractThinkStreaming(s: string): {
 }
 
 // ----- UI bits -----
+function formatStats(s: GenerationStats): string {
+  const parts = [`${s.tokens_per_second.toFixed(1)} tok/s`];
+  if (s.time_to_first_token_ms != null) {
+    parts.push(`TTFT ${(s.time_to_first_token_ms / 1000).toFixed(2)}s`);
+  }
+  parts.push(`${s.generated_tokens} tokens`);
+  parts.push(`prompt ${s.prompt_tokens} (${s.reused_tokens} cached)`);
+  return parts.join(" · ");
+}
+
 function ChevronRight({ className = "h-3.5 w-3.5" }: { className?: string }) {
   return (
     <svg
@@ -153,7 +163,11 @@ export default function ChatTranscript({
         <div className="mx-auto flex max-w-3xl flex-col gap-4">
           {messages.map((m, i) => {
             const aiRaw = m.ai ?? "";
-            const { think, visible, isOpen } = extractThinkStreaming(aiRaw);
+            const extracted = extractThinkStreaming(aiRaw);
+            // Reasoning streamed on its own channel wins over inline <think> tags.
+            const think = m.reasoning?.trim() || extracted.think;
+            const visible = extracted.visible;
+            const isOpen = m.reasoning ? !aiRaw : extracted.isOpen;
 
             return (
               <div key={i} className="flex flex-col gap-2">
@@ -186,6 +200,16 @@ export default function ChatTranscript({
                   </article>
                 )}
 
+                {/* generation timings */}
+                {m.stats && (
+                  <div
+                    className="mr-auto text-[11px] text-slate-500"
+                    title={`prefill ${m.stats.prefill_ms.toFixed(0)} ms (${m.stats.prefill_tokens_per_second.toFixed(1)} tok/s), decode ${m.stats.decode_ms.toFixed(0)} ms, total ${m.stats.total_ms.toFixed(0)} ms`}
+                  >
+                    {formatStats(m.stats)}
+                  </div>
+                )}
+
                 {i < messages.length - 1 && <div className="mt-1 h-px w-full bg-white/5" />}
               </div>
             );
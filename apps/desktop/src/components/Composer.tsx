@@ -5,6 +5,8 @@ export default function Composer({
   onChange,
   onSend,
   onCancel,
+  thinking,
+  onToggleThinking,
 }: {
   value: string;
   disabled: boolean;
@@ -12,6 +14,9 @@ export default function Composer({
   onChange: (v: string) => void;
   onSend: () => void;
   onCancel: () => void;
+  /** Shown only when the model's template supports turning reasoning off. */
+  thinking?: boolean;
+  onToggleThinking?: () => void;
 }) {
   return (
     <footer className="sticky bottom-0 left-0 right-0 border-t border-white/10 bg-[#0B0F1A] px-4 py-3">
@@ -37,6 +42,23 @@ export default function Composer({
           rows={1}
         />
 
+        {onToggleThinking && (
+          <button
+            className={[
+              "shrink-0 rounded-xl border px-3 py-3 text-sm",
+              thinking
+                ? "border-[#8FA2FF]/50 bg-[#8FA2FF]/10 text-slate-100"
+                : "border-white/10 bg-white/5 text-slate-400",
+            ].join(" ")}
+            onClick={onToggleThinking}
+            disabled={isGenerating}
+            title={thinking ? "Reasoning on" : "Reasoning off"}
+            aria-pressed={thinking}
+          >
+            Think
+          </button>
+        )}
+
         {isGenerating ? (
           <button
             className="shrink-0 rounded-xl bg-rose-600 px-4 py-3 text-sm font-semibold text-white hover:bg-rose-700 active:bg-rose-800
@@ -1,5 +1,58 @@
-import React from "react";
-import type { ModelEntry, ModelMeta } from "../types";
+import React, { useEffect, useState } from "react";
+import type {
+  FitReport,
+  FitVerdict,
+  KvCacheType,
+  LoraAdapter,
+  MemoryReport,
+  ModelEntry,
+  ModelMeta,
+  ModelPromptConfig,
+  PromptKind,
+  PluginReloadStage,
+  RuntimePluginInfo,
+  SessionParams,
+} from "../types";
+import {
+  checkModelFit,
+  describeError,
+  getLoraAdapters,
+  getMemoryReport,
+  getModelPromptConfig,
+  getSessionParams,
+  listRuntimePlugins,
+  reloadRuntimePlugins,
+  setKvCacheType,
+  setLoraAdapter,
+  setModelPlugin,
+  setModelPromptKind,
+  setModelTemplateFile,
+} from "../lib/api";
+import { pickTemplateFile } from "../lib/dialog";
+import { onPluginReload, safeUnlisten } from "../lib/events";
+
+const PROMPT_KINDS: { value: string; label: string; kind: PromptKind | null }[] = [
+  { value: "", label: "Model template", kind: null },
+  { value: "ChatMl", label: "ChatML", kind: { kind: "ChatMl" } },
+  { value: "Phi3", label: "Phi-3", kind: { kind: "Phi3" } },
+  { value: "InstBlock", label: "[INST] block", kind: { kind: "InstBlock" } },
+  { value: "UserAssistant", label: "User / Assistant", kind: { kind: "UserAssistant" } },
+  { value: "Plain", label: "Plain", kind: { kind: "Plain" } },
+];
+
+const fileName = (p: string) => p.split(/[\\/]/).pop() ?? p;
+
+const KV_TYPES: KvCacheType[] = ["f16", "q8_0", "q4_0"];
+
+const fmtBytes = (n?: number | null) =>
+  n == null ? "—" : n >= 1024 ** 3 ? `${(n / 1024 ** 3).toFixed(2)} GiB` : `${(n / 1024 ** 2).toFixed(0)} MiB`;
+
+const FIT_LABELS: Record<FitVerdict, { text: string; color: string }> = {
+  fits: { text: "Fits in RAM", color: "text-emerald-300" },
+  needs_gpu_offload: { text: "Needs GPU offload", color: "text-sky-300" },
+  tight: { text: "Tight (may swap)", color: "text-amber-300" },
+  too_large: { text: "Too large", color: "text-rose-300" },
+};
 
 function InfoDot({ colorClass = "bg-green-500" }: { colorClass?: string }) {
   return <span className={`inline-block h-2 w-2 rounded-full ${colorClass}`} aria-hidden="true" />;
@@ -39,7 +92,13 @@ export default function ModelInfoDrawer({
     return { text: "—", color: "bg-slate-500" };
   })();
 
-  const quant = meta?.quantization ?? "—";
+  const quant = meta?.quantization
+    ? meta.output_tensor_type
+      ? `${meta.quantization} with ${meta.output_tensor_type} output`
+      : meta.quantization
+    : "—";
+  const bpw = meta?.bits_per_weight != null ? `${meta.bits_per_weight.toFixed(2)} bpw` : "—";
+  const totalWeights = (meta?.tensor_types ?? []).reduce((n, t) => n + t.n_elements, 0);
   const ctxWin = meta?.context_length ? `${meta.context_length.toLocaleString()} tokens` : "—";
   const vocab = meta?.vocab_size ? meta.vocab_size.toLocaleString() : "—";
   const eosBos =
@@ -60,6 +119,161 @@ export default function ModelInfoDrawer({
 
   const chatTemplate = meta?.has_chat_template ? "Yes" : "No";
 
+  const [promptCfg, setPromptCfg] = useState<ModelPromptConfig | null>(null);
+  const [promptError, setPromptError] = useState<string | null>(null);
+
+  useEffect(() => {
+    setPromptCfg(null);
+    setPromptError(null);
+    if (!selectedModel) return;
+    let cancelled = false;
+    getModelPromptConfig(selectedModel.id)
+      .then((c) => { if (!cancelled) setPromptCfg(c); })
+      .catch((err) => { if (!cancelled) setPromptError(String(err)); });
+    return () => { cancelled = true; };
+  }, [selectedModel]);
+
+  const configured = !!(promptCfg?.prompt_kind || promptCfg?.template_path);
+  const needsTemplate = !!meta && !meta.has_chat_template && !configured;
+
+  const updatePrompt = async (op: () => Promise<ModelPromptConfig>) => {
+    setPromptError(null);
+    try {
+      setPromptCfg(await op());
+    } catch (err) {
+      setPromptError(String(err));
+    }
+  };
+
+  const onKindChange = (value: string) => {
+    if (!selectedModel) return;
+    const kind = PROMPT_KINDS.find((k) => k.value === value)?.kind ?? null;
+    void updatePrompt(() => setModelPromptKind(selectedModel.id, kind));
+  };
+
+  const onPickTemplate = async () => {
+    if (!selectedModel) return;
+    const path = await pickTemplateFile();
+    if (path) void updatePrompt(() => setModelTemplateFile(selectedModel.id, path));
+  };
+
+  const onClearTemplate = () => {
+    if (!selectedModel) return;
+    void updatePrompt(() => setModelTemplateFile(selectedModel.id, null));
+  };
+
+  // Runtime plugins; the picker only matters once more than one is installed.
+  const [plugins, setPlugins] = useState<RuntimePluginInfo[]>([]);
+  const [pluginError, setPluginError] = useState<string | null>(null);
+
+  const [reloadStage, setReloadStage] = useState<PluginReloadStage | null>(null);
+
+  useEffect(() => {
+    listRuntimePlugins().then(setPlugins).catch(() => setPlugins([]));
+    const unlisten = onPluginReload((p) => setReloadStage(p.stage));
+    return () => {
+      void unlisten.then(safeUnlisten);
+    };
+  }, []);
+
+  const onReloadPlugins = async () => {
+    setPluginError(null);
+    try {
+      setPlugins(await reloadRuntimePlugins());
+    } catch (err) {
+      setPluginError(String(err));
+    }
+  };
+  const reloading = !!reloadStage && reloadStage !== "done" && reloadStage !== "failed";
+
+  const onPluginChange = async (value: string) => {
+    if (!selectedModel) return;
+    setPluginError(null);
+    try {
+      setPromptCfg(await setModelPlugin(selectedModel.id, value || null));
+    } catch (err) {
+      setPluginError(String(err));
+    }
+  };
+
+  // LoRA adapters next to the model; attaching loads the engine if needed.
+  const [adapters, setAdapters] = useState<LoraAdapter[]>([]);
+  const [loraScales, setLoraScales] = useState<Record<string, number>>({});
+  const [loraBusy, setLoraBusy] = useState(false);
+  const [loraError, setLoraError] = useState<string | null>(null);
+
+  useEffect(() => {
+    setAdapters([]);
+    setLoraError(null);
+    if (!selectedModel?.loras?.length) return;
+    let cancelled = false;
+    getLoraAdapters(selectedModel.id)
+      .then((a) => { if (!cancelled) setAdapters(a); })
+      .catch((err) => { if (!cancelled) setLoraError(String(err)); });
+    return () => { cancelled = true; };
+  }, [selectedModel]);
+
+  const activeScale = (path: string) => adapters.find((a) => a.path === path)?.scale ?? null;
+
+  const applyLora = async (path: string, scale: number | null) => {
+    if (!selectedModel) return;
+    setLoraBusy(true);
+    setLoraError(null);
+    try {
+      setAdapters(await setLoraAdapter(selectedModel.id, path, scale));
+    } catch (err) {
+      setLoraError(describeError(err));
+    } finally {
+      setLoraBusy(false);
+    }
+  };
+
+  // KV cache types for new sessions + what the loaded session actually holds.
+  const [sessionParams, setSessionParams] = useState<SessionParams | null>(null);
+  const [memory, setMemory] = useState<MemoryReport | null>(null);
+  const [kvBusy, setKvBusy] = useState(false);
+  const [kvError, setKvError] = useState<string | null>(null);
+
+  useEffect(() => {
+    if (!open) return;
+    let cancelled = false;
+    getSessionParams()
+      .then((p) => { if (!cancelled) setSessionParams(p); })
+      .catch(() => {});
+    getMemoryReport()
+      .then((m) => { if (!cancelled) setMemory(m); })
+      .catch(() => {});
+    return () => { cancelled = true; };
+  }, [open, selectedModel]);
+
+  // Pre-load estimate for the selected model at the current session params.
+  const [fit, setFit] = useState<FitReport | null>(null);
+
+  useEffect(() => {
+    if (!open || !selectedModel) {
+      setFit(null);
+      return;
+    }
+    let cancelled = false;
+    checkModelFit(selectedModel.id)
+      .then((f) => { if (!cancelled) setFit(f); })
+      .catch(() => { if (!cancelled) setFit(null); });
+    return () => { cancelled = true; };
+  }, [open, selectedModel, sessionParams]);
+
+  const applyKv = async (typeK: KvCacheType, typeV: KvCacheType, flashAttn: boolean | null) => {
+    setKvBusy(true);
+    setKvError(null);
+    try {
+      setSessionParams(await setKvCacheType(typeK, typeV, flashAttn));
+      setMemory(await getMemoryReport());
+    } catch (err) {
+      setKvError(describeError(err));
+    } finally {
+      setKvBusy(false);
+    }
+  };
+
   return (
     <>
       {/* scrim */}
@@ -135,6 +349,223 @@ export default function ModelInfoDrawer({
           {/* Divider */}
           <div className="my-4 h-px bg-white/10" />
 
+          {/* Prompt format (per-model override) */}
+          <div className="mb-4">
+            <div className="mb-2 flex items-center justify-between">
+              <div className="text-[13px] font-semibold text-slate-200">
+                Prompt Format
+                <InfoI title="Used when the model has no chat template, or to override it. A template file wins over the formatter." />
+              </div>
+              {needsTemplate && (
+                <span className="rounded-full bg-amber-500/15 px-2 py-0.5 text-[11px] text-amber-300">
+                  Needs template
+                </span>
+              )}
+            </div>
+            <select
+              className="w-full rounded-md border border-white/10 bg-white/5 px-2 py-1.5 text-sm text-slate-100"
+              value={promptCfg?.prompt_kind?.kind ?? ""}
+              onChange={(e) => onKindChange(e.target.value)}
+              disabled={!selectedModel || !promptCfg}
+            >
+              {PROMPT_KINDS.map((k) => (
+                <option key={k.value} value={k.value}>{k.label}</option>
+              ))}
+            </select>
+            <div className="mt-2 flex items-center gap-2">
+              <button
+                className="rounded-md bg-white/5 px-2.5 py-1 text-xs text-slate-200 hover:bg-white/10"
+                onClick={onPickTemplate}
+                disabled={!selectedModel}
+              >
+                {promptCfg?.template_path ? "Replace template…" : "Load template file…"}
+              </button>
+              {promptCfg?.template_path && (
+                <button
+                  className="rounded-md px-2.5 py-1 text-xs text-slate-300 hover:bg-white/5"
+                  onClick={onClearTemplate}
+                >
+                  Remove
+                </button>
+              )}
+            </div>
+            {promptCfg?.template_path && (
+              <div className="mt-1 truncate font-mono text-[11px] text-slate-400" title={promptCfg.template_path}>
+                {promptCfg.template_path}
+              </div>
+            )}
+            {promptError && (
+              <div className="mt-2 rounded-md bg-rose-500/10 px-3 py-2 text-xs text-rose-300">
+                {promptError}
+              </div>
+            )}
+          </div>
+
+          {/* Runtime plugin (per-model pin) */}
+          {(plugins.length > 0 || !!promptCfg?.plugin_id) && (
+            <div className="mb-4">
+              <div className="mb-2 flex items-center justify-between">
+                <div className="text-[13px] font-semibold text-slate-200">
+                  Runtime Plugin
+                  <InfoI title="Which installed backend runs this model. Automatic picks the first plugin that accepts the file." />
+                </div>
+                <button
+                  className="rounded-md bg-white/5 px-2.5 py-1 text-xs text-slate-200 hover:bg-white/10"
+                  onClick={() => void onReloadPlugins()}
+                  disabled={reloading}
+                  title="Unload and reload installed runtimes, e.g. after switching a variant"
+                >
+                  {reloading ? `${reloadStage}…` : "Reload"}
+                </button>
+              </div>
+              <select
+                className="w-full rounded-md border border-white/10 bg-white/5 px-2 py-1.5 text-sm text-slate-100"
+                value={promptCfg?.plugin_id ?? ""}
+                onChange={(e) => void onPluginChange(e.target.value)}
+                disabled={!selectedModel || !promptCfg}
+              >
+                <option value="">Automatic</option>
+                {plugins.map((p) => (
+                  <option key={p.id} value={p.id}>{`${p.id} ${p.semver}`}</option>
+                ))}
+                {promptCfg?.plugin_id && !plugins.some((p) => p.id === promptCfg.plugin_id) && (
+                  <option value={promptCfg.plugin_id}>{`${promptCfg.plugin_id} (not installed)`}</option>
+                )}
+              </select>
+              {pluginError && (
+                <div className="mt-2 rounded-md bg-rose-500/10 px-3 py-2 text-xs text-rose-300">
+                  {pluginError}
+                </div>
+              )}
+            </div>
+          )}
+
+          {/* LoRA adapters */}
+          {!!selectedModel?.loras?.length && (
+            <div className="mb-4">
+              <div className="mb-2 text-[13px] font-semibold text-slate-200">
+                LoRA Adapters
+                <InfoI title="Fine-tuned adapters applied on top of the base weights. Changing them restarts the prompt cache." />
+              </div>
+              <ul className="space-y-2">
+                {selectedModel.loras.map((path) => {
+                  const active = activeScale(path);
+                  const scale = loraScales[path] ?? active ?? 1;
+                  return (
+                    <li key={path} className="flex items-center gap-2">
+                      <input
+                        type="checkbox"
+                        checked={active != null}
+                        disabled={loraBusy}
+                        onChange={(e) => void applyLora(path, e.target.checked ? scale : null)}
+                      />
+                      <span className="min-w-0 flex-1 truncate font-mono text-[12px] text-slate-200" title={path}>
+                        {fileName(path)}
+                      </span>
+                      <input
+                        type="number"
+                        min={0}
+                        max={2}
+                        step={0.1}
+                        value={scale}
+                        disabled={loraBusy}
+                        className="w-16 rounded-md border border-white/10 bg-white/5 px-1.5 py-0.5 text-right text-xs text-slate-100"
+                        onChange={(e) => setLoraScales((s) => ({ ...s, [path]: Number(e.target.value) }))}
+                        onBlur={() => { if (active != null && active !== scale) void applyLora(path, scale); }}
+                        aria-label={`Scale for ${fileName(path)}`}
+                      />
+                    </li>
+                  );
+                })}
+              </ul>
+              {loraError && (
+                <div className="mt-2 rounded-md bg-rose-500/10 px-3 py-2 text-xs text-rose-300">
+                  {loraError}
+                </div>
+              )}
+            </div>
+          )}
+
+          {/* KV cache + memory */}
+          <div className="mb-4">
+            <div className="mb-2 text-[13px] font-semibold text-slate-200">
+              Memory
+              <InfoI title="Quantized KV caches (q8_0, q4_0) use less RAM; a quantized V cache needs flash attention. Changing them reloads the model." />
+            </div>
+            <div className="grid grid-cols-3 gap-2">
+              {(["type_k", "type_v"] as const).map((field) => (
+                <label key={field} className="text-[12px] text-slate-400">
+                  {field === "type_k" ? "K cache" : "V cache"}
+                  <select
+                    className="mt-1 w-full rounded-md border border-white/10 bg-white/5 px-2 py-1 text-sm text-slate-100"
+                    value={sessionParams?.[field] ?? "f16"}
+                    disabled={!sessionParams || kvBusy}
+                    onChange={(e) => {
+                      if (!sessionParams) return;
+                      const next = { ...sessionParams, [field]: e.target.value as KvCacheType };
+                      void applyKv(next.type_k, next.type_v, next.flash_attn ?? null);
+                    }}
+                  >
+                    {KV_TYPES.map((t) => (
+                      <option key={t} value={t}>{t}</option>
+                    ))}
+                  </select>
+                </label>
+              ))}
+              <label className="text-[12px] text-slate-400">
+                Flash attn
+                <select
+                  className="mt-1 w-full rounded-md border border-white/10 bg-white/5 px-2 py-1 text-sm text-slate-100"
+                  value={sessionParams?.flash_attn == null ? "auto" : sessionParams.flash_attn ? "on" : "off"}
+                  disabled={!sessionParams || kvBusy}
+                  onChange={(e) => {
+                    if (!sessionParams) return;
+                    const fa = e.target.value === "auto" ? null : e.target.value === "on";
+                    void applyKv(sessionParams.type_k, sessionParams.type_v, fa);
+                  }}
+                >
+                  <option value="auto">auto</option>
+                  <option value="on">on</option>
+                  <option value="off">off</option>
+                </select>
+              </label>
+            </div>
+            <div className="mt-3 grid grid-cols-2 gap-3">
+              <KV label="Weights" value={fmtBytes(memory?.model_bytes)} />
+              <KV label="KV Cache" value={fmtBytes(memory?.kv_bytes)} />
+              <KV label="Compute Buffers" value={fmtBytes(memory?.compute_bytes)} />
+              <KV
+                label="Total"
+                value={
+                  memory
+                    ? fmtBytes(memory.model_bytes + memory.kv_bytes + (memory.compute_bytes ?? 0))
+                    : "—"
+                }
+              />
+            </div>
+            {fit && (
+              <div className="mt-3 grid grid-cols-2 gap-3">
+                <KV
+                  label={`Estimate @ ${fit.estimate.n_ctx.toLocaleString()} ctx`}
+                  value={fmtBytes(fit.estimate.total_bytes)}
+                />
+                <KV
+                  label="Fit"
+                  value={<span className={FIT_LABELS[fit.verdict].color}>{FIT_LABELS[fit.verdict].text}</span>}
+                />
+                <KV
+                  label="Max Context"
+                  value={fit.max_n_ctx ? `${fit.max_n_ctx.toLocaleString()} tokens` : "—"}
+                />
+              </div>
+            )}
+            {kvError && (
+              <div className="mt-2 rounded-md bg-rose-500/10 px-3 py-2 text-xs text-rose-300">
+                {kvError}
+              </div>
+            )}
+          </div>
+
           {/* Advanced */}
           <details className="group" open>
             <summary className="cursor-pointer select-none text-[13px] font-semibold text-slate-200 hover:text-white">
@@ -147,11 +578,73 @@ export default function ModelInfoDrawer({
               <KV label="Chat Template" value={chatTemplate} />
               <KV label="EOS / BOS" value={eosBos} />
               <KV label="Quant Label" value={quant} />
+              <KV label="Bits / Weight" value={bpw} />
+              <KV
+                label="Active LoRA"
+                value={
+                  adapters.filter((a) => a.scale != null).map((a) => `${fileName(a.path)} ×${a.scale}`).join(", ") ||
+                  "—"
+                }
+              />
               <KV
                 label="Path"
                 value={<span className="font-mono text-[12px] text-slate-300">{selectedModel?.path ?? "—"}</span>}
               />
+              {selectedModel?.is_dir && (
+                <KV
+                  label="Folder"
+                  value={`${selectedModel.shards?.length ?? 0} weight · ${
+                    selectedModel.tokenizer_files?.length ?? 0
+                  } tokenizer files · ${fmtBytes(selectedModel.size_bytes)}`}
+                />
+              )}
+              {!selectedModel?.is_dir && (selectedModel?.shards?.length ?? 0) > 1 && (
+                <KV
+                  label="Split"
+                  value={`${selectedModel!.shards!.length} files · ${fmtBytes(selectedModel!.size_bytes)}`}
+                />
+              )}
             </div>
+            {meta?.raw?.["onnx.ir_version"] && (
+              <div className="mt-3 grid grid-cols-2 gap-3">
+                <KV label="Opset" value={meta.raw["onnx.opset"]} />
+                <KV label="Producer" value={meta.raw["onnx.producer"]} />
+                <KV label="IR Version" value={meta.raw["onnx.ir_version"]} />
+                <KV
+                  label="Weights"
+                  value={`${fmtBytes(Number(meta.raw["onnx.initializer_bytes"]))}${
+                    meta.raw["onnx.external_data"] === "true" ? " (external)" : ""
+                  }`}
+                />
+                <div className="col-span-2 space-y-1 text-[12px]">
+                  <div className="text-slate-400">Inputs</div>
+                  <div className="break-words font-mono text-slate-200">{meta.raw["onnx.inputs"] || "—"}</div>
+                  <div className="text-slate-400">Outputs</div>
+                  <div className="break-words font-mono text-slate-200">{meta.raw["onnx.outputs"] || "—"}</div>
+                </div>
+              </div>
+            )}
+            {totalWeights > 0 && (
+              <div className="mt-3">
+                <div className="text-[12px] text-slate-400">Tensor Types</div>
+                <div className="mt-1 space-y-1">
+                  {meta!.tensor_types!.map((t) => (
+                    <div key={t.ggml_type} className="flex items-center gap-2 text-[12px] text-slate-200">
+                      <span className="w-16 shrink-0 font-mono">{t.ggml_type}</span>
+                      <div className="h-1.5 flex-1 rounded-full bg-white/5">
+                        <div
+                          className="h-1.5 rounded-full bg-sky-400/70"
+                          style={{ width: `${(100 * t.n_elements) / totalWeights}%` }}
+                        />
+                      </div>
+                      <span className="w-24 shrink-0 text-right text-slate-400">
+                        {((100 * t.n_elements) / totalWeights).toFixed(1)}% · {t.n_tensors}
+                      </span>
+                    </div>
+                  ))}
+                </div>
+              </div>
+            )}
           </details>
 
           {metaError && (
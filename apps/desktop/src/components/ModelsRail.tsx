@@ -8,6 +8,7 @@ type Props = {
   onBack: () => void;
   onPick: (m: ModelEntry) => void;
   onImport: () => void | Promise<void>;
+  onImportFolder: () => void | Promise<void>;
 };
 
 function BackendPill({ backend }: { backend: string }) {
@@ -19,6 +20,19 @@ function BackendPill({ backend }: { backend: string }) {
   );
 }
 
+const fmtSize = (n: number) =>
+  n >= 1024 ** 3 ? `${(n / 1024 ** 3).toFixed(1)} GiB` : `${(n / 1024 ** 2).toFixed(0)} MiB`;
+
+function entryTitle(m: ModelEntry) {
+  const shards = m.shards?.length ?? 0;
+  const missing = m.missing_shards?.length ?? 0;
+  if (missing > 0) return `${m.name} — incomplete split model, ${missing} of ${shards + missing} files missing`;
+  if (!m.is_dir) {
+    return shards > 1 ? `${m.name} — split into ${shards} files, ${fmtSize(m.size_bytes)}` : m.name;
+  }
+  return `${m.name} — folder, ${shards} weight file${shards === 1 ? "" : "s"}, ${fmtSize(m.size_bytes)}`;
+}
+
 export default function ModelsRail({
   open,
   selectedModelId,
@@ -26,6 +40,7 @@ export default function ModelsRail({
   onBack,
   onPick,
   onImport,
+  onImportFolder,
 }: Props) {
   const groups = useMemo(() => {
     const g = new Map<string, ModelEntry[]>();
@@ -80,7 +95,7 @@ export default function ModelsRail({
               )}
               <div className="flex flex-col gap-1">
                 {items.map((m) => {
-                  const supported = key === "gguf";
+                  const supported = m.can_run;
                   const isActive = m.id === selectedModelId;
                   const base = "group relative flex items-center gap-2 rounded-lg px-2 py-2 text-sm";
                   const style = !supported
@@ -93,7 +108,11 @@ export default function ModelsRail({
                       key={m.id}
                       className={`${base} ${style}`}
                       onClick={!supported ? undefined : () => onPick(m)}
-                      title={!supported ? "Backend not available yet" : m.name}
+                      title={
+                        !supported && !m.missing_shards?.length
+                          ? "No installed runtime can run this model"
+                          : entryTitle(m)
+                      }
                       aria-disabled={!supported || undefined}
                     >
                       <span className="text-[16px]" aria-hidden>🧠</span>
@@ -120,6 +139,13 @@ export default function ModelsRail({
         >
           Import Model
         </button>
+        <button
+          onClick={() => onImportFolder()}
+          className="mt-1 w-full rounded-lg px-2.5 py-1.5 text-center text-[11px] text-slate-400 hover:bg-white/10 transition"
+          title="Import a Hugging Face model folder (config.json + weights)"
+        >
+          Import Folder
+        </button>
       </div>
     </aside>
   );
@@ -1,7 +1,13 @@
 import { useCallback, useRef, useState } from "react";
 import type { Message } from "../types";
-import { runLLM, runLLMStream, cancelGeneration } from "../lib/api";
-import { onLLMStream, onLLMComplete, safeUnlisten } from "../lib/events";
+import { runLLM, runLLMStream, cancelGeneration, describeError } from "../lib/api";
+import {
+  onLLMStream,
+  onLLMReasoning,
+  onLLMComplete,
+  onLLMStats,
+  safeUnlisten,
+} from "../lib/events";
 import type { UnlistenFn } from "@tauri-apps/api/event";
 
 export function useLLM(selectedModelId?: string | null) {
@@ -9,10 +15,14 @@ export function useLLM(selectedModelId?: string | null) {
   const [input, setInput] = useState("");
   const [isGenerating, setIsGenerating] = useState(false);
   const [streamingEnabled, setStreamingEnabled] = useState(true);
+  // Only sent when off; `null` leaves the template's default.
+  const [thinkingEnabled, setThinkingEnabled] = useState(true);
 
   // keep current unlisten fns so we can cancel safely on error
   const unlistenStreamRef = useRef<UnlistenFn | null>(null);
   const unlistenDoneRef = useRef<UnlistenFn | null>(null);
+  const unlistenReasoningRef = useRef<UnlistenFn | null>(null);
+  const unlistenStatsRef = useRef<UnlistenFn | null>(null);
 
   // Trim overlap between previous text and the new delta.
   // Fixes doubled tokens like: "<think><think>Okay Okay so so ..."
@@ -51,6 +61,37 @@ export function useLLM(selectedModelId?: string | null) {
     });
   }, []);
 
+  const appendReasoningToLast = useCallback((delta: string) => {
+    if (!delta) return;
+    setMessages((prev) => {
+      if (prev.length === 0) return prev;
+      const out = [...prev];
+      const last = out[out.length - 1];
+      if (!last) return out;
+      out[out.length - 1] = { ...last, reasoning: (last.reasoning ?? "") + delta };
+      return out;
+    });
+  }, []);
+
+  const unlistenStreaming = useCallback(() => {
+    safeUnlisten(unlistenStreamRef.current);
+    safeUnlisten(unlistenReasoningRef.current);
+    safeUnlisten(unlistenDoneRef.current);
+    unlistenStreamRef.current = null;
+    unlistenReasoningRef.current = null;
+    unlistenDoneRef.current = null;
+  }, []);
+
+  const unlistenStats = useCallback(() => {
+    safeUnlisten(unlistenStatsRef.current);
+    unlistenStatsRef.current = null;
+  }, []);
+
+  const unlistenAll = useCallback(() => {
+    unlistenStreaming();
+    unlistenStats();
+  }, [unlistenStreaming, unlistenStats]);
+
   const sendMessage = useCallback(async () => {
     const prompt = input.trim();
     if (!prompt || isGenerating) return;
@@ -66,7 +107,8 @@ export function useLLM(selectedModelId?: string | null) {
         setMessages((prev) => {
           const out = [...prev];
           const last = out[out.length - 1];
-          if (last) out[out.length - 1] = { ...last, ai: response };
+          if (last)
+            out[out.length - 1] = { ...last, ai: response.text, stats: response.stats ?? undefined };
           return out;
         });
       } catch (err) {
@@ -74,7 +116,7 @@ export function useLLM(selectedModelId?: string | null) {
           if (prev.length === 0) return prev;
           const out = [...prev];
           const last = out[out.length - 1];
-          if (last) out[out.length - 1] = { ...last, ai: `[ERROR] ${String(err)}` };
+          if (last) out[out.length - 1] = { ...last, ai: `[ERROR] ${describeError(err)}` };
           return out;
         });
       } finally {
@@ -86,42 +128,64 @@ export function useLLM(selectedModelId?: string | null) {
     // streaming path
     try {
       // subscribe BEFORE invoking to avoid missing early tokens
+      unlistenAll();
       unlistenStreamRef.current = await onLLMStream((delta) => appendDeltaToLast(delta));
-      unlistenDoneRef.current = await onLLMComplete((finalText) => {
-        if (finalText) {
+      unlistenReasoningRef.current = await onLLMReasoning((delta) => appendReasoningToLast(delta));
+      unlistenDoneRef.current = await onLLMComplete((finalText, reasoning) => {
+        if (finalText || reasoning) {
           setMessages((prev) => {
             if (prev.length === 0) return prev;
             const out = [...prev];
             const last = out[out.length - 1];
             if (!last) return out;
-            out[out.length - 1] = { ...last, ai: finalText };
+            out[out.length - 1] = {
+              ...last,
+              ai: finalText || last.ai,
+              reasoning: reasoning ?? last.reasoning,
+            };
             return out;
           });
         }
         setIsGenerating(false);
-        safeUnlisten(unlistenStreamRef.current);
-        safeUnlisten(unlistenDoneRef.current);
-        unlistenStreamRef.current = null;
-        unlistenDoneRef.current = null;
+        // `llm-stats` comes after `llm-complete`; its handler unlistens itself.
+        unlistenStreaming();
+      });
+      unlistenStatsRef.current = await onLLMStats((stats) => {
+        setMessages((prev) => {
+          if (prev.length === 0) return prev;
+          const out = [...prev];
+          const last = out[out.length - 1];
+          if (last) out[out.length - 1] = { ...last, stats };
+          return out;
+        });
+        unlistenStats();
       });
 
-      await runLLMStream(prompt, selectedModelId ?? null);
+      await runLLMStream(prompt, selectedModelId ?? null, thinkingEnabled ? null : false);
     } catch (err) {
       setMessages((prev) => {
         if (prev.length === 0) return prev;
         const out = [...prev];
         const last = out[out.length - 1];
         if (!last) return out;
-        out[out.length - 1] = { ...last, ai: `[ERROR] ${String(err)}` };
+        out[out.length - 1] = { ...last, ai: `[ERROR] ${describeError(err)}` };
         return out;
       });
       setIsGenerating(false);
-      safeUnlisten(unlistenStreamRef.current);
-      safeUnlisten(unlistenDoneRef.current);
-      unlistenStreamRef.current = null;
-      unlistenDoneRef.current = null;
+      unlistenAll();
     }
-  }, [appendDeltaToLast, input, isGenerating, streamingEnabled, selectedModelId]);
+  }, [
+    appendDeltaToLast,
+    appendReasoningToLast,
+    unlistenAll,
+    unlistenStreaming,
+    unlistenStats,
+    input,
+    isGenerating,
+    streamingEnabled,
+    thinkingEnabled,
+    selectedModelId,
+  ]);
 
   const stop = useCallback(async () => {
     try {
@@ -146,6 +210,8 @@ export function useLLM(selectedModelId?: string | null) {
     setIsGenerating,
     streamingEnabled,
     setStreamingEnabled,
+    thinkingEnabled,
+    setThinkingEnabled,
     sendMessage,
     stop,
     newChat,
@@ -116,21 +116,10 @@ export function useModels() {
   }, [select]);
 
   // Import from file picker (plugin-dialog)
-  const importFromDialog = useCallback(async () => {
-    // Allow multi-select; filter known model extensions
-    const picked = await open({
-      multiple: true,
-      directory: false,
-      filters: [{ name: "Models", extensions: ["gguf", "bin", "safetensors", "onnx"] }],
-    });
-
-    if (!picked) return;
-
-    const files = Array.isArray(picked) ? picked : [picked];
-
-    // Import sequentially (keeps it simple); collect the last imported to select
+  // Import sequentially (keeps it simple); select the last one imported
+  const importPaths = useCallback(async (paths: string[]) => {
     let lastImported: string | null = null;
-    for (const srcPath of files) {
+    for (const srcPath of paths) {
       try {
         const entry = await importModel(srcPath);
         lastImported = entry.id;
@@ -147,6 +136,25 @@ export function useModels() {
     }
   }, [refresh, select, models]);
 
+  const importFromDialog = useCallback(async () => {
+    // Allow multi-select; filter known model extensions
+    const picked = await open({
+      multiple: true,
+      directory: false,
+      filters: [{ name: "Models", extensions: ["gguf", "bin", "safetensors", "onnx"] }],
+    });
+
+    if (!picked) return;
+    await importPaths(Array.isArray(picked) ? picked : [picked]);
+  }, [importPaths]);
+
+  // Hugging Face-style folder (config.json + weights + tokenizer files)
+  const importFolderFromDialog = useCallback(async () => {
+    const picked = await open({ multiple: false, directory: true });
+    if (typeof picked !== "string") return;
+    await importPaths([picked]);
+  }, [importPaths]);
+
   // Initial load: pick active model, then ask backend to preload the engine/context in the background.
   useEffect(() => {
     void (async () => {
@@ -172,5 +180,6 @@ export function useModels() {
     error,
     recent,
     importFromDialog,
+    importFolderFromDialog,
   };
 }
\ No newline at end of file
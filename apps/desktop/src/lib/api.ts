@@ -1,5 +1,19 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { ModelEntry, ModelMeta } from "../types";
+import type {
+  FitReport,
+  GenerationStats,
+  KvCacheType,
+  LoraAdapter,
+  MemoryReport,
+  ModelEntry,
+  ModelMeta,
+  ModelPromptConfig,
+  PromptKind,
+  RuntimePluginInfo,
+  SessionParams,
+  StrataError,
+  StrataErrorCode,
+} from "../types";
 
 export type MetaIndexState = "idle" | "loading" | "ready" | "error";
 export interface MetaIndexStatus {
@@ -9,6 +23,28 @@ export interface MetaIndexStatus {
   error: string | null;
 }
 
+// ---------- Errors ----------
+const ERROR_LABELS: Record<StrataErrorCode, string | null> = {
+  failed: null,
+  invalid_arg: "Invalid request",
+  oom: "Out of memory (try a smaller context or a quantized KV cache)",
+  context_full: "Context full (start a new chat or raise the context size)",
+  aborted: "Interrupted",
+  model_load: "Model failed to load",
+  unsupported: "Not supported by this runtime plugin",
+};
+
+function isStrataError(err: unknown): err is StrataError {
+  return typeof err === "object" && err !== null && "code" in err && "message" in err;
+}
+
+// Display text for a rejected command: a `StrataError` or a plain string.
+export function describeError(err: unknown): string {
+  if (!isStrataError(err)) return String(err);
+  const label = ERROR_LABELS[err.code];
+  return label ? `${label}: ${err.message}` : err.message;
+}
+
 // ---------- System prompt ----------
 export async function loadSystemPrompt(): Promise<string> {
   return invoke<string>("load_system_prompt");
@@ -36,6 +72,75 @@ export async function importModel(srcPath: string, family?: string): Promise<Mod
   return invoke<ModelEntry>("import_model", { srcPath, family: family ?? null });
 }
 
+// ---------- Per-model prompt settings ----------
+export async function getModelPromptConfig(id: string): Promise<ModelPromptConfig> {
+  return invoke<ModelPromptConfig>("get_model_prompt_config", { id });
+}
+
+// Pass null to go back to the model's own chat template.
+export async function setModelPromptKind(id: string, kind: PromptKind | null): Promise<ModelPromptConfig> {
+  return invoke<ModelPromptConfig>("set_model_prompt_kind", { id, kind });
+}
+
+// Copies the file into the app's template store; null removes the override.
+export async function setModelTemplateFile(id: string, srcPath: string | null): Promise<ModelPromptConfig> {
+  return invoke<ModelPromptConfig>("set_model_template_file", { id, srcPath });
+}
+
+// ---------- Runtime plugins ----------
+export async function listRuntimePlugins(): Promise<RuntimePluginInfo[]> {
+  return invoke<RuntimePluginInfo[]>("list_runtime_plugins");
+}
+
+// Unload one plugin (or all), rescan, and re-create the active engine; progress on `strata://plugin-reload`.
+export async function reloadRuntimePlugins(pluginId?: string): Promise<RuntimePluginInfo[]> {
+  return invoke<RuntimePluginInfo[]>("reload_runtime_plugins", { pluginId: pluginId ?? null });
+}
+
+// Pin the plugin that runs this model; null routes by file again. Reloads the model if active.
+export async function setModelPlugin(id: string, pluginId: string | null): Promise<ModelPromptConfig> {
+  return invoke<ModelPromptConfig>("set_model_plugin", { id, pluginId });
+}
+
+// ---------- LoRA adapters (active model) ----------
+// Empty unless `modelId` is the active model.
+export async function getLoraAdapters(modelId: string): Promise<LoraAdapter[]> {
+  return invoke<LoraAdapter[]>("get_lora_adapters", { modelId });
+}
+
+// Pass a null scale to detach the adapter. Rejects unless `modelId` is the active model.
+export async function setLoraAdapter(
+  modelId: string,
+  path: string,
+  scale: number | null,
+): Promise<LoraAdapter[]> {
+  return invoke<LoraAdapter[]>("set_lora_adapter", { modelId, path, scale });
+}
+
+// ---------- Session params / memory (active model) ----------
+export async function getSessionParams(): Promise<SessionParams> {
+  return invoke<SessionParams>("get_session_params");
+}
+
+// Rebuilds the engine; rejects quantized V caches with flash attention off.
+export async function setKvCacheType(
+  typeK: KvCacheType,
+  typeV: KvCacheType,
+  flashAttn: boolean | null,
+): Promise<SessionParams> {
+  return invoke<SessionParams>("set_kv_cache_type", { typeK, typeV, flashAttn });
+}
+
+// Null until the engine for the active model exists.
+export async function getMemoryReport(): Promise<MemoryReport | null> {
+  return invoke<MemoryReport | null>("get_memory_report");
+}
+
+// Estimate for model `id` at the current session params, without loading it.
+export async function checkModelFit(id: string, nCtx?: number): Promise<FitReport> {
+  return invoke<FitReport>("check_model_fit", { id, nCtx: nCtx ?? null });
+}
+
 // ---------- Metadata (single file) ----------
 export async function getModelMetadata(): Promise<ModelMeta> {
   return invoke<ModelMeta>("get_model_metadata");
@@ -59,19 +164,29 @@ export async function metaClear(): Promise<void> {
 }
 
 // ---------- LLM ----------
-export async function runLLM(prompt: string, modelId?: string | null): Promise<string> {
-  return invoke<string>("run_llm", {
+export interface LLMReply {
+  text: string;
+  stats?: GenerationStats | null;
+}
+
+export async function runLLM(prompt: string, modelId?: string | null): Promise<LLMReply> {
+  return invoke<LLMReply>("run_llm", {
     prompt,
     tts: false,
     model_id: modelId ?? null,
   });
 }
 
-export async function runLLMStream(prompt: string, modelId?: string | null): Promise<void> {
+export async function runLLMStream(
+  prompt: string,
+  modelId?: string | null,
+  thinking?: boolean | null
+): Promise<void> {
   return invoke("run_llm_stream", {
     prompt,
     tts: false,
     model_id: modelId ?? null,
+    thinking: thinking ?? null,
   });
 }
 
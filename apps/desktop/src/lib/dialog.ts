@@ -10,4 +10,16 @@ export async function pickModelFile(): Promise<string | null> {
     ],
   });
   return typeof file === "string" ? file : null;
+}
+
+export async function pickTemplateFile(): Promise<string | null> {
+  const file = await open({
+    multiple: false,
+    directory: false,
+    filters: [
+      { name: "Chat templates", extensions: ["jinja", "j2", "txt"] },
+      { name: "All files", extensions: ["*"] }
+    ],
+  });
+  return typeof file === "string" ? file : null;
 }
\ No newline at end of file
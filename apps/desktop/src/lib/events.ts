@@ -1,15 +1,28 @@
 import { listen, UnlistenFn } from "@tauri-apps/api/event";
-import type { HardwareProfile } from "../types";
+import type { GenerationStats, HardwareProfile, PluginReloadProgress } from "../types";
 
 export type StreamDeltaEvent = { delta: string };
-export type StreamCompleteEvent = { text: string };
+export type StreamCompleteEvent = { text: string; reasoning?: string | null };
 
 export function onLLMStream(handler: (delta: string) => void): Promise<UnlistenFn> {
   return listen<StreamDeltaEvent>("llm-stream", (e) => handler(e.payload?.delta ?? ""));
 }
 
-export function onLLMComplete(handler: (text: string) => void): Promise<UnlistenFn> {
-  return listen<StreamCompleteEvent>("llm-complete", (e) => handler(e.payload?.text ?? ""));
+export function onLLMReasoning(handler: (delta: string) => void): Promise<UnlistenFn> {
+  return listen<StreamDeltaEvent>("llm-reasoning", (e) => handler(e.payload?.delta ?? ""));
+}
+
+export function onLLMComplete(
+  handler: (text: string, reasoning: string | null) => void
+): Promise<UnlistenFn> {
+  return listen<StreamCompleteEvent>("llm-complete", (e) =>
+    handler(e.payload?.text ?? "", e.payload?.reasoning ?? null)
+  );
+}
+
+/** Timings for the generation just completed; follows `llm-complete`. */
+export function onLLMStats(handler: (stats: GenerationStats) => void): Promise<UnlistenFn> {
+  return listen<GenerationStats>("llm-stats", (e) => handler(e.payload));
 }
 
 // small helper to safely unlisten
@@ -26,4 +39,9 @@ export const HWPROFILE_EVENT = "strata://hwprofile";
 export function onHwProfile(cb: (p: HardwareProfile) => void) {
   // returns a Promise<UnlistenFn>
   return listen(HWPROFILE_EVENT, (e) => cb(e.payload as HardwareProfile));
-}
\ No newline at end of file
+}
+export const PLUGIN_RELOAD_EVENT = "strata://plugin-reload";
+
+export function onPluginReload(cb: (p: PluginReloadProgress) => void) {
+  return listen<PluginReloadProgress>(PLUGIN_RELOAD_EVENT, (e) => cb(e.payload));
+}
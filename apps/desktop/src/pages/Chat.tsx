@@ -17,7 +17,7 @@ export default function Chat() {
   const [modelsMode, setModelsMode] = useState(false);
 
   // models
-  const { models, selectedModel, setSelectedModel, importFromDialog, recent } = useModels();
+  const { models, selectedModel, setSelectedModel, importFromDialog, importFolderFromDialog, recent } = useModels();
 
   // metadata
   const { meta, metaLoading, metaError } = useModelMeta(selectedModel);
@@ -29,6 +29,8 @@ export default function Chat() {
     setInput,
     isGenerating,
     setStreamingEnabled,
+    thinkingEnabled,
+    setThinkingEnabled,
     sendMessage,
     stop,
     newChat,
@@ -107,6 +109,7 @@ export default function Chat() {
             onBack={() => setModelsMode(false)}
             onPick={handlePickModel}
             onImport={importFromDialog}
+            onImportFolder={importFolderFromDialog}
           />
         ) : (
           <LeftNav
@@ -132,6 +135,10 @@ export default function Chat() {
             onCancel={() => {
               void stop();
             }}
+            thinking={thinkingEnabled}
+            onToggleThinking={
+              meta?.supports_thinking_toggle ? () => setThinkingEnabled((v) => !v) : undefined
+            }
           />
         </div>
       </div>
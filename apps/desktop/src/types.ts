@@ -1,6 +1,10 @@
 export interface Message {
   user: string;
   ai?: string;
+  /** Model reasoning, streamed separately from the answer. */
+  reasoning?: string;
+  /** Timings of the generation that produced `ai`. */
+  stats?: GenerationStats;
 }
 
 export interface ModelEntry {
@@ -10,6 +14,129 @@ export interface ModelEntry {
   backend_hint: string;
   file_type: string;
   family?: string;
+  /** Vision projector paired with this model; present when it can take images. */
+  mmproj?: string;
+  /** LoRA adapters found next to the model. */
+  loras?: string[];
+  /** `path` is a Hugging Face-style model folder. */
+  is_dir: boolean;
+  /** Bytes on disk (all files for folder models). */
+  size_bytes: number;
+  /** Weight files of folder models and split GGUF sets. */
+  shards?: string[];
+  /** Split GGUF sets: files of the set not found on disk; such entries can't be activated. */
+  missing_shards?: string[];
+  /** Folder models: tokenizer/generation files. */
+  tokenizer_files?: string[];
+  /** Complete, and a loaded runtime plugin will run it. */
+  can_run: boolean;
+}
+
+/** A LoRA adapter loaded for the active model; `scale` is unset while detached. */
+export interface LoraAdapter {
+  id: number;
+  path: string;
+  scale?: number | null;
+}
+
+/** K/V cache element type; quantized V types need flash attention. */
+export type KvCacheType = "f32" | "f16" | "bf16" | "q8_0" | "q5_1" | "q5_0" | "q4_1" | "q4_0" | "iq4_nl";
+
+/** Context parameters used for new sessions (subset the UI edits). */
+export interface SessionParams {
+  n_ctx: number;
+  type_k: KvCacheType;
+  type_v: KvCacheType;
+  flash_attn?: boolean | null;
+}
+
+/** Why a runtime plugin call failed (`strata_abi::error::ErrorCode`). */
+export type StrataErrorCode =
+  | "failed"
+  | "invalid_arg"
+  | "oom"
+  | "context_full"
+  | "aborted"
+  | "model_load"
+  | "unsupported";
+
+/** Error payload of commands that reach the runtime plugin. */
+export interface StrataError {
+  code: StrataErrorCode;
+  message: string;
+}
+
+/** Bytes held by the active session. */
+export interface MemoryReport {
+  model_bytes: number;
+  kv_bytes: number;
+  compute_bytes?: number | null;
+}
+
+/** The runtime's own counters for one generation. */
+export interface PerfCounters {
+  prompt_eval_ms: number;
+  prompt_tokens: number;
+  eval_ms: number;
+  eval_tokens: number;
+  sample_ms: number;
+  sample_count: number;
+}
+
+/** Timings for one generation, sent with the reply (`llm-stats` when streaming). */
+export interface GenerationStats {
+  prompt_tokens: number;
+  /** Prompt tokens kept in the KV cache from the previous turn. */
+  reused_tokens: number;
+  generated_tokens: number;
+  prefill_ms: number;
+  time_to_first_token_ms?: number | null;
+  decode_ms: number;
+  total_ms: number;
+  prefill_tokens_per_second: number;
+  tokens_per_second: number;
+  backend?: PerfCounters | null;
+}
+
+/** Layer/head shape used for memory estimates. */
+export interface ModelGeometry {
+  weights_bytes: number;
+  n_layer: number;
+  n_embd: number;
+  n_head: number;
+  n_head_kv: number;
+  head_dim_k: number;
+  head_dim_v: number;
+  n_ctx_train: number;
+  n_vocab: number;
+}
+
+/** Estimated bytes for a session at `n_ctx`. */
+export interface MemoryEstimate {
+  n_ctx: number;
+  weights_bytes: number;
+  kv_bytes: number;
+  compute_bytes: number;
+  total_bytes: number;
+}
+
+export type FitVerdict = "fits" | "needs_gpu_offload" | "tight" | "too_large";
+
+/** Estimate checked against this machine's RAM/VRAM. */
+export interface FitReport {
+  estimate: MemoryEstimate;
+  budget: { ram_bytes: number; vram_bytes: number };
+  verdict: FitVerdict;
+  /** Largest context that fits; null if even the weights don't. */
+  max_n_ctx?: number | null;
+}
+
+/** Tensors of one element type (e.g. "q4_K"). */
+export interface TensorTypeCount {
+  ggml_type: string;
+  n_tensors: number;
+  n_elements: number;
+  bytes?: number | null;
 }
 
 export interface ModelMeta {
@@ -22,11 +149,53 @@ export interface ModelMeta {
   vocab_size?: number;
   eos_token_id?: number;
   bos_token_id?: number;
-  prompt_flavor_hint?: "ChatMl" | "InstBlock" | "UserAssistant" | "Plain" | "Phi3";
+  prompt_flavor_hint?: "ChatMl" | "InstBlock" | "UserAssistant" | "Plain" | "Phi3" | "Llama3" | "Gemma";
   has_chat_template: boolean;
+  /** No chat template and no user-chosen formatter/template yet. */
+  needs_template?: boolean;
+  /** Template honours `enable_thinking`, so reasoning can be turned off. */
+  supports_thinking_toggle?: boolean;
+  /** Average storage bits per weight. */
+  bits_per_weight?: number | null;
+  /** Weight share per tensor type, largest first. */
+  tensor_types?: TensorTypeCount[];
+  /** Output projection type when it differs from the bulk (e.g. "q6_K"). */
+  output_tensor_type?: string | null;
+  geometry?: ModelGeometry | null;
   raw?: Record<string, string>;
 }
 
+/** Generic formatter for models without a usable chat template. */
+export type PromptKind =
+  | { kind: "ChatMl"; system?: string | null }
+  | { kind: "UserAssistant" }
+  | { kind: "InstBlock" }
+  | { kind: "Plain" }
+  | { kind: "Phi3"; system?: string | null }
+  | { kind: "Custom"; pattern: string };
+
+export interface ModelPromptConfig {
+  prompt_kind: PromptKind | null;
+  template_path: string | null;
+  /** Runtime plugin pinned for this model; unset routes by file. */
+  plugin_id?: string | null;
+}
+
+export type PluginReloadStage = "draining" | "unloading" | "loading" | "restoring" | "done" | "failed";
+
+/** Payload of `strata://plugin-reload`; `detail` holds plugin ids or the error. */
+export interface PluginReloadProgress {
+  stage: PluginReloadStage;
+  detail: string | null;
+}
+
+/** A loaded runtime plugin (`list_runtime_plugins`). */
+export interface RuntimePluginInfo {
+  id: string;
+  semver: string;
+  path: string;
+}
+
 // src/types.ts
 export type PreloadState = "idle" | "loading" | "ready" | "error";
 
@@ -1,11 +1,17 @@
+use std::collections::HashMap;
 use std::path::Path;
+use std::ptr::NonNull;
 use std::sync::Arc;
 
+use llama_sys::llama_adapter_lora;
+
 use crate::{
     adapter::kv::KvState,
     backends::dispatch::Backend as LlamaCppBackend,
+    cvec::{self, ControlVectorData},
     format::format_with_native_template,
     model::LlamaModel,
+    mtmd::MtmdContext,
     params::{
         LlamaParams, MirostatV1, MirostatV2, PenaltyParams as RsPenaltyParams,
         SamplingParams as RsSamplingParams,
@@ -13,68 +19,109 @@ use crate::{
     token::LlamaToken,
 };
 
-use strata_abi::backend::{LLMBackend, PromptFlavor};
+use strata_abi::backend::{
+    derive_stop_strings, ControlVector, LLMBackend, LoraAdapter, PromptFlavor,
+};
+use strata_abi::error::{ErrorCode, StrataError};
 use strata_abi::sampling::{BackendSamplingCapabilities, SamplingParams as CoreSamplingParams};
+use strata_abi::session::{MemoryReport, PerfCounters, SessionParams};
 use strata_abi::token::Token;
 
+/// A LoRA adapter loaded against the model, and its scale on this session.
+struct LoadedLora {
+    adapter: NonNull<llama_adapter_lora>,
+    path: String,
+    scale: Option<f32>,
+}
+
 /// Llama backend implementation used by the engine.
 /// One instance = one loaded model + one inference context (session).
 pub struct LlamaBackendImpl {
+    /// Optional vision projector; declared first so it drops before the model.
+    mtmd: Option<MtmdContext>,
     /// Resident model weights (shared across spawned sessions).
     model: Arc<LlamaModel>,
     /// Session KV + sequencing.
     kv: KvState,
     /// Params used to create contexts; kept so we can spawn() cheap fresh sessions.
     params: LlamaParams,
+    /// Prompt flavor inferred from the model's chat template / architecture.
+    flavor: PromptFlavor,
+    /// EOG token pieces + template turn markers for this model.
+    stop_strings: Vec<String>,
+    /// LoRA adapters; the id handed out is the index. Freed with the model.
+    loras: Vec<LoadedLora>,
+    /// Control-vector files read so far, by path (switching presets skips the disk).
+    cvecs: HashMap<String, ControlVectorData>,
 }
 
 impl LlamaBackendImpl {
-    fn default_params() -> LlamaParams {
-        let mut p = LlamaParams::default();
-        p.n_ctx = std::env::var("STRATA_N_CTX")
-            .ok()
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(4096);
-        p.n_batch = std::env::var("STRATA_N_BATCH")
-            .ok()
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(64);
-        p.n_ubatch = std::env::var("STRATA_N_UBATCH")
-            .ok()
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(16);
-        p
+    /// Derive (flavor, stop strings) from the model's template, architecture and EOG tokens.
+    fn prompt_hints(model: &LlamaModel) -> (PromptFlavor, Vec<String>) {
+        let template = model.chat_template();
+        let flavor = PromptFlavor::infer(template.as_deref(), model.architecture().as_deref());
+        let stops = derive_stop_strings(&model.eog_pieces(), template.as_deref(), flavor);
+        (flavor, stops)
     }
 
-    pub fn from_model(model: Arc<LlamaModel>, params: LlamaParams) -> Result<Self, String> {
+    pub fn from_model(model: Arc<LlamaModel>, params: LlamaParams) -> Result<Self, StrataError> {
         // SAFETY: Widen &LlamaModel to 'static for context creation. Drop order is kv, then model.
         let static_ref: &'static LlamaModel =
             unsafe { std::mem::transmute::<&LlamaModel, &'static LlamaModel>(model.as_ref()) };
 
         let kv = KvState::new(static_ref, &params)?;
-        Ok(Self { model, kv, params })
+        let (flavor, stop_strings) = Self::prompt_hints(model.as_ref());
+        Ok(Self {
+            mtmd: None,
+            model,
+            kv,
+            params,
+            flavor,
+            stop_strings,
+            loras: Vec::new(),
+            cvecs: HashMap::new(),
+        })
     }
 
-    pub fn spawn(&self) -> Result<Self, String> {
+    pub fn spawn(&self) -> Result<Self, StrataError> {
         Self::from_model(Arc::clone(&self.model), self.params.clone())
     }
 }
 
 impl LLMBackend for LlamaBackendImpl {
-    fn load<P: AsRef<Path>>(model_path: P) -> Result<Self, String> {
-        let params = Self::default_params();
+    fn load<P: AsRef<Path>>(model_path: P) -> Result<Self, StrataError> {
+        Self::load_with_params(model_path, &SessionParams::default())
+    }
 
-        let backend =
-            LlamaCppBackend::load(&model_path, params.clone()).map_err(|e| format!("{e}"))?;
+    fn load_with_params<P: AsRef<Path>>(
+        model_path: P,
+        session: &SessionParams,
+    ) -> Result<Self, StrataError> {
+        session
+            .validate()
+            .map_err(|e| StrataError::new(ErrorCode::InvalidArg, e))?;
+        let backend = LlamaCppBackend::load(&model_path, LlamaParams::from(session))?;
         let model = backend.model();
+        // Thread counts are resolved by the backend; keep its copy.
+        let params = backend.params().clone();
 
         let static_ref = unsafe { std::mem::transmute::<&LlamaModel, &'static LlamaModel>(&model) };
         let kv = KvState::new(static_ref, &params)?;
+        let (flavor, stop_strings) = Self::prompt_hints(model.as_ref());
 
-        Ok(Self { model, kv, params })
+        Ok(Self {
+            mtmd: None,
+            model,
+            kv,
+            params,
+            flavor,
+            stop_strings,
+            loras: Vec::new(),
+            cvecs: HashMap::new(),
+        })
     }
 
-    fn tokenize(&self, text: &str) -> Result<Vec<Token>, String> {
+    fn tokenize(&self, text: &str) -> Result<Vec<Token>, StrataError> {
         let toks = self
             .model
             .as_ref()
@@ -83,7 +130,7 @@ impl LLMBackend for LlamaBackendImpl {
         Ok(toks.into_iter().map(|t| Token(t.0)).collect())
     }
 
-    fn evaluate(&mut self, tokens: &[Token], _n_past: i32) -> Result<(), String> {
+    fn evaluate(&mut self, tokens: &[Token], _n_past: i32) -> Result<(), StrataError> {
         let llama_tokens: Vec<LlamaToken> = tokens.iter().map(|Token(t)| LlamaToken(*t)).collect();
         self.kv.evaluate(&llama_tokens)
     }
@@ -93,7 +140,7 @@ impl LLMBackend for LlamaBackendImpl {
         _n_past: i32,
         params: &CoreSamplingParams,
         _token_history: &[Token],
-    ) -> Result<Token, String> {
+    ) -> Result<Token, StrataError> {
         let mut lp = RsSamplingParams::default();
         lp.greedy = params.greedy;
         lp.temperature = params.temperature;
@@ -136,24 +183,29 @@ impl LLMBackend for LlamaBackendImpl {
         Ok(Token(tok.0))
     }
 
-    fn decode_token(&self, token: Token) -> Result<String, String> {
+    fn decode_token(&self, token: Token) -> Result<String, StrataError> {
         let llama_tok = LlamaToken(token.0);
         self.model
             .as_ref()
             .token_to_str(llama_tok)
-            .map_err(|e| format!("Decode failed: {e}"))
+            .map_err(|e| format!("Decode failed: {e}").into())
     }
 
     fn eos_token(&self) -> Token {
         Token(self.model.as_ref().token_eos().0)
     }
 
+    fn bos_token(&self) -> Option<Token> {
+        let bos = self.model.as_ref().token_bos().0;
+        (bos >= 0).then_some(Token(bos))
+    }
+
     fn prompt_flavor(&self) -> PromptFlavor {
-        PromptFlavor::ChatMl
+        self.flavor
     }
 
-    fn default_stop_strings(&self) -> &'static [&'static str] {
-        &["<|im_end|>"]
+    fn default_stop_strings(&self) -> Vec<String> {
+        self.stop_strings.clone()
     }
 
     fn apply_native_chat_template(
@@ -163,6 +215,154 @@ impl LLMBackend for LlamaBackendImpl {
         format_with_native_template(self.model.as_ref(), turns, None, true)
     }
 
+    fn chat_template(&self) -> Option<String> {
+        self.model.as_ref().chat_template()
+    }
+
+    fn media_marker(&self) -> Option<String> {
+        self.mtmd.as_ref().map(|m| m.marker().to_string())
+    }
+
+    fn load_projector(&mut self, path: &Path) -> Result<(), StrataError> {
+        let path = path
+            .to_str()
+            .ok_or_else(|| "projector path is not valid UTF-8".to_string())?;
+        self.mtmd = Some(MtmdContext::load(
+            path,
+            self.model.as_ref(),
+            self.params.n_threads,
+        )?);
+        Ok(())
+    }
+
+    fn evaluate_with_media(
+        &mut self,
+        prompt: &str,
+        images: &[Vec<u8>],
+        n_past: i32,
+    ) -> Result<i32, StrataError> {
+        let mtmd = self.mtmd.as_mut().ok_or_else(|| {
+            StrataError::new(ErrorCode::Unsupported, "no projector loaded for this model")
+        })?;
+        Ok(mtmd.eval(
+            self.kv.ctx_ptr(),
+            prompt,
+            images,
+            n_past,
+            self.params.n_batch as i32,
+        )?)
+    }
+
+    fn load_lora(&mut self, path: &Path) -> Result<i32, StrataError> {
+        let path = path
+            .to_str()
+            .ok_or_else(|| "adapter path is not valid UTF-8".to_string())?;
+        if let Some(id) = self.loras.iter().position(|l| l.path == path) {
+            return Ok(id as i32);
+        }
+        let adapter = self.model.as_ref().load_lora(path)?;
+        self.loras.push(LoadedLora {
+            adapter,
+            path: path.to_string(),
+            scale: None,
+        });
+        Ok(self.loras.len() as i32 - 1)
+    }
+
+    fn set_lora(&mut self, id: i32, scale: f32) -> Result<(), StrataError> {
+        let lora = usize::try_from(id)
+            .ok()
+            .and_then(|i| self.loras.get_mut(i))
+            .ok_or_else(|| {
+                StrataError::new(
+                    ErrorCode::InvalidArg,
+                    format!("unknown LoRA adapter id {id}"),
+                )
+            })?;
+        self.kv.set_lora(lora.adapter, scale)?;
+        lora.scale = Some(scale);
+        Ok(())
+    }
+
+    fn remove_lora(&mut self, id: i32) -> Result<(), StrataError> {
+        let lora = usize::try_from(id)
+            .ok()
+            .and_then(|i| self.loras.get_mut(i))
+            .ok_or_else(|| {
+                StrataError::new(
+                    ErrorCode::InvalidArg,
+                    format!("unknown LoRA adapter id {id}"),
+                )
+            })?;
+        if lora.scale.take().is_some() {
+            self.kv.remove_lora(lora.adapter)?;
+        }
+        Ok(())
+    }
+
+    fn lora_adapters(&self) -> Vec<LoraAdapter> {
+        self.loras
+            .iter()
+            .enumerate()
+            .map(|(id, l)| LoraAdapter {
+                id: id as i32,
+                path: l.path.clone(),
+                scale: l.scale,
+            })
+            .collect()
+    }
+
+    fn set_control_vectors(&mut self, vectors: &[ControlVector]) -> Result<(), StrataError> {
+        if vectors.is_empty() {
+            return self.kv.apply_cvec(None, 0, -1, -1);
+        }
+
+        let n_embd = self.model.as_ref().n_embd();
+        let n_layer = self.model.as_ref().n_layer();
+        for v in vectors {
+            if self.cvecs.contains_key(&v.path) {
+                continue;
+            }
+            let data = ControlVectorData::load(&v.path)?;
+            if data.n_embd() != n_embd {
+                return Err(StrataError::new(
+                    ErrorCode::InvalidArg,
+                    format!(
+                        "{}: control vector width {} does not match the model ({n_embd})",
+                        v.path,
+                        data.n_embd()
+                    ),
+                ));
+            }
+            self.cvecs.insert(v.path.clone(), data);
+        }
+
+        let last_layer = n_layer.saturating_sub(1);
+        let entries: Vec<_> = vectors
+            .iter()
+            .map(|v| {
+                let first = v.layer_start.unwrap_or(1).max(1) as usize;
+                let last = v
+                    .layer_end
+                    .map_or(last_layer, |l| l.max(0) as usize)
+                    .min(last_layer);
+                (&self.cvecs[&v.path], v.strength, first, last)
+            })
+            .collect();
+        let combined = cvec::combine(&entries, n_embd, n_layer);
+
+        if combined.il_end < 0 {
+            self.kv.apply_cvec(None, 0, -1, -1)
+        } else {
+            self.kv.apply_cvec(
+                Some(&combined.data),
+                n_embd,
+                combined.il_start,
+                combined.il_end,
+            )
+        }
+    }
+
     fn context_window_hint(&self) -> Option<usize> {
         Some(self.kv.capacity())
     }
@@ -173,7 +373,7 @@ impl LLMBackend for LlamaBackendImpl {
         start: usize,
         remove_special: bool,
         unparse_special: bool,
-    ) -> Result<Vec<u8>, String> {
+    ) -> Result<Vec<u8>, StrataError> {
         let slice = &token_history[start..];
         if slice.is_empty() {
             return Ok(Vec::new());
@@ -188,7 +388,7 @@ impl LLMBackend for LlamaBackendImpl {
     // ────────────────────────────────────────────────
 
     fn clear_kv_cache(&mut self) {
-        println!("🧹 [llama-plugin] Clearing KV cache");
+        log::debug!("clearing KV cache");
         self.kv.clear();
     }
 
@@ -196,6 +396,18 @@ impl LLMBackend for LlamaBackendImpl {
         Some(self.kv.len())
     }
 
+    fn memory_report(&self) -> Option<MemoryReport> {
+        Some(MemoryReport {
+            model_bytes: self.model.size_bytes(),
+            kv_bytes: self.kv.kv_bytes(),
+            compute_bytes: self.kv.compute_bytes(),
+        })
+    }
+
+    fn take_perf_counters(&mut self) -> Option<PerfCounters> {
+        Some(self.kv.take_perf())
+    }
+
     fn sampling_capabilities(&self) -> BackendSamplingCapabilities {
         BackendSamplingCapabilities {
             supports_greedy: true,
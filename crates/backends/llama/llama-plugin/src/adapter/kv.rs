@@ -1,10 +1,26 @@
 // crates/backends/llama/llama-plugin/src/kv.rs
 
-use crate::{context::LlamaContext, model::LlamaModel, params::LlamaParams, token::LlamaToken};
+use std::time::Instant;
+
+use strata_abi::error::StrataError;
+use strata_abi::session::PerfCounters;
+
+use crate::{
+    context::LlamaContext, ffi::log::capture_buffers, model::LlamaModel, params::LlamaParams,
+    token::LlamaToken,
+};
 
 pub struct KvState {
     ctx: LlamaContext<'static>,
     n_ctx: usize,
+    /// KV cache bytes allocated for this context.
+    kv_bytes: u64,
+    /// Compute (scratch) buffer bytes, if llama.cpp reported them.
+    compute_bytes: Option<u64>,
+    /// Sampling time since the last `take_perf` (llama only times its own
+    /// sampler chains, and ours is rebuilt per call).
+    sample_ms: f64,
+    sample_count: u64,
 }
 
 impl KvState {
@@ -13,20 +29,35 @@ impl KvState {
     /// SAFETY: Caller must ensure the backing model outlives `self.ctx`.
     /// In our backend struct, `kv` is declared before `model`, so `kv` drops first,
     /// guaranteeing the context dies before the Arc<LlamaModel>.
-    pub fn new(model: &'static LlamaModel, params: &LlamaParams) -> Result<Self, String> {
-        let ctx = model
-            .create_context(params.to_ffi(), false)
-            .map_err(|e| format!("Failed to create context: {e}"))?;
+    pub fn new(model: &'static LlamaModel, params: &LlamaParams) -> Result<Self, StrataError> {
+        let (ctx, buffers) = capture_buffers(|| model.create_context(params.to_ffi(), false));
+        let ctx = ctx.map_err(|e| e.context("Failed to create context"))?;
         let n_ctx = ctx.n_ctx as usize;
-        Ok(Self { ctx, n_ctx })
+
+        // Prefer what llama.cpp actually allocated; fall back to the layout estimate.
+        let kv_bytes = if buffers.kv_bytes > 0 {
+            buffers.kv_bytes
+        } else {
+            model.kv_bytes_estimate(params.type_k, params.type_v, ctx.n_ctx)
+        };
+        let compute_bytes = (buffers.compute_bytes > 0).then_some(buffers.compute_bytes);
+
+        Ok(Self {
+            ctx,
+            n_ctx,
+            kv_bytes,
+            compute_bytes,
+            sample_ms: 0.0,
+            sample_count: 0,
+        })
     }
 
     /// Advance KV with a batch of tokens.
-    pub fn evaluate(&mut self, tokens: &[LlamaToken]) -> Result<(), String> {
+    pub fn evaluate(&mut self, tokens: &[LlamaToken]) -> Result<(), StrataError> {
         let n_past = self.ctx.next_position();
         self.ctx
             .evaluate_mut(tokens, n_past)
-            .map_err(|e| format!("Evaluate failed: {e}"))
+            .map_err(|e| e.context("Evaluate failed"))
     }
 
     /// Detokenize to UTF-8 bytes.
@@ -35,18 +66,74 @@ impl KvState {
         tokens: &[LlamaToken],
         remove_special: bool,
         unparse_special: bool,
-    ) -> Result<Vec<u8>, String> {
+    ) -> Result<Vec<u8>, StrataError> {
         self.ctx
             .detokenize_bytes(tokens, remove_special, unparse_special)
+            .map_err(StrataError::from)
     }
 
     /// Sample next token using llama-rs helper.
     pub fn sample(
-        &self,
+        &mut self,
         vocab_size: usize,
         params: &crate::params::SamplingParams,
     ) -> Result<LlamaToken, String> {
-        crate::sampling::sample_with_params(&self.ctx, vocab_size, params)
+        let started = Instant::now();
+        let token = crate::sampling::sample_with_params(&self.ctx, vocab_size, params);
+        self.sample_ms += started.elapsed().as_secs_f64() * 1000.0;
+        self.sample_count += 1;
+        token
+    }
+
+    /// Prompt/eval/sample counters since the last call; restarts them.
+    pub fn take_perf(&mut self) -> PerfCounters {
+        let data = self.ctx.take_perf();
+        let counters = PerfCounters {
+            prompt_eval_ms: data.t_p_eval_ms,
+            prompt_tokens: data.n_p_eval.max(0) as u64,
+            eval_ms: data.t_eval_ms,
+            eval_tokens: data.n_eval.max(0) as u64,
+            sample_ms: self.sample_ms,
+            sample_count: self.sample_count,
+        };
+        self.sample_ms = 0.0;
+        self.sample_count = 0;
+        counters
+    }
+
+    /// Raw context pointer, for evaluators that drive llama_decode themselves (mtmd).
+    pub fn ctx_ptr(&self) -> *mut llama_sys::llama_context {
+        self.ctx.as_ptr()
+    }
+
+    /// Attach / re-scale a LoRA adapter on this session.
+    pub fn set_lora(
+        &mut self,
+        adapter: std::ptr::NonNull<llama_sys::llama_adapter_lora>,
+        scale: f32,
+    ) -> Result<(), StrataError> {
+        self.ctx.set_lora(adapter, scale).map_err(StrataError::from)
+    }
+
+    /// Detach a LoRA adapter from this session.
+    pub fn remove_lora(
+        &mut self,
+        adapter: std::ptr::NonNull<llama_sys::llama_adapter_lora>,
+    ) -> Result<(), StrataError> {
+        self.ctx.remove_lora(adapter).map_err(StrataError::from)
+    }
+
+    /// Apply (or with `None`, clear) a combined control vector on this session.
+    pub fn apply_cvec(
+        &mut self,
+        data: Option<&[f32]>,
+        n_embd: usize,
+        il_start: i32,
+        il_end: i32,
+    ) -> Result<(), StrataError> {
+        self.ctx
+            .apply_cvec(data, n_embd, il_start, il_end)
+            .map_err(StrataError::from)
     }
 
     /// Clear resident KV.
@@ -63,4 +150,14 @@ impl KvState {
     pub fn capacity(&self) -> usize {
         self.n_ctx
     }
+
+    /// KV cache bytes for the full context window.
+    pub fn kv_bytes(&self) -> u64 {
+        self.kv_bytes
+    }
+
+    /// Compute buffer bytes, if known.
+    pub fn compute_bytes(&self) -> Option<u64> {
+        self.compute_bytes
+    }
 }
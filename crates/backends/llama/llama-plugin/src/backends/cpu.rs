@@ -3,6 +3,7 @@ use crate::model::LlamaModel;
 use crate::params::LlamaParams;
 use std::path::Path;
 use std::sync::Arc;
+use strata_abi::error::StrataError;
 
 /// CPU backend: owns loaded model + params to spawn contexts.
 pub struct CpuBackend {
@@ -15,41 +16,42 @@ impl CpuBackend {
     ///
     /// SAFETY NOTE: Caller should have initialized llama runtime once
     /// (e.g., `crate::ffi::init_backend()`) before calling `load`.
-    pub fn load<P: AsRef<Path>>(model_path: P, mut params: LlamaParams) -> Result<Self, String> {
+    pub fn load<P: AsRef<Path>>(
+        model_path: P,
+        mut params: LlamaParams,
+    ) -> Result<Self, StrataError> {
         Self::normalize_threads(&mut params);
 
-        #[cfg(feature = "ffi-trace")]
-        {
-            println!(
-                "[cpu] load: path={}, n_ctx={}, n_batch={}, n_ubatch={}, n_threads={}, n_threads_batch={}",
-                model_path.as_ref().display(),
-                params.n_ctx, params.n_batch, params.n_ubatch, params.n_threads, params.n_threads_batch
-            );
-        }
+        log::debug!(
+            "load: path={}, n_ctx={}, n_batch={}, n_ubatch={}, n_threads={}, n_threads_batch={}",
+            model_path.as_ref().display(),
+            params.n_ctx,
+            params.n_batch,
+            params.n_ubatch,
+            params.n_threads,
+            params.n_threads_batch
+        );
 
         // Use FFI to load the raw model; wrap in safe newtype.
         let path_str = model_path
             .as_ref()
             .to_str()
             .ok_or_else(|| "model path is not valid UTF-8".to_string())?;
-        let raw_model = unsafe { crate::ffi::load_model(path_str)? };
+        let raw_model = unsafe { crate::ffi::load_model(path_str, params.to_model_ffi())? };
         let model = Arc::new(LlamaModel::new(raw_model.as_ptr())?);
 
         Ok(Self { model, params })
     }
 
     /// Create a fresh inference context (session).
-    pub fn create_context(&self) -> Result<LlamaContext, String> {
-        #[cfg(feature = "ffi-trace")]
-        {
-            println!(
-                "[cpu] create_context: n_ctx={}, n_batch={}, n_ubatch={}, embeddings={}",
-                self.params.n_ctx,
-                self.params.n_batch,
-                self.params.n_ubatch,
-                self.params.embeddings
-            );
-        }
+    pub fn create_context(&self) -> Result<LlamaContext, StrataError> {
+        log::debug!(
+            "create_context: n_ctx={}, n_batch={}, n_ubatch={}, embeddings={}",
+            self.params.n_ctx,
+            self.params.n_batch,
+            self.params.n_ubatch,
+            self.params.embeddings
+        );
 
         self.model
             .create_context(self.params.to_ffi(), self.params.embeddings)
@@ -82,10 +84,11 @@ impl CpuBackend {
             p.n_threads_batch = p.n_threads;
         }
 
-        #[cfg(feature = "ffi-trace")]
-        eprintln!(
-            "[cpu] normalize_threads → n_threads={}, n_threads_batch={} (cores={})",
-            p.n_threads, p.n_threads_batch, cores
+        log::trace!(
+            "normalize_threads: n_threads={}, n_threads_batch={} (cores={})",
+            p.n_threads,
+            p.n_threads_batch,
+            cores
         );
     }
 }
@@ -3,6 +3,7 @@ use crate::model::LlamaModel;
 use crate::params::LlamaParams;
 use std::path::Path;
 use std::sync::Arc;
+use strata_abi::error::StrataError;
 
 use super::cpu::CpuBackend;
 // Stubs in place for future expansion; not used yet.
@@ -22,7 +23,7 @@ pub struct Backend {
 
 impl Backend {
     /// Load model + backend (CPU today; Vulkan/CUDA/ROCm later).
-    pub fn load<P: AsRef<Path>>(model_path: P, params: LlamaParams) -> Result<Self, String> {
+    pub fn load<P: AsRef<Path>>(model_path: P, params: LlamaParams) -> Result<Self, StrataError> {
         // Phase 1: CPU only
         let cpu = CpuBackend::load(model_path, params)?;
         Ok(Self {
@@ -31,7 +32,7 @@ impl Backend {
     }
 
     /// Create a session context.
-    pub fn create_context(&self) -> Result<LlamaContext, String> {
+    pub fn create_context(&self) -> Result<LlamaContext, StrataError> {
         match &self.inner {
             BackendKind::Cpu(b) => b.create_context(),
             // These arms will be implemented when those backends are real.
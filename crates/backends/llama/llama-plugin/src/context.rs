@@ -9,7 +9,8 @@ use crate::batch::LlamaBatch;
 use crate::ffi::context as cffi;
 use crate::model::LlamaModel;
 use crate::token::LlamaToken;
-use llama_sys::llama_context;
+use llama_sys::{llama_adapter_lora, llama_context, llama_perf_context_data};
+use strata_abi::error::StrataError;
 
 /// Borrowed context tied to a model's lifetime.
 pub struct LlamaContext<'a> {
@@ -46,7 +47,7 @@ impl<'a> LlamaContext<'a> {
     }
 
     /// Build a batch and decode it, marking only the final token for logits.
-    pub fn evaluate_mut(&mut self, tokens: &[LlamaToken], n_past: i32) -> Result<(), String> {
+    pub fn evaluate_mut(&mut self, tokens: &[LlamaToken], n_past: i32) -> Result<(), StrataError> {
         let mut batch = LlamaBatch::new(tokens.len());
         for (i, token) in tokens.iter().enumerate() {
             let pos = n_past + i as i32;
@@ -58,15 +59,47 @@ impl<'a> LlamaContext<'a> {
     }
 
     /// Decode an already-prepared batch.
-    pub fn decode(&mut self, batch: &mut LlamaBatch) -> Result<(), String> {
+    pub fn decode(&mut self, batch: &mut LlamaBatch) -> Result<(), StrataError> {
         cffi::decode_batch(self.ctx.as_ptr(), batch.raw)
     }
 
+    /// llama's prompt/eval timings since the last call; restarts them.
+    pub fn take_perf(&mut self) -> llama_perf_context_data {
+        let data = cffi::perf(self.ctx.as_ptr());
+        cffi::perf_reset(self.ctx.as_ptr());
+        data
+    }
+
     /// Clear the KV cache for this context.
     pub fn clear_kv_cache(&mut self) {
         cffi::clear_kv(self.ctx.as_ptr(), true);
     }
 
+    /// Attach a LoRA adapter at `scale`, or update its scale.
+    pub fn set_lora(
+        &mut self,
+        adapter: NonNull<llama_adapter_lora>,
+        scale: f32,
+    ) -> Result<(), String> {
+        unsafe { crate::ffi::lora::set_adapter(self.ctx.as_ptr(), adapter.as_ptr(), scale) }
+    }
+
+    /// Detach a LoRA adapter from this context.
+    pub fn remove_lora(&mut self, adapter: NonNull<llama_adapter_lora>) -> Result<(), String> {
+        unsafe { crate::ffi::lora::rm_adapter(self.ctx.as_ptr(), adapter.as_ptr()) }
+    }
+
+    /// Apply a combined control vector to layers `il_start..=il_end`; `None` turns it off.
+    pub fn apply_cvec(
+        &mut self,
+        data: Option<&[f32]>,
+        n_embd: usize,
+        il_start: i32,
+        il_end: i32,
+    ) -> Result<(), String> {
+        unsafe { crate::ffi::cvec::apply(self.ctx.as_ptr(), data, n_embd, il_start, il_end) }
+    }
+
     /// View of the current logits. Length == vocab size.
     pub fn get_logits(&self) -> &[f32] {
         cffi::logits(self.ctx.as_ptr(), self.model.as_ptr())
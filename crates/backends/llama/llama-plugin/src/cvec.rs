@@ -0,0 +1,78 @@
+// crates/backends/llama/llama-plugin/src/cvec.rs
+//
+// Control vectors loaded from GGUF and combined into the single per-layer
+// buffer llama.cpp applies. Pointer-level work lives in crate::ffi::cvec.
+
+use crate::ffi::cvec as cffi;
+
+/// Directions from one control-vector file.
+pub struct ControlVectorData {
+    n_embd: usize,
+    /// Layer `il` (1-based) at `(il - 1) * n_embd`.
+    data: Vec<f32>,
+}
+
+impl ControlVectorData {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let (n_embd, data) = unsafe { cffi::load_directions(path)? };
+        Ok(Self { n_embd, data })
+    }
+
+    pub fn n_embd(&self) -> usize {
+        self.n_embd
+    }
+
+    /// Direction for layer `il` (1-based), if the file has one.
+    pub fn layer(&self, il: usize) -> Option<&[f32]> {
+        let end = il.checked_mul(self.n_embd)?;
+        (il > 0 && end <= self.data.len()).then(|| &self.data[end - self.n_embd..end])
+    }
+}
+
+/// Sum of several scaled, layer-masked directions, ready for
+/// `LlamaContext::apply_cvec`.
+pub struct CombinedControlVector {
+    pub data: Vec<f32>,
+    pub il_start: i32,
+    pub il_end: i32,
+}
+
+/// Combine `(vector, strength, first_layer, last_layer)` entries (inclusive
+/// layer ranges, already clamped to `1..n_layer`).
+pub fn combine(
+    entries: &[(&ControlVectorData, f32, usize, usize)],
+    n_embd: usize,
+    n_layer: usize,
+) -> CombinedControlVector {
+    let mut data = vec![0.0f32; n_embd * n_layer.saturating_sub(1)];
+    let mut il_start = usize::MAX;
+    let mut il_end = 0usize;
+
+    for &(vector, strength, first, last) in entries {
+        for il in first..=last {
+            let Some(src) = vector.layer(il) else {
+                continue;
+            };
+            let dst = &mut data[(il - 1) * n_embd..il * n_embd];
+            for (d, s) in dst.iter_mut().zip(src) {
+                *d += s * strength;
+            }
+            il_start = il_start.min(il);
+            il_end = il_end.max(il);
+        }
+    }
+
+    if il_end == 0 {
+        // Nothing overlapped the requested ranges.
+        return CombinedControlVector {
+            data,
+            il_start: -1,
+            il_end: -1,
+        };
+    }
+    CombinedControlVector {
+        data,
+        il_start: il_start as i32,
+        il_end: il_end as i32,
+    }
+}
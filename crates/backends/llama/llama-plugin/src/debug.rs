@@ -12,8 +12,8 @@ pub fn dump_str(label: &str, s: &str) {
         let _ = write!(&mut cps, "U+{:04X} ", ch as u32);
     }
 
-    println!("🔎 [{label}] bytes: {hex}");
-    println!("🔎 [{label}] cps  : {cps}");
+    log::trace!("[{label}] bytes: {hex}");
+    log::trace!("[{label}] cps  : {cps}");
 }
 
 #[cfg(feature = "utf8-trace")]
@@ -23,7 +23,7 @@ pub fn dump_bytes(label: &str, bytes: &[u8]) {
     for b in bytes {
         let _ = write!(&mut hex, "{:02X} ", b);
     }
-    println!("🔎 [{label}] raw  : {hex}");
+    log::trace!("[{label}] raw  : {hex}");
 }
 
 // no-op stubs when feature is off
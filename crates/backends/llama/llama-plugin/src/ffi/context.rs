@@ -5,12 +5,15 @@
 
 use std::{ffi::CStr, ffi::CString, ptr::NonNull, slice};
 
+use strata_abi::error::{ErrorCode, StrataError};
+
 use llama_sys::{
-    llama_context, llama_context_default_params, llama_context_params, llama_decode,
-    llama_detokenize, llama_get_embeddings, llama_get_logits, llama_get_memory, llama_memory_clear,
-    llama_memory_seq_pos_max, llama_model, llama_model_get_vocab, llama_model_n_embd,
-    llama_n_vocab, llama_new_context_with_model, llama_token_eos, llama_token_get_text,
-    llama_tokenize,
+    ggml_row_size, ggml_type, llama_context, llama_context_default_params, llama_context_params,
+    llama_decode, llama_detokenize, llama_get_embeddings, llama_get_logits, llama_get_memory,
+    llama_memory_clear, llama_memory_seq_pos_max, llama_model, llama_model_get_vocab,
+    llama_model_n_embd, llama_n_ctx, llama_n_vocab, llama_new_context_with_model,
+    llama_perf_context, llama_perf_context_data, llama_perf_context_reset, llama_token_bos,
+    llama_token_eos, llama_token_get_text, llama_tokenize,
 };
 
 /// Default context params (CPU-friendly baseline).
@@ -49,19 +52,34 @@ pub fn default_context_params() -> llama_context_params {
 pub unsafe fn create_context_with_params(
     model: *mut llama_model,
     params: llama_context_params,
-) -> Result<NonNull<llama_context>, String> {
+) -> Result<NonNull<llama_context>, StrataError> {
     let ptr = llama_new_context_with_model(model, params);
-    NonNull::new(ptr).ok_or_else(|| "llama_new_context_with_model returned null".into())
+    // Null almost always means the KV cache / compute buffers did not fit.
+    NonNull::new(ptr).ok_or_else(|| {
+        StrataError::new(ErrorCode::Oom, "llama_new_context_with_model returned null")
+    })
 }
 
 /// Convenience: create a context with the local defaults above.
 pub unsafe fn create_context_default(
     model: *mut llama_model,
-) -> Result<NonNull<llama_context>, String> {
+) -> Result<NonNull<llama_context>, StrataError> {
     let params = default_context_params();
     create_context_with_params(model, params)
 }
 
+/// Actual context size (resolves n_ctx = 0 to the model's training context).
+#[inline]
+pub fn n_ctx(ctx: *mut llama_context) -> u32 {
+    unsafe { llama_n_ctx(ctx) }
+}
+
+/// Bytes of `ne` elements of type `ty` (accounts for block quantization).
+#[inline]
+pub fn row_size(ty: ggml_type, ne: i64) -> u64 {
+    unsafe { ggml_row_size(ty, ne) as u64 }
+}
+
 /// Compute the next KV position from llama’s memory bookkeeping.
 #[inline]
 pub fn next_position(ctx: *mut llama_context) -> i32 {
@@ -76,6 +94,18 @@ pub fn next_position(ctx: *mut llama_context) -> i32 {
     }
 }
 
+/// llama's timing counters (prompt eval, eval) since the last `perf_reset`.
+#[inline]
+pub fn perf(ctx: *mut llama_context) -> llama_perf_context_data {
+    unsafe { llama_perf_context(ctx) }
+}
+
+/// Restart llama's timing counters.
+#[inline]
+pub fn perf_reset(ctx: *mut llama_context) {
+    unsafe { llama_perf_context_reset(ctx) }
+}
+
 /// Clear the KV cache. If `clear_data` is true, also clears data buffers.
 #[inline]
 pub fn clear_kv(ctx: *mut llama_context, clear_data: bool) {
@@ -194,6 +224,14 @@ pub fn token_to_str(model: *mut llama_model, id: i32) -> Result<String, String>
     }
 }
 
+#[inline]
+pub fn token_bos(model: *mut llama_model) -> i32 {
+    unsafe {
+        let vocab = llama_model_get_vocab(model);
+        llama_token_bos(vocab)
+    }
+}
+
 #[inline]
 pub fn token_eos(model: *mut llama_model) -> i32 {
     unsafe {
@@ -266,13 +304,20 @@ pub fn detokenize_string(
     String::from_utf8(bytes).map_err(|e| format!("detokenize produced non-UTF-8: {e:?}"))
 }
 
-/// Thin safe wrapper for llama_decode.
+/// Thin safe wrapper for llama_decode; the error carries its `ErrorCode`.
 #[inline]
-pub fn decode_batch(ctx: *mut llama_context, batch: llama_sys::llama_batch) -> Result<(), String> {
+pub fn decode_batch(
+    ctx: *mut llama_context,
+    batch: llama_sys::llama_batch,
+) -> Result<(), StrataError> {
     let rc = unsafe { llama_decode(ctx, batch) };
-    if rc != 0 {
-        Err(format!("llama_decode failed: {rc}"))
-    } else {
-        Ok(())
-    }
+    let code = match rc {
+        0 => return Ok(()),
+        // 1: no KV slot for the batch, 2: aborted by the abort callback.
+        1 => ErrorCode::ContextFull,
+        2 => ErrorCode::Aborted,
+        -1 => ErrorCode::InvalidArg,
+        _ => ErrorCode::Failed,
+    };
+    Err(StrataError::new(code, format!("llama_decode failed: {rc}")))
 }
@@ -0,0 +1,106 @@
+// crates/backends/llama/llama-plugin/src/ffi/cvec.rs
+//
+// Control vectors: read `direction.<layer>` tensors out of a GGUF with ggml's
+// gguf reader, and hand combined directions to llama_apply_adapter_cvec.
+
+use std::{
+    ffi::{CStr, CString},
+    ptr, slice,
+};
+
+use llama_sys::{
+    ggml_context, ggml_free, ggml_get_tensor, ggml_n_dims, ggml_nelements, gguf_context, gguf_free,
+    gguf_get_n_tensors, gguf_get_tensor_name, gguf_init_from_file, gguf_init_params,
+    llama_apply_adapter_cvec, llama_context, GGML_TYPE_F32,
+};
+
+/// Read every `direction.<layer>` tensor of a control-vector file.
+/// Returns `(n_embd, data)` with layer `il` (1-based; layer 0 never has one)
+/// at `data[(il - 1) * n_embd..il * n_embd]`; missing layers are zero.
+pub unsafe fn load_directions(path: &str) -> Result<(usize, Vec<f32>), String> {
+    let c_path = CString::new(path).map_err(|_| "control vector path contains NUL".to_string())?;
+    let mut ctx: *mut ggml_context = ptr::null_mut();
+    let params = gguf_init_params {
+        no_alloc: false,
+        ctx: &mut ctx,
+    };
+    let gguf = gguf_init_from_file(c_path.as_ptr(), params);
+    if gguf.is_null() {
+        return Err(format!("failed to read control vector {path}"));
+    }
+
+    let result = read_directions(gguf, ctx, path);
+
+    gguf_free(gguf);
+    if !ctx.is_null() {
+        ggml_free(ctx);
+    }
+    result
+}
+
+unsafe fn read_directions(
+    gguf: *mut gguf_context,
+    ctx: *mut ggml_context,
+    path: &str,
+) -> Result<(usize, Vec<f32>), String> {
+    let mut n_embd = 0usize;
+    let mut data: Vec<f32> = Vec::new();
+
+    for i in 0..gguf_get_n_tensors(gguf) {
+        let name_ptr = gguf_get_tensor_name(gguf, i);
+        let name = CStr::from_ptr(name_ptr).to_string_lossy();
+        let layer = name
+            .strip_prefix("direction.")
+            .and_then(|l| l.parse::<usize>().ok())
+            .filter(|&l| l > 0)
+            .ok_or_else(|| format!("{path}: unexpected tensor '{name}'"))?;
+
+        let tensor = ggml_get_tensor(ctx, name_ptr);
+        if tensor.is_null() || (*tensor).data.is_null() {
+            return Err(format!("{path}: tensor '{name}' has no data"));
+        }
+        if (*tensor).type_ != GGML_TYPE_F32 || ggml_n_dims(tensor) != 1 {
+            return Err(format!("{path}: '{name}' must be a 1-D f32 tensor"));
+        }
+
+        let len = ggml_nelements(tensor) as usize;
+        if n_embd == 0 {
+            n_embd = len;
+        } else if len != n_embd {
+            return Err(format!(
+                "{path}: '{name}' has {len} values, expected {n_embd}"
+            ));
+        }
+
+        let end = layer * n_embd;
+        if data.len() < end {
+            data.resize(end, 0.0);
+        }
+        let src = slice::from_raw_parts((*tensor).data as *const f32, n_embd);
+        data[end - n_embd..end].copy_from_slice(src);
+    }
+
+    if n_embd == 0 {
+        return Err(format!("{path}: no direction tensors"));
+    }
+    Ok((n_embd, data))
+}
+
+/// Apply combined directions (same layout as [`load_directions`]) to layers
+/// `il_start..=il_end`. `None` turns steering off.
+pub unsafe fn apply(
+    ctx: *mut llama_context,
+    data: Option<&[f32]>,
+    n_embd: usize,
+    il_start: i32,
+    il_end: i32,
+) -> Result<(), String> {
+    let (ptr, len) = match data {
+        Some(d) => (d.as_ptr(), d.len()),
+        None => (ptr::null(), 0),
+    };
+    match llama_apply_adapter_cvec(ctx, ptr, len, n_embd as i32, il_start, il_end) {
+        0 => Ok(()),
+        rc => Err(format!("llama_apply_adapter_cvec failed ({rc})")),
+    }
+}
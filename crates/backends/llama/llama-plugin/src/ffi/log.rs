@@ -0,0 +1,105 @@
+// crates/backends/llama/llama-plugin/src/ffi/log.rs
+//
+// llama.cpp / ggml log hook. Lines are forwarded through crate::logging
+// (host sink or stderr) under the "llama" target; while a capture is active
+// on the calling thread we also pick the backend buffer sizes out of the
+// lines printed during context creation.
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, c_void, CStr},
+    sync::Once,
+};
+
+use llama_sys::{
+    ggml_log_level, llama_log_set, GGML_LOG_LEVEL_CONT, GGML_LOG_LEVEL_DEBUG, GGML_LOG_LEVEL_ERROR,
+    GGML_LOG_LEVEL_INFO, GGML_LOG_LEVEL_WARN,
+};
+use strata_abi::ffi::{LOG_DEBUG, LOG_ERROR, LOG_INFO, LOG_TRACE, LOG_WARN};
+
+use crate::logging;
+
+/// Target of forwarded llama.cpp / ggml records.
+const TARGET: &str = "llama";
+
+static INSTALL: Once = Once::new();
+
+thread_local! {
+    static CAPTURE: RefCell<Option<BufferSizes>> = const { RefCell::new(None) };
+    /// Text of a line ggml hasn't finished yet, and its level.
+    static PENDING: RefCell<(String, i32)> = const { RefCell::new((String::new(), LOG_INFO)) };
+}
+
+/// Backend buffer sizes reported by llama.cpp, summed over devices.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BufferSizes {
+    pub kv_bytes: u64,
+    pub compute_bytes: u64,
+}
+
+fn strata_level(level: ggml_log_level) -> i32 {
+    match level {
+        GGML_LOG_LEVEL_ERROR => LOG_ERROR,
+        GGML_LOG_LEVEL_WARN => LOG_WARN,
+        GGML_LOG_LEVEL_INFO => LOG_INFO,
+        GGML_LOG_LEVEL_DEBUG => LOG_DEBUG,
+        _ => LOG_TRACE,
+    }
+}
+
+unsafe extern "C" fn log_callback(level: ggml_log_level, text: *const c_char, _: *mut c_void) {
+    if text.is_null() {
+        return;
+    }
+    let text = CStr::from_ptr(text).to_string_lossy();
+
+    CAPTURE.with(|c| {
+        if let Some(acc) = c.borrow_mut().as_mut() {
+            if let Some(b) = mib_after(&text, "KV buffer size =") {
+                acc.kv_bytes += b;
+            } else if let Some(b) = mib_after(&text, "compute buffer size =") {
+                acc.compute_bytes += b;
+            }
+        }
+    });
+
+    // ggml prints a line in pieces (`CONT` carries on the previous level);
+    // forward whole lines only.
+    PENDING.with(|p| {
+        let (buf, buf_level) = &mut *p.borrow_mut();
+        if level != GGML_LOG_LEVEL_CONT && buf.is_empty() {
+            *buf_level = strata_level(level);
+        }
+        buf.push_str(&text);
+        while let Some(end) = buf.find('\n') {
+            let line: String = buf.drain(..=end).collect();
+            let line = line.trim_end();
+            if !line.is_empty() {
+                logging::emit(*buf_level, TARGET, line);
+            }
+        }
+    });
+}
+
+/// Parse `"<key>   123.45 MiB"` into bytes.
+fn mib_after(line: &str, key: &str) -> Option<u64> {
+    let rest = &line[line.find(key)? + key.len()..];
+    let mut parts = rest.split_whitespace();
+    let value: f64 = parts.next()?.parse().ok()?;
+    (parts.next()? == "MiB").then(|| (value * 1024.0 * 1024.0).round() as u64)
+}
+
+/// Route llama.cpp/ggml logging through our callback (idempotent).
+pub fn install() {
+    INSTALL.call_once(|| unsafe { llama_log_set(Some(log_callback), std::ptr::null_mut()) });
+}
+
+/// Run `f` and return what llama.cpp reported allocating while it ran
+/// (on this thread). Zero fields mean nothing was reported.
+pub fn capture_buffers<T>(f: impl FnOnce() -> T) -> (T, BufferSizes) {
+    install();
+    CAPTURE.with(|c| *c.borrow_mut() = Some(BufferSizes::default()));
+    let out = f();
+    let sizes = CAPTURE.with(|c| c.borrow_mut().take()).unwrap_or_default();
+    (out, sizes)
+}
@@ -0,0 +1,44 @@
+// crates/backends/llama/llama-plugin/src/ffi/lora.rs
+//
+// Unsafe helpers for LoRA adapters. Adapters belong to the model (llama.cpp
+// frees them with it); contexts only reference them with a scale.
+
+use std::{ffi::CString, ptr::NonNull};
+
+use llama_sys::{
+    llama_adapter_lora, llama_adapter_lora_init, llama_context, llama_model, llama_rm_adapter_lora,
+    llama_set_adapter_lora,
+};
+
+/// Load a LoRA adapter GGUF against `model`.
+pub unsafe fn adapter_init(
+    model: *mut llama_model,
+    path: &str,
+) -> Result<NonNull<llama_adapter_lora>, String> {
+    let c_path = CString::new(path).map_err(|_| "adapter path contains NUL".to_string())?;
+    let ptr = llama_adapter_lora_init(model, c_path.as_ptr());
+    NonNull::new(ptr).ok_or_else(|| format!("llama_adapter_lora_init failed for {path}"))
+}
+
+/// Attach `adapter` to `ctx` at `scale` (re-attaching updates the scale).
+pub unsafe fn set_adapter(
+    ctx: *mut llama_context,
+    adapter: *mut llama_adapter_lora,
+    scale: f32,
+) -> Result<(), String> {
+    match llama_set_adapter_lora(ctx, adapter, scale) {
+        0 => Ok(()),
+        rc => Err(format!("llama_set_adapter_lora failed ({rc})")),
+    }
+}
+
+/// Detach `adapter` from `ctx`.
+pub unsafe fn rm_adapter(
+    ctx: *mut llama_context,
+    adapter: *mut llama_adapter_lora,
+) -> Result<(), String> {
+    match llama_rm_adapter_lora(ctx, adapter) {
+        0 => Ok(()),
+        _ => Err("adapter is not attached to this context".to_string()),
+    }
+}
@@ -1,13 +1,15 @@
 pub mod batch;
 pub mod context;
-pub mod metadata;
+pub mod cvec;
+pub mod log;
+pub mod lora;
 pub mod model;
+pub mod mtmd;
 pub mod runtime;
 pub mod sampling;
 
 pub use batch::*;
 pub use context::*;
-pub use metadata::*;
 pub use model::*;
 pub use runtime::*;
 pub use sampling::*;
@@ -6,7 +6,9 @@
 use llama_sys::{
     llama_model, llama_model_chat_template, llama_model_desc, llama_model_get_vocab,
     llama_model_meta_count, llama_model_meta_key_by_index, llama_model_meta_val_str,
-    llama_model_meta_val_str_by_index, llama_n_vocab,
+    llama_model_meta_val_str_by_index, llama_model_n_embd, llama_model_n_head,
+    llama_model_n_head_kv, llama_model_n_layer, llama_model_size, llama_n_vocab,
+    llama_token_get_text, llama_vocab_is_eog,
 };
 use std::ffi::{CStr, CString};
 
@@ -91,3 +93,55 @@ pub unsafe fn n_vocab(model: *mut llama_model) -> usize {
     let vocab = llama_model_get_vocab(model);
     llama_n_vocab(vocab) as usize
 }
+
+/// Hidden size (embedding width).
+#[inline]
+pub unsafe fn n_embd(model: *mut llama_model) -> usize {
+    llama_model_n_embd(model) as usize
+}
+
+/// Number of transformer layers.
+#[inline]
+pub unsafe fn n_layer(model: *mut llama_model) -> usize {
+    llama_model_n_layer(model) as usize
+}
+
+/// Attention heads (query).
+#[inline]
+pub unsafe fn n_head(model: *mut llama_model) -> usize {
+    llama_model_n_head(model).max(0) as usize
+}
+
+/// Key/value heads (< `n_head` for GQA models).
+#[inline]
+pub unsafe fn n_head_kv(model: *mut llama_model) -> usize {
+    llama_model_n_head_kv(model).max(0) as usize
+}
+
+/// Total bytes of the model's weight tensors.
+#[inline]
+pub unsafe fn size_bytes(model: *mut llama_model) -> u64 {
+    llama_model_size(model)
+}
+
+/// Text pieces of every end-of-generation token (EOS, EOT, <|im_end|>, …).
+pub unsafe fn eog_token_texts(model: *mut llama_model) -> Vec<String> {
+    let vocab = llama_model_get_vocab(model);
+    let n = llama_n_vocab(vocab);
+
+    let mut out = Vec::new();
+    for id in 0..n {
+        if !llama_vocab_is_eog(vocab, id) {
+            continue;
+        }
+        let ptr = llama_token_get_text(vocab, id);
+        if ptr.is_null() {
+            continue;
+        }
+        let s = CStr::from_ptr(ptr).to_string_lossy().into_owned();
+        if !s.is_empty() {
+            out.push(s);
+        }
+    }
+    out
+}
@@ -0,0 +1,113 @@
+// crates/backends/llama/llama-plugin/src/ffi/mtmd.rs
+//
+// Unsafe helpers around libmtmd (multimodal projectors). Keep every
+// mtmd_* call in here; crate::mtmd wraps these in an owning handle.
+
+use std::{
+    ffi::{CStr, CString},
+    ptr::NonNull,
+};
+
+use llama_sys::{
+    llama_context, llama_model, mtmd_bitmap, mtmd_bitmap_free, mtmd_context,
+    mtmd_context_params_default, mtmd_default_marker, mtmd_free, mtmd_helper_bitmap_init_from_buf,
+    mtmd_helper_eval_chunks, mtmd_init_from_file, mtmd_input_chunks_free, mtmd_input_chunks_init,
+    mtmd_input_text, mtmd_tokenize,
+};
+
+/// Load a projector file for `model`. The model must outlive the returned context.
+pub unsafe fn init_from_file(
+    path: &str,
+    model: *mut llama_model,
+    n_threads: i32,
+) -> Result<NonNull<mtmd_context>, String> {
+    let c_path = CString::new(path).map_err(|_| "projector path contains NUL".to_string())?;
+    let mut params = mtmd_context_params_default();
+    if n_threads > 0 {
+        params.n_threads = n_threads;
+    }
+    let ptr = mtmd_init_from_file(c_path.as_ptr(), model, params);
+    NonNull::new(ptr).ok_or_else(|| format!("mtmd_init_from_file failed for {path}"))
+}
+
+pub unsafe fn free(ctx: *mut mtmd_context) {
+    mtmd_free(ctx);
+}
+
+/// Marker text mtmd replaces with image embeddings (e.g. `<__media__>`).
+pub fn default_marker() -> String {
+    unsafe {
+        let ptr = mtmd_default_marker();
+        if ptr.is_null() {
+            return String::new();
+        }
+        CStr::from_ptr(ptr).to_string_lossy().into_owned()
+    }
+}
+
+/// Tokenize `prompt` + `images` and decode every chunk into `lctx` from `n_past`.
+/// Returns the new n_past.
+pub unsafe fn eval_prompt(
+    ctx: *mut mtmd_context,
+    lctx: *mut llama_context,
+    prompt: &str,
+    images: &[Vec<u8>],
+    n_past: i32,
+    n_batch: i32,
+) -> Result<i32, String> {
+    let c_prompt = CString::new(prompt).map_err(|_| "prompt contains NUL".to_string())?;
+
+    let mut bitmaps: Vec<*mut mtmd_bitmap> = Vec::with_capacity(images.len());
+    for (i, img) in images.iter().enumerate() {
+        let bmp = mtmd_helper_bitmap_init_from_buf(ctx, img.as_ptr(), img.len());
+        if bmp.is_null() {
+            bitmaps.into_iter().for_each(|b| mtmd_bitmap_free(b));
+            return Err(format!("could not decode image #{i}"));
+        }
+        bitmaps.push(bmp);
+    }
+
+    let chunks = mtmd_input_chunks_init();
+    let text = mtmd_input_text {
+        text: c_prompt.as_ptr(),
+        add_special: n_past == 0,
+        parse_special: true,
+    };
+    let mut bitmap_ptrs: Vec<*const mtmd_bitmap> =
+        bitmaps.iter().map(|b| *b as *const mtmd_bitmap).collect();
+    let rc = mtmd_tokenize(
+        ctx,
+        chunks,
+        &text,
+        bitmap_ptrs.as_mut_ptr(),
+        bitmap_ptrs.len(),
+    );
+
+    let result = match rc {
+        0 => {
+            let mut new_n_past = n_past;
+            let rc = mtmd_helper_eval_chunks(
+                ctx,
+                lctx,
+                chunks,
+                n_past,
+                0,
+                n_batch,
+                true,
+                &mut new_n_past,
+            );
+            if rc == 0 {
+                Ok(new_n_past)
+            } else {
+                Err(format!("mtmd_helper_eval_chunks failed ({rc})"))
+            }
+        }
+        1 => Err("number of images does not match the media markers in the prompt".to_string()),
+        2 => Err("image preprocessing failed".to_string()),
+        rc => Err(format!("mtmd_tokenize failed ({rc})")),
+    };
+
+    mtmd_input_chunks_free(chunks);
+    bitmaps.into_iter().for_each(|b| mtmd_bitmap_free(b));
+    result
+}
@@ -5,6 +5,8 @@
 
 use std::{ffi::CString, ptr::NonNull, sync::OnceLock};
 
+use strata_abi::error::{ErrorCode, StrataError};
+
 use llama_sys::{
     llama_backend_free, llama_backend_init, llama_chat_apply_template, llama_chat_message,
     llama_context, llama_free, llama_free_model, llama_load_model_from_file, llama_model,
@@ -17,14 +19,14 @@ static DEINIT_CALLED: OnceLock<()> = OnceLock::new();
 
 #[inline]
 fn trace(msg: &str) {
-    #[cfg(feature = "ffi-trace")]
-    println!("{}", msg);
+    log::trace!("{msg}");
 }
 
 /// Call exactly once near process start.
 pub unsafe fn init_backend() {
     if INIT_CALLED.set(()).is_ok() {
         trace("🧠 [FFI] llama_backend_init()");
+        super::log::install();
         llama_backend_init();
     } else {
         trace("↩️ [FFI] init_backend() called again — ignored");
@@ -54,11 +56,20 @@ pub fn default_model_params() -> llama_model_params {
 }
 
 /// Load a model from disk. Caller owns the returned handle.
-pub unsafe fn load_model(path: &str) -> Result<NonNull<llama_model>, String> {
+pub unsafe fn load_model(
+    path: &str,
+    params: llama_model_params,
+) -> Result<NonNull<llama_model>, StrataError> {
     trace(&format!("📦 [FFI] load_model: {path}"));
-    let c_path = CString::new(path).map_err(|_| "Invalid model path".to_string())?;
-    let ptr = llama_load_model_from_file(c_path.as_ptr(), default_model_params());
-    NonNull::new(ptr).ok_or_else(|| "llama_load_model_from_file returned null".into())
+    let c_path = CString::new(path)
+        .map_err(|_| StrataError::new(ErrorCode::InvalidArg, "Invalid model path"))?;
+    let ptr = llama_load_model_from_file(c_path.as_ptr(), params);
+    NonNull::new(ptr).ok_or_else(|| {
+        StrataError::new(
+            ErrorCode::ModelLoad,
+            "llama_load_model_from_file returned null",
+        )
+    })
 }
 
 /// Free a model instance.
@@ -110,9 +121,8 @@ pub fn model_default_chat_template(
     model: *mut llama_sys::llama_model,
 ) -> Option<&'static std::ffi::CStr> {
     let ptr = unsafe { llama_model_chat_template(model, std::ptr::null()) };
-    println!("[DEBUG] llama_model_chat_template ptr = {ptr:p}");
     if ptr.is_null() {
-        println!("[DEBUG] → Returned NULL (no template pointer)");
+        log::debug!("model has no chat template");
         return None;
     }
     unsafe {
@@ -123,11 +133,7 @@ pub fn model_default_chat_template(
         } else {
             preview.to_string()
         };
-        println!(
-            "[DEBUG] → Chat template preview (len={}): {}",
-            preview.len(),
-            truncated
-        );
+        log::debug!("chat template (len={}): {}", preview.len(), truncated);
         Some(cstr)
     }
 }
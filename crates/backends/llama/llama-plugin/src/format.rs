@@ -22,6 +22,7 @@ pub fn format_with_native_template(
             strata_abi::backend::Role::System => "system",
             strata_abi::backend::Role::User => "user",
             strata_abi::backend::Role::Assistant => "assistant",
+            strata_abi::backend::Role::Tool => "tool",
         };
         let msg = crate::ffi::ChatMsgFFI::new(role, t.content.as_str()).ok()?;
         msgs.push(msg);
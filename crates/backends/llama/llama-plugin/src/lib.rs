@@ -6,11 +6,14 @@ pub mod backends;
 pub mod batch;
 pub mod cache;
 pub mod context;
+pub mod cvec;
 pub mod debug;
-pub mod ffi; // contains ffi::{context, metadata, ...}
+pub mod ffi; // contains ffi::{context, model, ...}
 pub mod format;
+pub mod logging;
 pub mod metadata; // safe scraper + provider (replaces old plugin_metadata)
 pub mod model;
+pub mod mtmd;
 pub mod params;
 pub mod sampling;
 pub mod token;
@@ -20,30 +23,73 @@ use crate::metadata::LlamaMetadataProvider;
 
 use core::ffi::{c_char, c_void};
 use std::{
+    collections::BTreeMap,
     ffi::{CStr, CString},
     path::Path,
     ptr, slice,
-    sync::Once,
+    sync::{Mutex, Once},
 };
 
 use serde_json;
-use strata_abi::backend::LLMBackend;
+use strata_abi::backend::{ControlVector, LLMBackend};
+use strata_abi::error::{ErrorCode, StrataError};
 use strata_abi::ffi::*;
-use strata_abi::metadata::BackendMetadataProvider;
+use strata_abi::metadata::{BackendMetadataProvider, PRIORITY_BUILTIN};
 use strata_abi::sampling::SamplingParams;
+use strata_abi::session::SessionParams;
 
 // -----------------------------
-// Error plumbing (thread-local)
+// Error plumbing
 // -----------------------------
 
 thread_local! {
     static LAST_ERR: std::cell::RefCell<Option<CString>> = const { std::cell::RefCell::new(None) };
+    /// Latest session-less failure on this thread, for `take_error(null)`.
+    static GLOBAL_ERR: std::cell::RefCell<Option<(i32, CString)>> = const { std::cell::RefCell::new(None) };
 }
 
-fn set_last_error(msg: impl AsRef<str>) -> i32 {
-    let s = CString::new(msg.as_ref()).unwrap_or_else(|_| CString::new("invalid utf8").unwrap());
-    LAST_ERR.with(|slot| *slot.borrow_mut() = Some(s));
-    ERR_FAIL
+/// Latest failure per session pointer, for `take_error`.
+static SESSION_ERRORS: Mutex<BTreeMap<usize, (i32, CString)>> = Mutex::new(BTreeMap::new());
+
+fn c_message(msg: &str) -> CString {
+    CString::new(msg).unwrap_or_else(|_| CString::new("invalid utf8").unwrap())
+}
+
+/// Record a failure on `session` (null for session-less calls) and in the
+/// thread-local `last_error`. `code` applies unless `err` already carries a
+/// more specific one. Returns the `ERR_*` code; value calls negate it.
+fn fail(session: *mut c_void, code: ErrorCode, err: impl Into<StrataError>) -> i32 {
+    let mut err = err.into();
+    if err.code == ErrorCode::Failed {
+        err.code = code;
+    }
+    LAST_ERR.with(|slot| *slot.borrow_mut() = Some(c_message(&err.to_string())));
+    let raw = err.code.raw();
+    let entry = (raw, c_message(&err.message));
+    if session.is_null() {
+        GLOBAL_ERR.with(|slot| *slot.borrow_mut() = Some(entry));
+    } else if let Ok(mut errors) = SESSION_ERRORS.lock() {
+        errors.insert(session as usize, entry);
+    }
+    raw
+}
+
+unsafe extern "C" fn take_error(session: *mut c_void, message: *mut StrataString) -> i32 {
+    let entry = if session.is_null() {
+        GLOBAL_ERR.with(|slot| slot.borrow_mut().take())
+    } else {
+        match SESSION_ERRORS.lock() {
+            Ok(mut errors) => errors.remove(&(session as usize)),
+            Err(_) => None,
+        }
+    };
+    let Some((code, msg)) = entry else {
+        return ERR_OK;
+    };
+    if !message.is_null() {
+        *message = make_string(&msg);
+    }
+    code
 }
 
 unsafe extern "C" fn last_error() -> StrataString {
@@ -117,7 +163,11 @@ unsafe extern "C" fn meta_collect_json(model_path: *const c_char) -> StrataStrin
     let s = match c.to_str() {
         Ok(v) => v,
         Err(e) => {
-            set_last_error(format!("invalid UTF-8 in path: {e}"));
+            fail(
+                ptr::null_mut(),
+                ErrorCode::InvalidArg,
+                format!("invalid UTF-8 in path: {e}"),
+            );
             return StrataString {
                 ptr: ptr::null_mut(),
                 len: 0,
@@ -129,7 +179,11 @@ unsafe extern "C" fn meta_collect_json(model_path: *const c_char) -> StrataStrin
         Ok(info) => match serde_json::to_string(&info) {
             Ok(js) => make_string_from_utf8(&js),
             Err(e) => {
-                set_last_error(format!("serde_json failed: {e}"));
+                fail(
+                    ptr::null_mut(),
+                    ErrorCode::Failed,
+                    format!("serde_json failed: {e}"),
+                );
                 StrataString {
                     ptr: ptr::null_mut(),
                     len: 0,
@@ -137,7 +191,7 @@ unsafe extern "C" fn meta_collect_json(model_path: *const c_char) -> StrataStrin
             }
         },
         Err(e) => {
-            set_last_error(e);
+            fail(ptr::null_mut(), ErrorCode::ModelLoad, e);
             StrataString {
                 ptr: ptr::null_mut(),
                 len: 0,
@@ -156,21 +210,84 @@ struct Session {
 
 unsafe extern "C" fn llm_create_session(model_path: *const c_char) -> *mut c_void {
     if model_path.is_null() {
-        set_last_error("null model path");
+        fail(ptr::null_mut(), ErrorCode::InvalidArg, "null model path");
         return ptr::null_mut();
     }
     let c = CStr::from_ptr(model_path);
     let s = match c.to_str() {
         Ok(v) => v,
         Err(e) => {
-            set_last_error(format!("invalid UTF-8 in path: {e}"));
+            fail(
+                ptr::null_mut(),
+                ErrorCode::InvalidArg,
+                format!("invalid UTF-8 in path: {e}"),
+            );
             return ptr::null_mut();
         }
     };
     match <LlamaBackendImpl as LLMBackend>::load(Path::new(s)) {
         Ok(inner) => Box::into_raw(Box::new(Session { inner })) as *mut c_void,
         Err(e) => {
-            set_last_error(e);
+            fail(ptr::null_mut(), ErrorCode::ModelLoad, e);
+            ptr::null_mut()
+        }
+    }
+}
+
+unsafe extern "C" fn llm_create_session_with_params(
+    model_path: *const c_char,
+    params_json: *const c_char,
+) -> *mut c_void {
+    if model_path.is_null() || params_json.is_null() {
+        fail(
+            ptr::null_mut(),
+            ErrorCode::InvalidArg,
+            "null model path/params_json",
+        );
+        return ptr::null_mut();
+    }
+    let path = match CStr::from_ptr(model_path).to_str() {
+        Ok(v) => v,
+        Err(e) => {
+            fail(
+                ptr::null_mut(),
+                ErrorCode::InvalidArg,
+                format!("invalid UTF-8 in path: {e}"),
+            );
+            return ptr::null_mut();
+        }
+    };
+    let json = match CStr::from_ptr(params_json).to_str() {
+        Ok(v) => v,
+        Err(e) => {
+            fail(
+                ptr::null_mut(),
+                ErrorCode::InvalidArg,
+                format!("invalid UTF-8 in params_json: {e}"),
+            );
+            return ptr::null_mut();
+        }
+    };
+    // Normalized by `load_with_params`.
+    let params: SessionParams = match serde_json::from_str::<SessionParams>(json) {
+        Ok(p) => p,
+        Err(e) => {
+            fail(
+                ptr::null_mut(),
+                ErrorCode::InvalidArg,
+                format!("bad SessionParams JSON: {e}"),
+            );
+            return ptr::null_mut();
+        }
+    };
+    if let Err(e) = params.validate() {
+        fail(ptr::null_mut(), ErrorCode::InvalidArg, e);
+        return ptr::null_mut();
+    }
+    match <LlamaBackendImpl as LLMBackend>::load_with_params(Path::new(path), &params) {
+        Ok(inner) => Box::into_raw(Box::new(Session { inner })) as *mut c_void,
+        Err(e) => {
+            fail(ptr::null_mut(), ErrorCode::ModelLoad, e);
             ptr::null_mut()
         }
     }
@@ -179,11 +296,15 @@ unsafe extern "C" fn llm_create_session(model_path: *const c_char) -> *mut c_voi
 unsafe extern "C" fn llm_destroy_session(session: *mut c_void) {
     if !session.is_null() {
         let _ = Box::<Session>::from_raw(session as *mut Session);
+        if let Ok(mut errors) = SESSION_ERRORS.lock() {
+            errors.remove(&(session as usize));
+        }
     }
 }
 
 unsafe extern "C" fn llm_tokenize_utf8(session: *mut c_void, text: *const c_char) -> Int32Array {
     if session.is_null() || text.is_null() {
+        fail(session, ErrorCode::InvalidArg, "null session/text");
         return Int32Array {
             ptr: ptr::null_mut(),
             len: 0,
@@ -194,7 +315,11 @@ unsafe extern "C" fn llm_tokenize_utf8(session: *mut c_void, text: *const c_char
     let txt = match c.to_str() {
         Ok(v) => v,
         Err(e) => {
-            set_last_error(format!("invalid UTF-8 in text: {e}"));
+            fail(
+                session,
+                ErrorCode::InvalidArg,
+                format!("invalid UTF-8 in text: {e}"),
+            );
             return Int32Array {
                 ptr: ptr::null_mut(),
                 len: 0,
@@ -203,14 +328,14 @@ unsafe extern "C" fn llm_tokenize_utf8(session: *mut c_void, text: *const c_char
     };
     match sref.inner.tokenize(txt) {
         Ok(tokens) => {
-            let mut v: Vec<i32> = tokens.into_iter().map(|t| t.0).collect();
-            let ptr = v.as_mut_ptr();
+            // Exact capacity for `free_ints`; non-null even when empty.
+            let v: Box<[i32]> = tokens.into_iter().map(|t| t.0).collect();
             let len = v.len();
-            std::mem::forget(v);
+            let ptr = Box::into_raw(v) as *mut i32;
             Int32Array { ptr, len }
         }
         Err(e) => {
-            set_last_error(e);
+            fail(session, ErrorCode::Failed, e);
             Int32Array {
                 ptr: ptr::null_mut(),
                 len: 0,
@@ -225,7 +350,7 @@ unsafe extern "C" fn llm_format_chat_json(
     _add_assistant: bool,
 ) -> strata_abi::ffi::StrataString {
     if session.is_null() || turns_json.is_null() {
-        set_last_error("null session/turns_json");
+        fail(session, ErrorCode::InvalidArg, "null session/turns_json");
         return strata_abi::ffi::StrataString {
             ptr: std::ptr::null_mut(),
             len: 0,
@@ -237,7 +362,11 @@ unsafe extern "C" fn llm_format_chat_json(
     let js = match ::std::ffi::CStr::from_ptr(turns_json).to_str() {
         Ok(v) => v,
         Err(e) => {
-            set_last_error(format!("invalid UTF-8 in turns_json: {e}"));
+            fail(
+                session,
+                ErrorCode::InvalidArg,
+                format!("invalid UTF-8 in turns_json: {e}"),
+            );
             return strata_abi::ffi::StrataString {
                 ptr: std::ptr::null_mut(),
                 len: 0,
@@ -248,7 +377,11 @@ unsafe extern "C" fn llm_format_chat_json(
     let turns: Vec<strata_abi::backend::ChatTurn> = match ::serde_json::from_str(js) {
         Ok(v) => v,
         Err(e) => {
-            set_last_error(format!("bad ChatTurn JSON: {e}"));
+            fail(
+                session,
+                ErrorCode::InvalidArg,
+                format!("bad ChatTurn JSON: {e}"),
+            );
             return strata_abi::ffi::StrataString {
                 ptr: std::ptr::null_mut(),
                 len: 0,
@@ -259,12 +392,7 @@ unsafe extern "C" fn llm_format_chat_json(
     match sref.inner.apply_native_chat_template(&turns) {
         Some(text) => {
             // Compose the JSON shape expected by the host (FormattedPrompt)
-            let stops: Vec<String> = sref
-                .inner
-                .default_stop_strings()
-                .iter()
-                .map(|s| s.to_string())
-                .collect();
+            let stops: Vec<String> = sref.inner.default_stop_strings();
 
             let payload = match ::serde_json::to_string(&::serde_json::json!({
                 "text": text,
@@ -273,7 +401,11 @@ unsafe extern "C" fn llm_format_chat_json(
             })) {
                 Ok(s) => s,
                 Err(e) => {
-                    set_last_error(format!("serde_json failed: {e}"));
+                    fail(
+                        session,
+                        ErrorCode::Failed,
+                        format!("serde_json failed: {e}"),
+                    );
                     return strata_abi::ffi::StrataString {
                         ptr: std::ptr::null_mut(),
                         len: 0,
@@ -284,7 +416,11 @@ unsafe extern "C" fn llm_format_chat_json(
             make_string_from_utf8(&payload)
         }
         None => {
-            set_last_error("no native chat template available for this model/backend");
+            fail(
+                session,
+                ErrorCode::Unsupported,
+                "no native chat template available for this model/backend",
+            );
             strata_abi::ffi::StrataString {
                 ptr: std::ptr::null_mut(),
                 len: 0,
@@ -300,14 +436,19 @@ unsafe extern "C" fn llm_detokenize_utf8(
     remove_special: bool,
     unparse_special: bool,
 ) -> StrataString {
-    if session.is_null() {
+    if session.is_null() || (tokens.is_null() && len > 0) {
+        fail(session, ErrorCode::InvalidArg, "null session/tokens");
         return StrataString {
             ptr: ptr::null_mut(),
             len: 0,
         };
     }
     let s = &mut *(session as *mut Session);
-    let ids = slice::from_raw_parts(tokens, len);
+    let ids: &[i32] = if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(tokens, len)
+    };
     let toks = ids
         .iter()
         .copied()
@@ -320,7 +461,11 @@ unsafe extern "C" fn llm_detokenize_utf8(
         Ok(bytes) => match String::from_utf8(bytes) {
             Ok(text) => make_string_from_utf8(&text),
             Err(e) => {
-                set_last_error(format!("detokenize returned non-UTF8: {e}"));
+                fail(
+                    session,
+                    ErrorCode::Failed,
+                    format!("detokenize returned non-UTF8: {e}"),
+                );
                 StrataString {
                     ptr: ptr::null_mut(),
                     len: 0,
@@ -328,7 +473,7 @@ unsafe extern "C" fn llm_detokenize_utf8(
             }
         },
         Err(e) => {
-            set_last_error(e);
+            fail(session, ErrorCode::Failed, e);
             StrataString {
                 ptr: ptr::null_mut(),
                 len: 0,
@@ -344,7 +489,7 @@ unsafe extern "C" fn llm_evaluate(
     n_past: i32,
 ) -> i32 {
     if session.is_null() || tokens.is_null() {
-        return set_last_error("null session/tokens");
+        return fail(session, ErrorCode::InvalidArg, "null session/tokens");
     }
     let sref = &mut *(session as *mut Session);
     let ids = slice::from_raw_parts(tokens, len);
@@ -355,32 +500,45 @@ unsafe extern "C" fn llm_evaluate(
         .collect::<Vec<_>>();
     match sref.inner.evaluate(&toks, n_past) {
         Ok(_) => ERR_OK,
-        Err(e) => set_last_error(e),
+        Err(e) => fail(session, ErrorCode::Failed, e),
     }
 }
 
 unsafe extern "C" fn llm_sample_json(session: *mut c_void, sampling_json: *const c_char) -> i32 {
     if session.is_null() || sampling_json.is_null() {
-        return set_last_error("null session/sampling_json");
+        return -fail(session, ErrorCode::InvalidArg, "null session/sampling_json");
     }
     let sref = &mut *(session as *mut Session);
     let c = CStr::from_ptr(sampling_json);
     let json = match c.to_str() {
         Ok(v) => v,
-        Err(e) => return set_last_error(format!("invalid UTF-8 in sampling_json: {e}")),
+        Err(e) => {
+            return -fail(
+                session,
+                ErrorCode::InvalidArg,
+                format!("invalid UTF-8 in sampling_json: {e}"),
+            );
+        }
     };
     let params: SamplingParams = match serde_json::from_str::<SamplingParams>(json) {
         Ok(p) => p.normalized(),
-        Err(e) => return set_last_error(format!("bad SamplingParams JSON: {e}")),
+        Err(e) => {
+            return -fail(
+                session,
+                ErrorCode::InvalidArg,
+                format!("bad SamplingParams JSON: {e}"),
+            );
+        }
     };
     match sref.inner.sample(0, &params, &[]) {
         Ok(tok) => tok.0,
-        Err(e) => set_last_error(e),
+        Err(e) => -fail(session, ErrorCode::Failed, e),
     }
 }
 
 unsafe extern "C" fn llm_decode_token(session: *mut c_void, token_id: i32) -> StrataString {
     if session.is_null() {
+        fail(session, ErrorCode::InvalidArg, "null session");
         return StrataString {
             ptr: ptr::null_mut(),
             len: 0,
@@ -390,7 +548,7 @@ unsafe extern "C" fn llm_decode_token(session: *mut c_void, token_id: i32) -> St
     match sref.inner.decode_token(strata_abi::token::Token(token_id)) {
         Ok(text) => make_string_from_utf8(&text),
         Err(e) => {
-            set_last_error(e);
+            fail(session, ErrorCode::Failed, e);
             StrataString {
                 ptr: ptr::null_mut(),
                 len: 0,
@@ -429,6 +587,282 @@ unsafe extern "C" fn llm_context_window_hint(session: *mut c_void) -> i32 {
     }
 }
 
+unsafe extern "C" fn llm_stop_strings_json(session: *mut c_void) -> StrataString {
+    if session.is_null() {
+        fail(session, ErrorCode::InvalidArg, "null session");
+        return StrataString {
+            ptr: ptr::null_mut(),
+            len: 0,
+        };
+    }
+    let sref = &*(session as *mut Session);
+    match serde_json::to_string(&sref.inner.default_stop_strings()) {
+        Ok(js) => make_string_from_utf8(&js),
+        Err(e) => {
+            fail(
+                session,
+                ErrorCode::Failed,
+                format!("serde_json failed: {e}"),
+            );
+            StrataString {
+                ptr: ptr::null_mut(),
+                len: 0,
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn llm_prompt_flavor(session: *mut c_void) -> StrataString {
+    if session.is_null() {
+        fail(session, ErrorCode::InvalidArg, "null session");
+        return StrataString {
+            ptr: ptr::null_mut(),
+            len: 0,
+        };
+    }
+    let sref = &*(session as *mut Session);
+    make_string_from_utf8(sref.inner.prompt_flavor().as_str())
+}
+
+unsafe extern "C" fn llm_load_projector(
+    session: *mut c_void,
+    projector_path: *const c_char,
+) -> i32 {
+    if session.is_null() || projector_path.is_null() {
+        return fail(
+            session,
+            ErrorCode::InvalidArg,
+            "null session/projector_path",
+        );
+    }
+    let sref = &mut *(session as *mut Session);
+    let path = match CStr::from_ptr(projector_path).to_str() {
+        Ok(v) => v,
+        Err(e) => {
+            return fail(
+                session,
+                ErrorCode::InvalidArg,
+                format!("invalid UTF-8 in projector path: {e}"),
+            )
+        }
+    };
+    match sref.inner.load_projector(Path::new(path)) {
+        Ok(()) => ERR_OK,
+        Err(e) => fail(session, ErrorCode::Failed, e),
+    }
+}
+
+unsafe extern "C" fn llm_media_marker(session: *mut c_void) -> StrataString {
+    if session.is_null() {
+        fail(session, ErrorCode::InvalidArg, "null session");
+        return StrataString {
+            ptr: ptr::null_mut(),
+            len: 0,
+        };
+    }
+    let sref = &*(session as *mut Session);
+    make_string_from_utf8(&sref.inner.media_marker().unwrap_or_default())
+}
+
+unsafe extern "C" fn llm_evaluate_media(
+    session: *mut c_void,
+    prompt: *const c_char,
+    images: *const ByteSlice,
+    n_images: usize,
+    n_past: i32,
+) -> i32 {
+    if session.is_null() || prompt.is_null() || (images.is_null() && n_images > 0) {
+        return -fail(session, ErrorCode::InvalidArg, "null session/prompt/images");
+    }
+    let sref = &mut *(session as *mut Session);
+    let prompt = match CStr::from_ptr(prompt).to_str() {
+        Ok(v) => v,
+        Err(e) => {
+            return -fail(
+                session,
+                ErrorCode::InvalidArg,
+                format!("invalid UTF-8 in prompt: {e}"),
+            );
+        }
+    };
+    let images: Vec<Vec<u8>> = if n_images == 0 {
+        Vec::new()
+    } else {
+        slice::from_raw_parts(images, n_images)
+            .iter()
+            .map(|b| slice::from_raw_parts(b.ptr, b.len).to_vec())
+            .collect()
+    };
+    match sref.inner.evaluate_with_media(prompt, &images, n_past) {
+        Ok(n) => n,
+        Err(e) => -fail(session, ErrorCode::Failed, e),
+    }
+}
+
+unsafe extern "C" fn llm_load_lora(session: *mut c_void, lora_path: *const c_char) -> i32 {
+    if session.is_null() || lora_path.is_null() {
+        return -fail(session, ErrorCode::InvalidArg, "null session/lora_path");
+    }
+    let sref = &mut *(session as *mut Session);
+    let path = match CStr::from_ptr(lora_path).to_str() {
+        Ok(v) => v,
+        Err(e) => {
+            return -fail(
+                session,
+                ErrorCode::InvalidArg,
+                format!("invalid UTF-8 in adapter path: {e}"),
+            );
+        }
+    };
+    match sref.inner.load_lora(Path::new(path)) {
+        Ok(id) => id,
+        Err(e) => -fail(session, ErrorCode::Failed, e),
+    }
+}
+
+unsafe extern "C" fn llm_set_lora(session: *mut c_void, id: i32, scale: f32) -> i32 {
+    if session.is_null() {
+        return fail(session, ErrorCode::InvalidArg, "null session");
+    }
+    let sref = &mut *(session as *mut Session);
+    match sref.inner.set_lora(id, scale) {
+        Ok(()) => ERR_OK,
+        Err(e) => fail(session, ErrorCode::Failed, e),
+    }
+}
+
+unsafe extern "C" fn llm_remove_lora(session: *mut c_void, id: i32) -> i32 {
+    if session.is_null() {
+        return fail(session, ErrorCode::InvalidArg, "null session");
+    }
+    let sref = &mut *(session as *mut Session);
+    match sref.inner.remove_lora(id) {
+        Ok(()) => ERR_OK,
+        Err(e) => fail(session, ErrorCode::Failed, e),
+    }
+}
+
+unsafe extern "C" fn llm_lora_list_json(session: *mut c_void) -> StrataString {
+    if session.is_null() {
+        fail(session, ErrorCode::InvalidArg, "null session");
+        return StrataString {
+            ptr: ptr::null_mut(),
+            len: 0,
+        };
+    }
+    let sref = &*(session as *mut Session);
+    match serde_json::to_string(&sref.inner.lora_adapters()) {
+        Ok(js) => make_string_from_utf8(&js),
+        Err(e) => {
+            fail(
+                session,
+                ErrorCode::Failed,
+                format!("serde_json failed: {e}"),
+            );
+            StrataString {
+                ptr: ptr::null_mut(),
+                len: 0,
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn llm_set_control_vectors_json(
+    session: *mut c_void,
+    vectors_json: *const c_char,
+) -> i32 {
+    if session.is_null() || vectors_json.is_null() {
+        return fail(session, ErrorCode::InvalidArg, "null session/vectors_json");
+    }
+    let sref = &mut *(session as *mut Session);
+    let json = match CStr::from_ptr(vectors_json).to_str() {
+        Ok(v) => v,
+        Err(e) => {
+            return fail(
+                session,
+                ErrorCode::InvalidArg,
+                format!("invalid UTF-8 in vectors_json: {e}"),
+            )
+        }
+    };
+    let vectors: Vec<ControlVector> = match serde_json::from_str(json) {
+        Ok(v) => v,
+        Err(e) => {
+            return fail(
+                session,
+                ErrorCode::InvalidArg,
+                format!("bad ControlVector JSON: {e}"),
+            )
+        }
+    };
+    match sref.inner.set_control_vectors(&vectors) {
+        Ok(()) => ERR_OK,
+        Err(e) => fail(session, ErrorCode::Failed, e),
+    }
+}
+
+unsafe extern "C" fn llm_memory_report_json(session: *mut c_void) -> StrataString {
+    if session.is_null() {
+        fail(session, ErrorCode::InvalidArg, "null session");
+        return StrataString {
+            ptr: ptr::null_mut(),
+            len: 0,
+        };
+    }
+    let sref = &*(session as *mut Session);
+    let Some(report) = sref.inner.memory_report() else {
+        return StrataString {
+            ptr: ptr::null_mut(),
+            len: 0,
+        };
+    };
+    match serde_json::to_string(&report) {
+        Ok(js) => make_string_from_utf8(&js),
+        Err(e) => {
+            fail(
+                session,
+                ErrorCode::Failed,
+                format!("serde_json failed: {e}"),
+            );
+            StrataString {
+                ptr: ptr::null_mut(),
+                len: 0,
+            }
+        }
+    }
+}
+
+unsafe extern "C" fn llm_take_perf_json(session: *mut c_void) -> StrataString {
+    if session.is_null() {
+        fail(session, ErrorCode::InvalidArg, "null session");
+        return StrataString {
+            ptr: ptr::null_mut(),
+            len: 0,
+        };
+    }
+    let sref = &mut *(session as *mut Session);
+    let Some(counters) = sref.inner.take_perf_counters() else {
+        return StrataString {
+            ptr: ptr::null_mut(),
+            len: 0,
+        };
+    };
+    match serde_json::to_string(&counters) {
+        Ok(js) => make_string_from_utf8(&js),
+        Err(e) => {
+            fail(
+                session,
+                ErrorCode::Failed,
+                format!("serde_json failed: {e}"),
+            );
+            StrataString {
+                ptr: ptr::null_mut(),
+                len: 0,
+            }
+        }
+    }
+}
+
 // -----------------------------
 // Static PluginApi surface
 // -----------------------------
@@ -439,8 +873,17 @@ static mut API: PluginApi = PluginApi {
         abi_version: 0,
         id: std::ptr::null(),
         semver: std::ptr::null(),
+        struct_size: std::mem::size_of::<PluginApi>(),
+        capabilities: CAP_VISION
+            | CAP_LORA
+            | CAP_CONTROL_VECTORS
+            | CAP_SESSION_PARAMS
+            | CAP_MEMORY_REPORT,
+        get_extension: None,
     },
     metadata: MetadataApi {
+        // Same GGUF reader as the host's built-in provider; only a fallback.
+        priority: PRIORITY_BUILTIN - 10,
         can_handle: meta_can_handle,
         collect_json: meta_collect_json,
         free_string: free_string,
@@ -465,12 +908,35 @@ static mut API: PluginApi = PluginApi {
         clear_kv_cache: llm_clear_kv_cache,
         kv_len_hint: llm_kv_len_hint,
         context_window_hint: llm_context_window_hint,
+
+        stop_strings_json: llm_stop_strings_json,
+        prompt_flavor: llm_prompt_flavor,
+
+        load_projector: llm_load_projector,
+        media_marker: llm_media_marker,
+        evaluate_media: llm_evaluate_media,
+
+        load_lora: llm_load_lora,
+        set_lora: llm_set_lora,
+        remove_lora: llm_remove_lora,
+        lora_list_json: llm_lora_list_json,
+
+        set_control_vectors_json: llm_set_control_vectors_json,
+
+        create_session_with_params: llm_create_session_with_params,
+
+        memory_report_json: llm_memory_report_json,
+
+        take_error: Some(take_error),
+        set_logger: Some(logging::set_logger),
+        take_perf_json: Some(llm_take_perf_json),
     },
 };
 
 #[no_mangle]
 pub extern "C" fn strata_plugin_entry_v1() -> *const PluginApi {
     INIT.call_once(|| unsafe {
+        logging::install();
         let id = CString::new("llama").unwrap();
         let ver = CString::new("0.1.0").unwrap();
         API.info.abi_version = STRATA_ABI_VERSION;
@@ -0,0 +1,120 @@
+// crates/backends/llama/llama-plugin/src/logging.rs
+//
+// Plugin-side logging. Records from this crate (`log` macros) and from
+// llama.cpp (see ffi::log) go to the host's `LogFn` once it has called
+// `set_logger`; until then, or after it passes `None`, they go to stderr.
+
+use std::{
+    ffi::{c_void, CString},
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Once, RwLock,
+    },
+};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use strata_abi::ffi::{LogFn, LOG_DEBUG, LOG_ERROR, LOG_INFO, LOG_OFF, LOG_TRACE, LOG_WARN};
+
+struct Sink {
+    log: LogFn,
+    /// The host's `user` pointer, kept as an address so `Sink` is `Send`.
+    user: usize,
+}
+
+static SINK: RwLock<Option<Sink>> = RwLock::new(None);
+static MAX_LEVEL: AtomicI32 = AtomicI32::new(LOG_INFO);
+static INSTALL: Once = Once::new();
+
+fn raw_level(level: Level) -> i32 {
+    match level {
+        Level::Error => LOG_ERROR,
+        Level::Warn => LOG_WARN,
+        Level::Info => LOG_INFO,
+        Level::Debug => LOG_DEBUG,
+        Level::Trace => LOG_TRACE,
+    }
+}
+
+fn level_filter(raw: i32) -> LevelFilter {
+    match raw {
+        LOG_OFF => LevelFilter::Off,
+        LOG_ERROR => LevelFilter::Error,
+        LOG_WARN => LevelFilter::Warn,
+        LOG_INFO => LevelFilter::Info,
+        LOG_DEBUG => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+/// Whether a record at `level` (`LOG_*`) would be kept.
+pub fn enabled(level: i32) -> bool {
+    level != LOG_OFF && level <= MAX_LEVEL.load(Ordering::Relaxed)
+}
+
+/// Send one line to the host sink (or stderr).
+pub fn emit(level: i32, target: &str, message: &str) {
+    if !enabled(level) {
+        return;
+    }
+    let sink = SINK.read().unwrap_or_else(|e| e.into_inner());
+    match sink.as_ref() {
+        Some(sink) => {
+            let target = CString::new(target.replace('\0', "")).unwrap_or_default();
+            let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+            unsafe {
+                (sink.log)(
+                    sink.user as *mut c_void,
+                    level,
+                    target.as_ptr(),
+                    message.as_ptr(),
+                )
+            };
+        }
+        None => {
+            let name = level_filter(level).as_str();
+            eprintln!("[{target}] {name} {message}");
+        }
+    }
+}
+
+struct PluginLogger;
+
+impl Log for PluginLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        enabled(raw_level(metadata.level()))
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            emit(
+                raw_level(record.level()),
+                record.target(),
+                &record.args().to_string(),
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: PluginLogger = PluginLogger;
+
+/// Install `PluginLogger` as this library's `log` backend (idempotent).
+pub fn install() {
+    INSTALL.call_once(|| {
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(level_filter(MAX_LEVEL.load(Ordering::Relaxed)));
+    });
+}
+
+/// `LlmApi::set_logger`.
+pub unsafe extern "C" fn set_logger(log: Option<LogFn>, user: *mut c_void, max_level: i32) {
+    install();
+    let level = if log.is_some() { max_level } else { LOG_INFO };
+    MAX_LEVEL.store(level, Ordering::Relaxed);
+    log::set_max_level(level_filter(level));
+    *SINK.write().unwrap_or_else(|e| e.into_inner()) = log.map(|log| Sink {
+        log,
+        user: user as usize,
+    });
+}
@@ -1,4 +1,5 @@
 use std::path::Path;
+use strata_abi::backend::PromptFlavor;
 use strata_abi::metadata::{BackendMetadataProvider, ModelCoreInfo};
 
 use super::{can_handle, scrape_metadata};
@@ -13,17 +14,14 @@ impl BackendMetadataProvider for LlamaMetadataProvider {
     fn collect(&self, file: &Path) -> Result<ModelCoreInfo, String> {
         let s = scrape_metadata(file)?;
 
-        // HARD REQUIREMENT: model must provide a native chat_template.
-        if s.chat_template
-            .as_deref()
-            .map(str::is_empty)
-            .unwrap_or(true)
-        {
-            return Err(format!(
-                "model '{}' is missing a native chat template, please refer to the model card!",
-                file.display()
-            ));
-        }
+        // A missing template is not an error: the app marks the model "needs template"
+        // and lets the user pick a formatter or supply a template file.
+        let chat_template = s.chat_template.filter(|t| !t.is_empty());
+
+        let flavor = PromptFlavor::infer(
+            chat_template.as_deref(),
+            s.raw.get("general.architecture").map(String::as_str),
+        );
 
         Ok(ModelCoreInfo {
             name: s.name,
@@ -36,8 +34,12 @@ impl BackendMetadataProvider for LlamaMetadataProvider {
             eos_token_id: s.eos_token_id,
             bos_token_id: s.bos_token_id,
             quantization: s.quantization,
-            chat_template: s.chat_template, // present & non-empty by here
-            prompt_flavor_hint: None,       // absolutely no fallback
+            chat_template,
+            prompt_flavor_hint: Some(flavor.as_str().to_string()),
+            geometry: s.geometry,
+            bits_per_weight: s.bits_per_weight,
+            tensor_types: s.tensor_types,
+            output_tensor_type: s.output_tensor_type,
             raw: s.raw,
         })
     }
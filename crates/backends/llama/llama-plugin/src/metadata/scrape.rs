@@ -1,11 +1,12 @@
 // llama-plugin/src/metadata/scrape.rs
 //
-// Pure safe code that calls into ffi::metadata to scrape GGUF headers.
+// Pure safe code: GGUF headers are read with strata-gguf, llama.cpp is not involved.
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-use crate::ffi::{metadata as fmeta, model as mffi};
+use strata_abi::metadata::{ModelGeometry, TensorTypeCount};
+use strata_gguf::{file_type_label, GgufFile};
 
 /// Normalized llama metadata result (backend-local).
 #[derive(Debug, Clone)]
@@ -21,6 +22,10 @@ pub struct LlamaScrape {
     pub bos_token_id: Option<i32>,
     pub quantization: Option<String>,
     pub chat_template: Option<String>,
+    pub geometry: Option<ModelGeometry>,
+    pub bits_per_weight: Option<f32>,
+    pub tensor_types: Vec<TensorTypeCount>,
+    pub output_tensor_type: Option<String>,
     pub raw: HashMap<String, String>,
 }
 
@@ -36,13 +41,10 @@ pub fn scrape_metadata(path: &Path) -> Result<LlamaScrape, String> {
         return Err("unsupported model file (expecting .gguf)".into());
     }
 
-    let model = unsafe { fmeta::open_header_only(path)? };
-    let raw = unsafe { fmeta::read_all_meta(model) };
-
-    // `chat_template` now resides in ffi::model
-    let chat_template = unsafe { mffi::chat_template(model.as_ptr()) };
-
-    unsafe { fmeta::close_model(model) };
+    // Header only: no llama.cpp, no vocab load. Split sets are read whole.
+    let gguf = GgufFile::open_split(path)?;
+    let raw = gguf.flatten();
+    let chat_template = gguf.chat_template().map(str::to_string);
 
     let name = raw
         .get("general.name")
@@ -54,35 +56,28 @@ pub fn scrape_metadata(path: &Path) -> Result<LlamaScrape, String> {
         .cloned()
         .or_else(|| raw.get("general.basename").cloned());
 
-    let context_length = pick_u32(
-        &raw,
-        &[
-            "llama.context_length",
-            "mistral.context_length",
-            "qwen.context_length",
-            "qwen2.context_length",
-            "qwen3.context_length",
-            "phi3.context_length",
-            "context_length",
-        ],
-    );
-
-    let vocab_size = pick_u32(
-        &raw,
-        &[
-            "llama.vocab_size",
-            "tokenizer.ggml.vocab_size",
-            "vocab_size",
-        ],
-    );
+    let context_length = gguf
+        .context_length()
+        .and_then(|n| u32::try_from(n).ok())
+        .or_else(|| pick_u32(&raw, &["context_length"]));
+
+    let vocab_size = gguf
+        .vocab_size()
+        .and_then(|n| u32::try_from(n).ok())
+        .or_else(|| pick_u32(&raw, &["vocab_size"]));
 
     let eos_token_id = pick_i32(&raw, &["tokenizer.ggml.eos_token_id", "eos_token_id"]);
     let bos_token_id = pick_i32(&raw, &["tokenizer.ggml.bos_token_id", "bos_token_id"]);
 
+    let tensor_types = tensor_types(&gguf);
     let quantization = raw
         .get("general.quantization")
         .cloned()
-        .or_else(|| ft_label_from_code(pick_u32(&raw, &["general.file_type"]).unwrap_or_default()));
+        .or_else(|| {
+            let code = pick_u32(&raw, &["general.file_type"])?;
+            file_type_label(code).map(str::to_string)
+        })
+        .or_else(|| tensor_types.first().map(|t| t.ggml_type.to_uppercase()));
 
     let file_type = path
         .extension()
@@ -102,12 +97,55 @@ pub fn scrape_metadata(path: &Path) -> Result<LlamaScrape, String> {
         bos_token_id,
         quantization,
         chat_template,
+        geometry: geometry(&gguf, path),
+        bits_per_weight: gguf.bits_per_weight().map(|b| b as f32),
+        output_tensor_type: output_tensor_type(&gguf, &tensor_types),
+        tensor_types,
         raw,
     })
 }
 
 // -------- helpers (pure safe) --------
 
+fn geometry(gguf: &GgufFile, path: &Path) -> Option<ModelGeometry> {
+    let d = gguf.model_dims()?;
+    let weights_bytes = gguf.tensor_data_size().unwrap_or_else(|| {
+        std::fs::metadata(path)
+            .map(|m| m.len().saturating_sub(gguf.data_offset))
+            .unwrap_or(0)
+    });
+    let n = |v: u64| u32::try_from(v).unwrap_or(u32::MAX);
+    Some(ModelGeometry {
+        weights_bytes,
+        n_layer: n(d.n_layer),
+        n_embd: n(d.n_embd),
+        n_head: n(d.n_head),
+        n_head_kv: n(d.n_head_kv),
+        head_dim_k: n(d.head_dim_k),
+        head_dim_v: n(d.head_dim_v),
+        n_ctx_train: n(d.n_ctx_train),
+        n_vocab: n(d.n_vocab),
+    })
+}
+
+fn tensor_types(gguf: &GgufFile) -> Vec<TensorTypeCount> {
+    gguf.tensor_type_histogram()
+        .into_iter()
+        .map(|s| TensorTypeCount {
+            ggml_type: s.ggml_type.name().to_string(),
+            n_tensors: u32::try_from(s.n_tensors).unwrap_or(u32::MAX),
+            n_elements: s.n_elements,
+            bytes: s.bytes,
+        })
+        .collect()
+}
+
+/// `output.weight`'s type, when it differs from the most common weight type.
+fn output_tensor_type(gguf: &GgufFile, types: &[TensorTypeCount]) -> Option<String> {
+    let out = gguf.output_tensor_type()?.name();
+    (types.first().map(|t| t.ggml_type.as_str()) != Some(out)).then(|| out.to_string())
+}
+
 fn parse_u32_loose(s: &str) -> Option<u32> {
     let t = s.trim().trim_matches('"').trim();
     t.parse::<u32>().ok()
@@ -138,26 +176,3 @@ fn pick_i32(map: &HashMap<String, String>, keys: &[&str]) -> Option<i32> {
     }
     None
 }
-
-// best-effort mapping for GGUF ftype codes -> labels
-fn ft_label_from_code(code: u32) -> Option<String> {
-    let label = match code {
-        0 => "F32",
-        1 => "F16",
-        2 => "Q4_0",
-        3 => "Q4_1",
-        6 => "Q5_1",
-        7 => "Q8_0",
-        8 => "Q2_K",
-        9 => "Q3_K_S",
-        10 => "Q3_K_M",
-        11 => "Q3_K_L",
-        12 => "Q4_K_S",
-        13 => "Q4_K_M",
-        14 => "Q5_K_S",
-        15 => "Q5_K_M",
-        16 => "Q6_K",
-        _ => return None,
-    };
-    Some(label.to_string())
-}
@@ -13,7 +13,8 @@ use crate::ffi; // init/cleanup, default_model_params, etc.
 use crate::ffi::context as cctx; // context creation + token/detok helpers
 use crate::ffi::model as mffi; // model-centric unsafe helpers
 
-use llama_sys::{llama_context_params, llama_model};
+use llama_sys::{ggml_type, llama_adapter_lora, llama_context_params, llama_model};
+use strata_abi::error::StrataError;
 
 /// Safe wrapper around `llama_model*`.
 pub struct LlamaModel {
@@ -30,8 +31,8 @@ impl LlamaModel {
 
     /// Convenience loader for callers that don't go through Backend::load.
     /// Uses crate::ffi::load_model() to stay forward-compatible with llama.cpp.
-    pub fn load_from_file(path: &str) -> Result<Self, String> {
-        let p = unsafe { ffi::load_model(path)? };
+    pub fn load_from_file(path: &str) -> Result<Self, StrataError> {
+        let p = unsafe { ffi::load_model(path, ffi::default_model_params())? };
         Ok(Self { model: p })
     }
 
@@ -45,13 +46,13 @@ impl LlamaModel {
         &'a self,
         params: llama_context_params,
         embeddings_enabled: bool,
-    ) -> Result<LlamaContext<'a>, String> {
+    ) -> Result<LlamaContext<'a>, StrataError> {
         let ctx_ptr = unsafe { cctx::create_context_with_params(self.as_ptr(), params)? };
         Ok(LlamaContext::new(
             self,
             ctx_ptr,
             embeddings_enabled,
-            params.n_ctx as u32,
+            cctx::n_ctx(ctx_ptr.as_ptr()),
         ))
     }
 
@@ -70,6 +71,11 @@ impl LlamaModel {
         cctx::token_to_str(self.as_ptr(), token.0)
     }
 
+    /// Beginning-of-sequence token (`-1` if the vocab has none).
+    pub fn token_bos(&self) -> LlamaToken {
+        LlamaToken(cctx::token_bos(self.as_ptr()))
+    }
+
     /// End-of-sequence token.
     pub fn token_eos(&self) -> LlamaToken {
         LlamaToken(cctx::token_eos(self.as_ptr()))
@@ -80,6 +86,35 @@ impl LlamaModel {
         unsafe { mffi::n_vocab(self.as_ptr()) }
     }
 
+    /// Hidden size (embedding width).
+    pub fn n_embd(&self) -> usize {
+        unsafe { mffi::n_embd(self.as_ptr()) }
+    }
+
+    /// Number of transformer layers.
+    pub fn n_layer(&self) -> usize {
+        unsafe { mffi::n_layer(self.as_ptr()) }
+    }
+
+    /// Bytes held by the weights.
+    pub fn size_bytes(&self) -> u64 {
+        unsafe { mffi::size_bytes(self.as_ptr()) }
+    }
+
+    /// K+V cache bytes for `n_ctx` cells at the given element types
+    /// (standard per-layer cache; SWA/MLA layouts are not modelled).
+    pub fn kv_bytes_estimate(&self, type_k: ggml_type, type_v: ggml_type, n_ctx: u32) -> u64 {
+        let (n_head, n_head_kv) =
+            unsafe { (mffi::n_head(self.as_ptr()), mffi::n_head_kv(self.as_ptr())) };
+        if n_head == 0 {
+            return 0;
+        }
+        let n_embd_gqa = (self.n_embd() / n_head * n_head_kv) as i64;
+        let cells = n_embd_gqa * n_ctx as i64;
+        let per_layer = cctx::row_size(type_k, cells) + cctx::row_size(type_v, cells);
+        per_layer * self.n_layer() as u64
+    }
+
     // --------------------------
     // Metadata / descriptors
     // --------------------------
@@ -104,6 +139,21 @@ impl LlamaModel {
     pub fn meta_iter(&self) -> Vec<(String, String)> {
         unsafe { mffi::meta_iter(self.as_ptr()) }
     }
+
+    /// `general.architecture` (e.g. "llama", "qwen3", "gemma2").
+    pub fn architecture(&self) -> Option<String> {
+        self.meta_get_str("general.architecture")
+    }
+
+    /// Load a LoRA adapter for this model. It lives as long as the model does.
+    pub fn load_lora(&self, path: &str) -> Result<NonNull<llama_adapter_lora>, String> {
+        unsafe { crate::ffi::lora::adapter_init(self.as_ptr(), path) }
+    }
+
+    /// Text pieces of all end-of-generation tokens in the vocab.
+    pub fn eog_pieces(&self) -> Vec<String> {
+        unsafe { mffi::eog_token_texts(self.as_ptr()) }
+    }
 }
 
 impl Drop for LlamaModel {
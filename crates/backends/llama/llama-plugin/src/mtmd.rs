@@ -0,0 +1,49 @@
+// crates/backends/llama/llama-plugin/src/mtmd.rs
+//
+// Owned multimodal projector context. Pointer-level work lives in crate::ffi::mtmd.
+
+use std::ptr::NonNull;
+
+use crate::ffi::mtmd as mffi;
+use crate::model::LlamaModel;
+use llama_sys::{llama_context, mtmd_context};
+
+/// A loaded mmproj projector bound to one text model.
+pub struct MtmdContext {
+    ctx: NonNull<mtmd_context>,
+    marker: String,
+}
+
+impl MtmdContext {
+    /// Load `path` against `model`. The model must outlive the returned value.
+    pub fn load(path: &str, model: &LlamaModel, n_threads: i32) -> Result<Self, String> {
+        let ctx = unsafe { mffi::init_from_file(path, model.as_ptr(), n_threads)? };
+        Ok(Self {
+            ctx,
+            marker: mffi::default_marker(),
+        })
+    }
+
+    /// Marker the prompt must contain once per image.
+    pub fn marker(&self) -> &str {
+        &self.marker
+    }
+
+    /// Evaluate a prompt with images into `lctx`; returns the new n_past.
+    pub fn eval(
+        &mut self,
+        lctx: *mut llama_context,
+        prompt: &str,
+        images: &[Vec<u8>],
+        n_past: i32,
+        n_batch: i32,
+    ) -> Result<i32, String> {
+        unsafe { mffi::eval_prompt(self.ctx.as_ptr(), lctx, prompt, images, n_past, n_batch) }
+    }
+}
+
+impl Drop for MtmdContext {
+    fn drop(&mut self) {
+        unsafe { mffi::free(self.ctx.as_ptr()) }
+    }
+}
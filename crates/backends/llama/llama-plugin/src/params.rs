@@ -5,8 +5,11 @@
 
 use llama_sys::{
     ggml_type, llama_attention_type, llama_context_default_params, llama_context_params,
-    llama_flash_attn_type, llama_pooling_type, llama_rope_scaling_type,
+    llama_flash_attn_type, llama_model_params, llama_pooling_type, llama_rope_scaling_type,
+    GGML_TYPE_BF16, GGML_TYPE_F16, GGML_TYPE_F32, GGML_TYPE_IQ4_NL, GGML_TYPE_Q4_0, GGML_TYPE_Q4_1,
+    GGML_TYPE_Q5_0, GGML_TYPE_Q5_1, GGML_TYPE_Q8_0,
 };
+use strata_abi::session::{KvCacheType, RopeScaling, SessionParams};
 
 // =========================
 // CONTEXT / RUNTIME PARAMS
@@ -14,22 +17,23 @@ use llama_sys::{
 
 #[derive(Debug, Clone)]
 pub struct LlamaParams {
-    pub n_ctx: u32,
+    pub n_ctx: u32, // 0 = model's training context
     pub n_batch: u32,
     pub n_ubatch: u32,
     pub n_seq_max: u32, // we force 1 in to_ffi()
     pub n_threads: i32,
     pub n_threads_batch: i32,
-    pub rope_scaling_type: llama_rope_scaling_type,
     pub pooling_type: llama_pooling_type,
     pub attention_type: llama_attention_type,
-    pub rope_freq_base: f32,
-    pub rope_freq_scale: f32,
-    pub yarn_ext_factor: f32,
-    pub yarn_attn_factor: f32,
-    pub yarn_beta_fast: f32,
-    pub yarn_beta_slow: f32,
-    pub yarn_orig_ctx: u32,
+    // RoPE / YaRN: `None` keeps llama.cpp's default (read from the model).
+    pub rope_scaling_type: Option<llama_rope_scaling_type>,
+    pub rope_freq_base: Option<f32>,
+    pub rope_freq_scale: Option<f32>,
+    pub yarn_ext_factor: Option<f32>,
+    pub yarn_attn_factor: Option<f32>,
+    pub yarn_beta_fast: Option<f32>,
+    pub yarn_beta_slow: Option<f32>,
+    pub yarn_orig_ctx: Option<u32>,
     pub defrag_thold: f32,
     pub type_k: ggml_type,
     pub type_v: ggml_type,
@@ -39,6 +43,10 @@ pub struct LlamaParams {
     pub no_perf: bool,
     pub op_offload: bool,
     pub swa_full: bool,
+    // Model loading (llama_model_params)
+    pub use_mmap: bool,
+    pub use_mlock: bool,
+    pub n_gpu_layers: Option<i32>,
 }
 
 impl Default for LlamaParams {
@@ -50,25 +58,81 @@ impl Default for LlamaParams {
             n_seq_max: 1, // single sequence by default
             n_threads: 0,
             n_threads_batch: 0,
-            rope_scaling_type: 0, // LLAMA_ROPE_SCALING_NONE
-            pooling_type: 0,      // LLAMA_POOLING_TYPE_NONE
-            attention_type: 0,    // model default (e.g., SCALE_NORM)
-            rope_freq_base: 10000.0,
-            rope_freq_scale: 1.0,
-            yarn_ext_factor: -1.0,
-            yarn_attn_factor: 1.0,
-            yarn_beta_fast: 32.0,
-            yarn_beta_slow: 1.0,
-            yarn_orig_ctx: 0,
+            pooling_type: 0,   // LLAMA_POOLING_TYPE_NONE
+            attention_type: 0, // model default (e.g., SCALE_NORM)
+            rope_scaling_type: None,
+            rope_freq_base: None,
+            rope_freq_scale: None,
+            yarn_ext_factor: None,
+            yarn_attn_factor: None,
+            yarn_beta_fast: None,
+            yarn_beta_slow: None,
+            yarn_orig_ctx: None,
             defrag_thold: 0.0,
-            type_k: 1, // GGML_TYPE_F16
-            type_v: 1, // GGML_TYPE_F16
+            type_k: GGML_TYPE_F16,
+            type_v: GGML_TYPE_F16,
             embeddings: false,
             offload_kqv: false,
             flash_attn_type: 0, // LLAMA_FLASH_ATTN_DISABLED
             no_perf: false,
             op_offload: false,
             swa_full: false,
+            use_mmap: true,
+            use_mlock: false,
+            n_gpu_layers: None,
+        }
+    }
+}
+
+/// ggml element type for a KV cache type.
+pub fn kv_cache_ggml_type(t: KvCacheType) -> ggml_type {
+    match t {
+        KvCacheType::F32 => GGML_TYPE_F32,
+        KvCacheType::F16 => GGML_TYPE_F16,
+        KvCacheType::Bf16 => GGML_TYPE_BF16,
+        KvCacheType::Q8_0 => GGML_TYPE_Q8_0,
+        KvCacheType::Q5_1 => GGML_TYPE_Q5_1,
+        KvCacheType::Q5_0 => GGML_TYPE_Q5_0,
+        KvCacheType::Q4_1 => GGML_TYPE_Q4_1,
+        KvCacheType::Q4_0 => GGML_TYPE_Q4_0,
+        KvCacheType::Iq4Nl => GGML_TYPE_IQ4_NL,
+    }
+}
+
+impl From<&SessionParams> for LlamaParams {
+    /// Map host session params (normalized first) onto llama params.
+    fn from(sp: &SessionParams) -> Self {
+        let sp = sp.normalized();
+        Self {
+            n_ctx: sp.n_ctx,
+            n_batch: sp.n_batch,
+            n_ubatch: sp.n_ubatch,
+            n_threads: sp.n_threads,
+            n_threads_batch: sp.n_threads_batch,
+            type_k: kv_cache_ggml_type(sp.type_k),
+            type_v: kv_cache_ggml_type(sp.type_v),
+            flash_attn_type: match sp.flash_attn {
+                None => -1, // LLAMA_FLASH_ATTN_TYPE_AUTO
+                Some(false) => 0,
+                Some(true) => 1,
+            },
+            offload_kqv: sp.offload_kqv.unwrap_or(true),
+            rope_scaling_type: sp.rope_scaling.map(|r| match r {
+                RopeScaling::None => 0,
+                RopeScaling::Linear => 1,
+                RopeScaling::Yarn => 2,
+            }),
+            rope_freq_base: sp.rope_freq_base,
+            rope_freq_scale: sp.rope_freq_scale,
+            yarn_ext_factor: sp.yarn_ext_factor,
+            yarn_attn_factor: sp.yarn_attn_factor,
+            yarn_beta_fast: sp.yarn_beta_fast,
+            yarn_beta_slow: sp.yarn_beta_slow,
+            yarn_orig_ctx: sp.yarn_orig_ctx,
+            use_mmap: sp.use_mmap,
+            use_mlock: sp.use_mlock,
+            n_gpu_layers: sp.n_gpu_layers,
+            ..Self::default()
         }
     }
 }
@@ -89,18 +153,33 @@ impl LlamaParams {
         p.n_threads = self.n_threads;
         p.n_threads_batch = self.n_threads_batch;
 
-        p.rope_scaling_type = self.rope_scaling_type;
         p.pooling_type = self.pooling_type;
         p.attention_type = self.attention_type;
 
-        p.rope_freq_base = self.rope_freq_base;
-        p.rope_freq_scale = self.rope_freq_scale;
-
-        p.yarn_ext_factor = self.yarn_ext_factor;
-        p.yarn_attn_factor = self.yarn_attn_factor;
-        p.yarn_beta_fast = self.yarn_beta_fast;
-        p.yarn_beta_slow = self.yarn_beta_slow;
-        p.yarn_orig_ctx = self.yarn_orig_ctx;
+        if let Some(v) = self.rope_scaling_type {
+            p.rope_scaling_type = v;
+        }
+        if let Some(v) = self.rope_freq_base {
+            p.rope_freq_base = v;
+        }
+        if let Some(v) = self.rope_freq_scale {
+            p.rope_freq_scale = v;
+        }
+        if let Some(v) = self.yarn_ext_factor {
+            p.yarn_ext_factor = v;
+        }
+        if let Some(v) = self.yarn_attn_factor {
+            p.yarn_attn_factor = v;
+        }
+        if let Some(v) = self.yarn_beta_fast {
+            p.yarn_beta_fast = v;
+        }
+        if let Some(v) = self.yarn_beta_slow {
+            p.yarn_beta_slow = v;
+        }
+        if let Some(v) = self.yarn_orig_ctx {
+            p.yarn_orig_ctx = v;
+        }
 
         p.defrag_thold = self.defrag_thold;
 
@@ -132,6 +211,17 @@ impl LlamaParams {
 
         p
     }
+
+    /// Model-load params (mmap/mlock/offload) on top of the crate defaults.
+    pub fn to_model_ffi(&self) -> llama_model_params {
+        let mut p = crate::ffi::default_model_params();
+        p.use_mmap = self.use_mmap;
+        p.use_mlock = self.use_mlock;
+        if let Some(n) = self.n_gpu_layers {
+            p.n_gpu_layers = n;
+        }
+        p
+    }
 }
 
 // =========================
@@ -109,6 +109,34 @@ fn get_cargo_target_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
     Ok(target_dir.to_path_buf())
 }
 
+/// Writes a CMake project that builds llama.cpp plus only the `mtmd` library
+/// from its tools, and returns its directory.
+fn write_mtmd_project(out_dir: &Path) -> PathBuf {
+    let dir = out_dir.join("mtmd-project");
+    std::fs::create_dir_all(&dir).expect("Failed to create mtmd project dir");
+    let cmake_lists = r#"cmake_minimum_required(VERSION 3.14)
+project(llama_sys C CXX)
+
+file(TO_CMAKE_PATH "${LLAMA_SRC}" LLAMA_SRC)
+add_subdirectory(${LLAMA_SRC} llama.cpp)
+add_subdirectory(${LLAMA_SRC}/tools/mtmd tools/mtmd)
+
+# tools/mtmd also declares the mtmd CLIs; only the library is needed.
+get_property(mtmd_targets DIRECTORY ${LLAMA_SRC}/tools/mtmd PROPERTY BUILDSYSTEM_TARGETS)
+foreach(target IN LISTS mtmd_targets)
+    if(NOT target STREQUAL "mtmd")
+        set_target_properties(${target} PROPERTIES EXCLUDE_FROM_ALL TRUE)
+    endif()
+endforeach()
+"#;
+    let path = dir.join("CMakeLists.txt");
+    // Rewriting an unchanged file would make CMake reconfigure every build.
+    if std::fs::read_to_string(&path).ok().as_deref() != Some(cmake_lists) {
+        std::fs::write(&path, cmake_lists).expect("Failed to write mtmd CMakeLists.txt");
+    }
+    dir
+}
+
 fn extract_lib_names(out_dir: &Path, build_shared_libs: bool) -> Vec<String> {
     let lib_pattern = if cfg!(windows) {
         "*.lib"
@@ -258,6 +286,7 @@ fn main() {
         llama_src.join("src"),
         llama_src.join("ggml/src"),
         llama_src.join("common"),
+        llama_src.join("tools/mtmd"),
     ];
     for entry in walkdir::WalkDir::new(&llama_src)
         .into_iter()
@@ -291,6 +320,7 @@ fn main() {
         .header("wrapper.h")
         .clang_arg(format!("-I{}", llama_src.join("include").display()))
         .clang_arg(format!("-I{}", llama_src.join("ggml/include").display()))
+        .clang_arg(format!("-I{}", llama_src.join("tools/mtmd").display()))
         .clang_arg(format!("--target={}", target_triple))
         .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
         .derive_partialeq(true)
@@ -298,6 +328,10 @@ fn main() {
         .allowlist_type("ggml_.*")
         .allowlist_function("llama_.*")
         .allowlist_type("llama_.*")
+        .allowlist_function("gguf_.*")
+        .allowlist_type("gguf_.*")
+        .allowlist_function("mtmd_.*")
+        .allowlist_type("mtmd_.*")
         .prepend_enum_name(false)
         .generate()
         .expect("Failed to generate bindings");
@@ -319,12 +353,18 @@ fn main() {
     }
 
     // ===== Original CMake flow (static or build-time shared link) =====
-    let mut config = Config::new(&llama_src);
+    // libmtmd (multimodal projector support) lives under tools/, which would
+    // also build every CLI tool; wrap llama.cpp and add only tools/mtmd.
+    let mtmd_project = write_mtmd_project(&out_dir);
+    let mut config = Config::new(&mtmd_project);
+    config.define("LLAMA_SRC", &llama_src);
 
     config.define("LLAMA_BUILD_TESTS", "OFF");
     config.define("LLAMA_BUILD_EXAMPLES", "OFF");
     config.define("LLAMA_BUILD_SERVER", "OFF");
     config.define("LLAMA_BUILD_TOOLS", "OFF");
+    config.define("LLAMA_BUILD_COMMON", "OFF");
+    config.define("LLAMA_TOOLS_INSTALL", "OFF");
     config.define("LLAMA_CURL", "OFF");
 
     config.define(
@@ -440,6 +480,10 @@ fn main() {
         out_dir.join("lib64").display()
     );
     println!("cargo:rustc-link-search={}", build_dir.display());
+    println!(
+        "cargo:rustc-link-search={}",
+        build_dir.join("build/tools/mtmd").display()
+    );
 
     if cfg!(feature = "cuda") && !build_shared_libs {
         println!("cargo:rerun-if-env-changed=CUDA_PATH");
@@ -465,7 +509,10 @@ fn main() {
     }
 
     let llama_libs_kind = if build_shared_libs { "dylib" } else { "static" };
-    let llama_libs = extract_lib_names(&out_dir, build_shared_libs);
+    let mut llama_libs = extract_lib_names(&out_dir, build_shared_libs);
+    // mtmd depends on llama, so it has to precede it for static linking.
+    llama_libs.retain(|lib| lib != "mtmd");
+    println!("cargo:rustc-link-lib={}={}", llama_libs_kind, "mtmd");
 
     if llama_libs.is_empty() {
         // fallback: explicitly link llama
@@ -1 +1,4 @@
-#include "llama.cpp/include/llama.h"
\ No newline at end of file
+#include "llama.cpp/include/llama.h"
+#include "llama.cpp/ggml/include/gguf.h"
+#include "llama.cpp/tools/mtmd/mtmd.h"
+#include "llama.cpp/tools/mtmd/mtmd-helper.h"
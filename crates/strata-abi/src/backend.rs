@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
+use crate::error::{ErrorCode, StrataError};
 use crate::sampling::{BackendSamplingCapabilities, SamplingParams};
+use crate::session::{MemoryReport, PerfCounters, SessionParams};
 use crate::token::Token;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,62 +11,290 @@ pub enum Role {
     System,
     User,
     Assistant,
+    /// Result of a tool call, fed back to the model.
+    Tool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PromptFlavor {
     ChatMl,
     InstBlock,
     UserAssistant,
     Plain,
     Phi3,
+    Llama3,
+    Gemma,
+}
+
+impl PromptFlavor {
+    /// Stable name used in metadata hints and across the C ABI.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PromptFlavor::ChatMl => "ChatMl",
+            PromptFlavor::InstBlock => "InstBlock",
+            PromptFlavor::UserAssistant => "UserAssistant",
+            PromptFlavor::Plain => "Plain",
+            PromptFlavor::Phi3 => "Phi3",
+            PromptFlavor::Llama3 => "Llama3",
+            PromptFlavor::Gemma => "Gemma",
+        }
+    }
+
+    /// Inverse of [`PromptFlavor::as_str`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "ChatMl" => PromptFlavor::ChatMl,
+            "InstBlock" => PromptFlavor::InstBlock,
+            "UserAssistant" => PromptFlavor::UserAssistant,
+            "Plain" => PromptFlavor::Plain,
+            "Phi3" => PromptFlavor::Phi3,
+            "Llama3" => PromptFlavor::Llama3,
+            "Gemma" => PromptFlavor::Gemma,
+            _ => return None,
+        })
+    }
+
+    /// Best-effort guess from a model's chat template text and architecture
+    /// (`general.architecture` for GGUF). Template markers win over the architecture,
+    /// since fine-tunes frequently swap templates (e.g. ChatML on a llama base).
+    pub fn infer(chat_template: Option<&str>, architecture: Option<&str>) -> Self {
+        if let Some(t) = chat_template {
+            if t.contains("<|im_start|>") {
+                return PromptFlavor::ChatMl;
+            }
+            if t.contains("<|start_header_id|>") || t.contains("<|eot_id|>") {
+                return PromptFlavor::Llama3;
+            }
+            if t.contains("<start_of_turn>") {
+                return PromptFlavor::Gemma;
+            }
+            if t.contains("<|user|>") && t.contains("<|end|>") {
+                return PromptFlavor::Phi3;
+            }
+            if t.contains("[INST]") {
+                return PromptFlavor::InstBlock;
+            }
+        }
+
+        let arch = architecture.unwrap_or_default().to_ascii_lowercase();
+        match arch.as_str() {
+            a if a.starts_with("qwen") => PromptFlavor::ChatMl,
+            a if a.starts_with("gemma") => PromptFlavor::Gemma,
+            a if a.starts_with("phi3") => PromptFlavor::Phi3,
+            "mistral" | "mixtral" => PromptFlavor::InstBlock,
+            // Unknown (or bare "llama", which spans both INST and Llama-3 styles):
+            // keep the historical default.
+            _ => PromptFlavor::ChatMl,
+        }
+    }
+
+    /// Textual end-of-turn markers this flavor's template emits.
+    pub fn turn_end_markers(&self) -> &'static [&'static str] {
+        match self {
+            PromptFlavor::ChatMl => &["<|im_end|>", "<|im_start|>"],
+            PromptFlavor::Llama3 => &["<|eot_id|>", "<|start_header_id|>"],
+            PromptFlavor::Gemma => &["<end_of_turn>", "<start_of_turn>"],
+            PromptFlavor::Phi3 => &["<|end|>", "<|user|>"],
+            PromptFlavor::InstBlock => &["</s>", "[INST]"],
+            PromptFlavor::UserAssistant => &["\nUser:"],
+            PromptFlavor::Plain => &[],
+        }
+    }
+}
+
+/// Merge end-of-generation token pieces with the chat-template turn markers
+/// for `flavor`. Markers only count if they actually occur in `chat_template`
+/// (when one is given). Order is preserved and duplicates are dropped.
+pub fn derive_stop_strings(
+    eog_pieces: &[String],
+    chat_template: Option<&str>,
+    flavor: PromptFlavor,
+) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    let mut push = |s: &str| {
+        if !s.is_empty() && !out.iter().any(|o| o == s) {
+            out.push(s.to_string());
+        }
+    };
+
+    for piece in eog_pieces {
+        push(piece);
+    }
+    for marker in flavor.turn_end_markers() {
+        match chat_template {
+            Some(t) if !t.contains(marker) => {}
+            _ => push(marker),
+        }
+    }
+    out
+}
+
+/// A function call requested by the model in an assistant turn.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// Call id, echoed back on the matching `Role::Tool` turn.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    /// Arguments as JSON text (an object for well-behaved models).
+    pub arguments: String,
+}
+
+/// A LoRA adapter loaded against the resident model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoraAdapter {
+    pub id: i32,
+    pub path: String,
+    /// Scale on this session; `None` while loaded but detached.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f32>,
+}
+
+/// A control vector (activation-steering direction) to apply to a session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ControlVector {
+    /// Control-vector GGUF (`direction.<layer>` tensors).
+    pub path: String,
+    /// Multiplier on the direction; negative values steer the other way.
+    #[serde(default = "default_cvec_strength")]
+    pub strength: f32,
+    /// First layer to steer (inclusive); `None` = from layer 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer_start: Option<i32>,
+    /// Last layer to steer (inclusive); `None` = through the last layer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer_end: Option<i32>,
+}
+
+fn default_cvec_strength() -> f32 {
+    1.0
+}
+
+/// One piece of a multimodal message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    Text {
+        text: String,
+    },
+    /// Encoded image file (PNG, JPEG, …) as raw bytes.
+    Image {
+        data: Vec<u8>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        mime: Option<String>,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatTurn {
     pub role: Role,
+    /// Text of the turn. For multimodal turns this is the concatenated text parts.
     pub content: String,
+    /// Ordered text/image parts; empty for plain-text turns.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parts: Vec<ContentPart>,
+    /// Calls requested by an assistant turn.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// For `Role::Tool` turns: the id of the call this answers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    /// For `Role::Tool` turns: the name of the tool that produced `content`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
 }
 impl ChatTurn {
     #[inline]
-    pub fn system<S: Into<String>>(s: S) -> Self {
+    pub fn new<S: Into<String>>(role: Role, content: S) -> Self {
         Self {
-            role: Role::System,
-            content: s.into(),
+            role,
+            content: content.into(),
+            parts: Vec::new(),
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+            name: None,
         }
     }
     #[inline]
+    pub fn system<S: Into<String>>(s: S) -> Self {
+        Self::new(Role::System, s)
+    }
+    #[inline]
     pub fn user<S: Into<String>>(s: S) -> Self {
+        Self::new(Role::User, s)
+    }
+    #[inline]
+    pub fn assistant<S: Into<String>>(s: S) -> Self {
+        Self::new(Role::Assistant, s)
+    }
+    /// User turn made of text and image parts.
+    pub fn user_parts(parts: Vec<ContentPart>) -> Self {
+        let content = parts
+            .iter()
+            .filter_map(|p| match p {
+                ContentPart::Text { text } => Some(text.as_str()),
+                ContentPart::Image { .. } => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
         Self {
-            role: Role::User,
-            content: s.into(),
+            parts,
+            ..Self::new(Role::User, content)
         }
     }
+    /// True if any part is an image.
+    pub fn has_images(&self) -> bool {
+        self.parts
+            .iter()
+            .any(|p| matches!(p, ContentPart::Image { .. }))
+    }
+    /// Assistant turn that requests `calls` (with optional accompanying text).
     #[inline]
-    pub fn assistant<S: Into<String>>(s: S) -> Self {
+    pub fn assistant_tool_calls<S: Into<String>>(s: S, calls: Vec<ToolCall>) -> Self {
+        Self {
+            tool_calls: calls,
+            ..Self::new(Role::Assistant, s)
+        }
+    }
+    /// Tool result for the call `call_id` made to `name`.
+    #[inline]
+    pub fn tool<S: Into<String>>(name: &str, call_id: Option<String>, s: S) -> Self {
         Self {
-            role: Role::Assistant,
-            content: s.into(),
+            tool_call_id: call_id,
+            name: Some(name.to_string()),
+            ..Self::new(Role::Tool, s)
         }
     }
 }
 
 /// Backend-agnostic interface for inference engines.
 pub trait LLMBackend {
-    fn load<P: AsRef<Path>>(model_path: P) -> Result<Self, String>
+    fn load<P: AsRef<Path>>(model_path: P) -> Result<Self, StrataError>
     where
         Self: Sized;
 
-    fn tokenize(&self, text: &str) -> Result<Vec<Token>, String>;
+    /// Load with explicit session parameters. Backends that can't honour them
+    /// fall back to [`LLMBackend::load`].
+    fn load_with_params<P: AsRef<Path>>(
+        model_path: P,
+        _params: &SessionParams,
+    ) -> Result<Self, StrataError>
+    where
+        Self: Sized,
+    {
+        Self::load(model_path)
+    }
 
-    fn evaluate(&mut self, tokens: &[Token], n_past: i32) -> Result<(), String>;
+    fn tokenize(&self, text: &str) -> Result<Vec<Token>, StrataError>;
+
+    fn evaluate(&mut self, tokens: &[Token], n_past: i32) -> Result<(), StrataError>;
 
     fn sample(
         &mut self,
         n_past: i32,
         params: &SamplingParams,
         token_history: &[Token],
-    ) -> Result<Token, String>;
+    ) -> Result<Token, StrataError>;
 
     /// Optional hint so core can choose a reasonable generic prompt wrapper.
     fn prompt_flavor(&self) -> PromptFlavor {
@@ -72,7 +302,7 @@ pub trait LLMBackend {
     }
 
     /// Decode a single token ID into a UTF-8 fragment.
-    fn decode_token(&self, token: Token) -> Result<String, String>;
+    fn decode_token(&self, token: Token) -> Result<String, StrataError>;
 
     /// Model’s EOS token.
     fn eos_token(&self) -> Token;
@@ -91,9 +321,93 @@ pub trait LLMBackend {
         None
     }
 
-    /// Backend-provided default stop strings (for UI/logging; core doesn’t enforce).
-    fn default_stop_strings(&self) -> &'static [&'static str] {
-        &[]
+    /// Raw Jinja chat template from model metadata (`tokenizer.chat_template`),
+    /// so core can render it when the native formatter declines.
+    fn chat_template(&self) -> Option<String> {
+        None
+    }
+
+    /// Text marker the backend replaces with image embeddings, once a projector
+    /// is loaded (`None` = no image input).
+    fn media_marker(&self) -> Option<String> {
+        None
+    }
+
+    /// Load a multimodal projector (e.g. an `mmproj-*.gguf`) for the current model.
+    fn load_projector(&mut self, _projector_path: &Path) -> Result<(), StrataError> {
+        Err(StrataError::new(
+            ErrorCode::Unsupported,
+            "this backend does not support image input",
+        ))
+    }
+
+    /// Evaluate `prompt` (containing one `media_marker` per image, in order) with
+    /// `images` (encoded files) into the KV cache, starting at `n_past`.
+    /// Returns the new `n_past`.
+    fn evaluate_with_media(
+        &mut self,
+        _prompt: &str,
+        _images: &[Vec<u8>],
+        _n_past: i32,
+    ) -> Result<i32, StrataError> {
+        Err(StrataError::new(
+            ErrorCode::Unsupported,
+            "this backend does not support image input",
+        ))
+    }
+
+    /// Load a LoRA adapter (GGUF) against the resident model without touching
+    /// the base weights. Returns its id; loading the same file again reuses it.
+    fn load_lora(&mut self, _path: &Path) -> Result<i32, StrataError> {
+        Err(StrataError::new(
+            ErrorCode::Unsupported,
+            "this backend does not support LoRA adapters",
+        ))
+    }
+
+    /// Attach a loaded adapter to this session at `scale`, or update its scale.
+    fn set_lora(&mut self, _id: i32, _scale: f32) -> Result<(), StrataError> {
+        Err(StrataError::new(
+            ErrorCode::Unsupported,
+            "this backend does not support LoRA adapters",
+        ))
+    }
+
+    /// Detach an adapter from this session; it stays loaded for reuse.
+    fn remove_lora(&mut self, _id: i32) -> Result<(), StrataError> {
+        Err(StrataError::new(
+            ErrorCode::Unsupported,
+            "this backend does not support LoRA adapters",
+        ))
+    }
+
+    /// Adapters loaded for this model, with their scale on this session.
+    fn lora_adapters(&self) -> Vec<LoraAdapter> {
+        Vec::new()
+    }
+
+    /// Replace the session's control vectors; several are summed, an empty
+    /// slice turns steering off.
+    fn set_control_vectors(&mut self, vectors: &[ControlVector]) -> Result<(), StrataError> {
+        if vectors.is_empty() {
+            Ok(())
+        } else {
+            Err(StrataError::new(
+                ErrorCode::Unsupported,
+                "this backend does not support control vectors",
+            ))
+        }
+    }
+
+    /// Model’s BOS token, if it has one (exposed to templates as `bos_token`).
+    fn bos_token(&self) -> Option<Token> {
+        None
+    }
+
+    /// Backend-provided default stop strings for the loaded model
+    /// (for UI/logging; core doesn’t enforce).
+    fn default_stop_strings(&self) -> Vec<String> {
+        Vec::new()
     }
 
     /// Clear any cached sequence/KV state while keeping the model loaded.
@@ -104,6 +418,17 @@ pub trait LLMBackend {
         None
     }
 
+    /// Bytes held by the model weights, KV cache and compute buffers, if known.
+    fn memory_report(&self) -> Option<MemoryReport> {
+        None
+    }
+
+    /// Backend timings since the previous call, which resets them; `None`
+    /// if the backend doesn't measure.
+    fn take_perf_counters(&mut self) -> Option<PerfCounters> {
+        None
+    }
+
     /// Report what sampler controls are supported.
     fn sampling_capabilities(&self) -> BackendSamplingCapabilities {
         BackendSamplingCapabilities::default()
@@ -116,13 +441,13 @@ pub trait LLMBackend {
         start: usize,
         _remove_special: bool,
         _unparse_special: bool,
-    ) -> Result<Vec<u8>, String> {
+    ) -> Result<Vec<u8>, StrataError> {
         let mut s = String::new();
         for tok in &token_history[start..] {
             s.push_str(
                 &self
                     .decode_token(*tok)
-                    .map_err(|e| format!("fallback detok: {e}"))?,
+                    .map_err(|e| e.context("fallback detok"))?,
             );
         }
         Ok(s.into_bytes())
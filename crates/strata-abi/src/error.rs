@@ -0,0 +1,188 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ffi::{
+    ERR_ABORTED, ERR_CONTEXT_FULL, ERR_FAIL, ERR_INVALID_ARG, ERR_MODEL_LOAD, ERR_OOM,
+    ERR_UNSUPPORTED,
+};
+
+/// Why a backend call failed; crosses the C ABI as an `ERR_*` code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// Unclassified failure (`ERR_FAIL`, and any code this host doesn't know).
+    Failed,
+    /// Bad input from the caller: null pointers, invalid UTF-8/JSON, rejected parameters.
+    InvalidArg,
+    /// The backend could not allocate (model weights, KV cache, compute buffers).
+    Oom,
+    /// No room left in the context window / KV cache.
+    ContextFull,
+    /// The call was interrupted (abort callback, cancellation).
+    Aborted,
+    /// The model file could not be opened or parsed.
+    ModelLoad,
+    /// The plugin or model does not implement the requested feature.
+    Unsupported,
+}
+
+impl ErrorCode {
+    const ALL: [Self; 7] = [
+        Self::Failed,
+        Self::InvalidArg,
+        Self::Oom,
+        Self::ContextFull,
+        Self::Aborted,
+        Self::ModelLoad,
+        Self::Unsupported,
+    ];
+
+    /// Code for an `ERR_*` value; negated codes (from value-returning calls)
+    /// are accepted too.
+    pub fn from_raw(code: i32) -> Self {
+        match code.saturating_abs() {
+            ERR_INVALID_ARG => Self::InvalidArg,
+            ERR_OOM => Self::Oom,
+            ERR_CONTEXT_FULL => Self::ContextFull,
+            ERR_ABORTED => Self::Aborted,
+            ERR_MODEL_LOAD => Self::ModelLoad,
+            ERR_UNSUPPORTED => Self::Unsupported,
+            _ => Self::Failed,
+        }
+    }
+
+    pub fn raw(self) -> i32 {
+        match self {
+            Self::Failed => ERR_FAIL,
+            Self::InvalidArg => ERR_INVALID_ARG,
+            Self::Oom => ERR_OOM,
+            Self::ContextFull => ERR_CONTEXT_FULL,
+            Self::Aborted => ERR_ABORTED,
+            Self::ModelLoad => ERR_MODEL_LOAD,
+            Self::Unsupported => ERR_UNSUPPORTED,
+        }
+    }
+
+    /// The serde name, e.g. `"context_full"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Failed => "failed",
+            Self::InvalidArg => "invalid_arg",
+            Self::Oom => "oom",
+            Self::ContextFull => "context_full",
+            Self::Aborted => "aborted",
+            Self::ModelLoad => "model_load",
+            Self::Unsupported => "unsupported",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A backend failure as surfaced to the host and the UI.
+///
+/// The code is carried as data from the plugin's `take_error` through the
+/// backend trait, the plugin host and the engine; callers add context with
+/// [`StrataError::context`], which keeps it. `Display` prefixes the message
+/// with the code (`"oom: …"`, nothing for `Failed`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StrataError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl StrataError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Prepend `what` to the message (`"what: message"`), keeping the code.
+    pub fn context(self, what: impl fmt::Display) -> Self {
+        Self::new(self.code, format!("{what}: {}", self.message))
+    }
+
+    /// Inverse of `Display`, for plugins that only report `last_error` text:
+    /// a leading `"<code>: "` tag sets the code and is dropped from the
+    /// message. Anything else, including a tag further in, is `Failed`.
+    pub fn parse(text: &str) -> Self {
+        for code in ErrorCode::ALL.into_iter().skip(1) {
+            if let Some(tail) = text
+                .strip_prefix(code.as_str())
+                .and_then(|t| t.strip_prefix(": "))
+            {
+                return Self::new(code, tail);
+            }
+        }
+        Self::new(ErrorCode::Failed, text)
+    }
+}
+
+impl fmt::Display for StrataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.code {
+            ErrorCode::Failed => f.write_str(&self.message),
+            code => write!(f, "{code}: {}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for StrataError {}
+
+impl From<StrataError> for String {
+    fn from(e: StrataError) -> Self {
+        e.to_string()
+    }
+}
+
+/// Plain text is an unclassified failure; the text is never sniffed for a code.
+impl From<String> for StrataError {
+    fn from(text: String) -> Self {
+        Self::new(ErrorCode::Failed, text)
+    }
+}
+
+impl From<&str> for StrataError {
+    fn from(text: &str) -> Self {
+        Self::new(ErrorCode::Failed, text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_only_a_leading_tag() {
+        let e = StrataError::new(ErrorCode::ContextFull, "llama_decode failed: 1");
+        assert_eq!(StrataError::parse(&e.to_string()), e);
+
+        let plain = "load failed: unsupported: tensor type 39";
+        assert_eq!(
+            StrataError::parse(plain),
+            StrataError::new(ErrorCode::Failed, plain)
+        );
+        assert_eq!(StrataError::parse(plain).to_string(), plain);
+    }
+
+    #[test]
+    fn plain_text_is_never_sniffed() {
+        let e = StrataError::from("unsupported: tensor type 39".to_string());
+        assert_eq!(e.code, ErrorCode::Failed);
+        assert_eq!(e.message, "unsupported: tensor type 39");
+    }
+
+    #[test]
+    fn context_keeps_the_code() {
+        let e = StrataError::new(ErrorCode::Oom, "no room for kv").context("loading model");
+        assert_eq!(e.code, ErrorCode::Oom);
+        assert_eq!(e.to_string(), "oom: loading model: no room for kv");
+    }
+}
@@ -1,12 +1,41 @@
-use core::ffi::{c_char, c_void};
+use core::ffi::{CStr, c_char, c_void};
 
-/// Bump this when you break the ABI. Host checks it at load time.
-pub const STRATA_ABI_VERSION: u32 = 4; // was 3
+use crate::error::{ErrorCode, StrataError};
+
+/// Bump this only when you break the ABI (reorder, remove or retype an entry
+/// point). Additive changes append an `Option<_>` entry point to the end of
+/// `LlmApi` (hosts see it through `PluginInfo::struct_size`) or ship as an
+/// extension vtable (`PluginInfo::get_extension`), and keep this version.
+pub const STRATA_ABI_VERSION: u32 = 12; // was 11
+
+/// Oldest `abi_version` hosts still load, via the `legacy` layouts. Of the
+/// older versions, only 4 and 11 are loadable.
+pub const STRATA_ABI_MIN_VERSION: u32 = 4;
 
 pub const PLUGIN_ENTRY_SYMBOL: &str = "strata_plugin_entry_v1";
 
+// Error codes (`crate::error::ErrorCode`). Status calls return `ERR_OK` or
+// one of these; calls returning a value (token id, n_past, adapter id)
+// return it negated; calls returning a pointer/string return null. Either
+// way the message is available from `LlmApi::take_error`.
 pub const ERR_OK: i32 = 0;
+/// Unclassified failure.
 pub const ERR_FAIL: i32 = 1;
+pub const ERR_INVALID_ARG: i32 = 2;
+pub const ERR_OOM: i32 = 3;
+pub const ERR_CONTEXT_FULL: i32 = 4;
+pub const ERR_ABORTED: i32 = 5;
+pub const ERR_MODEL_LOAD: i32 = 6;
+pub const ERR_UNSUPPORTED: i32 = 7;
+
+// Log levels for `LogFn`, in the `log` crate's order (`LOG_OFF` is only
+// meaningful as a `max_level`).
+pub const LOG_OFF: i32 = 0;
+pub const LOG_ERROR: i32 = 1;
+pub const LOG_WARN: i32 = 2;
+pub const LOG_INFO: i32 = 3;
+pub const LOG_DEBUG: i32 = 4;
+pub const LOG_TRACE: i32 = 5;
 
 #[repr(C)]
 #[derive(Clone, Copy)]
@@ -22,11 +51,51 @@ pub struct Int32Array {
     pub len: usize,
 }
 
+/// Borrowed byte buffer (e.g. an encoded image).
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct ByteSlice {
+    pub ptr: *const u8,
+    pub len: usize,
+}
+
+// ---------- Capabilities (`PluginInfo::capabilities`) ----------
+//
+// Optional features a plugin implements; the host turns the matching calls
+// into "not supported" errors when a bit is clear.
+
+/// `load_projector` / `media_marker` / `evaluate_media` (image input).
+pub const CAP_VISION: u64 = 1 << 0;
+/// `load_lora` / `set_lora` / `remove_lora` / `lora_list_json`.
+pub const CAP_LORA: u64 = 1 << 1;
+/// `set_control_vectors_json`.
+pub const CAP_CONTROL_VECTORS: u64 = 1 << 2;
+/// `create_session_with_params` honours `SessionParams` (else `create_session` is used).
+pub const CAP_SESSION_PARAMS: u64 = 1 << 3;
+/// `memory_report_json`.
+pub const CAP_MEMORY_REPORT: u64 = 1 << 4;
+
+/// Everything an ABI 11 plugin implements (they predate the bitset).
+pub const CAPS_V11: u64 =
+    CAP_VISION | CAP_LORA | CAP_CONTROL_VECTORS | CAP_SESSION_PARAMS | CAP_MEMORY_REPORT;
+
+/// ABI 4 plugins implement none of the optional features.
+pub const CAPS_V4: u64 = 0;
+
 #[repr(C)]
+#[derive(Clone, Copy)]
 pub struct PluginInfo {
     pub abi_version: u32,
     pub id: *const c_char,     // "llama"
     pub semver: *const c_char, // "0.1.0"
+    /// `size_of::<PluginApi>()` in the plugin's build, so hosts know which
+    /// appended entry points exist.
+    pub struct_size: usize,
+    /// `CAP_*` bits.
+    pub capabilities: u64,
+    /// Look up an extension vtable by name (e.g. `c"strata.example.v1"`);
+    /// null if the plugin doesn't provide it.
+    pub get_extension: Option<GetExtensionFn>,
 }
 
 // ---------- Function pointer types (C ABI) ----------
@@ -34,10 +103,16 @@ pub struct PluginInfo {
 pub type CanHandleFn = unsafe extern "C" fn(model_path: *const c_char) -> bool;
 pub type CollectJsonFn = unsafe extern "C" fn(model_path: *const c_char) -> StrataString;
 pub type FreeStringFn = unsafe extern "C" fn(s: StrataString);
+pub type GetExtensionFn = unsafe extern "C" fn(name: *const c_char) -> *const c_void;
 
 pub type CreateSessionFn = unsafe extern "C" fn(model_path: *const c_char) -> *mut c_void;
 pub type DestroySessionFn = unsafe extern "C" fn(session: *mut c_void);
+/// Like `CreateSessionFn`, with a JSON `strata_abi::session::SessionParams`
+/// (normalized by the plugin). Returns null on error.
+pub type CreateSessionWithParamsFn =
+    unsafe extern "C" fn(model_path: *const c_char, params_json: *const c_char) -> *mut c_void;
 
+/// Null on error; an empty result is a non-null array with `len == 0`.
 pub type TokenizeUtf8Fn =
     unsafe extern "C" fn(session: *mut c_void, text: *const c_char) -> Int32Array;
 pub type FreeIntsFn = unsafe extern "C" fn(arr: Int32Array);
@@ -46,7 +121,7 @@ pub type EvaluateFn =
     unsafe extern "C" fn(session: *mut c_void, tokens: *const i32, len: usize, n_past: i32) -> i32;
 
 /// `sampling_json` is UTF-8 JSON of `strata_abi::sampling::SamplingParams::normalized()`.
-/// Returns next token id (>= 0) or a negative error code (ERR_FAIL et al).
+/// Returns next token id (>= 0) or a negated error code (`-ERR_*`).
 pub type SampleJsonFn =
     unsafe extern "C" fn(session: *mut c_void, sampling_json: *const c_char) -> i32;
 
@@ -66,24 +141,93 @@ pub type DetokenizeUtf8Fn = unsafe extern "C" fn(
     unparse_special: bool,
 ) -> StrataString;
 
+/// Message of the calling thread's most recent failure (any session).
 pub type LastErrorFn = unsafe extern "C" fn() -> StrataString;
+/// Code of the most recent failure on `session` (null: session-less calls such
+/// as `create_session*` and metadata), or `ERR_OK` if there is none, and clears
+/// it. When `message` is non-null and there was a failure, `*message` receives
+/// its text (free with `free_string`).
+pub type TakeErrorFn =
+    unsafe extern "C" fn(session: *mut c_void, message: *mut StrataString) -> i32;
+
+/// Host sink for one log record. `target` (e.g. `"llama"`) and `message`
+/// (one line, no trailing newline) are NUL-terminated UTF-8 valid for the
+/// call only. May be called from any plugin thread.
+pub type LogFn = unsafe extern "C" fn(
+    user: *mut c_void,
+    level: i32,
+    target: *const c_char,
+    message: *const c_char,
+);
+/// Send the plugin's records, and those of the runtime it wraps, to `log`
+/// (with `user` passed back), dropping records above `max_level`. `None`
+/// restores the plugin's default of writing to stderr.
+pub type SetLoggerFn = unsafe extern "C" fn(log: Option<LogFn>, user: *mut c_void, max_level: i32);
+
+/// JSON for `strata_abi::session::PerfCounters` accumulated on `session` since
+/// the previous call, then resets them. Null if the session doesn't measure.
+pub type TakePerfJsonFn = unsafe extern "C" fn(session: *mut c_void) -> StrataString;
 
 // small helpers the host/engine already uses conceptually
 pub type ClearKvFn = unsafe extern "C" fn(session: *mut c_void);
 pub type KvLenHintFn = unsafe extern "C" fn(session: *mut c_void) -> i32; // -1 if unknown
 pub type ContextWindowHintFn = unsafe extern "C" fn(session: *mut c_void) -> i32; // 0 if unknown
 
+/// Returns a JSON array of the session model's default stop strings (EOG pieces + template markers).
+pub type StopStringsJsonFn = unsafe extern "C" fn(session: *mut c_void) -> StrataString;
+/// Returns the session model's `PromptFlavor` name (e.g. "ChatMl", "Llama3"); empty if unknown.
+pub type PromptFlavorFn = unsafe extern "C" fn(session: *mut c_void) -> StrataString;
+
+/// Load a multimodal projector (mmproj GGUF) into the session. Returns ERR_OK or an error code.
+pub type LoadProjectorFn =
+    unsafe extern "C" fn(session: *mut c_void, projector_path: *const c_char) -> i32;
+/// Marker text replaced by image embeddings; empty if no projector is loaded.
+pub type MediaMarkerFn = unsafe extern "C" fn(session: *mut c_void) -> StrataString;
+/// Evaluate `prompt` (with one media marker per image) and `images` starting at `n_past`.
+/// Returns the new n_past (>= 0) or a negated error code.
+pub type EvaluateMediaFn = unsafe extern "C" fn(
+    session: *mut c_void,
+    prompt: *const c_char,
+    images: *const ByteSlice,
+    n_images: usize,
+    n_past: i32,
+) -> i32;
+
+/// Load a LoRA adapter GGUF against the session's model.
+/// Returns the adapter id (>= 0) or a negated error code.
+pub type LoadLoraFn = unsafe extern "C" fn(session: *mut c_void, lora_path: *const c_char) -> i32;
+/// Attach adapter `id` to the session at `scale` (or update it). Returns ERR_OK or an error code.
+pub type SetLoraFn = unsafe extern "C" fn(session: *mut c_void, id: i32, scale: f32) -> i32;
+/// Detach adapter `id` from the session. Returns ERR_OK or an error code.
+pub type RemoveLoraFn = unsafe extern "C" fn(session: *mut c_void, id: i32) -> i32;
+/// JSON array of `strata_abi::backend::LoraAdapter`.
+pub type LoraListJsonFn = unsafe extern "C" fn(session: *mut c_void) -> StrataString;
+
+/// Replace the session's control vectors with a JSON array of
+/// `strata_abi::backend::ControlVector` (`[]` clears). Returns ERR_OK or an error code.
+pub type SetControlVectorsJsonFn =
+    unsafe extern "C" fn(session: *mut c_void, vectors_json: *const c_char) -> i32;
+
+/// JSON `strata_abi::session::MemoryReport` for the session; empty string if unknown.
+pub type MemoryReportJsonFn = unsafe extern "C" fn(session: *mut c_void) -> StrataString;
+
 // ---------- VTables ----------
 
 #[repr(C)]
+#[derive(Clone, Copy)]
 pub struct MetadataApi {
+    /// Resolution priority against other providers (higher is asked first);
+    /// see `strata_abi::metadata::BackendMetadataProvider::priority`.
+    pub priority: i32,
     pub can_handle: CanHandleFn,
-    /// JSON for `strata_abi::metadata::ModelCoreInfo`.
+    /// JSON for `strata_abi::metadata::ModelCoreInfo`; null on error (see `LlmApi::last_error`).
     pub collect_json: CollectJsonFn,
     pub free_string: FreeStringFn,
 }
 
+/// Frozen as of ABI 12: new entry points go at the end, as `Option<_>`.
 #[repr(C)]
+#[derive(Clone, Copy)]
 pub struct LlmApi {
     pub create_session: CreateSessionFn,
     pub destroy_session: DestroySessionFn,
@@ -106,14 +250,713 @@ pub struct LlmApi {
     pub clear_kv_cache: ClearKvFn,
     pub kv_len_hint: KvLenHintFn,
     pub context_window_hint: ContextWindowHintFn,
+
+    // Per-model prompt hints
+    pub stop_strings_json: StopStringsJsonFn,
+    pub prompt_flavor: PromptFlavorFn,
+
+    // Multimodal (vision projector)
+    pub load_projector: LoadProjectorFn,
+    pub media_marker: MediaMarkerFn,
+    pub evaluate_media: EvaluateMediaFn,
+
+    // LoRA adapters
+    pub load_lora: LoadLoraFn,
+    pub set_lora: SetLoraFn,
+    pub remove_lora: RemoveLoraFn,
+    pub lora_list_json: LoraListJsonFn,
+
+    // Control vectors (activation steering)
+    pub set_control_vectors_json: SetControlVectorsJsonFn,
+
+    // Session creation with explicit parameters
+    pub create_session_with_params: CreateSessionWithParamsFn,
+
+    // Memory accounting
+    pub memory_report_json: MemoryReportJsonFn,
+
+    // ---- appended in ABI 12 (absent from smaller tables) ----
+    /// Per-session error codes; without it hosts fall back to `last_error`.
+    pub take_error: Option<TakeErrorFn>,
+    /// Host-provided logging; without it the plugin writes to stderr.
+    pub set_logger: Option<SetLoggerFn>,
+    /// Performance counters; without it generations report engine timings only.
+    pub take_perf_json: Option<TakePerfJsonFn>,
 }
 
 #[repr(C)]
+#[derive(Clone, Copy)]
 pub struct PluginApi {
     pub info: PluginInfo,
     pub metadata: MetadataApi,
     pub llm: LlmApi,
 }
 
-/// Plugin must export `strata_plugin_entry_v1` returning a pointer to a static `PluginApi`.
+/// Plugin must export `strata_plugin_entry_v1` returning a pointer to a static `PluginApi`
+/// (or, for ABI 4 and 11, a `legacy::PluginApiV4` / `legacy::PluginApiV11`).
 pub type PluginEntryFn = unsafe extern "C" fn() -> *const PluginApi;
+
+/// `LlmApi` up to `memory_report_json`: the entry points every plugin has.
+pub const LLM_API_BASE_SIZE: usize =
+    std::mem::offset_of!(LlmApi, memory_report_json) + size_of::<MemoryReportJsonFn>();
+
+/// Smallest `struct_size` an ABI 12 plugin may report. Entry points appended
+/// after `memory_report_json` are optional.
+pub const PLUGIN_API_BASE_SIZE: usize = std::mem::offset_of!(PluginApi, llm) + LLM_API_BASE_SIZE;
+
+impl PluginApi {
+    pub fn supports(&self, cap: u64) -> bool {
+        self.info.capabilities & cap == cap
+    }
+
+    /// `Unsupported` error naming `what` unless the plugin declared `cap`.
+    pub fn require(&self, cap: u64, what: &str) -> Result<(), StrataError> {
+        if self.supports(cap) {
+            Ok(())
+        } else {
+            Err(StrataError::new(
+                ErrorCode::Unsupported,
+                format!("this runtime plugin does not support {what}"),
+            ))
+        }
+    }
+
+    /// The extension vtable registered as `name`, if the plugin has one.
+    ///
+    /// # Safety
+    /// `T` must be the `repr(C)` table documented for `name`, and the plugin
+    /// library must stay loaded while the reference is used.
+    pub unsafe fn extension<T>(&self, name: &CStr) -> Option<&'static T> {
+        let get = self.info.get_extension?;
+        let ptr = unsafe { get(name.as_ptr()) } as *const T;
+        unsafe { ptr.as_ref() }
+    }
+
+    /// The failure behind a call on `session` (null for session-less calls)
+    /// that returned `rc`: an `ERR_*` code, negated for value calls, or
+    /// `ERR_FAIL` when the call signalled failure with a null result.
+    ///
+    /// Uses `take_error` when the plugin has it, else the thread-local
+    /// `last_error` with the code from `rc`, or from a leading tag in the
+    /// message (see `StrataError::parse`).
+    ///
+    /// # Safety
+    /// `session` must be null or a live session of this plugin.
+    pub unsafe fn error(&self, session: *mut c_void, rc: i32, what: &str) -> StrataError {
+        let llm = &self.llm;
+        let fallback = || format!("{what} failed");
+        if let Some(take) = llm.take_error {
+            let mut msg = StrataString {
+                ptr: std::ptr::null_mut(),
+                len: 0,
+            };
+            let code = unsafe { take(session, &mut msg) };
+            let msg = unsafe { take_string(llm.free_string, msg) };
+            if code != ERR_OK {
+                let msg = if msg.is_empty() { fallback() } else { msg };
+                return StrataError::new(ErrorCode::from_raw(code), msg);
+            }
+        }
+        let msg = unsafe { take_string(llm.free_string, (llm.last_error)()) };
+        let mut err = if msg.is_empty() {
+            StrataError::new(ErrorCode::Failed, fallback())
+        } else {
+            StrataError::parse(&msg)
+        };
+        if err.code == ErrorCode::Failed {
+            err.code = ErrorCode::from_raw(rc);
+        }
+        err
+    }
+}
+
+/// Copy a plugin-owned string and release it with `free`.
+unsafe fn take_string(free: FreeStringFn, s: StrataString) -> String {
+    if s.ptr.is_null() {
+        return String::new();
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(s.ptr as *const u8, s.len) };
+    let out = String::from_utf8_lossy(bytes).into_owned();
+    unsafe { free(s) };
+    out
+}
+
+/// Read the tables behind a plugin entry pointer into the current layout.
+///
+/// ABI 4 and 11 plugins are mapped from `legacy::PluginApiV4` (with
+/// `CAPS_V4`) and `legacy::PluginApiV11` (with `CAPS_V11`), without
+/// extensions. ABI 12 plugins built against a newer, larger `PluginApi`
+/// are read up to the fields this host knows; appended fields a smaller
+/// plugin lacks come back as `None`.
+///
+/// # Safety
+/// `ptr` must come from a plugin's `strata_plugin_entry_v1` and point to
+/// tables of the layout its `abi_version` names.
+pub unsafe fn negotiate(ptr: *const PluginApi) -> Result<PluginApi, String> {
+    if ptr.is_null() {
+        return Err("plugin entry returned null".into());
+    }
+    // `abi_version` is the first field in every layout.
+    let version = unsafe { *(ptr as *const u32) };
+    match version {
+        STRATA_ABI_VERSION => {
+            let size = unsafe { (*ptr).info.struct_size };
+            if size < PLUGIN_API_BASE_SIZE {
+                return Err(format!(
+                    "plugin tables too small: {size} bytes (ABI {version} needs {PLUGIN_API_BASE_SIZE})"
+                ));
+            }
+            let mut api = std::mem::MaybeUninit::<PluginApi>::zeroed();
+            let n = size.min(size_of::<PluginApi>());
+            unsafe {
+                std::ptr::copy_nonoverlapping(ptr as *const u8, api.as_mut_ptr() as *mut u8, n);
+                Ok(api.assume_init())
+            }
+        }
+        4 => Ok(unsafe { (*(ptr as *const legacy::PluginApiV4)).upgrade() }),
+        11 => Ok(unsafe { (*(ptr as *const legacy::PluginApiV11)).upgrade() }),
+        v if v > STRATA_ABI_VERSION => Err(format!(
+            "plugin needs a newer host: host ABI={STRATA_ABI_VERSION} plugin ABI={v}"
+        )),
+        v if v < STRATA_ABI_MIN_VERSION => Err(format!(
+            "plugin ABI {v} is too old: host loads ABI 4, 11 and {STRATA_ABI_VERSION}"
+        )),
+        v => Err(format!(
+            "plugin ABI {v} is not supported: host loads ABI 4, 11 and {STRATA_ABI_VERSION}"
+        )),
+    }
+}
+
+/// Layouts of older ABIs the host can still load.
+pub mod legacy {
+    use super::*;
+
+    /// `PluginInfo` of ABI 4 through 11: no `struct_size` / capabilities /
+    /// extensions.
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct PluginInfoV4 {
+        pub abi_version: u32,
+        pub id: *const c_char,
+        pub semver: *const c_char,
+    }
+
+    /// ABI 4 `MetadataApi`: no `priority`.
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct MetadataApiV4 {
+        pub can_handle: CanHandleFn,
+        pub collect_json: CollectJsonFn,
+        pub free_string: FreeStringFn,
+    }
+
+    /// ABI 4 `LlmApi`: the current table up to `context_window_hint`.
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct LlmApiV4 {
+        pub create_session: CreateSessionFn,
+        pub destroy_session: DestroySessionFn,
+        pub tokenize_utf8: TokenizeUtf8Fn,
+        pub free_ints: FreeIntsFn,
+        pub evaluate: EvaluateFn,
+        pub sample_json: SampleJsonFn,
+        pub decode_token: DecodeTokenFn,
+        pub detokenize_utf8: DetokenizeUtf8Fn,
+        pub format_chat_json: FormatChatJsonFn,
+        pub last_error: LastErrorFn,
+        pub free_string: FreeStringFn,
+        pub clear_kv_cache: ClearKvFn,
+        pub kv_len_hint: KvLenHintFn,
+        pub context_window_hint: ContextWindowHintFn,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct PluginApiV4 {
+        pub info: PluginInfoV4,
+        pub metadata: MetadataApiV4,
+        pub llm: LlmApiV4,
+    }
+
+    // Host-side stand-ins for the entry points ABI 4 lacks. Their
+    // capabilities are off, so hosts only reach the ones that aren't gated
+    // (prompt hints), which answer "unknown".
+    unsafe extern "C" fn no_string(_: *mut c_void) -> StrataString {
+        StrataString {
+            ptr: std::ptr::null_mut(),
+            len: 0,
+        }
+    }
+    unsafe extern "C" fn no_session(_: *const c_char, _: *const c_char) -> *mut c_void {
+        std::ptr::null_mut()
+    }
+    unsafe extern "C" fn unsupported(_: *mut c_void, _: *const c_char) -> i32 {
+        ERR_UNSUPPORTED
+    }
+    unsafe extern "C" fn unsupported_value(_: *mut c_void, _: *const c_char) -> i32 {
+        -ERR_UNSUPPORTED
+    }
+    unsafe extern "C" fn unsupported_media(
+        _: *mut c_void,
+        _: *const c_char,
+        _: *const ByteSlice,
+        _: usize,
+        _: i32,
+    ) -> i32 {
+        -ERR_UNSUPPORTED
+    }
+    unsafe extern "C" fn unsupported_set_lora(_: *mut c_void, _: i32, _: f32) -> i32 {
+        ERR_UNSUPPORTED
+    }
+    unsafe extern "C" fn unsupported_remove_lora(_: *mut c_void, _: i32) -> i32 {
+        ERR_UNSUPPORTED
+    }
+
+    impl PluginApiV4 {
+        pub fn upgrade(&self) -> PluginApi {
+            let llm = &self.llm;
+            PluginApi {
+                info: PluginInfo {
+                    abi_version: self.info.abi_version,
+                    id: self.info.id,
+                    semver: self.info.semver,
+                    struct_size: size_of::<Self>(),
+                    capabilities: CAPS_V4,
+                    get_extension: None,
+                },
+                metadata: MetadataApi {
+                    priority: crate::metadata::PRIORITY_BUILTIN,
+                    can_handle: self.metadata.can_handle,
+                    collect_json: self.metadata.collect_json,
+                    free_string: self.metadata.free_string,
+                },
+                llm: LlmApi {
+                    create_session: llm.create_session,
+                    destroy_session: llm.destroy_session,
+                    tokenize_utf8: llm.tokenize_utf8,
+                    free_ints: llm.free_ints,
+                    evaluate: llm.evaluate,
+                    sample_json: llm.sample_json,
+                    decode_token: llm.decode_token,
+                    detokenize_utf8: llm.detokenize_utf8,
+                    format_chat_json: llm.format_chat_json,
+                    last_error: llm.last_error,
+                    free_string: llm.free_string,
+                    clear_kv_cache: llm.clear_kv_cache,
+                    kv_len_hint: llm.kv_len_hint,
+                    context_window_hint: llm.context_window_hint,
+                    stop_strings_json: no_string,
+                    prompt_flavor: no_string,
+                    load_projector: unsupported,
+                    media_marker: no_string,
+                    evaluate_media: unsupported_media,
+                    load_lora: unsupported_value,
+                    set_lora: unsupported_set_lora,
+                    remove_lora: unsupported_remove_lora,
+                    lora_list_json: no_string,
+                    set_control_vectors_json: unsupported,
+                    create_session_with_params: no_session,
+                    memory_report_json: no_string,
+                    take_error: None,
+                    set_logger: None,
+                    take_perf_json: None,
+                },
+            }
+        }
+    }
+
+    /// ABI 11: header as in 4, `metadata` as in 12 and `llm` the first
+    /// `LLM_API_BASE_SIZE` bytes of `LlmApi`.
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct PluginApiV11 {
+        pub info: PluginInfoV4,
+        pub metadata: MetadataApi,
+        pub llm: [usize; LLM_API_BASE_SIZE / size_of::<usize>()],
+    }
+
+    impl PluginApiV11 {
+        pub fn upgrade(&self) -> PluginApi {
+            PluginApi {
+                info: PluginInfo {
+                    abi_version: self.info.abi_version,
+                    id: self.info.id,
+                    semver: self.info.semver,
+                    struct_size: size_of::<Self>(),
+                    capabilities: CAPS_V11,
+                    get_extension: None,
+                },
+                metadata: self.metadata,
+                llm: self.llm(),
+            }
+        }
+
+        fn llm(&self) -> LlmApi {
+            let mut llm = std::mem::MaybeUninit::<LlmApi>::zeroed();
+            // SAFETY: both are entry points in the same order; the zeroed
+            // tail reads as `None` for the entry points ABI 11 lacks.
+            unsafe {
+                std::ptr::copy_nonoverlapping(
+                    self.llm.as_ptr() as *const u8,
+                    llm.as_mut_ptr() as *mut u8,
+                    LLM_API_BASE_SIZE,
+                );
+                llm.assume_init()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::legacy::{LlmApiV4, MetadataApiV4, PluginApiV4, PluginApiV11, PluginInfoV4};
+    use super::*;
+    use std::ptr;
+
+    // Stub entry points; only their addresses matter.
+    unsafe extern "C" fn path_ptr(_: *const c_char) -> *mut c_void {
+        ptr::null_mut()
+    }
+    unsafe extern "C" fn path_path_ptr(_: *const c_char, _: *const c_char) -> *mut c_void {
+        ptr::null_mut()
+    }
+    unsafe extern "C" fn path_bool(_: *const c_char) -> bool {
+        true
+    }
+    unsafe extern "C" fn path_str(_: *const c_char) -> StrataString {
+        no_string()
+    }
+    unsafe extern "C" fn session_unit(_: *mut c_void) {}
+    unsafe extern "C" fn session_i32(_: *mut c_void) -> i32 {
+        0
+    }
+    unsafe extern "C" fn session_str(_: *mut c_void) -> StrataString {
+        no_string()
+    }
+    unsafe extern "C" fn session_cstr_i32(_: *mut c_void, _: *const c_char) -> i32 {
+        0
+    }
+    unsafe extern "C" fn session_id_i32(_: *mut c_void, _: i32) -> i32 {
+        0
+    }
+    unsafe extern "C" fn session_id_str(_: *mut c_void, _: i32) -> StrataString {
+        no_string()
+    }
+    unsafe extern "C" fn set_lora(_: *mut c_void, _: i32, _: f32) -> i32 {
+        0
+    }
+    unsafe extern "C" fn tokenize(_: *mut c_void, _: *const c_char) -> Int32Array {
+        Int32Array {
+            ptr: ptr::null_mut(),
+            len: 0,
+        }
+    }
+    unsafe extern "C" fn free_ints(_: Int32Array) {}
+    unsafe extern "C" fn evaluate(_: *mut c_void, _: *const i32, _: usize, _: i32) -> i32 {
+        0
+    }
+    unsafe extern "C" fn format_chat(_: *mut c_void, _: *const c_char, _: bool) -> StrataString {
+        no_string()
+    }
+    unsafe extern "C" fn detokenize(
+        _: *mut c_void,
+        _: *const i32,
+        _: usize,
+        _: bool,
+        _: bool,
+    ) -> StrataString {
+        no_string()
+    }
+    unsafe extern "C" fn last_error() -> StrataString {
+        no_string()
+    }
+    unsafe extern "C" fn free_string(_: StrataString) {}
+    unsafe extern "C" fn evaluate_media(
+        _: *mut c_void,
+        _: *const c_char,
+        _: *const ByteSlice,
+        _: usize,
+        _: i32,
+    ) -> i32 {
+        0
+    }
+    unsafe extern "C" fn take_error(_: *mut c_void, _: *mut StrataString) -> i32 {
+        ERR_OK
+    }
+    unsafe extern "C" fn set_logger(_: Option<LogFn>, _: *mut c_void, _: i32) {}
+
+    static EXTENSION: u32 = 77;
+
+    unsafe extern "C" fn get_extension(name: *const c_char) -> *const c_void {
+        if unsafe { CStr::from_ptr(name) } == c"strata.test.v1" {
+            &EXTENSION as *const u32 as *const c_void
+        } else {
+            ptr::null()
+        }
+    }
+
+    fn no_string() -> StrataString {
+        StrataString {
+            ptr: ptr::null_mut(),
+            len: 0,
+        }
+    }
+
+    /// A current-layout table with every entry point set.
+    fn full_api() -> PluginApi {
+        PluginApi {
+            info: PluginInfo {
+                abi_version: STRATA_ABI_VERSION,
+                id: c"test".as_ptr(),
+                semver: c"0.1.0".as_ptr(),
+                struct_size: size_of::<PluginApi>(),
+                capabilities: CAP_VISION | CAP_MEMORY_REPORT,
+                get_extension: Some(get_extension),
+            },
+            metadata: MetadataApi {
+                priority: 3,
+                can_handle: path_bool,
+                collect_json: path_str,
+                free_string,
+            },
+            llm: LlmApi {
+                create_session: path_ptr,
+                destroy_session: session_unit,
+                tokenize_utf8: tokenize,
+                free_ints,
+                evaluate,
+                sample_json: session_cstr_i32,
+                decode_token: session_id_str,
+                detokenize_utf8: detokenize,
+                format_chat_json: format_chat,
+                last_error,
+                free_string,
+                clear_kv_cache: session_unit,
+                kv_len_hint: session_i32,
+                context_window_hint: session_i32,
+                stop_strings_json: session_str,
+                prompt_flavor: session_str,
+                load_projector: session_cstr_i32,
+                media_marker: session_str,
+                evaluate_media,
+                load_lora: session_cstr_i32,
+                set_lora,
+                remove_lora: session_id_i32,
+                lora_list_json: session_str,
+                set_control_vectors_json: session_cstr_i32,
+                create_session_with_params: path_path_ptr,
+                memory_report_json: session_str,
+                take_error: Some(take_error),
+                set_logger: Some(set_logger),
+                take_perf_json: Some(session_str),
+            },
+        }
+    }
+
+    fn same_base(a: &LlmApi, b: &LlmApi) -> bool {
+        a.create_session as usize == b.create_session as usize
+            && a.evaluate as usize == b.evaluate as usize
+            && a.set_lora as usize == b.set_lora as usize
+            && a.memory_report_json as usize == b.memory_report_json as usize
+    }
+
+    #[test]
+    fn negotiate_v11_table() {
+        let full = full_api();
+        let mut llm = [0usize; LLM_API_BASE_SIZE / size_of::<usize>()];
+        unsafe {
+            ptr::copy_nonoverlapping(
+                &full.llm as *const LlmApi as *const u8,
+                llm.as_mut_ptr() as *mut u8,
+                LLM_API_BASE_SIZE,
+            )
+        };
+        let v11 = PluginApiV11 {
+            info: PluginInfoV4 {
+                abi_version: 11,
+                id: full.info.id,
+                semver: full.info.semver,
+            },
+            metadata: full.metadata,
+            llm,
+        };
+
+        let api = unsafe { negotiate(&v11 as *const PluginApiV11 as *const PluginApi) }.unwrap();
+        assert_eq!(api.info.abi_version, 11);
+        assert_eq!(api.info.capabilities, CAPS_V11);
+        assert_eq!(api.info.struct_size, size_of::<PluginApiV11>());
+        assert!(api.info.get_extension.is_none());
+        assert_eq!(api.metadata.priority, 3);
+        assert!(same_base(&api.llm, &full.llm));
+        assert!(api.llm.take_error.is_none());
+        assert!(api.llm.set_logger.is_none());
+        assert!(api.llm.take_perf_json.is_none());
+    }
+
+    #[test]
+    fn negotiate_v4_table() {
+        let v4 = PluginApiV4 {
+            info: PluginInfoV4 {
+                abi_version: 4,
+                id: c"old".as_ptr(),
+                semver: c"0.0.4".as_ptr(),
+            },
+            metadata: MetadataApiV4 {
+                can_handle: path_bool,
+                collect_json: path_str,
+                free_string,
+            },
+            llm: LlmApiV4 {
+                create_session: path_ptr,
+                destroy_session: session_unit,
+                tokenize_utf8: tokenize,
+                free_ints,
+                evaluate,
+                sample_json: session_cstr_i32,
+                decode_token: session_id_str,
+                detokenize_utf8: detokenize,
+                format_chat_json: format_chat,
+                last_error,
+                free_string,
+                clear_kv_cache: session_unit,
+                kv_len_hint: session_i32,
+                context_window_hint: session_i32,
+            },
+        };
+
+        let api = unsafe { negotiate(&v4 as *const PluginApiV4 as *const PluginApi) }.unwrap();
+        assert_eq!(api.info.abi_version, 4);
+        assert_eq!(unsafe { CStr::from_ptr(api.info.id) }, c"old");
+        assert_eq!(api.info.struct_size, size_of::<PluginApiV4>());
+        assert_eq!(api.info.capabilities, CAPS_V4);
+        assert!(api.info.get_extension.is_none());
+        assert_eq!(api.metadata.priority, crate::metadata::PRIORITY_BUILTIN);
+        assert_eq!(
+            api.metadata.can_handle as usize,
+            v4.metadata.can_handle as usize
+        );
+        assert_eq!(
+            api.llm.create_session as usize,
+            v4.llm.create_session as usize
+        );
+        assert_eq!(api.llm.evaluate as usize, v4.llm.evaluate as usize);
+        assert_eq!(
+            api.llm.context_window_hint as usize,
+            v4.llm.context_window_hint as usize
+        );
+
+        for (cap, what) in [
+            (CAP_VISION, "image input"),
+            (CAP_LORA, "LoRA adapters"),
+            (CAP_CONTROL_VECTORS, "control vectors"),
+            (CAP_SESSION_PARAMS, "session parameters"),
+            (CAP_MEMORY_REPORT, "memory reports"),
+        ] {
+            let err = api.require(cap, what).unwrap_err();
+            assert_eq!(err.code, ErrorCode::Unsupported);
+        }
+
+        // Entry points ABI 4 lacks are stand-ins that fail or answer "unknown".
+        let s = ptr::null_mut();
+        unsafe {
+            assert!((api.llm.prompt_flavor)(s).ptr.is_null());
+            assert!((api.llm.stop_strings_json)(s).ptr.is_null());
+            assert!((api.llm.memory_report_json)(s).ptr.is_null());
+            assert_eq!((api.llm.load_lora)(s, c"a".as_ptr()), -ERR_UNSUPPORTED);
+            assert_eq!((api.llm.set_lora)(s, 0, 1.0), ERR_UNSUPPORTED);
+            assert_eq!((api.llm.load_projector)(s, c"p".as_ptr()), ERR_UNSUPPORTED);
+            assert_eq!(
+                (api.llm.set_control_vectors_json)(s, c"[]".as_ptr()),
+                ERR_UNSUPPORTED
+            );
+            let p = c"m".as_ptr();
+            assert!((api.llm.create_session_with_params)(p, c"{}".as_ptr()).is_null());
+        }
+        assert!(api.llm.take_error.is_none());
+        assert!(api.llm.set_logger.is_none());
+        assert!(api.llm.take_perf_json.is_none());
+    }
+
+    #[test]
+    fn negotiate_smaller_struct_size() {
+        let mut full = full_api();
+
+        // Built before any appended entry point: the tail reads as `None`,
+        // whatever lies beyond `struct_size`.
+        full.info.struct_size = PLUGIN_API_BASE_SIZE;
+        let api = unsafe { negotiate(&full) }.unwrap();
+        assert!(same_base(&api.llm, &full.llm));
+        assert!(api.llm.take_error.is_none());
+        assert!(api.llm.set_logger.is_none());
+        assert!(api.llm.take_perf_json.is_none());
+
+        // Built with `take_error` only.
+        full.info.struct_size = PLUGIN_API_BASE_SIZE + size_of::<Option<TakeErrorFn>>();
+        let api = unsafe { negotiate(&full) }.unwrap();
+        assert!(api.llm.take_error.is_some());
+        assert!(api.llm.set_logger.is_none());
+        assert!(api.llm.take_perf_json.is_none());
+    }
+
+    #[test]
+    fn negotiate_larger_struct_size() {
+        #[repr(C)]
+        struct Newer {
+            api: PluginApi,
+            appended: Option<unsafe extern "C" fn()>,
+            more: u64,
+        }
+        let mut api = full_api();
+        api.info.struct_size = size_of::<Newer>();
+        let newer = Newer {
+            api,
+            appended: None,
+            more: 0xdead,
+        };
+
+        let got = unsafe { negotiate(&newer as *const Newer as *const PluginApi) }.unwrap();
+        assert_eq!(got.info.struct_size, size_of::<Newer>());
+        assert!(same_base(&got.llm, &newer.api.llm));
+        assert!(got.llm.take_error.is_some());
+        assert!(got.llm.set_logger.is_some());
+        assert!(got.llm.take_perf_json.is_some());
+        assert!(got.supports(CAP_VISION) && !got.supports(CAP_LORA));
+    }
+
+    #[test]
+    fn negotiate_rejects() {
+        let mut api = full_api();
+        assert!(unsafe { negotiate(ptr::null()) }.is_err());
+
+        api.info.struct_size = PLUGIN_API_BASE_SIZE - 1;
+        let err = unsafe { negotiate(&api) }.err().unwrap();
+        assert!(err.contains("too small"), "{err}");
+
+        api.info.struct_size = size_of::<PluginApi>();
+        api.info.abi_version = STRATA_ABI_VERSION + 1;
+        let err = unsafe { negotiate(&api) }.err().unwrap();
+        assert!(err.contains("newer host"), "{err}");
+
+        api.info.abi_version = STRATA_ABI_MIN_VERSION - 1;
+        let err = unsafe { negotiate(&api) }.err().unwrap();
+        assert!(err.contains("too old"), "{err}");
+
+        // Versions between the legacy layouts have no table to read.
+        for v in 5..=10 {
+            api.info.abi_version = v;
+            let err = unsafe { negotiate(&api) }.err().unwrap();
+            assert!(err.contains("not supported"), "{err}");
+        }
+    }
+
+    #[test]
+    fn extensions_by_name() {
+        let api = unsafe { negotiate(&full_api()) }.unwrap();
+        let ext = unsafe { api.extension::<u32>(c"strata.test.v1") };
+        assert_eq!(ext.copied(), Some(77));
+        assert!(unsafe { api.extension::<u32>(c"strata.unknown.v1") }.is_none());
+
+        let get = api.info.get_extension.unwrap();
+        assert!(unsafe { get(c"strata.unknown.v1".as_ptr()) }.is_null());
+    }
+}
@@ -1,12 +1,16 @@
 //! Strata ABI crate: stable contracts shared by the host app and runtime plugins.
 
 pub mod backend;
+pub mod error;
 pub mod ffi;
 pub mod metadata;
 pub mod sampling;
+pub mod session;
 pub mod token;
 
 pub use backend::*;
+pub use error::*;
 pub use metadata::*;
 pub use sampling::*;
+pub use session::*;
 pub use token::*;
@@ -37,12 +37,63 @@ pub struct ModelCoreInfo {
     /// Native chat template string if provided by the model.
     pub chat_template: Option<String>,
     /// Hint for a reasonable default prompt wrapper when no native template is used.
+    /// Holds a `PromptFlavor` name (see [`PromptFlavor::as_str`]).
     pub prompt_flavor_hint: Option<String>,
 
+    /// Sizes needed to estimate memory use, when the format exposes them.
+    #[serde(default)]
+    pub geometry: Option<ModelGeometry>,
+
+    /// Average storage bits per weight, from the actual tensor types.
+    #[serde(default)]
+    pub bits_per_weight: Option<f32>,
+    /// Weight share per tensor type, largest first (mixed-quant files list several).
+    #[serde(default)]
+    pub tensor_types: Vec<TensorTypeCount>,
+    /// Type of the output projection when it differs from the bulk of the
+    /// weights (e.g. "q6_K" in a Q4_K_M file).
+    #[serde(default)]
+    pub output_tensor_type: Option<String>,
+
     /// Anything else the backend scraped (simple flattened map).
     pub raw: HashMap<String, String>,
 }
 
+/// Weight size and transformer shape, enough to estimate RAM for a context.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelGeometry {
+    /// Bytes of weight data as stored.
+    pub weights_bytes: u64,
+    pub n_layer: u32,
+    pub n_embd: u32,
+    pub n_head: u32,
+    pub n_head_kv: u32,
+    /// Per-head K and V widths.
+    pub head_dim_k: u32,
+    pub head_dim_v: u32,
+    /// Training context (0 if unknown).
+    pub n_ctx_train: u32,
+    pub n_vocab: u32,
+}
+
+/// Tensors of one element type (e.g. "q4_K", "f32").
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TensorTypeCount {
+    pub ggml_type: String,
+    pub n_tensors: u32,
+    pub n_elements: u64,
+    pub bytes: Option<u64>,
+}
+
+impl ModelCoreInfo {
+    /// Parsed `prompt_flavor_hint`, if present and recognized.
+    pub fn prompt_flavor(&self) -> Option<PromptFlavor> {
+        self.prompt_flavor_hint
+            .as_deref()
+            .and_then(PromptFlavor::from_name)
+    }
+}
+
 /// A backend-specific metadata adapter registers one of these with the service.
 /// It decides whether it can parse the given file and, if so, returns metadata.
 pub trait BackendMetadataProvider: Send + Sync + 'static {
@@ -51,4 +102,14 @@ pub trait BackendMetadataProvider: Send + Sync + 'static {
 
     /// Scrape and normalize metadata for this file.
     fn collect(&self, file: &Path) -> Result<ModelCoreInfo, String>;
+
+    /// Providers are asked in descending priority; ties keep registration
+    /// order. Built-in providers use [`PRIORITY_BUILTIN`].
+    fn priority(&self) -> i32 {
+        PRIORITY_BUILTIN
+    }
 }
+
+/// Default provider priority. Plugins go above it to override the built-in
+/// readers for a format, or below it to act only as a fallback.
+pub const PRIORITY_BUILTIN: i32 = 0;
@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+
+/// Per-session model/context parameters passed from the host to backends.
+/// `None` fields leave the backend's (usually the model's own) choice alone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionParams {
+    /// Context window in tokens; 0 = the model's training context.
+    pub n_ctx: u32,
+    /// Logical batch size for prompt evaluation.
+    pub n_batch: u32,
+    /// Physical (micro) batch size; never larger than `n_batch`.
+    pub n_ubatch: u32,
+
+    /// Generation threads; <= 0 picks the physical core count.
+    pub n_threads: i32,
+    /// Prompt-processing threads; <= 0 follows `n_threads`.
+    pub n_threads_batch: i32,
+
+    // KV cache
+    pub type_k: KvCacheType,
+    pub type_v: KvCacheType,
+    /// `None` lets the backend decide (llama.cpp: auto).
+    pub flash_attn: Option<bool>,
+    /// Keep the KV cache (and KQV ops) on the GPU; `None` = backend default.
+    pub offload_kqv: Option<bool>,
+
+    // RoPE / YaRN overrides
+    pub rope_scaling: Option<RopeScaling>,
+    pub rope_freq_base: Option<f32>,
+    pub rope_freq_scale: Option<f32>,
+    pub yarn_ext_factor: Option<f32>,
+    pub yarn_attn_factor: Option<f32>,
+    pub yarn_beta_fast: Option<f32>,
+    pub yarn_beta_slow: Option<f32>,
+    pub yarn_orig_ctx: Option<u32>,
+
+    // Model loading
+    pub use_mmap: bool,
+    pub use_mlock: bool,
+    /// Layers to offload to the GPU; negative = all, `None` = backend default.
+    pub n_gpu_layers: Option<i32>,
+}
+
+/// Element type of the K or V cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KvCacheType {
+    F32,
+    #[default]
+    F16,
+    Bf16,
+    Q8_0,
+    Q5_1,
+    Q5_0,
+    Q4_1,
+    Q4_0,
+    Iq4Nl,
+}
+
+impl KvCacheType {
+    /// Quantized types; a quantized V cache needs flash attention.
+    pub fn is_quantized(&self) -> bool {
+        !matches!(self, Self::F32 | Self::F16 | Self::Bf16)
+    }
+
+    /// Storage per element, block scales included (q8_0 = 34 bytes / 32).
+    pub fn bits_per_element(&self) -> f64 {
+        match self {
+            Self::F32 => 32.0,
+            Self::F16 | Self::Bf16 => 16.0,
+            Self::Q8_0 => 8.5,
+            Self::Q5_1 => 6.0,
+            Self::Q5_0 => 5.5,
+            Self::Q4_1 => 5.0,
+            Self::Q4_0 | Self::Iq4Nl => 4.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RopeScaling {
+    None,
+    Linear,
+    Yarn,
+}
+
+impl Default for SessionParams {
+    fn default() -> Self {
+        Self {
+            n_ctx: 4096,
+            n_batch: 64,
+            n_ubatch: 16,
+            n_threads: 0,
+            n_threads_batch: 0,
+            type_k: KvCacheType::F16,
+            type_v: KvCacheType::F16,
+            flash_attn: None,
+            offload_kqv: None,
+            rope_scaling: None,
+            rope_freq_base: None,
+            rope_freq_scale: None,
+            yarn_ext_factor: None,
+            yarn_attn_factor: None,
+            yarn_beta_fast: None,
+            yarn_beta_slow: None,
+            yarn_orig_ctx: None,
+            use_mmap: true,
+            use_mlock: false,
+            n_gpu_layers: None,
+        }
+    }
+}
+
+impl SessionParams {
+    /// Reject combinations a backend cannot honour, instead of silently
+    /// changing them the way [`SessionParams::normalized`] does.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.type_v.is_quantized() && self.flash_attn == Some(false) {
+            return Err(format!(
+                "V cache type {:?} requires flash attention (flash_attn is off)",
+                self.type_v
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns a consistent, clamped version of these parameters.
+    ///
+    /// - `n_batch` / `n_ubatch` < 1 → 1; `n_ubatch` <= `n_batch`; `n_batch` <= `n_ctx` (if set)
+    /// - thread counts <= 0 → 0 (auto)
+    /// - quantized `type_v` with `flash_attn = Some(false)` → `type_v = F16`
+    /// - rope base/scale <= 0 or non-finite → `None`
+    /// - non-finite YaRN factors → `None`; `yarn_orig_ctx = 0` → `None`
+    /// - negative `n_gpu_layers` → -1 (all)
+    pub fn normalized(&self) -> Self {
+        let mut p = self.clone();
+
+        p.n_batch = p.n_batch.max(1);
+        if p.n_ctx > 0 {
+            p.n_batch = p.n_batch.min(p.n_ctx);
+        }
+        p.n_ubatch = p.n_ubatch.clamp(1, p.n_batch);
+
+        p.n_threads = p.n_threads.max(0);
+        p.n_threads_batch = p.n_threads_batch.max(0);
+
+        if p.type_v.is_quantized() && p.flash_attn == Some(false) {
+            p.type_v = KvCacheType::F16;
+        }
+
+        let positive = |v: Option<f32>| v.filter(|x| x.is_finite() && *x > 0.0);
+        p.rope_freq_base = positive(p.rope_freq_base);
+        p.rope_freq_scale = positive(p.rope_freq_scale);
+
+        let finite = |v: Option<f32>| v.filter(|x| x.is_finite());
+        p.yarn_ext_factor = finite(p.yarn_ext_factor);
+        p.yarn_attn_factor = finite(p.yarn_attn_factor);
+        p.yarn_beta_fast = finite(p.yarn_beta_fast);
+        p.yarn_beta_slow = finite(p.yarn_beta_slow);
+        p.yarn_orig_ctx = p.yarn_orig_ctx.filter(|&n| n > 0);
+
+        if p.n_gpu_layers.is_some_and(|n| n < 0) {
+            p.n_gpu_layers = Some(-1);
+        }
+
+        p
+    }
+}
+
+/// Memory held by one loaded session, in bytes, as allocated by the backend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryReport {
+    /// Model weights (shared by sessions on the same model).
+    pub model_bytes: u64,
+    /// K and V cache for the session's context window.
+    pub kv_bytes: u64,
+    /// Scratch buffers for graph evaluation; `None` if the backend can't tell.
+    pub compute_bytes: Option<u64>,
+}
+
+impl MemoryReport {
+    pub fn total_bytes(&self) -> u64 {
+        self.model_bytes + self.kv_bytes + self.compute_bytes.unwrap_or(0)
+    }
+}
+
+/// Time spent by the backend on one session since the counters were last
+/// taken, as measured by the runtime itself.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PerfCounters {
+    /// Prompt (batch) evaluation.
+    pub prompt_eval_ms: f64,
+    pub prompt_tokens: u64,
+    /// Single-token evaluation while decoding.
+    pub eval_ms: f64,
+    pub eval_tokens: u64,
+    /// Sampling, summed over `sample_count` calls.
+    pub sample_ms: f64,
+    pub sample_count: u64,
+}
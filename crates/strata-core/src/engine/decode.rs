@@ -1,7 +1,10 @@
 use super::LLMEngine;
+use super::stats::GenerationTimer;
 use crate::format::format::FormattedPrompt;
+use log::{debug, info, trace};
 use std::panic;
 use strata_abi::backend::LLMBackend;
+use strata_abi::error::StrataError;
 
 use super::utils::utf8_valid_prefix_len;
 
@@ -9,30 +12,37 @@ impl<B: LLMBackend> LLMEngine<B> {
     pub(super) fn infer_with_formatted(
         &mut self,
         formatted: FormattedPrompt,
-    ) -> Result<String, String> {
+    ) -> Result<String, StrataError> {
         self.clear_stop();
+        self.last_stats = None;
 
         panic::catch_unwind(panic::AssertUnwindSafe(|| {
-            println!("🧠 [infer] Starting inference");
-            println!("🧾 [infer] Formatted prompt: {}", formatted.text);
+            let mut timer = GenerationTimer::start();
+            info!("starting inference");
+            trace!("formatted prompt: {}", formatted.text);
 
             // Tokenize full prompt.
             let prompt_tokens = self
                 .backend
                 .tokenize(&formatted.text)
-                .map_err(|e| format!("❌ [infer] Tokenization failed: {e}"))?;
-            println!(
-                "🔤 [infer] Tokenized input ({} tokens)",
-                prompt_tokens.len()
-            );
+                .map_err(|e| e.context("❌ [infer] Tokenization failed"))?;
+            debug!("tokenized input ({} tokens)", prompt_tokens.len());
 
             // Dynamic decode cap.
             let step_limit = self.compute_step_limit(prompt_tokens.len());
-            println!("🧮 step_limit={}", step_limit);
-
-            // Prefill (incremental, STOP-aware).
-            let (mut n_past, mut token_history, mut detok_start_idx) =
-                self.prefill_incremental(&prompt_tokens)?;
+            debug!("step_limit={step_limit}");
+
+            // Prefill (incremental, STOP-aware). Backend counters restart here.
+            let _ = self.backend.take_perf_counters();
+            timer.prefill_started();
+            let (mut n_past, mut token_history, mut detok_start_idx, reused) =
+                if formatted.images.is_empty() {
+                    self.prefill_incremental(&prompt_tokens)?
+                } else {
+                    self.prefill_media(&formatted.text, &formatted.images)?
+                };
+            timer.prefilled(n_past, reused);
+            let mut generated = 0usize;
 
             // UTF-8 streaming state (accumulate valid prefix only).
             let mut out_text = String::new();
@@ -41,27 +51,28 @@ impl<B: LLMBackend> LLMEngine<B> {
             // Decode loop (STOP-aware).
             for step in 0..step_limit {
                 if self.stop_flag.load(std::sync::atomic::Ordering::Relaxed) {
-                    println!("⏹️ [infer] STOP requested. Ending.");
+                    info!("stop requested at step {step}");
                     break;
                 }
-                println!("🔁 [infer] Step {}", step);
 
                 let token = self
                     .backend
                     .sample(n_past, &self.sample_params, &token_history)
-                    .map_err(|e| format!("❌ [infer] Sampling failed: {e}"))?;
-                println!("🎯 [infer] Sampled token: {:?}", token);
+                    .map_err(|e| e.context("❌ [infer] Sampling failed"))?;
+                timer.sampled();
+                trace!("step {step}: sampled {token:?}");
 
                 if token == self.backend.eos_token() {
-                    println!("🏁 [infer] Reached EOS token. Ending.");
+                    debug!("end of generation at step {step}");
                     break;
                 }
 
-                self.backend
-                    .evaluate(&[token], n_past)
-                    .map_err(|e| format!("❌ [infer] Re-eval failed at step {step}: {e}"))?;
+                self.backend.evaluate(&[token], n_past).map_err(|e| {
+                    e.context(format_args!("❌ [infer] Re-eval failed at step {step}"))
+                })?;
                 token_history.push(token);
                 n_past += 1;
+                generated += 1;
 
                 // Detokenize only the new range; emit valid UTF-8 prefix.
                 let new_bytes = self.backend.detokenize_range(
@@ -89,10 +100,13 @@ impl<B: LLMBackend> LLMEngine<B> {
             self.prev_prompt_tokens = token_history[..detok_start_idx].to_vec();
 
             let out_text = out_text.trim().to_string();
-            println!(
-                "✅ [infer] Complete. Output length: {} chars",
-                out_text.len()
+            let stats = timer.finish(generated, self.backend.take_perf_counters());
+            info!(
+                "complete: {} chars, {generated} tokens at {:.1} tok/s",
+                out_text.len(),
+                stats.tokens_per_second
             );
+            self.last_stats = Some(stats);
             Ok(out_text)
         }))
         .map_err(|_| "💥 [infer] PANIC occurred during inference!".to_string())?
@@ -102,33 +116,40 @@ impl<B: LLMBackend> LLMEngine<B> {
         &mut self,
         formatted: FormattedPrompt,
         mut on_delta: F,
-    ) -> Result<String, String>
+    ) -> Result<String, StrataError>
     where
         F: FnMut(&str),
     {
         self.clear_stop();
+        self.last_stats = None;
 
         panic::catch_unwind(panic::AssertUnwindSafe(|| {
-            println!("🧠 [infer-stream] Starting inference");
-            println!("🧾 [infer-stream] Formatted prompt: {}", formatted.text);
+            let mut timer = GenerationTimer::start();
+            info!("starting inference");
+            trace!("formatted prompt: {}", formatted.text);
 
             // Tokenize full prompt.
             let prompt_tokens = self
                 .backend
                 .tokenize(&formatted.text)
-                .map_err(|e| format!("❌ [infer-stream] Tokenization failed: {e}"))?;
-            println!(
-                "🔤 [infer-stream] Tokenized input ({} tokens)",
-                prompt_tokens.len()
-            );
+                .map_err(|e| e.context("❌ [infer-stream] Tokenization failed"))?;
+            debug!("tokenized input ({} tokens)", prompt_tokens.len());
 
             // Dynamic decode cap.
             let step_limit = self.compute_step_limit(prompt_tokens.len());
-            println!("🧮 [stream] step_limit={}", step_limit);
-
-            // Prefill (incremental, STOP-aware).
-            let (mut n_past, mut token_history, mut detok_start_idx) =
-                self.prefill_incremental(&prompt_tokens)?;
+            debug!("step_limit={step_limit}");
+
+            // Prefill (incremental, STOP-aware). Backend counters restart here.
+            let _ = self.backend.take_perf_counters();
+            timer.prefill_started();
+            let (mut n_past, mut token_history, mut detok_start_idx, reused) =
+                if formatted.images.is_empty() {
+                    self.prefill_incremental(&prompt_tokens)?
+                } else {
+                    self.prefill_media(&formatted.text, &formatted.images)?
+                };
+            timer.prefilled(n_past, reused);
+            let mut generated = 0usize;
 
             // UTF-8 streaming state.
             let mut out_text = String::new();
@@ -137,27 +158,30 @@ impl<B: LLMBackend> LLMEngine<B> {
             // Decode loop (STOP-aware).
             for step in 0..step_limit {
                 if self.stop_flag.load(std::sync::atomic::Ordering::Relaxed) {
-                    println!("⏹️ [infer-stream] STOP requested. Ending.");
+                    info!("stop requested at step {step}");
                     break;
                 }
-                println!("🔁 [infer-stream] Step {}", step);
 
                 let token = self
                     .backend
                     .sample(n_past, &self.sample_params, &token_history)
-                    .map_err(|e| format!("❌ [infer-stream] Sampling failed: {e}"))?;
-                println!("🎯 [infer-stream] Sampled token: {:?}", token);
+                    .map_err(|e| e.context("❌ [infer-stream] Sampling failed"))?;
+                timer.sampled();
+                trace!("step {step}: sampled {token:?}");
 
                 if token == self.backend.eos_token() {
-                    println!("🏁 [infer-stream] Reached EOS token. Ending.");
+                    debug!("end of generation at step {step}");
                     break;
                 }
 
-                self.backend
-                    .evaluate(&[token], n_past)
-                    .map_err(|e| format!("❌ [infer-stream] Re-eval failed at step {step}: {e}"))?;
+                self.backend.evaluate(&[token], n_past).map_err(|e| {
+                    e.context(format_args!(
+                        "❌ [infer-stream] Re-eval failed at step {step}"
+                    ))
+                })?;
                 token_history.push(token);
                 n_past += 1;
+                generated += 1;
 
                 // Detokenize only the new range; emit valid UTF-8 prefix.
                 let new_bytes =
@@ -184,10 +208,13 @@ impl<B: LLMBackend> LLMEngine<B> {
             self.prev_prompt_tokens = token_history[..detok_start_idx].to_vec();
 
             let out_text = out_text.trim().to_string();
-            println!(
-                "✅ [infer-stream] Complete. Output length: {} chars",
-                out_text.len()
+            let stats = timer.finish(generated, self.backend.take_perf_counters());
+            info!(
+                "complete: {} chars, {generated} tokens at {:.1} tok/s",
+                out_text.len(),
+                stats.tokens_per_second
             );
+            self.last_stats = Some(stats);
             Ok(out_text)
         }))
         .map_err(|_| "💥 [infer-stream] PANIC occurred during inference!".to_string())?
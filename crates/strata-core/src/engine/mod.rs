@@ -6,17 +6,32 @@ use std::sync::{
     atomic::{AtomicBool, Ordering},
 };
 
+use crate::format::chat_template::{ChatTemplateInputs, TemplateMode, render_chat_template};
 use crate::format::format::FormattedPrompt;
+use crate::format::media::inline_media;
+use crate::format::prompt_format::{PromptKind, select_prompt};
+use crate::format::prompting::PromptStrategy;
+use crate::format::reasoning::{
+    Channel, ChatReply, ReasoningSplitter, prompt_opens_reasoning, split_reasoning,
+};
 use crate::memory::SessionMemory;
-use strata_abi::backend::{ChatTurn, LLMBackend, Role};
+use crate::tools::{Tool, ToolChatOutcome, tool_definition};
+use log::debug;
+use strata_abi::backend::{ChatTurn, ControlVector, LLMBackend, LoraAdapter, Role};
+use strata_abi::error::{ErrorCode, StrataError};
 use strata_abi::sampling::SamplingParams;
+use strata_abi::session::MemoryReport;
 use strata_abi::token::Token;
 
 // Child modules (private to this crate). They can access private fields here.
 mod decode;
 mod prefill;
+mod stats;
+mod tools;
 mod utils;
 
+pub use stats::GenerationStats;
+
 /// Engine = {loaded backend session} + {prompt strategy} + {rolling dialog memory}.
 /// One `LLMEngine` is one logical chat session.
 pub struct LLMEngine<B: LLMBackend> {
@@ -26,9 +41,23 @@ pub struct LLMEngine<B: LLMBackend> {
     memory: SessionMemory,
     prompt_token_budget: usize,
     stop_flag: Arc<AtomicBool>,
+    template_mode: TemplateMode,
+    /// User-selected formatter; wins over the model's own template.
+    prompt_strategy: Option<Box<dyn PromptStrategy>>,
+    /// User-supplied Jinja template; wins over everything else.
+    template_override: Option<String>,
+    /// Tools advertised to the template and dispatched by `infer_chat_with_tools`.
+    tools: Vec<Box<dyn Tool>>,
+    max_tool_rounds: usize,
+    /// Keep `<think>` blocks in stored history (off: only the answer is kept).
+    keep_reasoning: bool,
+    /// Control vectors currently applied to the backend session.
+    control_vectors: Vec<ControlVector>,
     // ========== KV reuse bookkeeping ==========
     prev_prompt_tokens: Vec<Token>,
     kv_warm: bool,
+    /// Timings of the most recent generation that completed.
+    last_stats: Option<GenerationStats>,
 }
 
 impl<B: LLMBackend> LLMEngine<B> {
@@ -41,8 +70,16 @@ impl<B: LLMBackend> LLMEngine<B> {
             memory: SessionMemory::new(),
             prompt_token_budget: 3072, // refined in `with_auto`
             stop_flag: Arc::new(AtomicBool::new(false)),
+            template_mode: TemplateMode::default(),
+            prompt_strategy: None,
+            template_override: None,
+            tools: Vec::new(),
+            max_tool_rounds: 8,
+            keep_reasoning: false,
+            control_vectors: Vec::new(),
             prev_prompt_tokens: Vec::new(),
             kv_warm: false,
+            last_stats: None,
         }
     }
 
@@ -54,11 +91,11 @@ impl<B: LLMBackend> LLMEngine<B> {
 
         if let Some(n_ctx) = s.backend.context_window_hint() {
             let budget = ((n_ctx as f32) * 0.75) as usize;
-            println!("🧮 context_window_hint = {n_ctx}, prompt_token_budget = {budget}");
+            debug!("context_window_hint = {n_ctx}, prompt_token_budget = {budget}");
             s.set_prompt_token_budget(budget);
         } else {
-            println!(
-                "🧮 context_window_hint not provided; using default prompt_token_budget = {}",
+            debug!(
+                "context_window_hint not provided; using default prompt_token_budget = {}",
                 s.prompt_token_budget
             );
         }
@@ -75,6 +112,46 @@ impl<B: LLMBackend> LLMEngine<B> {
         self.prompt_token_budget = budget.max(1);
     }
 
+    /// Choose between the backend's native formatter and the core Jinja renderer.
+    pub fn set_template_mode(&mut self, mode: TemplateMode) {
+        self.template_mode = mode;
+    }
+
+    /// Force a generic formatter (`None` goes back to the model's template).
+    pub fn set_prompt_kind(&mut self, kind: Option<PromptKind>) {
+        self.prompt_strategy = kind.map(select_prompt);
+    }
+
+    /// Use `template` (Jinja source) instead of the model's `chat_template`.
+    pub fn set_chat_template_override(&mut self, template: Option<String>) {
+        self.template_override = template;
+    }
+
+    /// Make `tool` callable by the model (replaces a tool with the same name).
+    pub fn register_tool(&mut self, tool: Box<dyn Tool>) {
+        self.tools.retain(|t| t.name() != tool.name());
+        self.tools.push(tool);
+    }
+
+    /// Remove all registered tools.
+    pub fn clear_tools(&mut self) {
+        self.tools.clear();
+    }
+
+    /// Cap on model → tool → model round-trips per `infer_chat_with_tools` call.
+    pub fn set_max_tool_rounds(&mut self, rounds: usize) {
+        self.max_tool_rounds = rounds.max(1);
+    }
+
+    /// Keep model reasoning in stored history instead of only the answer.
+    pub fn set_keep_reasoning(&mut self, keep: bool) {
+        self.keep_reasoning = keep;
+    }
+
+    pub fn keep_reasoning(&self) -> bool {
+        self.keep_reasoning
+    }
+
     /// Handle you can keep and flip to cancel decoding (`store(true)`).
     pub fn stop_handle(&self) -> Arc<AtomicBool> {
         self.stop_flag.clone()
@@ -85,6 +162,56 @@ impl<B: LLMBackend> LLMEngine<B> {
         self.backend.clear_kv_cache();
     }
 
+    /// Load a LoRA adapter against the resident model; returns its id.
+    pub fn load_lora<P: AsRef<std::path::Path>>(&mut self, path: P) -> Result<i32, StrataError> {
+        self.backend.load_lora(path.as_ref())
+    }
+
+    /// Attach (or re-scale) a loaded adapter. Cached KV was computed with the old
+    /// weights, so the next prompt is prefilled from scratch.
+    pub fn set_lora(&mut self, id: i32, scale: f32) -> Result<(), StrataError> {
+        self.backend.set_lora(id, scale)?;
+        self.reset_kv();
+        Ok(())
+    }
+
+    /// Detach an adapter from this session (it stays loaded).
+    pub fn remove_lora(&mut self, id: i32) -> Result<(), StrataError> {
+        self.backend.remove_lora(id)?;
+        self.reset_kv();
+        Ok(())
+    }
+
+    /// Loaded adapters and their scale on this session.
+    pub fn lora_adapters(&self) -> Vec<LoraAdapter> {
+        self.backend.lora_adapters()
+    }
+
+    /// Steer generation with control vectors (summed; empty turns steering off).
+    /// Like adapters, this invalidates cached KV.
+    pub fn set_control_vectors(&mut self, vectors: Vec<ControlVector>) -> Result<(), StrataError> {
+        self.backend.set_control_vectors(&vectors)?;
+        self.control_vectors = vectors;
+        self.reset_kv();
+        Ok(())
+    }
+
+    /// Control vectors currently applied.
+    pub fn control_vectors(&self) -> &[ControlVector] {
+        &self.control_vectors
+    }
+
+    /// Memory held by the backend session (weights, KV cache, compute buffers).
+    pub fn memory_report(&self) -> Option<MemoryReport> {
+        self.backend.memory_report()
+    }
+
+    /// Timings of the last generation (for tool calls, the last round);
+    /// `None` before the first one or after one that failed.
+    pub fn last_generation_stats(&self) -> Option<&GenerationStats> {
+        self.last_stats.as_ref()
+    }
+
     #[inline]
     fn clear_stop(&self) {
         self.stop_flag.store(false, Ordering::Relaxed);
@@ -95,31 +222,90 @@ impl<B: LLMBackend> LLMEngine<B> {
     // ─────────────────────────────────────────────
 
     /// Stateful single-turn: appends to engine memory, prunes to budget, generates, stores reply.
-    pub fn infer(&mut self, user_input: &str) -> Result<String, String> {
+    /// Returns the answer; reasoning is kept in memory only if `keep_reasoning` is set.
+    pub fn infer(&mut self, user_input: &str) -> Result<String, StrataError> {
         self.memory.push_user(user_input);
         let formatted = self.prune_to_budget_native()?;
+        let opens = prompt_opens_reasoning(&formatted.text);
         let out = self.infer_with_formatted(formatted)?;
-        self.memory.push_assistant(out.clone());
-        Ok(out)
+        let reply = split_reasoning(&out, opens);
+        self.memory
+            .push_assistant(reply.history_text(self.keep_reasoning));
+        Ok(reply.answer)
     }
 
-    /// Stateless multi-turn (does not mutate engine memory).
-    pub fn infer_chat(&mut self, turns: &[ChatTurn]) -> Result<String, String> {
-        let formatted = self.format_turns_via_backend(turns)?;
-        self.infer_with_formatted(formatted)
+    /// Stateless multi-turn (does not mutate engine memory). Returns the answer only.
+    pub fn infer_chat(&mut self, turns: &[ChatTurn]) -> Result<String, StrataError> {
+        Ok(self.infer_chat_reply(turns, None)?.answer)
     }
 
-    /// Streaming multi-turn. Calls `on_delta` with UTF-8 chunks; also returns the final string.
+    /// Stateless multi-turn keeping reasoning and answer apart.
+    /// `enable_thinking` is passed to templates that honour it (Qwen3 and friends).
+    pub fn infer_chat_reply(
+        &mut self,
+        turns: &[ChatTurn],
+        enable_thinking: Option<bool>,
+    ) -> Result<ChatReply, StrataError> {
+        let formatted = self.format_turns_via_backend(turns, enable_thinking)?;
+        let opens = prompt_opens_reasoning(&formatted.text);
+        let out = self.infer_with_formatted(formatted)?;
+        Ok(split_reasoning(&out, opens))
+    }
+
+    /// Streaming multi-turn. Calls `on_delta` with UTF-8 answer chunks (reasoning is
+    /// dropped); also returns the final answer.
     pub fn infer_chat_stream<F>(
         &mut self,
         turns: &[ChatTurn],
         on_delta: F,
-    ) -> Result<String, String>
+    ) -> Result<String, StrataError>
     where
         F: FnMut(&str),
     {
-        let formatted = self.format_turns_via_backend(turns)?;
-        self.stream_with_formatted(formatted, on_delta)
+        Ok(self
+            .infer_chat_stream_split(turns, None, |_| {}, on_delta)?
+            .answer)
+    }
+
+    /// Streaming multi-turn with reasoning and answer on separate callbacks.
+    pub fn infer_chat_stream_split<R, A>(
+        &mut self,
+        turns: &[ChatTurn],
+        enable_thinking: Option<bool>,
+        mut on_reasoning: R,
+        mut on_answer: A,
+    ) -> Result<ChatReply, StrataError>
+    where
+        R: FnMut(&str),
+        A: FnMut(&str),
+    {
+        let formatted = self.format_turns_via_backend(turns, enable_thinking)?;
+        let mut splitter = ReasoningSplitter::new(prompt_opens_reasoning(&formatted.text));
+        let mut reasoning = String::new();
+        let mut answer = String::new();
+        let mut emit = |channel: Channel, s: &str| match channel {
+            Channel::Reasoning => {
+                on_reasoning(s);
+                reasoning.push_str(s);
+            }
+            Channel::Answer => {
+                on_answer(s);
+                answer.push_str(s);
+            }
+        };
+
+        self.stream_with_formatted(formatted, |delta| splitter.push(delta, &mut emit))?;
+        splitter.finish(&mut emit);
+        Ok(splitter.into_reply(reasoning, answer))
+    }
+
+    /// Stateless multi-turn with tool calling: runs registered tools whenever the
+    /// model asks for them and feeds the results back until it answers in prose.
+    pub fn infer_chat_with_tools(
+        &mut self,
+        turns: &[ChatTurn],
+    ) -> Result<ToolChatOutcome, StrataError> {
+        self.run_tool_loop(turns)
     }
 
     // ─────────────────────────────────────────────
@@ -142,38 +328,130 @@ impl<B: LLMBackend> LLMEngine<B> {
         if step_limit == 0 { 32 } else { step_limit }
     }
 
-    fn format_turns_via_backend(&self, turns: &[ChatTurn]) -> Result<FormattedPrompt, String> {
+    fn format_turns_via_backend(
+        &self,
+        turns: &[ChatTurn],
+        enable_thinking: Option<bool>,
+    ) -> Result<FormattedPrompt, StrataError> {
         // Inject system prompt if we have one and caller didn't provide a system turn
         let mut t: Vec<ChatTurn> = Vec::with_capacity(turns.len() + 1);
         let has_sys = turns.iter().any(|tt| matches!(tt.role, Role::System));
-        if !has_sys {
-            if let Some(sys) = self.system_prompt.as_deref() {
-                t.push(ChatTurn::system(sys.to_string()));
-            }
+        if !has_sys && let Some(sys) = self.system_prompt.as_deref() {
+            t.push(ChatTurn::system(sys.to_string()));
         }
         t.extend_from_slice(turns);
+        // Image parts become the backend's media marker; the bytes travel alongside.
+        let marker = self.backend.media_marker();
+        let images = inline_media(&mut t, marker.as_deref())?;
 
-        if let Some(text) = self.backend.apply_native_chat_template(&t) {
-            let stops = self
-                .backend
-                .default_stop_strings()
-                .iter()
-                .map(|s| s.to_string())
-                .collect::<Vec<_>>();
-            Ok(FormattedPrompt {
+        // Precedence: user template > user-selected formatter > model template.
+        if let Some(template) = self.template_override.as_deref() {
+            return Ok(FormattedPrompt {
+                text: self.render_core_template(template, &t, enable_thinking)?,
+                stop_sequences: self.backend.default_stop_strings(),
+                add_space_prefix: true,
+                images,
+            });
+        }
+        if let Some(strategy) = self.prompt_strategy.as_ref() {
+            // System turn is already in `t`; don't let the strategy add another.
+            let mut formatted = strategy.format_dialog(&t, None);
+            formatted.images = images;
+            return Ok(formatted);
+        }
+
+        let text = match self.template_mode {
+            // Native formatters can't see tool definitions; don't drop them silently.
+            TemplateMode::Native if !self.tools.is_empty() => {
+                return Err(StrataError::new(
+                    ErrorCode::Unsupported,
+                    "the native chat template can't render tool definitions; \
+                     use the core or auto template mode",
+                ));
+            }
+            TemplateMode::Native => self.backend.apply_native_chat_template(&t),
+            TemplateMode::Core => self.render_model_template(&t, enable_thinking)?,
+            // Native formatters can't see tool definitions or template variables;
+            // go straight to core.
+            TemplateMode::Auto if !self.tools.is_empty() || enable_thinking.is_some() => {
+                self.render_model_template(&t, enable_thinking)?
+            }
+            TemplateMode::Auto => match self.backend.apply_native_chat_template(&t) {
+                Some(text) => Some(text),
+                None => self.render_model_template(&t, enable_thinking)?,
+            },
+        };
+
+        match text {
+            Some(text) => Ok(FormattedPrompt {
                 text,
-                stop_sequences: stops,
+                stop_sequences: self.backend.default_stop_strings(),
                 add_space_prefix: true,
-            })
-        } else {
-            Err("No native chat template available for this backend/model; refusing to fall back. Please paste a chat_template or select an explicit formatter in the UI.".into())
+                images,
+            }),
+            None => Err("No chat template available for this backend/model; refusing to fall back. Please paste a chat_template or select an explicit formatter in the UI.".into()),
         }
     }
 
-    fn prune_to_budget_native(&mut self) -> Result<FormattedPrompt, String> {
+    /// Render the model's own `chat_template` with the core Jinja engine.
+    /// `Ok(None)` when the backend exposes no template.
+    fn render_model_template(
+        &self,
+        turns: &[ChatTurn],
+        enable_thinking: Option<bool>,
+    ) -> Result<Option<String>, StrataError> {
+        match self.backend.chat_template() {
+            Some(template) => self
+                .render_core_template(&template, turns, enable_thinking)
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn render_core_template(
+        &self,
+        template: &str,
+        turns: &[ChatTurn],
+        enable_thinking: Option<bool>,
+    ) -> Result<String, StrataError> {
+        let bos = match self.backend.bos_token() {
+            Some(tok) => self.backend.decode_token(tok)?,
+            None => String::new(),
+        };
+        let eos = self.backend.decode_token(self.backend.eos_token())?;
+        let extra = enable_thinking.map(|on| serde_json::json!({ "enable_thinking": on }));
+        let tools = (!self.tools.is_empty()).then(|| {
+            serde_json::Value::Array(
+                self.tools
+                    .iter()
+                    .map(|t| tool_definition(t.as_ref()))
+                    .collect(),
+            )
+        });
+
+        let text = render_chat_template(
+            template,
+            &ChatTemplateInputs {
+                messages: turns,
+                add_generation_prompt: true,
+                bos_token: &bos,
+                eos_token: &eos,
+                tools: tools.as_ref(),
+                extra: extra.as_ref(),
+            },
+        )?;
+
+        // The tokenizer adds BOS itself; drop the template's copy so it isn't doubled.
+        Ok(match text.strip_prefix(bos.as_str()) {
+            Some(rest) if !bos.is_empty() => rest.to_string(),
+            _ => text,
+        })
+    }
+
+    fn prune_to_budget_native(&mut self) -> Result<FormattedPrompt, StrataError> {
         loop {
             let turns = self.memory.turns().to_vec();
-            let formatted = self.format_turns_via_backend(&turns)?;
+            let formatted = self.format_turns_via_backend(&turns, None)?;
             let toks = self.backend.tokenize(&formatted.text)?;
             if toks.len() <= self.prompt_token_budget {
                 return Ok(formatted);
@@ -191,4 +469,5 @@ impl<B: LLMBackend> LLMEngine<B> {
 //
 // - prefill.rs:    prefill_incremental(...) + lcp_len(...)
 // - decode.rs:     infer_with_formatted(...), stream_with_formatted(...)
+// - tools.rs:     run_tool_loop(...)
 // - utils.rs:      utf8_valid_prefix_len(...)
@@ -1,6 +1,8 @@
 use super::LLMEngine;
+use log::{debug, info, trace};
 use std::sync::atomic::Ordering;
 use strata_abi::backend::LLMBackend;
+use strata_abi::error::StrataError;
 use strata_abi::token::Token;
 
 impl<B: LLMBackend> LLMEngine<B> {
@@ -15,11 +17,12 @@ impl<B: LLMBackend> LLMEngine<B> {
         n
     }
 
-    /// Incremental prefill with KV reuse; returns (n_past, token_history, detok_start_idx).
+    /// Incremental prefill with KV reuse; returns (n_past, token_history, detok_start_idx,
+    /// reused_tokens).
     pub(super) fn prefill_incremental(
         &mut self,
         prompt_tokens: &[Token],
-    ) -> Result<(i32, Vec<Token>, usize), String> {
+    ) -> Result<(i32, Vec<Token>, usize, usize), StrataError> {
         const PREFILL_CHUNK: usize = 64;
 
         // 1) Compare with previous prompt
@@ -27,15 +30,15 @@ impl<B: LLMBackend> LLMEngine<B> {
         let append_only = self.kv_warm && lcp == self.prev_prompt_tokens.len();
 
         if append_only {
-            println!(
-                "♻️  [prefill] Reusing KV (lcp={}, prev_len={}, new_len={})",
+            debug!(
+                "reusing KV (lcp={}, prev_len={}, new_len={})",
                 lcp,
                 self.prev_prompt_tokens.len(),
                 prompt_tokens.len()
             );
         } else {
-            println!(
-                "🧹 [prefill] Prompt diverged or cold KV (lcp={}, prev_len={}, new_len={}) → clearing KV",
+            debug!(
+                "prompt diverged or cold KV (lcp={}, prev_len={}, new_len={}); clearing KV",
                 lcp,
                 self.prev_prompt_tokens.len(),
                 prompt_tokens.len()
@@ -56,26 +59,50 @@ impl<B: LLMBackend> LLMEngine<B> {
         // 2) Evaluate only the delta
         for (i, chunk) in prompt_tokens[start_idx..].chunks(PREFILL_CHUNK).enumerate() {
             if self.stop_flag.load(Ordering::Relaxed) {
-                println!("⏹️ [prefill] STOP requested during prefill.");
+                info!("stop requested during prefill");
                 break;
             }
-            println!(
-                "⚙️ [evaluate] Prefill chunk {i} (len {}), n_past = {n_past}",
-                chunk.len()
-            );
+            trace!("prefill chunk {i} (len {}), n_past = {n_past}", chunk.len());
             self.backend
                 .evaluate(chunk, n_past)
-                .map_err(|e| format!("❌ [infer] Prefill failed: {e}"))?;
+                .map_err(|e| e.context("❌ [infer] Prefill failed"))?;
             token_history.extend_from_slice(chunk);
             n_past += chunk.len() as i32;
         }
-        println!("✅ [prefill] Done ({} tokens); kv_warm = true", n_past);
+        debug!("prefill done ({n_past} tokens)");
 
         // 3) KV now matches the new prompt
         self.prev_prompt_tokens = token_history.clone();
         self.kv_warm = true;
 
         let detok_start_idx = token_history.len(); // start detok after the prompt
-        Ok((n_past, token_history, detok_start_idx))
+        Ok((n_past, token_history, detok_start_idx, start_idx))
+    }
+
+    /// Drop backend KV and forget the cached prompt, so the next prefill starts cold.
+    pub(super) fn reset_kv(&mut self) {
+        self.backend.clear_kv_cache();
+        self.prev_prompt_tokens.clear();
+        self.kv_warm = false;
+    }
+
+    /// Prefill a prompt that carries images. Media can't be diffed token-wise,
+    /// so KV is rebuilt from scratch and the next turn starts cold.
+    pub(super) fn prefill_media(
+        &mut self,
+        prompt: &str,
+        images: &[Vec<u8>],
+    ) -> Result<(i32, Vec<Token>, usize, usize), StrataError> {
+        debug!("evaluating prompt with {} image(s)", images.len());
+        self.reset_kv();
+
+        let n_past = self
+            .backend
+            .evaluate_with_media(prompt, images, 0)
+            .map_err(|e| e.context("❌ [infer] Media prefill failed"))?;
+        debug!("media prefill done (n_past = {n_past})");
+
+        // No token mirror for the prompt; history holds generated tokens only.
+        Ok((n_past, Vec::new(), 0, 0))
     }
 }
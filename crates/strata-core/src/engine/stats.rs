@@ -0,0 +1,100 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use strata_abi::session::PerfCounters;
+
+/// Timings for one generation: wall clock as seen by the engine, plus the
+/// backend's own counters when it keeps them.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenerationStats {
+    /// Tokens in the formatted prompt.
+    pub prompt_tokens: usize,
+    /// Prompt tokens whose KV was kept from the previous turn instead of
+    /// being evaluated again.
+    pub reused_tokens: usize,
+    /// Tokens sampled and evaluated (the end-of-generation token excluded).
+    pub generated_tokens: usize,
+    /// Evaluating the prompt (or the part of it not reused).
+    pub prefill_ms: f64,
+    /// From the start of the call to the first sampled token; `None` if
+    /// generation stopped before one was sampled.
+    pub time_to_first_token_ms: Option<f64>,
+    /// The decode loop: sampling, evaluating and detokenizing.
+    pub decode_ms: f64,
+    /// The whole call, tokenization included.
+    pub total_ms: f64,
+    /// Prompt tokens evaluated per second (reused tokens don't count).
+    pub prefill_tokens_per_second: f64,
+    /// Generated tokens per second of decode time.
+    pub tokens_per_second: f64,
+    /// The backend's counters for this generation.
+    pub backend: Option<PerfCounters>,
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+fn per_second(tokens: usize, ms: f64) -> f64 {
+    if ms > 0.0 {
+        tokens as f64 * 1000.0 / ms
+    } else {
+        0.0
+    }
+}
+
+/// Stopwatch for the phases of one generation.
+pub(super) struct GenerationTimer {
+    started: Instant,
+    phase: Instant,
+    stats: GenerationStats,
+}
+
+impl GenerationTimer {
+    pub(super) fn start() -> Self {
+        let now = Instant::now();
+        Self {
+            started: now,
+            phase: now,
+            stats: GenerationStats::default(),
+        }
+    }
+
+    /// Prompt tokenized; prefill starts.
+    pub(super) fn prefill_started(&mut self) {
+        self.phase = Instant::now();
+    }
+
+    /// Prefill done with `n_past` positions in KV (images included); decoding starts.
+    pub(super) fn prefilled(&mut self, n_past: i32, reused_tokens: usize) {
+        self.stats.prompt_tokens = n_past.max(0) as usize;
+        self.stats.reused_tokens = reused_tokens;
+        self.stats.prefill_ms = millis(self.phase.elapsed());
+        self.phase = Instant::now();
+    }
+
+    /// A token was sampled.
+    pub(super) fn sampled(&mut self) {
+        if self.stats.time_to_first_token_ms.is_none() {
+            self.stats.time_to_first_token_ms = Some(millis(self.started.elapsed()));
+        }
+    }
+
+    pub(super) fn finish(
+        mut self,
+        generated_tokens: usize,
+        backend: Option<PerfCounters>,
+    ) -> GenerationStats {
+        let s = &mut self.stats;
+        s.generated_tokens = generated_tokens;
+        s.decode_ms = millis(self.phase.elapsed());
+        s.total_ms = millis(self.started.elapsed());
+        s.prefill_tokens_per_second = per_second(
+            s.prompt_tokens.saturating_sub(s.reused_tokens),
+            s.prefill_ms,
+        );
+        s.tokens_per_second = per_second(generated_tokens, s.decode_ms);
+        s.backend = backend;
+        self.stats
+    }
+}
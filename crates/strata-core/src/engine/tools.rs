@@ -0,0 +1,83 @@
+use super::LLMEngine;
+use crate::format::reasoning::{prompt_opens_reasoning, split_reasoning};
+use crate::format::tool_calls::parse_tool_calls;
+use crate::tools::ToolChatOutcome;
+use log::debug;
+use strata_abi::backend::{ChatTurn, LLMBackend};
+use strata_abi::error::StrataError;
+
+impl<B: LLMBackend> LLMEngine<B> {
+    /// Generate → parse calls → run tools → append results, until the model
+    /// replies without calling a registered tool.
+    pub(super) fn run_tool_loop(
+        &mut self,
+        turns: &[ChatTurn],
+    ) -> Result<ToolChatOutcome, StrataError> {
+        let mut dialog: Vec<ChatTurn> = turns.to_vec();
+        let start = dialog.len();
+        let mut next_id = 0usize;
+
+        for round in 0..self.max_tool_rounds {
+            let formatted = self.format_turns_via_backend(&dialog, None)?;
+            let opens = prompt_opens_reasoning(&formatted.text);
+            let out = self.infer_with_formatted(formatted)?;
+            let reply = split_reasoning(&out, opens);
+            let mut parsed = parse_tool_calls(&reply.answer);
+
+            // A bare JSON answer can look like a call; only act on known tools.
+            let known = parsed
+                .calls
+                .iter()
+                .any(|c| self.tools.iter().any(|t| t.name() == c.name));
+            if !known {
+                dialog.push(ChatTurn::assistant(reply.history_text(self.keep_reasoning)));
+                return Ok(ToolChatOutcome {
+                    reply: reply.answer,
+                    turns: dialog.split_off(start),
+                });
+            }
+
+            // Some templates (Mistral) insist on 9-char alphanumeric ids.
+            for call in parsed.calls.iter_mut() {
+                if call.id.is_none() {
+                    call.id = Some(format!("{next_id:09}"));
+                    next_id += 1;
+                }
+            }
+            debug!(
+                "round {round}: {} tool call(s): {}",
+                parsed.calls.len(),
+                parsed
+                    .calls
+                    .iter()
+                    .map(|c| c.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+
+            let mut results = Vec::with_capacity(parsed.calls.len());
+            for call in &parsed.calls {
+                let result = match self.tools.iter_mut().find(|t| t.name() == call.name) {
+                    Some(tool) => match serde_json::from_str(&call.arguments) {
+                        Ok(args) => tool.call(&args),
+                        Err(e) => Err(format!("invalid arguments JSON: {e}")),
+                    },
+                    None => Err(format!("unknown tool `{}`", call.name)),
+                };
+                // Errors go back to the model so it can correct itself.
+                let content =
+                    result.unwrap_or_else(|e| serde_json::json!({ "error": e }).to_string());
+                results.push(ChatTurn::tool(&call.name, call.id.clone(), content));
+            }
+
+            dialog.push(ChatTurn::assistant_tool_calls(parsed.content, parsed.calls));
+            dialog.extend(results);
+        }
+
+        Err(format!(
+            "tool loop did not finish within {} rounds",
+            self.max_tool_rounds
+        )
+        .into())
+    }
+}
@@ -0,0 +1,110 @@
+//! Render a model's own `tokenizer.chat_template` in core, without relying on
+//! the backend's built-in template list.
+
+use strata_abi::backend::{ChatTurn, Role, ToolCall};
+
+use super::jinja::{Template, Value};
+
+/// Which renderer produces the prompt text for a chat.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TemplateMode {
+    /// Backend's native formatter first, core Jinja renderer if it declines.
+    #[default]
+    Auto,
+    /// Only the backend's native formatter; chats with tools are rejected,
+    /// since it can't render their definitions.
+    Native,
+    /// Only the core Jinja renderer.
+    Core,
+}
+
+/// Variables HF passes to `apply_chat_template`.
+#[derive(Debug, Clone, Default)]
+pub struct ChatTemplateInputs<'a> {
+    pub messages: &'a [ChatTurn],
+    pub add_generation_prompt: bool,
+    pub bos_token: &'a str,
+    pub eos_token: &'a str,
+    /// JSON array of tool schemas (`tools` in HF templates).
+    pub tools: Option<&'a serde_json::Value>,
+    /// Extra top-level variables (e.g. `{"enable_thinking": false}`).
+    pub extra: Option<&'a serde_json::Value>,
+}
+
+pub fn role_name(role: &Role) -> &'static str {
+    match role {
+        Role::System => "system",
+        Role::User => "user",
+        Role::Assistant => "assistant",
+        Role::Tool => "tool",
+    }
+}
+
+/// One HF-style message: `{role, content}` plus `tool_calls` on assistant turns
+/// and `tool_call_id`/`name` on tool results.
+pub fn message_json(turn: &ChatTurn) -> serde_json::Value {
+    let mut msg = serde_json::Map::new();
+    msg.insert("role".into(), role_name(&turn.role).into());
+    msg.insert("content".into(), turn.content.as_str().into());
+    if !turn.tool_calls.is_empty() {
+        let calls = turn.tool_calls.iter().map(tool_call_json).collect();
+        msg.insert("tool_calls".into(), serde_json::Value::Array(calls));
+    }
+    if let Some(id) = &turn.tool_call_id {
+        msg.insert("tool_call_id".into(), id.as_str().into());
+    }
+    if let Some(name) = &turn.name {
+        msg.insert("name".into(), name.as_str().into());
+    }
+    serde_json::Value::Object(msg)
+}
+
+/// Templates expect `arguments` as a mapping (they `| tojson` it themselves),
+/// so decode the JSON text when possible.
+fn tool_call_json(call: &ToolCall) -> serde_json::Value {
+    let arguments = serde_json::from_str::<serde_json::Value>(&call.arguments)
+        .unwrap_or_else(|_| call.arguments.as_str().into());
+    let mut function = serde_json::Map::new();
+    function.insert("name".into(), call.name.as_str().into());
+    function.insert("arguments".into(), arguments);
+
+    let mut out = serde_json::Map::new();
+    if let Some(id) = &call.id {
+        out.insert("id".into(), id.as_str().into());
+    }
+    out.insert("type".into(), "function".into());
+    out.insert("function".into(), serde_json::Value::Object(function));
+    serde_json::Value::Object(out)
+}
+
+/// Parse and render `template` with HF-compatible inputs.
+pub fn render_chat_template(template: &str, inputs: &ChatTemplateInputs) -> Result<String, String> {
+    let tpl = Template::parse(template).map_err(|e| format!("chat template parse error: {e}"))?;
+
+    let messages = inputs
+        .messages
+        .iter()
+        .map(|t| Value::from(&message_json(t)))
+        .collect();
+
+    let mut globals = vec![
+        ("messages".to_string(), Value::List(messages)),
+        (
+            "add_generation_prompt".to_string(),
+            Value::from(inputs.add_generation_prompt),
+        ),
+        ("bos_token".to_string(), Value::from(inputs.bos_token)),
+        ("eos_token".to_string(), Value::from(inputs.eos_token)),
+    ];
+    if let Some(tools) = inputs.tools {
+        globals.push(("tools".to_string(), Value::from(tools)));
+    }
+    if let Some(serde_json::Value::Object(extra)) = inputs.extra {
+        for (k, v) in extra {
+            globals.push((k.clone(), Value::from(v)));
+        }
+    }
+
+    tpl.render(globals)
+        .map_err(|e| format!("chat template render error: {e}"))
+}
@@ -13,6 +13,8 @@ pub struct FormattedPrompt {
     /// Some tokenizers prefer a leading space to avoid odd tokenization;
     /// backends can ignore this if they handle space-prefix internally.
     pub add_space_prefix: bool,
+    /// Encoded images, one per media marker in `text` (in order).
+    pub images: Vec<Vec<u8>>,
 }
 
 impl FormattedPrompt {
@@ -21,6 +23,7 @@ impl FormattedPrompt {
             text: text.into(),
             stop_sequences: Vec::new(),
             add_space_prefix: true,
+            images: Vec::new(),
         }
     }
 }
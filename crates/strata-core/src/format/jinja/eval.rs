@@ -0,0 +1,1326 @@
+//! Tree-walking renderer for parsed templates: scopes, filters, tests and
+//! the handful of Python methods HF templates call on strings and dicts.
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::parser::{BinOp, Expr, MacroDef, Node, SetTarget};
+use super::value::{Value, py_index};
+
+/// Guards against runaway recursive macros in hostile templates.
+const MAX_CALL_DEPTH: usize = 64;
+
+struct Frame {
+    vars: HashMap<String, Value>,
+    /// Macro frames only see their own locals plus the root frame.
+    barrier: bool,
+}
+
+enum Flow {
+    Normal,
+    Break,
+    Continue,
+}
+
+pub(super) struct Renderer {
+    frames: Vec<Frame>,
+    depth: usize,
+}
+
+type Kwargs = [(String, Value)];
+
+impl Renderer {
+    pub(super) fn new(globals: Vec<(String, Value)>) -> Self {
+        Self {
+            frames: vec![Frame {
+                vars: globals.into_iter().collect(),
+                barrier: false,
+            }],
+            depth: 0,
+        }
+    }
+
+    pub(super) fn render(&mut self, nodes: &[Node]) -> Result<String, String> {
+        let mut out = String::new();
+        self.render_nodes(nodes, &mut out)?;
+        Ok(out)
+    }
+
+    fn lookup(&self, name: &str) -> Value {
+        for frame in self.frames.iter().rev() {
+            if let Some(v) = frame.vars.get(name) {
+                return v.clone();
+            }
+            if frame.barrier {
+                return self.frames[0].vars.get(name).cloned().unwrap_or_default();
+            }
+        }
+        Value::Undefined
+    }
+
+    fn assign(&mut self, name: &str, value: Value) {
+        let frame = self.frames.last_mut().expect("root frame");
+        frame.vars.insert(name.to_string(), value);
+    }
+
+    fn push_frame(&mut self, barrier: bool) {
+        self.frames.push(Frame {
+            vars: HashMap::new(),
+            barrier,
+        });
+    }
+
+    fn pop_frame(&mut self) {
+        self.frames.pop();
+    }
+
+    fn render_nodes(&mut self, nodes: &[Node], out: &mut String) -> Result<Flow, String> {
+        for node in nodes {
+            match self.render_node(node, out)? {
+                Flow::Normal => {}
+                flow => return Ok(flow),
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    fn render_node(&mut self, node: &Node, out: &mut String) -> Result<Flow, String> {
+        match node {
+            Node::Text(t) => out.push_str(t),
+            Node::Output(e) => {
+                let v = self.eval(e)?;
+                out.push_str(&v.to_output());
+            }
+            Node::If {
+                branches,
+                otherwise,
+            } => {
+                for (cond, body) in branches {
+                    if self.eval(cond)?.truthy() {
+                        return self.render_nodes(body, out);
+                    }
+                }
+                return self.render_nodes(otherwise, out);
+            }
+            Node::For {
+                targets,
+                iter,
+                filter,
+                body,
+                otherwise,
+            } => {
+                let source = self.eval(iter)?.iter_items()?;
+                self.push_frame(false);
+                let result = self.render_for(targets, source, filter.as_ref(), body, out);
+                self.pop_frame();
+                if !result? {
+                    return self.render_nodes(otherwise, out);
+                }
+            }
+            Node::Set { target, value } => {
+                let v = self.eval(value)?;
+                match target {
+                    SetTarget::Names(names) if names.len() == 1 => self.assign(&names[0], v),
+                    SetTarget::Names(names) => {
+                        let items = v.iter_items()?;
+                        if items.len() != names.len() {
+                            return Err(format!(
+                                "cannot unpack {} values into {} names",
+                                items.len(),
+                                names.len()
+                            ));
+                        }
+                        for (n, item) in names.iter().zip(items) {
+                            self.assign(n, item);
+                        }
+                    }
+                    SetTarget::Attr(obj, attr) => match self.lookup(obj) {
+                        Value::Namespace(ns) => set_ns(&ns, attr, v),
+                        other => {
+                            return Err(format!(
+                                "cannot set attribute on '{}' (only namespace objects are mutable)",
+                                other.type_name()
+                            ));
+                        }
+                    },
+                }
+            }
+            Node::SetBlock { name, body } => {
+                let mut buf = String::new();
+                self.render_nodes(body, &mut buf)?;
+                self.assign(name, Value::Str(buf));
+            }
+            Node::Macro(def) => self.assign(&def.name, Value::Macro(def.clone())),
+            Node::FilterBlock { name, args, body } => {
+                let mut buf = String::new();
+                self.render_nodes(body, &mut buf)?;
+                let args = self.eval_all(args)?;
+                let v = self.apply_filter(name, Value::Str(buf), args, &[])?;
+                out.push_str(&v.to_output());
+            }
+            Node::Block(body) => return self.render_nodes(body, out),
+            Node::Break => return Ok(Flow::Break),
+            Node::Continue => return Ok(Flow::Continue),
+        }
+        Ok(Flow::Normal)
+    }
+
+    /// Returns whether the loop body ran at least once (drives `{% else %}`).
+    fn render_for(
+        &mut self,
+        targets: &[String],
+        source: Vec<Value>,
+        filter: Option<&Expr>,
+        body: &[Node],
+        out: &mut String,
+    ) -> Result<bool, String> {
+        // Loop filters apply before `loop.length` and friends are computed.
+        let items = match filter {
+            None => source,
+            Some(cond) => {
+                let mut kept = Vec::new();
+                for item in source {
+                    self.bind_targets(targets, &item)?;
+                    if self.eval(cond)?.truthy() {
+                        kept.push(item);
+                    }
+                }
+                kept
+            }
+        };
+
+        let len = items.len();
+        for (i, item) in items.iter().enumerate() {
+            self.bind_targets(targets, item)?;
+            let loop_var = Value::Dict(vec![
+                ("index".into(), Value::Int(i as i64 + 1)),
+                ("index0".into(), Value::Int(i as i64)),
+                ("revindex".into(), Value::Int((len - i) as i64)),
+                ("revindex0".into(), Value::Int((len - i - 1) as i64)),
+                ("first".into(), Value::Bool(i == 0)),
+                ("last".into(), Value::Bool(i + 1 == len)),
+                ("length".into(), Value::Int(len as i64)),
+                (
+                    "previtem".into(),
+                    i.checked_sub(1)
+                        .map(|p| items[p].clone())
+                        .unwrap_or_default(),
+                ),
+                (
+                    "nextitem".into(),
+                    items.get(i + 1).cloned().unwrap_or_default(),
+                ),
+            ]);
+            self.assign("loop", loop_var);
+            match self.render_nodes(body, out)? {
+                Flow::Break => break,
+                Flow::Continue | Flow::Normal => {}
+            }
+        }
+        Ok(len > 0)
+    }
+
+    fn bind_targets(&mut self, targets: &[String], item: &Value) -> Result<(), String> {
+        if targets.len() == 1 {
+            self.assign(&targets[0], item.clone());
+            return Ok(());
+        }
+        let parts = item.iter_items()?;
+        if parts.len() != targets.len() {
+            return Err(format!(
+                "cannot unpack {} values into {} loop variables",
+                parts.len(),
+                targets.len()
+            ));
+        }
+        for (t, v) in targets.iter().zip(parts) {
+            self.assign(t, v);
+        }
+        Ok(())
+    }
+
+    fn eval_all(&mut self, exprs: &[Expr]) -> Result<Vec<Value>, String> {
+        exprs.iter().map(|e| self.eval(e)).collect()
+    }
+
+    fn eval_kwargs(&mut self, kwargs: &[(String, Expr)]) -> Result<Vec<(String, Value)>, String> {
+        kwargs
+            .iter()
+            .map(|(k, e)| Ok((k.clone(), self.eval(e)?)))
+            .collect()
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Result<Value, String> {
+        Ok(match expr {
+            Expr::Literal(v) => v.clone(),
+            Expr::Name(n) => self.lookup(n),
+            Expr::List(items) => Value::List(self.eval_all(items)?),
+            Expr::Dict(pairs) => {
+                let mut d = Vec::with_capacity(pairs.len());
+                for (k, v) in pairs {
+                    let k = self.eval(k)?.to_output();
+                    let v = self.eval(v)?;
+                    dict_insert(&mut d, k, v);
+                }
+                Value::Dict(d)
+            }
+            Expr::Attr(obj, attr) => self.eval(obj)?.get_attr(attr),
+            Expr::Index(obj, idx) => {
+                let target = self.eval(obj)?;
+                let idx = self.eval(idx)?;
+                target.get_item(&idx)
+            }
+            Expr::Slice {
+                target,
+                start,
+                stop,
+                step,
+            } => {
+                let target = self.eval(target)?;
+                let mut bound = |e: &Option<Box<Expr>>| -> Result<Option<i64>, String> {
+                    match e {
+                        None => Ok(None),
+                        Some(e) => Ok(self.eval(e)?.as_i64()),
+                    }
+                };
+                let (start, stop, step) = (bound(start)?, bound(stop)?, bound(step)?);
+                slice(&target, start, stop, step.unwrap_or(1))?
+            }
+            Expr::Call {
+                callee,
+                args,
+                kwargs,
+            } => {
+                let args = self.eval_all(args)?;
+                let kwargs = self.eval_kwargs(kwargs)?;
+                self.eval_call(callee, args, kwargs)?
+            }
+            Expr::Filter {
+                target,
+                name,
+                args,
+                kwargs,
+            } => {
+                let target = self.eval(target)?;
+                let args = self.eval_all(args)?;
+                let kwargs = self.eval_kwargs(kwargs)?;
+                self.apply_filter(name, target, args, &kwargs)?
+            }
+            Expr::Test {
+                target,
+                name,
+                args,
+                negated,
+            } => {
+                let target = self.eval(target)?;
+                let args = self.eval_all(args)?;
+                Value::Bool(apply_test(name, &target, &args)? != *negated)
+            }
+            Expr::Not(e) => Value::Bool(!self.eval(e)?.truthy()),
+            Expr::Neg(e) => match self.eval(e)? {
+                Value::Int(i) => Value::Int(-i),
+                Value::Float(f) => Value::Float(-f),
+                other => return Err(format!("bad operand for unary -: '{}'", other.type_name())),
+            },
+            Expr::Binary(BinOp::And, a, b) => {
+                let a = self.eval(a)?;
+                if !a.truthy() { a } else { self.eval(b)? }
+            }
+            Expr::Binary(BinOp::Or, a, b) => {
+                let a = self.eval(a)?;
+                if a.truthy() { a } else { self.eval(b)? }
+            }
+            Expr::Binary(op, a, b) => {
+                let a = self.eval(a)?;
+                let b = self.eval(b)?;
+                binary(*op, &a, &b)?
+            }
+            Expr::Cond {
+                cond,
+                then,
+                otherwise,
+            } => {
+                if self.eval(cond)?.truthy() {
+                    self.eval(then)?
+                } else {
+                    match otherwise {
+                        Some(e) => self.eval(e)?,
+                        None => Value::Undefined,
+                    }
+                }
+            }
+        })
+    }
+
+    fn eval_call(
+        &mut self,
+        callee: &Expr,
+        args: Vec<Value>,
+        kwargs: Vec<(String, Value)>,
+    ) -> Result<Value, String> {
+        if let Expr::Attr(obj, method) = callee {
+            let target = self.eval(obj)?;
+            if let Some(v) = call_method(&target, method, &args, &kwargs)? {
+                return Ok(v);
+            }
+            let f = target.get_attr(method);
+            return self.call_value(f, args, kwargs, method);
+        }
+        if let Expr::Name(name) = callee {
+            let f = self.lookup(name);
+            if f.is_undefined() {
+                return call_global(name, args, kwargs);
+            }
+            return self.call_value(f, args, kwargs, name);
+        }
+        let f = self.eval(callee)?;
+        self.call_value(f, args, kwargs, "<expr>")
+    }
+
+    fn call_value(
+        &mut self,
+        f: Value,
+        args: Vec<Value>,
+        kwargs: Vec<(String, Value)>,
+        name: &str,
+    ) -> Result<Value, String> {
+        match f {
+            Value::Macro(def) => self.call_macro(&def, args, kwargs),
+            Value::Undefined => Err(format!("'{name}' is undefined")),
+            other => Err(format!("'{}' object is not callable", other.type_name())),
+        }
+    }
+
+    fn call_macro(
+        &mut self,
+        def: &MacroDef,
+        args: Vec<Value>,
+        kwargs: Vec<(String, Value)>,
+    ) -> Result<Value, String> {
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(format!("macro '{}' recursed too deeply", def.name));
+        }
+        self.depth += 1;
+        self.push_frame(true);
+        let result = (|| {
+            let mut args = args.into_iter();
+            for (pname, default) in &def.params {
+                let v = match args.next() {
+                    Some(v) => v,
+                    None => match kwargs.iter().find(|(k, _)| k == pname) {
+                        Some((_, v)) => v.clone(),
+                        None => match default {
+                            Some(e) => self.eval(e)?,
+                            None => Value::Undefined,
+                        },
+                    },
+                };
+                self.assign(pname, v);
+            }
+            let mut out = String::new();
+            self.render_nodes(&def.body, &mut out)?;
+            Ok(Value::Str(out))
+        })();
+        self.pop_frame();
+        self.depth -= 1;
+        result
+    }
+
+    fn apply_filter(
+        &mut self,
+        name: &str,
+        v: Value,
+        args: Vec<Value>,
+        kw: &Kwargs,
+    ) -> Result<Value, String> {
+        let arg = |i: usize, key: &str| arg(&args, kw, i, key);
+        Ok(match name {
+            "safe" => v,
+            "e" | "escape" | "forceescape" => Value::Str(html_escape(&v.to_output())),
+            "string" => Value::Str(v.to_output()),
+            "trim" => match arg(0, "chars") {
+                Some(Value::Str(chars)) => Value::Str(
+                    v.to_output()
+                        .trim_matches(|c| chars.contains(c))
+                        .to_string(),
+                ),
+                _ => Value::Str(v.to_output().trim().to_string()),
+            },
+            "upper" => Value::Str(v.to_output().to_uppercase()),
+            "lower" => Value::Str(v.to_output().to_lowercase()),
+            "title" => Value::Str(py_title(&v.to_output())),
+            "capitalize" => Value::Str(py_capitalize(&v.to_output())),
+            "length" | "count" => Value::Int(v.length().unwrap_or(0) as i64),
+            "first" => v.iter_items()?.into_iter().next().unwrap_or_default(),
+            "last" => v.iter_items()?.pop().unwrap_or_default(),
+            "list" => Value::List(v.iter_items()?),
+            "reverse" => match v {
+                Value::Str(s) => Value::Str(s.chars().rev().collect()),
+                other => {
+                    let mut items = other.iter_items()?;
+                    items.reverse();
+                    Value::List(items)
+                }
+            },
+            "join" => {
+                let sep = arg(0, "d").map(|s| s.to_output()).unwrap_or_default();
+                let attr = arg(1, "attribute");
+                let parts: Vec<String> = v
+                    .iter_items()?
+                    .iter()
+                    .map(|item| match &attr {
+                        Some(a) => item.get_attr(&a.to_output()).to_output(),
+                        None => item.to_output(),
+                    })
+                    .collect();
+                Value::Str(parts.join(&sep))
+            }
+            "default" | "d" => {
+                let fallback = arg(0, "default_value").unwrap_or(Value::Str(String::new()));
+                let boolean = arg(1, "boolean").is_some_and(|b| b.truthy());
+                if v.is_undefined() || (boolean && !v.truthy()) {
+                    fallback
+                } else {
+                    v
+                }
+            }
+            "tojson" => {
+                let indent = arg(0, "indent")
+                    .and_then(|i| i.as_i64())
+                    .map(|i| i as usize);
+                Value::Str(v.to_json(indent))
+            }
+            "int" => {
+                let default = arg(0, "default").and_then(|d| d.as_i64()).unwrap_or(0);
+                Value::Int(match &v {
+                    Value::Str(s) => s
+                        .trim()
+                        .parse::<i64>()
+                        .ok()
+                        .or_else(|| s.trim().parse::<f64>().ok().map(|f| f as i64))
+                        .unwrap_or(default),
+                    Value::Float(f) => *f as i64,
+                    other => other.as_i64().unwrap_or(default),
+                })
+            }
+            "float" => {
+                let default = arg(0, "default").and_then(|d| d.as_f64()).unwrap_or(0.0);
+                Value::Float(match &v {
+                    Value::Str(s) => s.trim().parse::<f64>().unwrap_or(default),
+                    other => other.as_f64().unwrap_or(default),
+                })
+            }
+            "abs" => match v {
+                Value::Int(i) => Value::Int(i.abs()),
+                Value::Float(f) => Value::Float(f.abs()),
+                other => return Err(format!("bad operand for abs(): '{}'", other.type_name())),
+            },
+            "round" => {
+                let precision = arg(0, "precision").and_then(|p| p.as_i64()).unwrap_or(0);
+                let method = arg(1, "method").map(|m| m.to_output());
+                let f = v.as_f64().unwrap_or(0.0);
+                let scale = 10f64.powi(precision as i32);
+                let r = match method.as_deref() {
+                    Some("floor") => (f * scale).floor(),
+                    Some("ceil") => (f * scale).ceil(),
+                    _ => (f * scale).round(),
+                };
+                Value::Float(r / scale)
+            }
+            "replace" => {
+                let old = arg(0, "old").map(|s| s.to_output()).unwrap_or_default();
+                let new = arg(1, "new").map(|s| s.to_output()).unwrap_or_default();
+                let s = v.to_output();
+                match arg(2, "count").and_then(|c| c.as_i64()) {
+                    Some(n) => Value::Str(s.replacen(&old, &new, n.max(0) as usize)),
+                    None => Value::Str(s.replace(&old, &new)),
+                }
+            }
+            "indent" => {
+                let width = match arg(0, "width") {
+                    Some(Value::Str(s)) => s,
+                    Some(n) => " ".repeat(n.as_i64().unwrap_or(4).max(0) as usize),
+                    None => "    ".into(),
+                };
+                let first = arg(1, "first").is_some_and(|b| b.truthy());
+                let blank = arg(2, "blank").is_some_and(|b| b.truthy());
+                Value::Str(indent(&v.to_output(), &width, first, blank))
+            }
+            "items" => match v {
+                Value::Dict(d) => Value::List(
+                    d.into_iter()
+                        .map(|(k, v)| Value::List(vec![Value::Str(k), v]))
+                        .collect(),
+                ),
+                Value::Undefined | Value::None => Value::List(Vec::new()),
+                other => return Err(format!("'{}' has no items", other.type_name())),
+            },
+            "dictsort" => match v {
+                Value::Dict(mut d) => {
+                    let by_value = arg(1, "by").is_some_and(|b| b.to_output() == "value");
+                    let reverse = arg(2, "reverse").is_some_and(|b| b.truthy());
+                    d.sort_by(|a, b| {
+                        let ord = if by_value {
+                            compare(&a.1, &b.1).unwrap_or(Ordering::Equal)
+                        } else {
+                            a.0.to_lowercase().cmp(&b.0.to_lowercase())
+                        };
+                        if reverse { ord.reverse() } else { ord }
+                    });
+                    Value::List(
+                        d.into_iter()
+                            .map(|(k, v)| Value::List(vec![Value::Str(k), v]))
+                            .collect(),
+                    )
+                }
+                other => {
+                    return Err(format!(
+                        "dictsort expects a dict, got '{}'",
+                        other.type_name()
+                    ));
+                }
+            },
+            "sort" => {
+                let reverse = arg(0, "reverse").is_some_and(|b| b.truthy());
+                let attr = arg(2, "attribute").map(|a| a.to_output());
+                let mut items = v.iter_items()?;
+                let key = |x: &Value| match &attr {
+                    Some(a) => x.get_attr(a),
+                    None => x.clone(),
+                };
+                let mut err = None;
+                items.sort_by(|a, b| {
+                    compare(&key(a), &key(b)).unwrap_or_else(|e| {
+                        err.get_or_insert(e);
+                        Ordering::Equal
+                    })
+                });
+                if let Some(e) = err {
+                    return Err(e);
+                }
+                if reverse {
+                    items.reverse();
+                }
+                Value::List(items)
+            }
+            "unique" => {
+                let mut seen: Vec<Value> = Vec::new();
+                for item in v.iter_items()? {
+                    if !seen.iter().any(|s| s.loose_eq(&item)) {
+                        seen.push(item);
+                    }
+                }
+                Value::List(seen)
+            }
+            "min" | "max" | "sum" => {
+                let items = v.iter_items()?;
+                if name == "sum" {
+                    let mut acc = arg(1, "start").unwrap_or(Value::Int(0));
+                    for item in &items {
+                        acc = binary(BinOp::Add, &acc, item)?;
+                    }
+                    acc
+                } else {
+                    let mut best: Option<Value> = None;
+                    for item in items {
+                        let replace = match &best {
+                            None => true,
+                            Some(b) => {
+                                let ord = compare(&item, b)?;
+                                if name == "min" {
+                                    ord == Ordering::Less
+                                } else {
+                                    ord == Ordering::Greater
+                                }
+                            }
+                        };
+                        if replace {
+                            best = Some(item);
+                        }
+                    }
+                    best.unwrap_or_default()
+                }
+            }
+            "map" => {
+                let items = v.iter_items()?;
+                if let Some(attr) = kwarg(kw, "attribute") {
+                    let attr = attr.to_output();
+                    let default = kwarg(kw, "default").cloned();
+                    Value::List(
+                        items
+                            .iter()
+                            .map(|i| match (i.get_attr(&attr), &default) {
+                                (Value::Undefined, Some(d)) => d.clone(),
+                                (v, _) => v,
+                            })
+                            .collect(),
+                    )
+                } else {
+                    let filter = args
+                        .first()
+                        .map(|f| f.to_output())
+                        .ok_or_else(|| "map requires a filter name or attribute=".to_string())?;
+                    let rest = args[1..].to_vec();
+                    let mut out = Vec::with_capacity(items.len());
+                    for item in items {
+                        out.push(self.apply_filter(&filter, item, rest.clone(), &[])?);
+                    }
+                    Value::List(out)
+                }
+            }
+            "select" | "reject" => {
+                let want = name == "select";
+                let test = args.first().map(|t| t.to_output());
+                let rest = args.get(1..).unwrap_or_default();
+                let mut out = Vec::new();
+                for item in v.iter_items()? {
+                    let hit = match &test {
+                        Some(t) => apply_test(t, &item, rest)?,
+                        None => item.truthy(),
+                    };
+                    if hit == want {
+                        out.push(item);
+                    }
+                }
+                Value::List(out)
+            }
+            "selectattr" | "rejectattr" => {
+                let want = name == "selectattr";
+                let attr = args
+                    .first()
+                    .map(|a| a.to_output())
+                    .ok_or_else(|| format!("{name} requires an attribute name"))?;
+                let test = args.get(1).map(|t| t.to_output());
+                let rest = args.get(2..).unwrap_or_default();
+                let mut out = Vec::new();
+                for item in v.iter_items()? {
+                    let field = item.get_attr(&attr);
+                    let hit = match &test {
+                        Some(t) => apply_test(t, &field, rest)?,
+                        None => field.truthy(),
+                    };
+                    if hit == want {
+                        out.push(item);
+                    }
+                }
+                Value::List(out)
+            }
+            "format" => Value::Str(py_percent_format(&v.to_output(), &args)?),
+            "wordcount" => Value::Int(v.to_output().split_whitespace().count() as i64),
+            _ => return Err(format!("unknown filter '{name}'")),
+        })
+    }
+}
+
+fn set_ns(ns: &Rc<RefCell<Vec<(String, Value)>>>, key: &str, v: Value) {
+    dict_insert(&mut ns.borrow_mut(), key.to_string(), v);
+}
+
+fn dict_insert(d: &mut Vec<(String, Value)>, key: String, v: Value) {
+    match d.iter_mut().find(|(k, _)| *k == key) {
+        Some(slot) => slot.1 = v,
+        None => d.push((key, v)),
+    }
+}
+
+fn kwarg<'a>(kw: &'a Kwargs, key: &str) -> Option<&'a Value> {
+    kw.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+}
+
+/// Positional-or-keyword argument lookup.
+fn arg(args: &[Value], kw: &Kwargs, i: usize, key: &str) -> Option<Value> {
+    args.get(i).or_else(|| kwarg(kw, key)).cloned()
+}
+
+fn call_global(name: &str, args: Vec<Value>, kw: Vec<(String, Value)>) -> Result<Value, String> {
+    match name {
+        "raise_exception" => Err(format!(
+            "template raised: {}",
+            args.first().map(Value::to_output).unwrap_or_default()
+        )),
+        "range" => {
+            let n: Vec<i64> = args.iter().filter_map(Value::as_i64).collect();
+            let (start, stop, step) = match n.as_slice() {
+                [stop] => (0, *stop, 1),
+                [start, stop] => (*start, *stop, 1),
+                [start, stop, step] if *step != 0 => (*start, *stop, *step),
+                _ => return Err("range() expects 1-3 integer arguments".into()),
+            };
+            let mut out = Vec::new();
+            let mut i = start;
+            while (step > 0 && i < stop) || (step < 0 && i > stop) {
+                out.push(Value::Int(i));
+                i += step;
+            }
+            Ok(Value::List(out))
+        }
+        "namespace" => {
+            let mut fields = match args.into_iter().next() {
+                Some(Value::Dict(d)) => d,
+                _ => Vec::new(),
+            };
+            for (k, v) in kw {
+                dict_insert(&mut fields, k, v);
+            }
+            Ok(Value::Namespace(Rc::new(RefCell::new(fields))))
+        }
+        "dict" => {
+            let mut fields = match args.into_iter().next() {
+                Some(Value::Dict(d)) => d,
+                _ => Vec::new(),
+            };
+            for (k, v) in kw {
+                dict_insert(&mut fields, k, v);
+            }
+            Ok(Value::Dict(fields))
+        }
+        "strftime_now" => {
+            let fmt = args.first().map(Value::to_output).unwrap_or_default();
+            Ok(Value::Str(strftime_now(&fmt)))
+        }
+        _ => Err(format!("'{name}' is undefined")),
+    }
+}
+
+/// Python `str`/`dict`/`list` methods. `Ok(None)` means "no such method".
+fn call_method(
+    target: &Value,
+    method: &str,
+    args: &[Value],
+    kw: &Kwargs,
+) -> Result<Option<Value>, String> {
+    let v = match target {
+        Value::Str(s) => {
+            let chars = args.first().map(Value::to_output);
+            match method {
+                "strip" => Value::Str(match &chars {
+                    Some(c) => s.trim_matches(|x| c.contains(x)).to_string(),
+                    None => s.trim().to_string(),
+                }),
+                "lstrip" => Value::Str(match &chars {
+                    Some(c) => s.trim_start_matches(|x| c.contains(x)).to_string(),
+                    None => s.trim_start().to_string(),
+                }),
+                "rstrip" => Value::Str(match &chars {
+                    Some(c) => s.trim_end_matches(|x| c.contains(x)).to_string(),
+                    None => s.trim_end().to_string(),
+                }),
+                "upper" => Value::Str(s.to_uppercase()),
+                "lower" => Value::Str(s.to_lowercase()),
+                "title" => Value::Str(py_title(s)),
+                "capitalize" => Value::Str(py_capitalize(s)),
+                "startswith" | "endswith" => {
+                    let test = |p: &str| {
+                        if method == "startswith" {
+                            s.starts_with(p)
+                        } else {
+                            s.ends_with(p)
+                        }
+                    };
+                    Value::Bool(match args.first() {
+                        Some(Value::List(ps)) => ps.iter().any(|p| test(&p.to_output())),
+                        Some(p) => test(&p.to_output()),
+                        None => return Err(format!("{method}() takes one argument")),
+                    })
+                }
+                "split" => {
+                    let sep = arg(args, kw, 0, "sep").filter(|v| !v.is_none());
+                    let max = arg(args, kw, 1, "maxsplit")
+                        .and_then(|m| m.as_i64())
+                        .filter(|m| *m >= 0);
+                    let parts: Vec<Value> = match (sep, max) {
+                        (Some(sep), Some(m)) => s
+                            .splitn(m as usize + 1, sep.to_output().as_str())
+                            .map(Value::from)
+                            .collect(),
+                        (Some(sep), None) => {
+                            s.split(sep.to_output().as_str()).map(Value::from).collect()
+                        }
+                        (None, _) => s.split_whitespace().map(Value::from).collect(),
+                    };
+                    Value::List(parts)
+                }
+                "replace" => {
+                    let old = args.first().map(Value::to_output).unwrap_or_default();
+                    let new = args.get(1).map(Value::to_output).unwrap_or_default();
+                    match args.get(2).and_then(Value::as_i64) {
+                        Some(n) if n >= 0 => Value::Str(s.replacen(&old, &new, n as usize)),
+                        _ => Value::Str(s.replace(&old, &new)),
+                    }
+                }
+                "find" | "rfind" => {
+                    let needle = args.first().map(Value::to_output).unwrap_or_default();
+                    let pos = if method == "find" {
+                        s.find(&needle)
+                    } else {
+                        s.rfind(&needle)
+                    };
+                    Value::Int(pos.map(|b| s[..b].chars().count() as i64).unwrap_or(-1))
+                }
+                "count" => {
+                    let needle = args.first().map(Value::to_output).unwrap_or_default();
+                    Value::Int(s.matches(&needle).count() as i64)
+                }
+                "join" => {
+                    let parts: Vec<String> = args
+                        .first()
+                        .map(Value::iter_items)
+                        .transpose()?
+                        .unwrap_or_default()
+                        .iter()
+                        .map(Value::to_output)
+                        .collect();
+                    Value::Str(parts.join(s))
+                }
+                "format" => {
+                    let mut out = s.clone();
+                    for a in args {
+                        out = out.replacen("{}", &a.to_output(), 1);
+                    }
+                    for (k, v) in kw {
+                        out = out.replace(&format!("{{{k}}}"), &v.to_output());
+                    }
+                    Value::Str(out)
+                }
+                _ => return Ok(None),
+            }
+        }
+        Value::Dict(d) => match method {
+            "items" => Value::List(
+                d.iter()
+                    .map(|(k, v)| Value::List(vec![Value::Str(k.clone()), v.clone()]))
+                    .collect(),
+            ),
+            "keys" => Value::List(d.iter().map(|(k, _)| Value::Str(k.clone())).collect()),
+            "values" => Value::List(d.iter().map(|(_, v)| v.clone()).collect()),
+            "get" => {
+                let key = args.first().map(Value::to_output).unwrap_or_default();
+                match target.get_attr(&key) {
+                    Value::Undefined => args.get(1).cloned().unwrap_or(Value::None),
+                    v => v,
+                }
+            }
+            _ => return Ok(None),
+        },
+        Value::List(l) => match method {
+            "index" => {
+                let needle = args.first().cloned().unwrap_or_default();
+                match l.iter().position(|x| x.loose_eq(&needle)) {
+                    Some(i) => Value::Int(i as i64),
+                    None => return Err("value is not in list".into()),
+                }
+            }
+            "count" => {
+                let needle = args.first().cloned().unwrap_or_default();
+                Value::Int(l.iter().filter(|x| x.loose_eq(&needle)).count() as i64)
+            }
+            _ => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(v))
+}
+
+fn apply_test(name: &str, v: &Value, args: &[Value]) -> Result<bool, String> {
+    let other = args.first();
+    Ok(match name {
+        "defined" => !v.is_undefined(),
+        "undefined" => v.is_undefined(),
+        "none" => v.is_none(),
+        "string" => matches!(v, Value::Str(_)),
+        "number" => matches!(v, Value::Int(_) | Value::Float(_)),
+        "integer" => matches!(v, Value::Int(_)),
+        "float" => matches!(v, Value::Float(_)),
+        "boolean" => matches!(v, Value::Bool(_)),
+        "true" => matches!(v, Value::Bool(true)),
+        "false" => matches!(v, Value::Bool(false)),
+        "mapping" => matches!(v, Value::Dict(_) | Value::Namespace(_)),
+        "sequence" => matches!(v, Value::List(_) | Value::Str(_) | Value::Dict(_)),
+        "iterable" => matches!(v, Value::List(_) | Value::Str(_) | Value::Dict(_)),
+        "callable" => matches!(v, Value::Macro(_)),
+        "odd" => v.as_i64().is_some_and(|i| i % 2 != 0),
+        "even" => v.as_i64().is_some_and(|i| i % 2 == 0),
+        "divisibleby" => match (v.as_i64(), other.and_then(Value::as_i64)) {
+            (Some(a), Some(b)) if b != 0 => a % b == 0,
+            _ => false,
+        },
+        "equalto" | "eq" | "==" | "sameas" => other.is_some_and(|o| v.loose_eq(o)),
+        "ne" | "!=" => !other.is_some_and(|o| v.loose_eq(o)),
+        "lt" | "<" => other.is_some_and(|o| compare(v, o) == Ok(Ordering::Less)),
+        "gt" | ">" => other.is_some_and(|o| compare(v, o) == Ok(Ordering::Greater)),
+        "le" | "<=" => other.is_some_and(|o| compare(v, o).is_ok_and(|c| c != Ordering::Greater)),
+        "ge" | ">=" => other.is_some_and(|o| compare(v, o).is_ok_and(|c| c != Ordering::Less)),
+        "in" => match other {
+            Some(container) => contains(container, v)?,
+            None => false,
+        },
+        "lower" => v.as_str().is_some_and(|s| s.to_lowercase() == s),
+        "upper" => v.as_str().is_some_and(|s| s.to_uppercase() == s),
+        _ => return Err(format!("unknown test '{name}'")),
+    })
+}
+
+fn contains(container: &Value, needle: &Value) -> Result<bool, String> {
+    Ok(match container {
+        Value::Str(s) => s.contains(&needle.to_output()),
+        Value::List(l) => l.iter().any(|x| x.loose_eq(needle)),
+        Value::Dict(d) => d.iter().any(|(k, _)| Some(k.as_str()) == needle.as_str()),
+        Value::Namespace(ns) => ns
+            .borrow()
+            .iter()
+            .any(|(k, _)| Some(k.as_str()) == needle.as_str()),
+        Value::Undefined | Value::None => false,
+        other => {
+            return Err(format!(
+                "argument of type '{}' is not iterable",
+                other.type_name()
+            ));
+        }
+    })
+}
+
+fn compare(a: &Value, b: &Value) -> Result<Ordering, String> {
+    match (a, b) {
+        (Value::Str(x), Value::Str(y)) => Ok(x.cmp(y)),
+        (Value::List(x), Value::List(y)) => {
+            for (p, q) in x.iter().zip(y) {
+                match compare(p, q)? {
+                    Ordering::Equal => {}
+                    ord => return Ok(ord),
+                }
+            }
+            Ok(x.len().cmp(&y.len()))
+        }
+        _ => match (a.as_f64(), b.as_f64()) {
+            (Some(x), Some(y)) => x
+                .partial_cmp(&y)
+                .ok_or_else(|| "cannot compare NaN".to_string()),
+            _ => Err(format!(
+                "'<' not supported between '{}' and '{}'",
+                a.type_name(),
+                b.type_name()
+            )),
+        },
+    }
+}
+
+fn binary(op: BinOp, a: &Value, b: &Value) -> Result<Value, String> {
+    let unsupported = || {
+        format!(
+            "unsupported operand types for {op:?}: '{}' and '{}'",
+            a.type_name(),
+            b.type_name()
+        )
+    };
+    let ints = match (a, b) {
+        (Value::Int(x), Value::Int(y)) => Some((*x, *y)),
+        _ => None,
+    };
+    let floats = match (a, b) {
+        (Value::Str(_), _) | (_, Value::Str(_)) => None,
+        _ => a.as_f64().zip(b.as_f64()),
+    };
+
+    Ok(match op {
+        BinOp::Eq => Value::Bool(a.loose_eq(b)),
+        BinOp::Ne => Value::Bool(!a.loose_eq(b)),
+        BinOp::Lt => Value::Bool(compare(a, b)? == Ordering::Less),
+        BinOp::Le => Value::Bool(compare(a, b)? != Ordering::Greater),
+        BinOp::Gt => Value::Bool(compare(a, b)? == Ordering::Greater),
+        BinOp::Ge => Value::Bool(compare(a, b)? != Ordering::Less),
+        BinOp::In => Value::Bool(contains(b, a)?),
+        BinOp::NotIn => Value::Bool(!contains(b, a)?),
+        BinOp::Concat => Value::Str(a.to_output() + &b.to_output()),
+        BinOp::Add => match (a, b) {
+            (Value::Str(x), Value::Str(y)) => Value::Str(format!("{x}{y}")),
+            (Value::List(x), Value::List(y)) => Value::List(x.iter().chain(y).cloned().collect()),
+            _ => match (ints, floats) {
+                (Some((x, y)), _) => Value::Int(x.wrapping_add(y)),
+                (_, Some((x, y))) => Value::Float(x + y),
+                _ => return Err(unsupported()),
+            },
+        },
+        BinOp::Sub => match (ints, floats) {
+            (Some((x, y)), _) => Value::Int(x.wrapping_sub(y)),
+            (_, Some((x, y))) => Value::Float(x - y),
+            _ => return Err(unsupported()),
+        },
+        BinOp::Mul => match (a, b) {
+            (Value::Str(s), Value::Int(n)) | (Value::Int(n), Value::Str(s)) => {
+                Value::Str(s.repeat((*n).max(0) as usize))
+            }
+            (Value::List(l), Value::Int(n)) | (Value::Int(n), Value::List(l)) => Value::List(
+                l.iter()
+                    .cloned()
+                    .cycle()
+                    .take(l.len() * (*n).max(0) as usize)
+                    .collect(),
+            ),
+            _ => match (ints, floats) {
+                (Some((x, y)), _) => Value::Int(x.wrapping_mul(y)),
+                (_, Some((x, y))) => Value::Float(x * y),
+                _ => return Err(unsupported()),
+            },
+        },
+        BinOp::Div => match floats {
+            Some((_, 0.0)) => return Err("division by zero".into()),
+            Some((x, y)) => Value::Float(x / y),
+            None => return Err(unsupported()),
+        },
+        BinOp::FloorDiv => match (ints, floats) {
+            (Some((_, 0)), _) => return Err("integer division by zero".into()),
+            (Some((x, y)), _) => {
+                Value::Int(x.div_euclid(y) - i64::from(y < 0 && x.rem_euclid(y) != 0))
+            }
+            (_, Some((_, 0.0))) => return Err("division by zero".into()),
+            (_, Some((x, y))) => Value::Float((x / y).floor()),
+            _ => return Err(unsupported()),
+        },
+        BinOp::Mod => match (a, ints, floats) {
+            (Value::Str(fmt), _, _) => {
+                let args = match b {
+                    Value::List(l) => l.clone(),
+                    other => vec![other.clone()],
+                };
+                Value::Str(py_percent_format(fmt, &args)?)
+            }
+            (_, Some((_, 0)), _) => return Err("integer modulo by zero".into()),
+            // Python's remainder takes the sign of the divisor.
+            (_, Some((x, y)), _) => Value::Int(((x % y) + y) % y),
+            (_, _, Some((_, 0.0))) => return Err("modulo by zero".into()),
+            (_, _, Some((x, y))) => Value::Float(x - y * (x / y).floor()),
+            _ => return Err(unsupported()),
+        },
+        BinOp::Pow => match (ints, floats) {
+            (Some((x, y)), _) if (0..=u32::MAX as i64).contains(&y) => {
+                Value::Int(x.wrapping_pow(y as u32))
+            }
+            (_, Some((x, y))) => Value::Float(x.powf(y)),
+            _ => return Err(unsupported()),
+        },
+        BinOp::And | BinOp::Or => unreachable!("short-circuit ops are evaluated lazily"),
+    })
+}
+
+/// Python slice semantics (`xs[1:]`, `xs[::-1]`, `s[:-1]`).
+fn slice(
+    target: &Value,
+    start: Option<i64>,
+    stop: Option<i64>,
+    step: i64,
+) -> Result<Value, String> {
+    if step == 0 {
+        return Err("slice step cannot be zero".into());
+    }
+    let items: Vec<Value> = match target {
+        Value::Str(s) => s.chars().map(|c| Value::Str(c.to_string())).collect(),
+        Value::Undefined | Value::None => return Ok(Value::Undefined),
+        other => other.iter_items()?,
+    };
+    let len = items.len() as i64;
+    let norm = |i: i64, lo: i64, hi: i64| {
+        let i = if i < 0 { i + len } else { i };
+        i.clamp(lo, hi)
+    };
+
+    let mut picked = Vec::new();
+    if step > 0 {
+        let mut i = start.map_or(0, |s| norm(s, 0, len));
+        let end = stop.map_or(len, |s| norm(s, 0, len));
+        while i < end {
+            picked.push(items[i as usize].clone());
+            i += step;
+        }
+    } else {
+        let mut i = start.map_or(len - 1, |s| norm(s, -1, len - 1));
+        let end = stop.map_or(-1, |s| norm(s, -1, len - 1));
+        while i > end {
+            if let Some(idx) = py_index(i, items.len()) {
+                picked.push(items[idx].clone());
+            }
+            i += step;
+        }
+    }
+
+    Ok(match target {
+        Value::Str(_) => Value::Str(picked.iter().map(Value::to_output).collect()),
+        _ => Value::List(picked),
+    })
+}
+
+fn py_title(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut prev_alpha = false;
+    for c in s.chars() {
+        if prev_alpha {
+            out.extend(c.to_lowercase());
+        } else {
+            out.extend(c.to_uppercase());
+        }
+        prev_alpha = c.is_alphabetic();
+    }
+    out
+}
+
+fn py_capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&#34;")
+        .replace('\'', "&#39;")
+}
+
+fn indent(s: &str, pad: &str, first: bool, blank: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for (i, line) in s.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let wants_pad = (i > 0 || first) && (blank || !line.trim().is_empty());
+        if wants_pad {
+            out.push_str(pad);
+        }
+        out.push_str(line);
+    }
+    out
+}
+
+/// Minimal `%`-formatting (`%s`, `%d`, `%r`, `%%`) for `"..." % x` and `|format`.
+fn py_percent_format(fmt: &str, args: &[Value]) -> Result<String, String> {
+    let mut out = String::with_capacity(fmt.len());
+    let mut args = args.iter();
+    let mut chars = fmt.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('%') => out.push('%'),
+            Some(spec @ ('s' | 'd' | 'r' | 'i')) => {
+                let v = args
+                    .next()
+                    .ok_or_else(|| "not enough arguments for format string".to_string())?;
+                match spec {
+                    'r' => out.push_str(&v.to_repr()),
+                    'd' | 'i' => out.push_str(
+                        &v.as_f64()
+                            .map(|f| (f as i64).to_string())
+                            .unwrap_or_default(),
+                    ),
+                    _ => out.push_str(&v.to_output()),
+                }
+            }
+            other => {
+                return Err(format!(
+                    "unsupported format specifier %{}",
+                    other.unwrap_or(' ')
+                ));
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// `strftime_now(fmt)` in UTC: the common directives templates use for the date line.
+fn strftime_now(fmt: &str) -> String {
+    const MONTHS: [&str; 12] = [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ];
+    const DAYS: [&str; 7] = [
+        "Sunday",
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+    ];
+
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let days = secs.div_euclid(86_400);
+    let tod = secs.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+    let weekday = (days + 4).rem_euclid(7) as usize; // 1970-01-01 was a Thursday
+    let (hour, minute, second) = (tod / 3600, (tod / 60) % 60, tod % 60);
+
+    let mut out = String::new();
+    let mut chars = fmt.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        // `%-d` (glibc extension) drops zero padding.
+        let unpadded = chars.next_if_eq(&'-').is_some();
+        let num = |n: i64| {
+            if unpadded {
+                n.to_string()
+            } else {
+                format!("{n:02}")
+            }
+        };
+        match chars.next() {
+            Some('d') => out.push_str(&num(day as i64)),
+            Some('m') => out.push_str(&num(month as i64)),
+            Some('Y') => out.push_str(&year.to_string()),
+            Some('y') => out.push_str(&num(year.rem_euclid(100))),
+            Some('B') => out.push_str(MONTHS[month as usize - 1]),
+            Some('b') => out.push_str(&MONTHS[month as usize - 1][..3]),
+            Some('A') => out.push_str(DAYS[weekday]),
+            Some('a') => out.push_str(&DAYS[weekday][..3]),
+            Some('H') => out.push_str(&num(hour)),
+            Some('M') => out.push_str(&num(minute)),
+            Some('S') => out.push_str(&num(second)),
+            Some('%') => out.push('%'),
+            Some(other) => {
+                out.push('%');
+                out.push(other);
+            }
+            None => out.push('%'),
+        }
+    }
+    out
+}
+
+/// Days since 1970-01-01 → (year, month, day) in the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,446 @@
+//! Tokenizer for the Jinja2 subset used by HF chat templates.
+//!
+//! Produces a flat list of template pieces (text / `{{ }}` / `{% %}`) with
+//! whitespace control already applied, HF-style (`trim_blocks` and
+//! `lstrip_blocks` both on, `-` and `+` modifiers honored).
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Tok {
+    Name(String),
+    Str(String),
+    Int(i64),
+    Float(f64),
+    /// Operators and punctuation (`==`, `//`, `(`, `|`, …).
+    Op(&'static str),
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum Piece {
+    Text(String),
+    /// `{{ … }}`
+    Expr(Vec<Tok>),
+    /// `{% … %}`
+    Stmt(Vec<Tok>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TagKind {
+    Expr,
+    Stmt,
+    Comment,
+}
+
+/// Operators, longest first so greedy matching works.
+const OPS: &[&str] = &[
+    "//", "**", "==", "!=", "<=", ">=", "+", "-", "*", "/", "%", "~", "<", ">", "=", "(", ")", "[",
+    "]", "{", "}", ",", ".", ":", "|",
+];
+
+struct RawTag {
+    kind: TagKind,
+    toks: Vec<Tok>,
+    /// `{%-` / `{{-` / `{#-`
+    strip_before: bool,
+    /// `{%+` disables lstrip_blocks for this tag.
+    keep_before: bool,
+    /// `-%}` / `-}}` / `-#}`
+    strip_after: bool,
+    /// `+%}` disables trim_blocks for this tag.
+    keep_after: bool,
+}
+
+enum RawPiece {
+    Text(String),
+    Tag(RawTag),
+}
+
+pub(super) fn tokenize(src: &str) -> Result<Vec<Piece>, String> {
+    let raw = split_tags(src)?;
+    Ok(apply_whitespace_control(raw))
+}
+
+fn split_tags(src: &str) -> Result<Vec<RawPiece>, String> {
+    let bytes = src.as_bytes();
+    let mut out = Vec::new();
+    let mut text_start = 0usize;
+    let mut i = 0usize;
+
+    while i + 1 < bytes.len() {
+        let kind = match (bytes[i], bytes[i + 1]) {
+            (b'{', b'{') => TagKind::Expr,
+            (b'{', b'%') => TagKind::Stmt,
+            (b'{', b'#') => TagKind::Comment,
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+
+        if i > text_start {
+            out.push(RawPiece::Text(src[text_start..i].to_string()));
+        }
+
+        let mut pos = i + 2;
+        let (strip_before, keep_before) = match bytes.get(pos) {
+            Some(b'-') => {
+                pos += 1;
+                (true, false)
+            }
+            Some(b'+') => {
+                pos += 1;
+                (false, true)
+            }
+            _ => (false, false),
+        };
+
+        let tag = if kind == TagKind::Comment {
+            let end = src[pos..]
+                .find("#}")
+                .map(|e| pos + e)
+                .ok_or_else(|| format!("unterminated comment at byte {i}"))?;
+            let strip_after = end > pos && bytes[end - 1] == b'-';
+            let keep_after = end > pos && bytes[end - 1] == b'+';
+            pos = end + 2;
+            RawTag {
+                kind,
+                toks: Vec::new(),
+                strip_before,
+                keep_before,
+                strip_after,
+                keep_after,
+            }
+        } else {
+            let close = if kind == TagKind::Expr { b'}' } else { b'%' };
+            let (toks, end, strip_after, keep_after) = lex_tag_body(src, pos, close)?;
+            pos = end;
+            RawTag {
+                kind,
+                toks,
+                strip_before,
+                keep_before,
+                strip_after,
+                keep_after,
+            }
+        };
+
+        // `{% raw %}…{% endraw %}` is passed through verbatim.
+        if tag.kind == TagKind::Stmt && matches!(tag.toks.as_slice(), [Tok::Name(n)] if n == "raw")
+        {
+            let (body, after) = take_raw_block(src, pos)?;
+            out.push(RawPiece::Tag(RawTag {
+                kind: TagKind::Comment,
+                toks: Vec::new(),
+                ..tag
+            }));
+            out.push(RawPiece::Text(body));
+            pos = after;
+        } else {
+            out.push(RawPiece::Tag(tag));
+        }
+
+        i = pos;
+        text_start = pos;
+    }
+
+    if text_start < src.len() {
+        out.push(RawPiece::Text(src[text_start..].to_string()));
+    }
+    Ok(out)
+}
+
+/// Find `{% endraw %}` starting at `from`; returns (body, index after the end tag).
+fn take_raw_block(src: &str, from: usize) -> Result<(String, usize), String> {
+    let mut search = from;
+    while let Some(rel) = src[search..].find("{%") {
+        let start = search + rel;
+        let inner = src[start + 2..].trim_start_matches(['-', '+']).trim_start();
+        if let Some(rest) = inner.strip_prefix("endraw") {
+            let close = rest
+                .find("%}")
+                .ok_or_else(|| "unterminated endraw tag".to_string())?;
+            let end = src.len() - rest.len() + close + 2;
+            return Ok((src[from..start].to_string(), end));
+        }
+        search = start + 2;
+    }
+    Err("missing {% endraw %}".into())
+}
+
+/// Lex expression tokens until the closing `}}` / `%}`.
+/// Returns (tokens, index after the closer, strip_after, keep_after).
+fn lex_tag_body(
+    src: &str,
+    mut pos: usize,
+    close: u8,
+) -> Result<(Vec<Tok>, usize, bool, bool), String> {
+    let bytes = src.as_bytes();
+    let mut toks = Vec::new();
+
+    loop {
+        while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        if pos >= bytes.len() {
+            return Err("unterminated tag".into());
+        }
+
+        // Closers, optionally preceded by a whitespace modifier.
+        let at_close = |p: usize| p + 1 < bytes.len() && bytes[p] == close && bytes[p + 1] == b'}';
+        if at_close(pos) {
+            return Ok((toks, pos + 2, false, false));
+        }
+        if (bytes[pos] == b'-' || bytes[pos] == b'+') && at_close(pos + 1) {
+            return Ok((toks, pos + 3, bytes[pos] == b'-', bytes[pos] == b'+'));
+        }
+
+        let c = bytes[pos];
+        if c == b'\'' || c == b'"' {
+            let (s, next) = lex_string(src, pos)?;
+            toks.push(Tok::Str(s));
+            pos = next;
+        } else if c.is_ascii_digit() {
+            let start = pos;
+            while pos < bytes.len() && (bytes[pos].is_ascii_digit() || bytes[pos] == b'_') {
+                pos += 1;
+            }
+            let is_float =
+                pos + 1 < bytes.len() && bytes[pos] == b'.' && bytes[pos + 1].is_ascii_digit();
+            if is_float {
+                pos += 1;
+                while pos < bytes.len() && bytes[pos].is_ascii_digit() {
+                    pos += 1;
+                }
+                let text = src[start..pos].replace('_', "");
+                let v = text
+                    .parse::<f64>()
+                    .map_err(|e| format!("bad float literal {text}: {e}"))?;
+                toks.push(Tok::Float(v));
+            } else {
+                let text = src[start..pos].replace('_', "");
+                let v = text
+                    .parse::<i64>()
+                    .map_err(|e| format!("bad int literal {text}: {e}"))?;
+                toks.push(Tok::Int(v));
+            }
+        } else if c == b'_' || c.is_ascii_alphabetic() {
+            let start = pos;
+            while pos < bytes.len() && (bytes[pos] == b'_' || bytes[pos].is_ascii_alphanumeric()) {
+                pos += 1;
+            }
+            toks.push(Tok::Name(src[start..pos].to_string()));
+        } else {
+            let rest = &src[pos..];
+            let op = OPS
+                .iter()
+                .find(|op| rest.starts_with(**op))
+                .ok_or_else(|| {
+                    let ch = rest.chars().next().unwrap_or('?');
+                    format!("unexpected character {ch:?} in tag")
+                })?;
+            toks.push(Tok::Op(op));
+            pos += op.len();
+        }
+    }
+}
+
+fn lex_string(src: &str, start: usize) -> Result<(String, usize), String> {
+    let quote = src.as_bytes()[start] as char;
+    let mut out = String::new();
+    let mut chars = src[start + 1..].char_indices();
+
+    while let Some((off, ch)) = chars.next() {
+        match ch {
+            c if c == quote => return Ok((out, start + 1 + off + 1)),
+            '\\' => {
+                let (_, esc) = chars
+                    .next()
+                    .ok_or_else(|| "unterminated string escape".to_string())?;
+                match esc {
+                    'n' => out.push('\n'),
+                    't' => out.push('\t'),
+                    'r' => out.push('\r'),
+                    '0' => out.push('\0'),
+                    '\\' => out.push('\\'),
+                    '\'' => out.push('\''),
+                    '"' => out.push('"'),
+                    'u' => {
+                        let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                        let cp = u32::from_str_radix(&hex, 16)
+                            .map_err(|_| format!("bad \\u escape: {hex}"))?;
+                        out.push(char::from_u32(cp).unwrap_or('\u{FFFD}'));
+                    }
+                    other => {
+                        out.push('\\');
+                        out.push(other);
+                    }
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    Err("unterminated string literal".into())
+}
+
+fn apply_whitespace_control(raw: Vec<RawPiece>) -> Vec<Piece> {
+    let mut texts: Vec<Option<String>> = Vec::with_capacity(raw.len());
+    let mut tags: Vec<Option<RawTag>> = Vec::with_capacity(raw.len());
+    for p in raw {
+        match p {
+            RawPiece::Text(t) => {
+                texts.push(Some(t));
+                tags.push(None);
+            }
+            RawPiece::Tag(t) => {
+                texts.push(None);
+                tags.push(Some(t));
+            }
+        }
+    }
+
+    // Left side first: lstrip_blocks must see the source as written, before
+    // trim_blocks removes the newline that puts a tag at the start of a line.
+    for i in 1..tags.len() {
+        let Some(tag) = tags[i].as_ref() else {
+            continue;
+        };
+        let Some(prev) = texts[i - 1].as_mut() else {
+            continue;
+        };
+        if tag.strip_before {
+            let keep = prev.trim_end().len();
+            prev.truncate(keep);
+        } else if tag.kind != TagKind::Expr && !tag.keep_before {
+            // lstrip_blocks: drop spaces/tabs between line start and the tag.
+            let trimmed = prev.trim_end_matches([' ', '\t']);
+            let at_line_start = (trimmed.is_empty() && i == 1) || trimmed.ends_with('\n');
+            if at_line_start {
+                let keep = trimmed.len();
+                prev.truncate(keep);
+            }
+        }
+    }
+
+    for (i, tag) in tags.iter().enumerate() {
+        let Some(tag) = tag.as_ref() else {
+            continue;
+        };
+        let Some(next) = texts.get_mut(i + 1).and_then(Option::as_mut) else {
+            continue;
+        };
+        if tag.strip_after {
+            *next = next.trim_start().to_string();
+        } else if tag.kind != TagKind::Expr && !tag.keep_after {
+            // trim_blocks: drop the first newline after a block tag.
+            if let Some(rest) = next.strip_prefix("\r\n") {
+                *next = rest.to_string();
+            } else if let Some(rest) = next.strip_prefix('\n') {
+                *next = rest.to_string();
+            }
+        }
+    }
+
+    let mut out = Vec::with_capacity(tags.len());
+    for (text, tag) in texts.into_iter().zip(tags) {
+        match (text, tag) {
+            (Some(t), _) if !t.is_empty() => out.push(Piece::Text(t)),
+            (_, Some(tag)) => match tag.kind {
+                TagKind::Expr => out.push(Piece::Expr(tag.toks)),
+                TagKind::Stmt => out.push(Piece::Stmt(tag.toks)),
+                TagKind::Comment => {}
+            },
+            _ => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn err(src: &str) -> String {
+        tokenize(src).unwrap_err()
+    }
+
+    fn texts(src: &str) -> Vec<String> {
+        tokenize(src)
+            .unwrap()
+            .into_iter()
+            .filter_map(|p| match p {
+                Piece::Text(t) => Some(t),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lexes_tag_tokens() {
+        let pieces = tokenize("{{ a.b(1, 2.5, 'x') | upper }}").unwrap();
+        let [Piece::Expr(toks)] = pieces.as_slice() else {
+            panic!("{pieces:?}");
+        };
+        let name = |n: &str| Tok::Name(n.into());
+        assert_eq!(
+            toks,
+            &[
+                name("a"),
+                Tok::Op("."),
+                name("b"),
+                Tok::Op("("),
+                Tok::Int(1),
+                Tok::Op(","),
+                Tok::Float(2.5),
+                Tok::Op(","),
+                Tok::Str("x".into()),
+                Tok::Op(")"),
+                Tok::Op("|"),
+                name("upper"),
+            ]
+        );
+    }
+
+    #[test]
+    fn string_escapes() {
+        let pieces = tokenize(r#"{{ "a\"b\né" }}"#).unwrap();
+        let [Piece::Expr(toks)] = pieces.as_slice() else {
+            panic!("{pieces:?}");
+        };
+        assert_eq!(toks, &[Tok::Str("a\"b\n\u{e9}".into())]);
+    }
+
+    #[test]
+    fn unterminated_tags_are_errors() {
+        assert_eq!(err("Hi {{ name"), "unterminated tag");
+        assert_eq!(err("{% if x"), "unterminated tag");
+        assert_eq!(err("{{ x }"), "unterminated tag");
+        assert_eq!(err("ok {# note"), "unterminated comment at byte 3");
+        assert_eq!(err("{{ 'abc }}"), "unterminated string literal");
+        assert_eq!(err("{% raw %}{{ x }}"), "missing {% endraw %}");
+    }
+
+    #[test]
+    fn bad_characters_and_literals_are_errors() {
+        assert_eq!(err("{{ a $ b }}"), "unexpected character '$' in tag");
+        assert!(err("{{ 99999999999999999999 }}").starts_with("bad int literal"));
+        assert!(err(r"{{ '\uzz' }}").starts_with("bad \\u escape"));
+    }
+
+    #[test]
+    fn raw_block_is_verbatim() {
+        assert_eq!(texts("{% raw %}{{ x }}{% endraw %}"), ["{{ x }}"]);
+    }
+
+    #[test]
+    fn whitespace_modifiers() {
+        assert_eq!(texts("a  {%- if x -%}  b\n{% endif %}"), ["a", "b\n"]);
+        assert_eq!(texts("x\n  {%+ if y +%}\nz"), ["x\n  ", "\nz"]);
+    }
+
+    #[test]
+    fn trim_and_lstrip_blocks() {
+        assert_eq!(texts("{% if x %}\n  hi\n{% endif %}\n"), ["  hi\n"]);
+        assert_eq!(texts("x\n    {% if y %}z{% endif %}"), ["x\n", "z"]);
+        // Expression tags are left alone.
+        assert_eq!(texts("  {{ a }}\nb"), ["  ", "\nb"]);
+    }
+}
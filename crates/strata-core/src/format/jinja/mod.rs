@@ -0,0 +1,109 @@
+//! Minimal Jinja2 engine for HF-style chat templates (`tokenizer.chat_template`).
+//!
+//! Covers the subset real model templates use: `if`/`for`/`set`/`macro`,
+//! namespaces, loop variables, the common filters and tests, and the HF
+//! extensions (`raise_exception`, `tojson`, `strftime_now`). Rendering follows
+//! HF's environment settings (`trim_blocks`, `lstrip_blocks`, no autoescape).
+
+mod eval;
+mod lexer;
+mod parser;
+mod value;
+
+pub use value::Value;
+
+use parser::Node;
+
+/// A parsed template, reusable across renders.
+#[derive(Debug, Clone)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    pub fn parse(src: &str) -> Result<Self, String> {
+        // Jinja's default `keep_trailing_newline=False`.
+        let src = src
+            .strip_suffix("\r\n")
+            .or_else(|| src.strip_suffix('\n'))
+            .unwrap_or(src);
+        let pieces = lexer::tokenize(src)?;
+        let nodes = parser::parse(pieces)?;
+        Ok(Self { nodes })
+    }
+
+    /// Render with the given top-level variables.
+    pub fn render(&self, globals: Vec<(String, Value)>) -> Result<String, String> {
+        eval::Renderer::new(globals).render(&self.nodes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(src: &str) -> Result<String, String> {
+        let globals = vec![("n".to_string(), Value::Int(3))];
+        Template::parse(src)?.render(globals)
+    }
+
+    #[test]
+    fn renders_with_globals() {
+        assert_eq!(
+            render("{% for i in range(n) %}{{ i }}{% endfor %}\n").unwrap(),
+            "012"
+        );
+    }
+
+    #[test]
+    fn parse_errors_surface() {
+        assert_eq!(render("{{ x").unwrap_err(), "unterminated tag");
+        assert!(
+            render("{% if x %}")
+                .unwrap_err()
+                .starts_with("unexpected end")
+        );
+    }
+
+    #[test]
+    fn unknown_filters_and_tests_are_errors() {
+        assert_eq!(
+            render("{{ 'a' | shout }}").unwrap_err(),
+            "unknown filter 'shout'"
+        );
+        assert_eq!(
+            render("{% if n is prime %}{% endif %}").unwrap_err(),
+            "unknown test 'prime'"
+        );
+        assert_eq!(render("{{ nope() }}").unwrap_err(), "'nope' is undefined");
+    }
+
+    #[test]
+    fn macro_recursion_is_bounded() {
+        let countdown =
+            "{% macro f(k) %}{% if k > 0 %}{{ k }}{{ f(k - 1) }}{% endif %}{% endmacro %}";
+        assert_eq!(
+            render(&format!("{countdown}{{{{ f(n) }}}}")).unwrap(),
+            "321"
+        );
+
+        let forever = "{% macro f(k) %}{{ f(k + 1) }}{% endmacro %}{{ f(0) }}";
+        assert_eq!(
+            render(forever).unwrap_err(),
+            "macro 'f' recursed too deeply"
+        );
+    }
+
+    #[test]
+    fn runtime_errors_are_reported() {
+        assert!(
+            render("{{ raise_exception('bad role') }}")
+                .unwrap_err()
+                .contains("bad role")
+        );
+        assert_eq!(
+            render("{{ n // 0 }}").unwrap_err(),
+            "integer division by zero"
+        );
+    }
+}
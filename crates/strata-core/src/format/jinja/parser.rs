@@ -0,0 +1,871 @@
+//! Recursive-descent parser: lexer pieces → statement tree.
+//! Expression precedence mirrors `jinja2.parser`.
+
+use std::rc::Rc;
+
+use super::lexer::{Piece, Tok};
+use super::value::Value;
+
+/// Deepest nesting of blocks, and separately of expressions, a template may
+/// use. Templates come from model files, so recursion is bounded rather than
+/// left to the stack.
+const MAX_NESTING: usize = 64;
+
+#[derive(Debug, Clone)]
+pub(super) enum Node {
+    Text(String),
+    Output(Expr),
+    If {
+        branches: Vec<(Expr, Vec<Node>)>,
+        otherwise: Vec<Node>,
+    },
+    For {
+        targets: Vec<String>,
+        iter: Expr,
+        filter: Option<Expr>,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Set {
+        target: SetTarget,
+        value: Expr,
+    },
+    SetBlock {
+        name: String,
+        body: Vec<Node>,
+    },
+    Macro(Rc<MacroDef>),
+    /// `{% filter name %}…{% endfilter %}`
+    FilterBlock {
+        name: String,
+        args: Vec<Expr>,
+        body: Vec<Node>,
+    },
+    /// `{% generation %}` (HF assistant-mask marker): rendered transparently.
+    Block(Vec<Node>),
+    Break,
+    Continue,
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum SetTarget {
+    Names(Vec<String>),
+    /// `ns.attr`
+    Attr(String, String),
+}
+
+#[derive(Debug)]
+pub struct MacroDef {
+    pub(super) name: String,
+    pub(super) params: Vec<(String, Option<Expr>)>,
+    pub(super) body: Vec<Node>,
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum Expr {
+    Literal(Value),
+    Name(String),
+    List(Vec<Expr>),
+    Dict(Vec<(Expr, Expr)>),
+    Attr(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    Slice {
+        target: Box<Expr>,
+        start: Option<Box<Expr>>,
+        stop: Option<Box<Expr>>,
+        step: Option<Box<Expr>>,
+    },
+    Call {
+        callee: Box<Expr>,
+        args: Vec<Expr>,
+        kwargs: Vec<(String, Expr)>,
+    },
+    Filter {
+        target: Box<Expr>,
+        name: String,
+        args: Vec<Expr>,
+        kwargs: Vec<(String, Expr)>,
+    },
+    Test {
+        target: Box<Expr>,
+        name: String,
+        args: Vec<Expr>,
+        negated: bool,
+    },
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Cond {
+        cond: Box<Expr>,
+        then: Box<Expr>,
+        otherwise: Option<Box<Expr>>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum BinOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    In,
+    NotIn,
+    Add,
+    Sub,
+    Concat,
+    Mul,
+    Div,
+    FloorDiv,
+    Mod,
+    Pow,
+}
+
+pub(super) fn parse(pieces: Vec<Piece>) -> Result<Vec<Node>, String> {
+    let mut p = Parser {
+        pieces,
+        pos: 0,
+        depth: 0,
+    };
+    let (body, end) = p.parse_body(&[])?;
+    if let Some(tag) = end {
+        return Err(format!("unexpected {{% {tag} %}}"));
+    }
+    Ok(body)
+}
+
+struct Parser {
+    pieces: Vec<Piece>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    /// Parse nodes until one of `terminators` (a statement keyword) is met.
+    /// Returns the body and the terminating keyword; the terminator tag itself is left unconsumed.
+    fn parse_body(&mut self, terminators: &[&str]) -> Result<(Vec<Node>, Option<String>), String> {
+        let mut nodes = Vec::new();
+        while self.pos < self.pieces.len() {
+            let piece = self.pieces[self.pos].clone();
+            self.pos += 1;
+            match piece {
+                Piece::Text(t) => nodes.push(Node::Text(t)),
+                Piece::Expr(toks) => {
+                    let mut ep = ExprParser::new(toks);
+                    let e = ep.parse_expr()?;
+                    ep.expect_end()?;
+                    nodes.push(Node::Output(e));
+                }
+                Piece::Stmt(toks) => {
+                    let kw = match toks.first() {
+                        Some(Tok::Name(n)) => n.clone(),
+                        _ => return Err("empty or malformed statement tag".into()),
+                    };
+                    if terminators.contains(&kw.as_str()) {
+                        // Leave the terminator for the caller to inspect.
+                        self.pos -= 1;
+                        return Ok((nodes, Some(kw)));
+                    }
+                    if self.depth >= MAX_NESTING {
+                        return Err(format!("blocks nested too deeply at {{% {kw} %}}"));
+                    }
+                    self.depth += 1;
+                    let node = self.parse_stmt(&kw, toks);
+                    self.depth -= 1;
+                    nodes.push(node?);
+                }
+            }
+        }
+        if terminators.is_empty() {
+            Ok((nodes, None))
+        } else {
+            Err(format!(
+                "unexpected end of template; expected one of {terminators:?}"
+            ))
+        }
+    }
+
+    /// Consume the terminator tag returned by `parse_body` and hand back its tokens.
+    fn take_terminator(&mut self) -> Vec<Tok> {
+        let Piece::Stmt(toks) = self.pieces[self.pos].clone() else {
+            unreachable!("terminator must be a statement");
+        };
+        self.pos += 1;
+        toks
+    }
+
+    fn parse_stmt(&mut self, kw: &str, toks: Vec<Tok>) -> Result<Node, String> {
+        let mut ep = ExprParser::new(toks);
+        ep.next(); // keyword
+        match kw {
+            "if" => {
+                let mut branches = Vec::new();
+                let mut cond = ep.parse_expr()?;
+                ep.expect_end()?;
+                loop {
+                    let (body, end) = self.parse_body(&["elif", "else", "endif"])?;
+                    branches.push((cond, body));
+                    let toks = self.take_terminator();
+                    match end.as_deref() {
+                        Some("elif") => {
+                            let mut ep = ExprParser::new(toks);
+                            ep.next();
+                            cond = ep.parse_expr()?;
+                            ep.expect_end()?;
+                        }
+                        Some("else") => {
+                            let (otherwise, _) = self.parse_body(&["endif"])?;
+                            self.take_terminator();
+                            return Ok(Node::If {
+                                branches,
+                                otherwise,
+                            });
+                        }
+                        _ => {
+                            return Ok(Node::If {
+                                branches,
+                                otherwise: Vec::new(),
+                            });
+                        }
+                    }
+                }
+            }
+            "for" => {
+                let mut targets = vec![ep.expect_name()?];
+                while ep.eat_op(",") {
+                    targets.push(ep.expect_name()?);
+                }
+                ep.expect_keyword("in")?;
+                // A trailing `if` is a loop filter, not a conditional expression.
+                let iter = ep.parse_or()?;
+                let filter = if ep.eat_keyword("if") {
+                    Some(ep.parse_or()?)
+                } else {
+                    None
+                };
+                ep.eat_keyword("recursive");
+                ep.expect_end()?;
+
+                let (body, end) = self.parse_body(&["else", "endfor"])?;
+                self.take_terminator();
+                let otherwise = if end.as_deref() == Some("else") {
+                    let (o, _) = self.parse_body(&["endfor"])?;
+                    self.take_terminator();
+                    o
+                } else {
+                    Vec::new()
+                };
+                Ok(Node::For {
+                    targets,
+                    iter,
+                    filter,
+                    body,
+                    otherwise,
+                })
+            }
+            "set" => {
+                let first = ep.expect_name()?;
+                if ep.eat_op(".") {
+                    let attr = ep.expect_name()?;
+                    ep.expect_op("=")?;
+                    let value = ep.parse_expr()?;
+                    ep.expect_end()?;
+                    return Ok(Node::Set {
+                        target: SetTarget::Attr(first, attr),
+                        value,
+                    });
+                }
+                let mut names = vec![first];
+                while ep.eat_op(",") {
+                    names.push(ep.expect_name()?);
+                }
+                if ep.at_end() {
+                    // Block set: {% set x %}…{% endset %}
+                    let (body, _) = self.parse_body(&["endset"])?;
+                    self.take_terminator();
+                    return Ok(Node::SetBlock {
+                        name: names.remove(0),
+                        body,
+                    });
+                }
+                ep.expect_op("=")?;
+                let value = ep.parse_tuple_or_expr()?;
+                ep.expect_end()?;
+                Ok(Node::Set {
+                    target: SetTarget::Names(names),
+                    value,
+                })
+            }
+            "macro" => {
+                let name = ep.expect_name()?;
+                ep.expect_op("(")?;
+                let mut params = Vec::new();
+                while !ep.eat_op(")") {
+                    let pname = ep.expect_name()?;
+                    let default = if ep.eat_op("=") {
+                        Some(ep.parse_expr()?)
+                    } else {
+                        None
+                    };
+                    params.push((pname, default));
+                    if !ep.eat_op(",") {
+                        ep.expect_op(")")?;
+                        break;
+                    }
+                }
+                ep.expect_end()?;
+                let (body, _) = self.parse_body(&["endmacro"])?;
+                self.take_terminator();
+                Ok(Node::Macro(Rc::new(MacroDef { name, params, body })))
+            }
+            "filter" => {
+                let name = ep.expect_name()?;
+                let mut args = Vec::new();
+                if ep.eat_op("(") {
+                    while !ep.eat_op(")") {
+                        args.push(ep.parse_expr()?);
+                        if !ep.eat_op(",") {
+                            ep.expect_op(")")?;
+                            break;
+                        }
+                    }
+                }
+                ep.expect_end()?;
+                let (body, _) = self.parse_body(&["endfilter"])?;
+                self.take_terminator();
+                Ok(Node::FilterBlock { name, args, body })
+            }
+            "generation" => {
+                let (body, _) = self.parse_body(&["endgeneration"])?;
+                self.take_terminator();
+                Ok(Node::Block(body))
+            }
+            "break" => Ok(Node::Break),
+            "continue" => Ok(Node::Continue),
+            other => Err(format!("unsupported statement {{% {other} %}}")),
+        }
+    }
+}
+
+/// Positional and keyword arguments of a call or filter.
+type CallArgs = (Vec<Expr>, Vec<(String, Expr)>);
+
+struct ExprParser {
+    toks: Vec<Tok>,
+    pos: usize,
+    depth: usize,
+}
+
+impl ExprParser {
+    fn new(toks: Vec<Tok>) -> Self {
+        Self {
+            toks,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    /// Run `f` one nesting level deeper.
+    fn nested(&mut self, f: fn(&mut Self) -> Result<Expr, String>) -> Result<Expr, String> {
+        if self.depth >= MAX_NESTING {
+            return Err("expression nested too deeply".into());
+        }
+        self.depth += 1;
+        let expr = f(self);
+        self.depth -= 1;
+        expr
+    }
+
+    fn peek(&self) -> Option<&Tok> {
+        self.toks.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Tok> {
+        let t = self.toks.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.toks.len()
+    }
+
+    fn expect_end(&self) -> Result<(), String> {
+        match self.peek() {
+            None => Ok(()),
+            Some(t) => Err(format!("unexpected token {t:?}")),
+        }
+    }
+
+    fn is_op(&self, op: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Op(o)) if *o == op)
+    }
+
+    fn is_keyword(&self, kw: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Name(n)) if n == kw)
+    }
+
+    fn eat_op(&mut self, op: &str) -> bool {
+        if self.is_op(op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, kw: &str) -> bool {
+        if self.is_keyword(kw) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_op(&mut self, op: &str) -> Result<(), String> {
+        if self.eat_op(op) {
+            Ok(())
+        } else {
+            Err(format!("expected '{op}', found {:?}", self.peek()))
+        }
+    }
+
+    fn expect_keyword(&mut self, kw: &str) -> Result<(), String> {
+        if self.eat_keyword(kw) {
+            Ok(())
+        } else {
+            Err(format!("expected '{kw}', found {:?}", self.peek()))
+        }
+    }
+
+    fn expect_name(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(Tok::Name(n)) => Ok(n),
+            other => Err(format!("expected identifier, found {other:?}")),
+        }
+    }
+
+    /// `a, b` on the right of `set` builds a tuple (list).
+    fn parse_tuple_or_expr(&mut self) -> Result<Expr, String> {
+        let first = self.parse_expr()?;
+        if !self.is_op(",") {
+            return Ok(first);
+        }
+        let mut items = vec![first];
+        while self.eat_op(",") {
+            if self.at_end() {
+                break;
+            }
+            items.push(self.parse_expr()?);
+        }
+        Ok(Expr::List(items))
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, String> {
+        self.nested(Self::parse_cond)
+    }
+
+    /// `a if cond else b`
+    fn parse_cond(&mut self) -> Result<Expr, String> {
+        let expr = self.parse_or()?;
+        if self.eat_keyword("if") {
+            let cond = self.parse_or()?;
+            let otherwise = if self.eat_keyword("else") {
+                Some(Box::new(self.parse_expr()?))
+            } else {
+                None
+            };
+            return Ok(Expr::Cond {
+                cond: Box::new(cond),
+                then: Box::new(expr),
+                otherwise,
+            });
+        }
+        Ok(expr)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_and()?;
+        while self.eat_keyword("or") {
+            let right = self.parse_and()?;
+            left = Expr::Binary(BinOp::Or, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_not()?;
+        while self.eat_keyword("and") {
+            let right = self.parse_not()?;
+            left = Expr::Binary(BinOp::And, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, String> {
+        if self.eat_keyword("not") {
+            return Ok(Expr::Not(Box::new(self.nested(Self::parse_not)?)));
+        }
+        self.parse_compare()
+    }
+
+    fn parse_compare(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_math1()?;
+        loop {
+            let op = match self.peek() {
+                Some(Tok::Op("==")) => BinOp::Eq,
+                Some(Tok::Op("!=")) => BinOp::Ne,
+                Some(Tok::Op("<")) => BinOp::Lt,
+                Some(Tok::Op("<=")) => BinOp::Le,
+                Some(Tok::Op(">")) => BinOp::Gt,
+                Some(Tok::Op(">=")) => BinOp::Ge,
+                Some(Tok::Name(n)) if n == "in" => BinOp::In,
+                Some(Tok::Name(n))
+                    if n == "not"
+                        && matches!(self.toks.get(self.pos + 1), Some(Tok::Name(m)) if m == "in") =>
+                {
+                    self.pos += 1;
+                    BinOp::NotIn
+                }
+                _ => break,
+            };
+            self.pos += 1;
+            let right = self.parse_math1()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_math1(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_concat()?;
+        loop {
+            let op = if self.eat_op("+") {
+                BinOp::Add
+            } else if self.eat_op("-") {
+                BinOp::Sub
+            } else {
+                break;
+            };
+            let right = self.parse_concat()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_concat(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_math2()?;
+        while self.eat_op("~") {
+            let right = self.parse_math2()?;
+            left = Expr::Binary(BinOp::Concat, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_math2(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_pow()?;
+        loop {
+            let op = if self.eat_op("*") {
+                BinOp::Mul
+            } else if self.eat_op("//") {
+                BinOp::FloorDiv
+            } else if self.eat_op("/") {
+                BinOp::Div
+            } else if self.eat_op("%") {
+                BinOp::Mod
+            } else {
+                break;
+            };
+            let right = self.parse_pow()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_pow(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_unary()?;
+        while self.eat_op("**") {
+            let right = self.parse_unary()?;
+            left = Expr::Binary(BinOp::Pow, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        if self.eat_op("-") {
+            return Ok(Expr::Neg(Box::new(self.nested(Self::parse_unary)?)));
+        }
+        if self.eat_op("+") {
+            return self.nested(Self::parse_unary);
+        }
+        let primary = self.parse_primary()?;
+        let node = self.parse_postfix(primary)?;
+        self.parse_filter_expr(node)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Tok::Str(s)) => {
+                // Adjacent string literals concatenate, as in Python.
+                let mut s = s;
+                while let Some(Tok::Str(more)) = self.peek().cloned() {
+                    self.pos += 1;
+                    s.push_str(&more);
+                }
+                Ok(Expr::Literal(Value::Str(s)))
+            }
+            Some(Tok::Int(i)) => Ok(Expr::Literal(Value::Int(i))),
+            Some(Tok::Float(f)) => Ok(Expr::Literal(Value::Float(f))),
+            Some(Tok::Name(n)) => Ok(match n.as_str() {
+                "true" | "True" => Expr::Literal(Value::Bool(true)),
+                "false" | "False" => Expr::Literal(Value::Bool(false)),
+                "none" | "None" => Expr::Literal(Value::None),
+                _ => Expr::Name(n),
+            }),
+            Some(Tok::Op("(")) => {
+                let e = self.parse_tuple_or_expr()?;
+                self.expect_op(")")?;
+                Ok(e)
+            }
+            Some(Tok::Op("[")) => {
+                let mut items = Vec::new();
+                while !self.eat_op("]") {
+                    items.push(self.parse_expr()?);
+                    if !self.eat_op(",") {
+                        self.expect_op("]")?;
+                        break;
+                    }
+                }
+                Ok(Expr::List(items))
+            }
+            Some(Tok::Op("{")) => {
+                let mut items = Vec::new();
+                while !self.eat_op("}") {
+                    let k = self.parse_expr()?;
+                    self.expect_op(":")?;
+                    let v = self.parse_expr()?;
+                    items.push((k, v));
+                    if !self.eat_op(",") {
+                        self.expect_op("}")?;
+                        break;
+                    }
+                }
+                Ok(Expr::Dict(items))
+            }
+            other => Err(format!("unexpected token {other:?} in expression")),
+        }
+    }
+
+    fn parse_postfix(&mut self, mut node: Expr) -> Result<Expr, String> {
+        loop {
+            if self.eat_op(".") {
+                let attr = match self.next() {
+                    Some(Tok::Name(n)) => n,
+                    Some(Tok::Int(i)) => i.to_string(),
+                    other => return Err(format!("expected attribute name, found {other:?}")),
+                };
+                node = Expr::Attr(Box::new(node), attr);
+            } else if self.is_op("[") {
+                self.pos += 1;
+                node = self.parse_subscript(node)?;
+            } else if self.is_op("(") {
+                self.pos += 1;
+                let (args, kwargs) = self.parse_call_args()?;
+                node = Expr::Call {
+                    callee: Box::new(node),
+                    args,
+                    kwargs,
+                };
+            } else {
+                return Ok(node);
+            }
+        }
+    }
+
+    fn parse_subscript(&mut self, target: Expr) -> Result<Expr, String> {
+        let mut parts: Vec<Option<Box<Expr>>> = vec![None];
+        let mut is_slice = false;
+        loop {
+            if self.eat_op("]") {
+                break;
+            }
+            if self.eat_op(":") {
+                is_slice = true;
+                parts.push(None);
+                continue;
+            }
+            let e = self.parse_expr()?;
+            *parts.last_mut().unwrap() = Some(Box::new(e));
+        }
+
+        if !is_slice {
+            let idx = parts
+                .pop()
+                .flatten()
+                .ok_or_else(|| "empty subscript".to_string())?;
+            return Ok(Expr::Index(Box::new(target), idx));
+        }
+        let mut it = parts.into_iter();
+        Ok(Expr::Slice {
+            target: Box::new(target),
+            start: it.next().flatten(),
+            stop: it.next().flatten(),
+            step: it.next().flatten(),
+        })
+    }
+
+    fn parse_call_args(&mut self) -> Result<CallArgs, String> {
+        let mut args = Vec::new();
+        let mut kwargs = Vec::new();
+        while !self.eat_op(")") {
+            let is_kw = matches!(self.peek(), Some(Tok::Name(_)))
+                && matches!(self.toks.get(self.pos + 1), Some(Tok::Op("=")));
+            if is_kw {
+                let name = self.expect_name()?;
+                self.pos += 1; // '='
+                kwargs.push((name, self.parse_expr()?));
+            } else {
+                args.push(self.parse_expr()?);
+            }
+            if !self.eat_op(",") {
+                self.expect_op(")")?;
+                break;
+            }
+        }
+        Ok((args, kwargs))
+    }
+
+    fn parse_filter_expr(&mut self, mut node: Expr) -> Result<Expr, String> {
+        loop {
+            if self.eat_op("|") {
+                let name = self.expect_name()?;
+                let (args, kwargs) = if self.eat_op("(") {
+                    self.parse_call_args()?
+                } else {
+                    (Vec::new(), Vec::new())
+                };
+                node = Expr::Filter {
+                    target: Box::new(node),
+                    name,
+                    args,
+                    kwargs,
+                };
+            } else if self.eat_keyword("is") {
+                let negated = self.eat_keyword("not");
+                let name = match self.next() {
+                    Some(Tok::Name(n)) => n,
+                    Some(Tok::Op("==")) => "eq".into(),
+                    other => return Err(format!("expected test name, found {other:?}")),
+                };
+                let args = if self.eat_op("(") {
+                    self.parse_call_args()?.0
+                } else if matches!(
+                    self.peek(),
+                    Some(Tok::Str(_)) | Some(Tok::Int(_)) | Some(Tok::Float(_))
+                ) {
+                    // `x is sameas false` / `x is divisibleby 3`
+                    vec![self.parse_primary()?]
+                } else {
+                    Vec::new()
+                };
+                node = Expr::Test {
+                    target: Box::new(node),
+                    name,
+                    args,
+                    negated,
+                };
+            } else if self.is_op("(") || self.is_op(".") || self.is_op("[") {
+                node = self.parse_postfix(node)?;
+            } else {
+                return Ok(node);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::lexer::tokenize;
+    use super::*;
+
+    fn parse_src(src: &str) -> Result<Vec<Node>, String> {
+        parse(tokenize(src)?)
+    }
+
+    fn err(src: &str) -> String {
+        parse_src(src).unwrap_err()
+    }
+
+    #[test]
+    fn parses_for_with_filter_and_else() {
+        let nodes = parse_src("{% for k, v in d if v %}{{ k }}{% else %}-{% endfor %}").unwrap();
+        let [
+            Node::For {
+                targets, filter, ..
+            },
+        ] = nodes.as_slice()
+        else {
+            panic!("{nodes:?}");
+        };
+        assert_eq!(targets, &["k", "v"]);
+        assert!(filter.is_some());
+    }
+
+    #[test]
+    fn unclosed_blocks_are_errors() {
+        assert_eq!(
+            err("{% if x %}a"),
+            r#"unexpected end of template; expected one of ["elif", "else", "endif"]"#
+        );
+        assert!(err("{% for x in y %}").contains("endfor"));
+        assert!(err("{% macro m() %}").contains("endmacro"));
+    }
+
+    #[test]
+    fn malformed_statements_are_errors() {
+        assert_eq!(err("{% %}"), "empty or malformed statement tag");
+        assert_eq!(
+            err("{% include 'x' %}"),
+            "unsupported statement {% include %}"
+        );
+        assert!(err("{% endif %}").contains("endif"));
+    }
+
+    #[test]
+    fn malformed_expressions_are_errors() {
+        assert_eq!(err(r#"{{ a b }}"#), r#"unexpected token Name("b")"#);
+        assert_eq!(err("{{ (a }}"), "expected ')', found None");
+        assert!(err("{{ }}").contains("in expression"));
+        assert!(err("{{ a. }}").starts_with("expected attribute name"));
+        assert!(err("{{ a is }}").starts_with("expected test name"));
+    }
+
+    #[test]
+    fn expression_nesting_is_bounded() {
+        let nested = |open: &str, close: &str, n: usize| {
+            format!("{{{{ {}1{} }}}}", open.repeat(n), close.repeat(n))
+        };
+        assert!(parse_src(&nested("(", ")", 32)).is_ok());
+        assert_eq!(err(&nested("(", ")", 1000)), "expression nested too deeply");
+        assert_eq!(err(&nested("[", "]", 1000)), "expression nested too deeply");
+        assert_eq!(
+            err(&nested("not ", "", 1000)),
+            "expression nested too deeply"
+        );
+        assert_eq!(err(&nested("-", "", 1000)), "expression nested too deeply");
+        assert_eq!(
+            err(&nested("f(", ")", 1000)),
+            "expression nested too deeply"
+        );
+    }
+
+    #[test]
+    fn block_nesting_is_bounded() {
+        let nested = |n: usize| format!("{}x{}", "{% if a %}".repeat(n), "{% endif %}".repeat(n));
+        assert!(parse_src(&nested(32)).is_ok());
+        assert_eq!(err(&nested(1000)), "blocks nested too deeply at {% if %}");
+    }
+}
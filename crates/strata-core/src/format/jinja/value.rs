@@ -0,0 +1,386 @@
+//! Runtime values for the Jinja evaluator, with Python-flavoured semantics
+//! (truthiness, `str()`/`repr()` output) so rendered prompts match HF byte-for-byte.
+
+use std::cell::RefCell;
+use std::fmt::Write as _;
+use std::rc::Rc;
+
+use super::parser::MacroDef;
+
+#[derive(Debug, Clone, Default)]
+pub enum Value {
+    #[default]
+    Undefined,
+    None,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    List(Vec<Value>),
+    /// Insertion-ordered mapping (Python dict semantics).
+    Dict(Vec<(String, Value)>),
+    /// `namespace()` object: the only mutable value templates can share across scopes.
+    Namespace(Rc<RefCell<Vec<(String, Value)>>>),
+    Macro(Rc<MacroDef>),
+}
+
+impl Value {
+    pub fn str<S: Into<String>>(s: S) -> Self {
+        Value::Str(s.into())
+    }
+
+    pub fn is_undefined(&self) -> bool {
+        matches!(self, Value::Undefined)
+    }
+
+    pub fn is_none(&self) -> bool {
+        matches!(self, Value::None)
+    }
+
+    pub fn truthy(&self) -> bool {
+        match self {
+            Value::Undefined | Value::None => false,
+            Value::Bool(b) => *b,
+            Value::Int(i) => *i != 0,
+            Value::Float(f) => *f != 0.0,
+            Value::Str(s) => !s.is_empty(),
+            Value::List(l) => !l.is_empty(),
+            Value::Dict(d) => !d.is_empty(),
+            Value::Namespace(_) | Value::Macro(_) => true,
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Undefined => "undefined",
+            Value::None => "none",
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::Str(_) => "str",
+            Value::List(_) => "list",
+            Value::Dict(_) => "dict",
+            Value::Namespace(_) => "namespace",
+            Value::Macro(_) => "macro",
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Int(i) => Some(*i),
+            Value::Bool(b) => Some(*b as i64),
+            Value::Float(f) if f.fract() == 0.0 => Some(*f as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int(i) => Some(*i as f64),
+            Value::Float(f) => Some(*f),
+            Value::Bool(b) => Some(*b as i64 as f64),
+            _ => None,
+        }
+    }
+
+    /// Attribute / key lookup (`x.key`, `x['key']`). Missing keys are `Undefined`.
+    pub fn get_attr(&self, key: &str) -> Value {
+        match self {
+            Value::Dict(d) => d
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.clone())
+                .unwrap_or_default(),
+            Value::Namespace(ns) => ns
+                .borrow()
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.clone())
+                .unwrap_or_default(),
+            _ => Value::Undefined,
+        }
+    }
+
+    /// Subscript (`x[i]`) with Python negative-index semantics.
+    pub fn get_item(&self, key: &Value) -> Value {
+        match (self, key) {
+            (Value::List(l), k) => match k.as_i64() {
+                Some(i) => py_index(i, l.len())
+                    .map(|i| l[i].clone())
+                    .unwrap_or_default(),
+                None => Value::Undefined,
+            },
+            (Value::Str(s), k) => match k.as_i64() {
+                Some(i) => {
+                    let chars: Vec<char> = s.chars().collect();
+                    py_index(i, chars.len())
+                        .map(|i| Value::Str(chars[i].to_string()))
+                        .unwrap_or_default()
+                }
+                None => Value::Undefined,
+            },
+            (_, Value::Str(k)) => self.get_attr(k),
+            (_, k) => self.get_attr(&k.to_output()),
+        }
+    }
+
+    /// Iteration order used by `for` loops and sequence filters.
+    pub fn iter_items(&self) -> Result<Vec<Value>, String> {
+        match self {
+            Value::Undefined | Value::None => Ok(Vec::new()),
+            Value::List(l) => Ok(l.clone()),
+            Value::Dict(d) => Ok(d.iter().map(|(k, _)| Value::str(k.clone())).collect()),
+            Value::Str(s) => Ok(s.chars().map(|c| Value::Str(c.to_string())).collect()),
+            other => Err(format!("'{}' object is not iterable", other.type_name())),
+        }
+    }
+
+    pub fn length(&self) -> Option<usize> {
+        match self {
+            Value::Str(s) => Some(s.chars().count()),
+            Value::List(l) => Some(l.len()),
+            Value::Dict(d) => Some(d.len()),
+            Value::Namespace(ns) => Some(ns.borrow().len()),
+            Value::Undefined => Some(0),
+            _ => None,
+        }
+    }
+
+    /// Python `str(x)`: what `{{ x }}` prints.
+    pub fn to_output(&self) -> String {
+        match self {
+            Value::Undefined => String::new(),
+            Value::Str(s) => s.clone(),
+            other => other.to_repr(),
+        }
+    }
+
+    /// Python `repr(x)`: used when containers are stringified.
+    pub fn to_repr(&self) -> String {
+        match self {
+            Value::Undefined => String::new(),
+            Value::None => "None".into(),
+            Value::Bool(true) => "True".into(),
+            Value::Bool(false) => "False".into(),
+            Value::Int(i) => i.to_string(),
+            Value::Float(f) => py_float(*f),
+            Value::Str(s) => py_str_repr(s),
+            Value::List(l) => {
+                let parts: Vec<String> = l.iter().map(Value::to_repr).collect();
+                format!("[{}]", parts.join(", "))
+            }
+            Value::Dict(d) => {
+                let parts: Vec<String> = d
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", py_str_repr(k), v.to_repr()))
+                    .collect();
+                format!("{{{}}}", parts.join(", "))
+            }
+            Value::Namespace(ns) => {
+                let parts: Vec<String> = ns
+                    .borrow()
+                    .iter()
+                    .map(|(k, v)| format!("{k}={}", v.to_repr()))
+                    .collect();
+                format!("<Namespace {{{}}}>", parts.join(", "))
+            }
+            Value::Macro(m) => format!("<Macro '{}'>", m.name),
+        }
+    }
+
+    /// Python `json.dumps` (HF's `tojson`: non-ASCII kept, `", "`/`": "` separators).
+    pub fn to_json(&self, indent: Option<usize>) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out, indent, 0);
+        out
+    }
+
+    fn write_json(&self, out: &mut String, indent: Option<usize>, depth: usize) {
+        let newline = |out: &mut String, depth: usize| {
+            if let Some(n) = indent {
+                out.push('\n');
+                out.push_str(&" ".repeat(n * depth));
+            }
+        };
+        let item_sep = if indent.is_some() { "," } else { ", " };
+
+        match self {
+            Value::Undefined | Value::None | Value::Macro(_) => out.push_str("null"),
+            Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+            Value::Int(i) => {
+                let _ = write!(out, "{i}");
+            }
+            Value::Float(f) => out.push_str(&py_float(*f)),
+            Value::Str(s) => write_json_str(out, s),
+            Value::List(l) => {
+                if l.is_empty() {
+                    out.push_str("[]");
+                    return;
+                }
+                out.push('[');
+                for (i, v) in l.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(item_sep);
+                    }
+                    newline(out, depth + 1);
+                    v.write_json(out, indent, depth + 1);
+                }
+                newline(out, depth);
+                out.push(']');
+            }
+            Value::Dict(_) | Value::Namespace(_) => {
+                let entries = match self {
+                    Value::Dict(d) => d.clone(),
+                    Value::Namespace(ns) => ns.borrow().clone(),
+                    _ => unreachable!(),
+                };
+                if entries.is_empty() {
+                    out.push_str("{}");
+                    return;
+                }
+                out.push('{');
+                for (i, (k, v)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(item_sep);
+                    }
+                    newline(out, depth + 1);
+                    write_json_str(out, k);
+                    out.push_str(": ");
+                    v.write_json(out, indent, depth + 1);
+                }
+                newline(out, depth);
+                out.push('}');
+            }
+        }
+    }
+
+    /// Equality as Python sees it (`1 == 1.0`, `None == None`).
+    pub fn loose_eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Undefined, Value::Undefined) | (Value::None, Value::None) => true,
+            (Value::Str(a), Value::Str(b)) => a == b,
+            (Value::List(a), Value::List(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.loose_eq(y))
+            }
+            (Value::Dict(a), Value::Dict(b)) => {
+                a.len() == b.len()
+                    && a.iter().all(|(k, v)| {
+                        b.iter()
+                            .find(|(k2, _)| k2 == k)
+                            .map(|(_, v2)| v.loose_eq(v2))
+                            .unwrap_or(false)
+                    })
+            }
+            (a, b) => match (a.as_f64(), b.as_f64()) {
+                (Some(x), Some(y)) if !matches!(a, Value::Str(_)) => x == y,
+                _ => false,
+            },
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Str(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Str(s)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<&serde_json::Value> for Value {
+    fn from(v: &serde_json::Value) -> Self {
+        match v {
+            serde_json::Value::Null => Value::None,
+            serde_json::Value::Bool(b) => Value::Bool(*b),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => Value::Int(i),
+                None => Value::Float(n.as_f64().unwrap_or_default()),
+            },
+            serde_json::Value::String(s) => Value::Str(s.clone()),
+            serde_json::Value::Array(a) => Value::List(a.iter().map(Value::from).collect()),
+            serde_json::Value::Object(o) => {
+                Value::Dict(o.iter().map(|(k, v)| (k.clone(), Value::from(v))).collect())
+            }
+        }
+    }
+}
+
+/// Resolve a possibly-negative Python index against `len`.
+pub(super) fn py_index(i: i64, len: usize) -> Option<usize> {
+    let idx = if i < 0 { len as i64 + i } else { i };
+    if idx >= 0 && (idx as usize) < len {
+        Some(idx as usize)
+    } else {
+        None
+    }
+}
+
+fn py_float(f: f64) -> String {
+    if f.is_finite() && f.fract() == 0.0 && f.abs() < 1e16 {
+        format!("{f:.1}")
+    } else {
+        format!("{f}")
+    }
+}
+
+fn py_str_repr(s: &str) -> String {
+    let quote = if s.contains('\'') && !s.contains('"') {
+        '"'
+    } else {
+        '\''
+    };
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push(quote);
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c == quote => {
+                out.push('\\');
+                out.push(c);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push(quote);
+    out
+}
+
+fn write_json_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0C}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
@@ -0,0 +1,31 @@
+//! Flatten multimodal turns into marker text + image bytes for the backend.
+
+use strata_abi::backend::{ChatTurn, ContentPart};
+
+/// Rewrite each multimodal turn's `content` so every image part becomes `marker`,
+/// and return the image bytes in prompt order. Plain-text turns are untouched.
+pub fn inline_media(turns: &mut [ChatTurn], marker: Option<&str>) -> Result<Vec<Vec<u8>>, String> {
+    let mut images = Vec::new();
+    for turn in turns.iter_mut().filter(|t| !t.parts.is_empty()) {
+        let mut text = String::new();
+        for part in std::mem::take(&mut turn.parts) {
+            match part {
+                ContentPart::Text { text: t } => text.push_str(&t),
+                ContentPart::Image { data, .. } => {
+                    let marker = marker.ok_or(
+                        "this model cannot read images (no vision projector loaded)".to_string(),
+                    )?;
+                    // Keep markers on their own line so they never fuse with words.
+                    if !text.is_empty() && !text.ends_with('\n') {
+                        text.push('\n');
+                    }
+                    text.push_str(marker);
+                    text.push('\n');
+                    images.push(data);
+                }
+            }
+        }
+        turn.content = text.trim_end().to_string();
+    }
+    Ok(images)
+}
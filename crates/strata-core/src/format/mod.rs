@@ -1,2 +1,9 @@
+pub mod chat_template;
 pub mod format;
+pub mod jinja;
+pub mod media;
+pub mod prompt_format;
+pub mod prompting;
+pub mod reasoning;
+pub mod tool_calls;
 pub use format::*;
@@ -1,16 +1,23 @@
-use super::prompting::{PromptStrategy, normalize_bpe_markers}; // if you ever use normalize
+use serde::{Deserialize, Serialize};
+
+use super::prompting::PromptStrategy;
 use crate::format::FormattedPrompt;
 use strata_abi::backend::{ChatTurn, Role};
 
 /// Generic, model-agnostic prompt kinds.
+/// Serialized as `{"kind": "ChatMl", "system": null}` etc. for per-model settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
 pub enum PromptKind {
     ChatMl {
+        #[serde(default)]
         system: Option<String>,
     },
     UserAssistant,
     InstBlock,
     Plain,
     Phi3 {
+        #[serde(default)]
         system: Option<String>,
     },
     /// A simple pattern with a single `{}` placeholder for the last user message.
@@ -69,6 +76,11 @@ impl PromptStrategy for ChatMlFormat {
                     out.push_str(t.content.trim());
                     out.push_str("<|im_end|>\n");
                 }
+                Role::Tool => {
+                    out.push_str("<|im_start|>tool\n");
+                    out.push_str(t.content.trim());
+                    out.push_str("<|im_end|>\n");
+                }
             }
         }
 
@@ -84,6 +96,7 @@ impl PromptStrategy for ChatMlFormat {
                 "<|im_start|>system".to_string(),
             ],
             add_space_prefix: true,
+            images: Vec::new(),
         }
     }
 }
@@ -115,6 +128,11 @@ impl PromptStrategy for UserAssistantFormat {
                     out.push_str(t.content.trim());
                     out.push('\n');
                 }
+                Role::Tool => {
+                    out.push_str("Tool: ");
+                    out.push_str(t.content.trim());
+                    out.push('\n');
+                }
             }
         }
         out.push_str("Assistant: ");
@@ -122,6 +140,7 @@ impl PromptStrategy for UserAssistantFormat {
             text: out,
             stop_sequences: vec!["\nUser:".into(), "\nSystem:".into()],
             add_space_prefix: true,
+            images: Vec::new(),
         }
     }
 }
@@ -152,6 +171,11 @@ impl PromptStrategy for InstBlockFormat {
                     instruction.push_str(t.content.trim());
                     instruction.push('\n');
                 }
+                Role::Tool => {
+                    instruction.push_str("Tool: ");
+                    instruction.push_str(t.content.trim());
+                    instruction.push('\n');
+                }
             }
         }
         let mut text = String::new();
@@ -163,6 +187,7 @@ impl PromptStrategy for InstBlockFormat {
             text,
             stop_sequences: vec!["</s>".into()],
             add_space_prefix: true,
+            images: Vec::new(),
         }
     }
 }
@@ -184,6 +209,7 @@ impl PromptStrategy for PlainFormat {
             text: out,
             stop_sequences: vec![],
             add_space_prefix: true,
+            images: Vec::new(),
         }
     }
 }
@@ -221,7 +247,7 @@ impl PromptStrategy for Phi3Format {
                     out.push_str(t.content.trim());
                     out.push_str("\n<|end|>\n");
                 }
-                Role::System => {
+                Role::System | Role::Tool => {
                     out.push_str("<|user|>\n");
                     out.push_str(t.content.trim());
                     out.push_str("\n<|end|>\n");
@@ -240,6 +266,7 @@ impl PromptStrategy for Phi3Format {
                 "<|assistant|>\n".into(),
             ],
             add_space_prefix: true,
+            images: Vec::new(),
         }
     }
 }
@@ -273,6 +300,7 @@ impl PromptStrategy for CustomFormat {
             text,
             stop_sequences: vec![],
             add_space_prefix: true,
+            images: Vec::new(),
         }
     }
 }
@@ -1,7 +1,7 @@
 //! Model-agnostic prompt formatting strategies (fallback path).
 
 use crate::format::FormattedPrompt;
-use strata_abi::backend::{ChatTurn, Role};
+use strata_abi::backend::ChatTurn;
 
 /// Format a user input or a full dialog into a complete prompt string for the backend.
 pub trait PromptStrategy: Send + Sync {
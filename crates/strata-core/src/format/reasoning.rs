@@ -0,0 +1,318 @@
+//! Split `<think>…</think>` reasoning (Qwen3, DeepSeek-R1, …) from the answer.
+//!
+//! Only a block at the very start of the output counts as reasoning; a literal
+//! `<think>` later in the answer is left alone.
+
+pub const THINK_OPEN: &str = "<think>";
+pub const THINK_CLOSE: &str = "</think>";
+
+/// Which stream a piece of generated text belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Reasoning,
+    Answer,
+}
+
+/// A finished reply with its reasoning kept apart.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChatReply {
+    pub reasoning: Option<String>,
+    pub answer: String,
+}
+
+impl ChatReply {
+    /// Text to store in history: the answer alone, or with the reasoning re-wrapped.
+    pub fn history_text(&self, keep_reasoning: bool) -> String {
+        match self.reasoning.as_deref() {
+            Some(r) if keep_reasoning => {
+                format!("{THINK_OPEN}\n{r}\n{THINK_CLOSE}\n\n{}", self.answer)
+            }
+            _ => self.answer.clone(),
+        }
+    }
+}
+
+/// True when the generation prompt opens a `<think>` block (R1-style), i.e.
+/// the model starts mid-reasoning without emitting the tag. Only the end of
+/// the prompt counts: a `<think>` inside a message is followed by at least the
+/// assistant header.
+pub fn prompt_opens_reasoning(prompt: &str) -> bool {
+    prompt.trim_end().ends_with(THINK_OPEN)
+}
+
+/// Split a complete output; see [`ReasoningSplitter`] for the rules.
+pub fn split_reasoning(text: &str, starts_in_reasoning: bool) -> ChatReply {
+    let mut reasoning = String::new();
+    let mut answer = String::new();
+    let mut emit = |ch: Channel, s: &str| match ch {
+        Channel::Reasoning => reasoning.push_str(s),
+        Channel::Answer => answer.push_str(s),
+    };
+    let mut splitter = ReasoningSplitter::new(starts_in_reasoning);
+    splitter.push(text, &mut emit);
+    splitter.finish(&mut emit);
+    splitter.into_reply(reasoning, answer)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Nothing but whitespace seen; a `<think>` here opens reasoning.
+    Start,
+    Reasoning,
+    /// Just closed a block; leading whitespace of the answer is dropped.
+    AfterReasoning,
+    Answer,
+}
+
+/// Incremental splitter for streamed deltas. Tags may arrive split across
+/// deltas, so a possible tag prefix is held back until it resolves.
+#[derive(Debug, Clone)]
+pub struct ReasoningSplitter {
+    state: State,
+    pending: String,
+    saw_reasoning: bool,
+}
+
+impl ReasoningSplitter {
+    pub fn new(starts_in_reasoning: bool) -> Self {
+        Self {
+            state: if starts_in_reasoning {
+                State::Reasoning
+            } else {
+                State::Start
+            },
+            pending: String::new(),
+            saw_reasoning: starts_in_reasoning,
+        }
+    }
+
+    /// Feed one delta; complete pieces are passed to `emit`.
+    pub fn push<F: FnMut(Channel, &str)>(&mut self, delta: &str, emit: &mut F) {
+        self.pending.push_str(delta);
+
+        loop {
+            match self.state {
+                State::Start => {
+                    let trimmed = self.pending.trim_start();
+                    if let Some(rest) = trimmed.strip_prefix(THINK_OPEN) {
+                        self.pending = rest.to_string();
+                        self.state = State::Reasoning;
+                        self.saw_reasoning = true;
+                    } else if trimmed.is_empty() || THINK_OPEN.starts_with(trimmed) {
+                        return; // undecided
+                    } else {
+                        self.state = State::Answer;
+                    }
+                }
+                State::Reasoning => match self.pending.find(THINK_CLOSE) {
+                    Some(idx) => {
+                        let head = self.pending[..idx].to_string();
+                        let rest = self.pending[idx + THINK_CLOSE.len()..].to_string();
+                        if !head.is_empty() {
+                            emit(Channel::Reasoning, &head);
+                        }
+                        self.pending = rest;
+                        self.state = State::AfterReasoning;
+                    }
+                    None => {
+                        let keep = partial_suffix(&self.pending, THINK_CLOSE);
+                        let cut = self.pending.len() - keep;
+                        if cut > 0 {
+                            emit(Channel::Reasoning, &self.pending[..cut]);
+                            self.pending.drain(..cut);
+                        }
+                        return;
+                    }
+                },
+                State::AfterReasoning => {
+                    let trimmed = self.pending.trim_start();
+                    if trimmed.is_empty() {
+                        self.pending.clear();
+                        return;
+                    }
+                    self.pending = trimmed.to_string();
+                    self.state = State::Answer;
+                }
+                State::Answer => {
+                    if !self.pending.is_empty() {
+                        emit(Channel::Answer, &self.pending);
+                        self.pending.clear();
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Flush whatever is still held back (end of generation).
+    pub fn finish<F: FnMut(Channel, &str)>(&mut self, emit: &mut F) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let channel = match self.state {
+            State::Reasoning => Channel::Reasoning,
+            _ => Channel::Answer,
+        };
+        emit(channel, &self.pending);
+        self.pending.clear();
+    }
+
+    /// Assemble a [`ChatReply`] from the collected channel text.
+    pub fn into_reply(self, reasoning: String, answer: String) -> ChatReply {
+        let reasoning = reasoning.trim();
+        ChatReply {
+            reasoning: (self.saw_reasoning && !reasoning.is_empty()).then(|| reasoning.to_string()),
+            answer: answer.trim().to_string(),
+        }
+    }
+}
+
+/// Length of the longest suffix of `s` that is a proper prefix of `tag`.
+fn partial_suffix(s: &str, tag: &str) -> usize {
+    (1..tag.len().min(s.len() + 1))
+        .rev()
+        .find(|&n| s.is_char_boundary(s.len() - n) && tag.starts_with(&s[s.len() - n..]))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed `deltas` through a splitter; returns what each channel received,
+    /// in order, and the assembled reply.
+    fn stream(deltas: &[&str], starts_in_reasoning: bool) -> (Vec<(Channel, String)>, ChatReply) {
+        let mut pieces = Vec::new();
+        let mut reasoning = String::new();
+        let mut answer = String::new();
+        let mut emit = |ch: Channel, s: &str| {
+            pieces.push((ch, s.to_string()));
+            match ch {
+                Channel::Reasoning => reasoning.push_str(s),
+                Channel::Answer => answer.push_str(s),
+            }
+        };
+        let mut splitter = ReasoningSplitter::new(starts_in_reasoning);
+        for d in deltas {
+            splitter.push(d, &mut emit);
+        }
+        splitter.finish(&mut emit);
+        let reply = splitter.into_reply(reasoning, answer);
+        (pieces, reply)
+    }
+
+    fn reply(reasoning: Option<&str>, answer: &str) -> ChatReply {
+        ChatReply {
+            reasoning: reasoning.map(str::to_string),
+            answer: answer.to_string(),
+        }
+    }
+
+    #[test]
+    fn generation_prompt_opens_reasoning() {
+        assert!(prompt_opens_reasoning(
+            "<｜User｜>2+2?<｜Assistant｜><think>\n"
+        ));
+        assert!(prompt_opens_reasoning("<|im_start|>assistant\n<think>\n"));
+        // Qwen3 with thinking disabled closes the block itself.
+        assert!(!prompt_opens_reasoning(
+            "<|im_start|>assistant\n<think>\n\n</think>\n\n"
+        ));
+        assert!(!prompt_opens_reasoning("<|im_start|>assistant\n"));
+    }
+
+    #[test]
+    fn think_tag_in_a_message_is_ignored() {
+        let prompt = "<|im_start|>user\nWhat does <think> do?<|im_end|>\n<|im_start|>assistant\n";
+        assert!(!prompt_opens_reasoning(prompt));
+    }
+
+    #[test]
+    fn no_tags_is_all_answer() {
+        let (pieces, out) = stream(&["Hello", ", world"], false);
+        assert_eq!(
+            pieces,
+            [
+                (Channel::Answer, "Hello".into()),
+                (Channel::Answer, ", world".into())
+            ]
+        );
+        assert_eq!(out, reply(None, "Hello, world"));
+    }
+
+    #[test]
+    fn tags_split_across_deltas() {
+        let (pieces, out) = stream(
+            &[
+                "  <th", "ink>", "plan", "ning</th", "ink>", "\n\n", "Answer",
+            ],
+            false,
+        );
+        assert!(
+            pieces
+                .iter()
+                .all(|(_, s)| !s.contains('<') && !s.contains('>')),
+            "{pieces:?}"
+        );
+        assert_eq!(out, reply(Some("planning"), "Answer"));
+    }
+
+    #[test]
+    fn lookalike_prefix_is_answer() {
+        let (_, out) = stream(&["<th", "ings to do"], false);
+        assert_eq!(out, reply(None, "<things to do"));
+    }
+
+    #[test]
+    fn later_think_tag_is_left_in_the_answer() {
+        let (_, out) = stream(&["Use <think>", " tags"], false);
+        assert_eq!(out, reply(None, "Use <think> tags"));
+    }
+
+    #[test]
+    fn unclosed_tag_is_all_reasoning() {
+        let (pieces, out) = stream(&["<think>still going</", "thi"], false);
+        assert_eq!(
+            pieces.last().unwrap(),
+            &(Channel::Reasoning, "</thi".into())
+        );
+        assert_eq!(out, reply(Some("still going</thi"), ""));
+    }
+
+    #[test]
+    fn prompt_opened_reasoning_needs_no_open_tag() {
+        let (_, out) = stream(&["step one", "</think>", "42"], true);
+        assert_eq!(out, reply(Some("step one"), "42"));
+    }
+
+    #[test]
+    fn multibyte_text_near_a_tag() {
+        let (_, out) = stream(&["<think>é", "</th", "ink>ü"], false);
+        assert_eq!(out, reply(Some("é"), "ü"));
+    }
+
+    #[test]
+    fn split_reasoning_whole_text() {
+        assert_eq!(
+            split_reasoning("<think>\nadd\n</think>\n\n4", false),
+            reply(Some("add"), "4")
+        );
+        assert_eq!(
+            split_reasoning("add</think>4", true),
+            reply(Some("add"), "4")
+        );
+        assert_eq!(split_reasoning(" 4 ", false), reply(None, "4"));
+        // An empty block is no reasoning.
+        assert_eq!(
+            split_reasoning("<think>\n\n</think>4", false),
+            reply(None, "4")
+        );
+    }
+
+    #[test]
+    fn history_text_rewraps_reasoning() {
+        let r = reply(Some("add"), "4");
+        assert_eq!(r.history_text(false), "4");
+        assert_eq!(r.history_text(true), "<think>\nadd\n</think>\n\n4");
+    }
+}
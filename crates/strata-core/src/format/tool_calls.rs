@@ -0,0 +1,301 @@
+//! Extract tool calls from raw model output.
+//!
+//! Recognised shapes:
+//! - Hermes / Qwen: `<tool_call>{"name": …, "arguments": {…}}</tool_call>` (repeatable)
+//! - Mistral: `[TOOL_CALLS] [{"name": …, "arguments": {…}, "id": …}]`, or the newer
+//!   `[TOOL_CALLS]name[ARGS]{…}`
+//! - Llama 3.1: `<|python_tag|>{"name": …, "parameters": {…}}` or the bare JSON object
+//!
+//! Special-token markers are often stripped during detokenization, so a reply that is
+//! nothing but a call-shaped JSON object/array is accepted too.
+
+use serde_json::Value as Json;
+use strata_abi::backend::ToolCall;
+
+/// Model output split into prose and tool calls.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedToolCalls {
+    /// Text outside the call markup (trimmed).
+    pub content: String,
+    pub calls: Vec<ToolCall>,
+}
+
+/// Parse `text`; `calls` is empty when the model answered in plain prose.
+pub fn parse_tool_calls(text: &str) -> ParsedToolCalls {
+    if text.contains("<tool_call>") {
+        return parse_hermes(text);
+    }
+    if let Some(idx) = text.find("[TOOL_CALLS]") {
+        let rest = &text[idx + "[TOOL_CALLS]".len()..];
+        let calls = parse_mistral(rest);
+        if !calls.is_empty() {
+            return ParsedToolCalls {
+                content: text[..idx].trim().to_string(),
+                calls,
+            };
+        }
+    }
+    if let Some(idx) = text.find("<|python_tag|>") {
+        let rest = &text[idx + "<|python_tag|>".len()..];
+        let calls = parse_json_calls(rest);
+        if !calls.is_empty() {
+            return ParsedToolCalls {
+                content: text[..idx].trim().to_string(),
+                calls,
+            };
+        }
+    }
+
+    // Marker-less: the whole reply must be call-shaped JSON.
+    let trimmed = text.trim();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        let calls = parse_json_calls(trimmed);
+        if !calls.is_empty() {
+            return ParsedToolCalls {
+                content: String::new(),
+                calls,
+            };
+        }
+    }
+
+    ParsedToolCalls {
+        content: text.trim().to_string(),
+        calls: Vec::new(),
+    }
+}
+
+fn parse_hermes(text: &str) -> ParsedToolCalls {
+    let mut content = String::new();
+    let mut calls = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("<tool_call>") {
+        content.push_str(&rest[..start]);
+        let body_start = start + "<tool_call>".len();
+        let (body, next) = match rest[body_start..].find("</tool_call>") {
+            Some(end) => (
+                &rest[body_start..body_start + end],
+                &rest[body_start + end + "</tool_call>".len()..],
+            ),
+            // Generation stopped before the closing tag.
+            None => (&rest[body_start..], ""),
+        };
+        match parse_json_calls(body.trim()).as_slice() {
+            [] => content.push_str(&rest[start..rest.len() - next.len()]),
+            parsed => calls.extend_from_slice(parsed),
+        }
+        rest = next;
+    }
+    content.push_str(rest);
+
+    ParsedToolCalls {
+        content: content.trim().to_string(),
+        calls,
+    }
+}
+
+fn parse_mistral(rest: &str) -> Vec<ToolCall> {
+    // v11+: name[ARGS]{...}, possibly repeated with further [TOOL_CALLS] markers.
+    if rest.contains("[ARGS]") {
+        let mut calls = Vec::new();
+        for chunk in rest.split("[TOOL_CALLS]") {
+            let Some((name, args)) = chunk.split_once("[ARGS]") else {
+                continue;
+            };
+            let Some((args, _)) = first_json(args) else {
+                continue;
+            };
+            calls.push(ToolCall {
+                id: None,
+                name: name.trim().to_string(),
+                arguments: args.to_string(),
+            });
+        }
+        return calls;
+    }
+    parse_json_calls(rest)
+}
+
+/// Parse a leading JSON object or array of call objects; `;`-separated
+/// objects (Llama 3.1 parallel calls) are accepted as well.
+fn parse_json_calls(text: &str) -> Vec<ToolCall> {
+    let mut calls = Vec::new();
+    let mut rest = text.trim_start();
+    while let Some((value, tail)) = first_json(rest) {
+        match value {
+            Json::Array(items) => {
+                for item in &items {
+                    match call_from_json(item) {
+                        Some(call) => calls.push(call),
+                        None => return Vec::new(),
+                    }
+                }
+            }
+            obj @ Json::Object(_) => match call_from_json(&obj) {
+                Some(call) => calls.push(call),
+                None => return Vec::new(),
+            },
+            _ => return Vec::new(),
+        }
+        rest = tail.trim_start().trim_start_matches(';').trim_start();
+        if rest.is_empty() {
+            break;
+        }
+    }
+    calls
+}
+
+/// First JSON value in `text` plus whatever follows it.
+fn first_json(text: &str) -> Option<(Json, &str)> {
+    let text = text.trim_start();
+    let mut stream = serde_json::Deserializer::from_str(text).into_iter::<Json>();
+    let value = stream.next()?.ok()?;
+    Some((value, &text[stream.byte_offset()..]))
+}
+
+fn call_from_json(v: &Json) -> Option<ToolCall> {
+    let obj = v.as_object()?;
+    // OpenAI-style wrapper: {"type": "function", "function": {...}}
+    if let Some(f) = obj.get("function").filter(|f| f.is_object()) {
+        let mut call = call_from_json(f)?;
+        call.id = call.id.or_else(|| json_str(obj.get("id")));
+        return Some(call);
+    }
+
+    let name = obj.get("name")?.as_str()?.to_string();
+    let arguments = match obj.get("arguments").or_else(|| obj.get("parameters")) {
+        // Some models emit the arguments as an already-encoded string.
+        Some(Json::String(s)) => s.clone(),
+        Some(args) => args.to_string(),
+        None => "{}".to_string(),
+    };
+    Some(ToolCall {
+        id: json_str(obj.get("id")),
+        name,
+        arguments,
+    })
+}
+
+fn json_str(v: Option<&Json>) -> Option<String> {
+    v.and_then(Json::as_str).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, arguments: &str) -> ToolCall {
+        ToolCall {
+            id: None,
+            name: name.into(),
+            arguments: arguments.into(),
+        }
+    }
+
+    #[test]
+    fn prose_has_no_calls() {
+        let parsed = parse_tool_calls("  It is sunny in Paris.\n");
+        assert_eq!(parsed.content, "It is sunny in Paris.");
+        assert!(parsed.calls.is_empty());
+    }
+
+    #[test]
+    fn hermes_calls_and_surrounding_text() {
+        let parsed = parse_tool_calls(
+            "Let me check.\n<tool_call>\n{\"name\": \"get_weather\", \"arguments\": {\"city\": \"Paris\"}}\n</tool_call>\n\
+             <tool_call>{\"name\": \"get_time\", \"arguments\": {}}</tool_call>",
+        );
+        assert_eq!(parsed.content, "Let me check.");
+        assert_eq!(
+            parsed.calls,
+            [
+                call("get_weather", r#"{"city":"Paris"}"#),
+                call("get_time", "{}")
+            ]
+        );
+    }
+
+    #[test]
+    fn hermes_without_closing_tag() {
+        let parsed = parse_tool_calls(
+            "<tool_call>{\"name\": \"get_time\", \"arguments\": {\"tz\": \"UTC\"}}",
+        );
+        assert_eq!(parsed.calls, [call("get_time", r#"{"tz":"UTC"}"#)]);
+        assert_eq!(parsed.content, "");
+    }
+
+    #[test]
+    fn hermes_malformed_json_stays_in_content() {
+        let text = "<tool_call>{\"name\": \"get_time\", </tool_call> done";
+        let parsed = parse_tool_calls(text);
+        assert!(parsed.calls.is_empty());
+        assert_eq!(parsed.content, text);
+    }
+
+    #[test]
+    fn mistral_json_array_keeps_ids() {
+        let parsed = parse_tool_calls(
+            r#"[TOOL_CALLS] [{"name": "get_weather", "arguments": {"city": "Paris"}, "id": "a1b2c3d4e"}, {"name": "get_time", "arguments": {}}]"#,
+        );
+        assert_eq!(parsed.calls.len(), 2);
+        assert_eq!(parsed.calls[0].id.as_deref(), Some("a1b2c3d4e"));
+        assert_eq!(parsed.calls[0].arguments, r#"{"city":"Paris"}"#);
+        assert_eq!(parsed.calls[1], call("get_time", "{}"));
+    }
+
+    #[test]
+    fn mistral_name_args_form() {
+        let parsed = parse_tool_calls(
+            r#"[TOOL_CALLS]get_weather[ARGS]{"city": "Paris"}[TOOL_CALLS]get_time[ARGS]{}"#,
+        );
+        assert_eq!(
+            parsed.calls,
+            [
+                call("get_weather", r#"{"city":"Paris"}"#),
+                call("get_time", "{}")
+            ]
+        );
+    }
+
+    #[test]
+    fn llama31_python_tag_and_parallel_calls() {
+        let parsed = parse_tool_calls(
+            r#"<|python_tag|>{"name": "get_weather", "parameters": {"city": "Paris"}}; {"name": "get_time", "parameters": {"tz": "CET"}}"#,
+        );
+        assert_eq!(
+            parsed.calls,
+            [
+                call("get_weather", r#"{"city":"Paris"}"#),
+                call("get_time", r#"{"tz":"CET"}"#)
+            ]
+        );
+    }
+
+    #[test]
+    fn bare_json_calls() {
+        let parsed = parse_tool_calls(r#"  {"name": "get_time", "parameters": {}}  "#);
+        assert_eq!(parsed.calls, [call("get_time", "{}")]);
+
+        // OpenAI wrapper, arguments already encoded as a string.
+        let parsed = parse_tool_calls(
+            r#"[{"id": "call_1", "type": "function", "function": {"name": "get_time", "arguments": "{\"tz\": \"UTC\"}"}}]"#,
+        );
+        assert_eq!(parsed.calls.len(), 1);
+        assert_eq!(parsed.calls[0].id.as_deref(), Some("call_1"));
+        assert_eq!(parsed.calls[0].arguments, r#"{"tz": "UTC"}"#);
+    }
+
+    #[test]
+    fn json_answers_are_not_calls() {
+        for text in [
+            r#"{"answer": 4}"#,
+            r#"[1, 2, 3]"#,
+            r#"[{"name": "get_time"}, {"value": 1}]"#,
+            r#"{"name": "get_time", "arguments":"#,
+        ] {
+            let parsed = parse_tool_calls(text);
+            assert!(parsed.calls.is_empty(), "{text}");
+            assert_eq!(parsed.content, text);
+        }
+    }
+}
@@ -1,5 +1,7 @@
 pub mod config;
 pub mod engine;
 pub mod format;
+pub mod logging;
 pub mod memory;
 pub mod metadata;
+pub mod tools;
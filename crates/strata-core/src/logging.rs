@@ -0,0 +1,70 @@
+//! Bridge from plugin log records (`strata_abi::ffi::LogFn`) to the `log`
+//! facade, so whatever logger the app installs also receives plugin and
+//! runtime (llama.cpp) output.
+
+use std::ffi::{CStr, c_char, c_void};
+
+use log::{Level, LevelFilter, Record};
+use strata_abi::ffi::{LOG_DEBUG, LOG_ERROR, LOG_INFO, LOG_OFF, LOG_TRACE, LOG_WARN, PluginApi};
+
+/// `Level` for a `LOG_*` value; `None` for `LOG_OFF` and unknown values.
+pub fn level_from_raw(level: i32) -> Option<Level> {
+    match level {
+        LOG_ERROR => Some(Level::Error),
+        LOG_WARN => Some(Level::Warn),
+        LOG_INFO => Some(Level::Info),
+        LOG_DEBUG => Some(Level::Debug),
+        LOG_TRACE => Some(Level::Trace),
+        _ => None,
+    }
+}
+
+/// `LOG_*` value for a filter, as passed to `SetLoggerFn::max_level`.
+pub fn filter_to_raw(filter: LevelFilter) -> i32 {
+    match filter {
+        LevelFilter::Off => LOG_OFF,
+        LevelFilter::Error => LOG_ERROR,
+        LevelFilter::Warn => LOG_WARN,
+        LevelFilter::Info => LOG_INFO,
+        LevelFilter::Debug => LOG_DEBUG,
+        LevelFilter::Trace => LOG_TRACE,
+    }
+}
+
+/// Emit one plugin record through the installed logger.
+pub fn log_plugin_record(level: i32, target: &str, message: &str) {
+    let Some(level) = level_from_raw(level) else {
+        return;
+    };
+    log::logger().log(
+        &Record::builder()
+            .level(level)
+            .target(target)
+            .args(format_args!("{message}"))
+            .build(),
+    );
+}
+
+/// `LogFn` handed to plugins; `user` is unused.
+unsafe extern "C" fn forward(
+    _user: *mut c_void,
+    level: i32,
+    target: *const c_char,
+    message: *const c_char,
+) {
+    if target.is_null() || message.is_null() {
+        return;
+    }
+    let target = unsafe { CStr::from_ptr(target) }.to_string_lossy();
+    let message = unsafe { CStr::from_ptr(message) }.to_string_lossy();
+    log_plugin_record(level, &target, &message);
+}
+
+/// Route `api`'s logging into this process's logger at the current
+/// `log::max_level()`. Plugins without `set_logger` keep writing to stderr.
+pub fn attach_plugin_logger(api: &PluginApi) {
+    if let Some(set_logger) = api.llm.set_logger {
+        let max_level = filter_to_raw(log::max_level());
+        unsafe { set_logger(Some(forward), std::ptr::null_mut(), max_level) };
+    }
+}
@@ -23,28 +23,19 @@ impl SessionMemory {
     /// Push a new user turn.
     #[inline]
     pub fn push_user<S: Into<String>>(&mut self, s: S) {
-        self.turns.push(ChatTurn {
-            role: Role::User,
-            content: s.into(),
-        });
+        self.turns.push(ChatTurn::new(Role::User, s));
     }
 
     /// Push a new assistant turn.
     #[inline]
     pub fn push_assistant<S: Into<String>>(&mut self, s: S) {
-        self.turns.push(ChatTurn {
-            role: Role::Assistant,
-            content: s.into(),
-        });
+        self.turns.push(ChatTurn::new(Role::Assistant, s));
     }
 
     /// Push a system turn (rare mid-session; usually set at engine-level).
     #[inline]
     pub fn push_system<S: Into<String>>(&mut self, s: S) {
-        self.turns.push(ChatTurn {
-            role: Role::System,
-            content: s.into(),
-        });
+        self.turns.push(ChatTurn::new(Role::System, s));
     }
 
     /// Remove all history.
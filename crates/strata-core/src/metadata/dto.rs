@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use strata_abi::metadata::ModelCoreInfo;
+use strata_abi::metadata::{ModelCoreInfo, ModelGeometry, TensorTypeCount};
 
 /// Matches the UI’s `ModelMeta` (snake_case -> JSON).
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,9 +15,29 @@ pub struct ModelMetaOut {
     pub eos_token_id: Option<i32>,
     pub bos_token_id: Option<i32>,
 
-    /// "ChatMl" | "InstBlock" | "UserAssistant" | "Plain" | "Phi3"
+    /// "ChatMl" | "InstBlock" | "UserAssistant" | "Plain" | "Phi3" | "Llama3" | "Gemma"
     pub prompt_flavor_hint: Option<String>,
     pub has_chat_template: bool,
+    /// No chat template and no user-chosen formatter/template yet (the app may refine this).
+    #[serde(default)]
+    pub needs_template: bool,
+    /// Template reads `enable_thinking`, so reasoning can be switched off per request.
+    #[serde(default)]
+    pub supports_thinking_toggle: bool,
+
+    /// Average bits per weight over all tensors.
+    #[serde(default)]
+    pub bits_per_weight: Option<f32>,
+    /// Weight share per tensor type, largest first.
+    #[serde(default)]
+    pub tensor_types: Vec<TensorTypeCount>,
+    /// Output projection type when it differs from the bulk (e.g. "q6_K").
+    #[serde(default)]
+    pub output_tensor_type: Option<String>,
+
+    /// Layer/head shape and weight bytes, when the format exposes them.
+    #[serde(default)]
+    pub geometry: Option<ModelGeometry>,
 
     /// Optional passthrough for advanced/debug views.
     pub raw: Option<std::collections::HashMap<String, String>>,
@@ -43,6 +63,17 @@ pub fn to_ui_meta(s: &ModelCoreInfo) -> ModelMetaOut {
             .as_ref()
             .map(|t| !t.is_empty())
             .unwrap_or(false),
+        needs_template: s.chat_template.as_ref().is_none_or(|t| t.is_empty()),
+        supports_thinking_toggle: s
+            .chat_template
+            .as_ref()
+            .is_some_and(|t| t.contains("enable_thinking")),
+
+        bits_per_weight: s.bits_per_weight,
+        tensor_types: s.tensor_types.clone(),
+        output_tensor_type: s.output_tensor_type.clone(),
+
+        geometry: s.geometry,
 
         raw: if s.raw.is_empty() {
             None
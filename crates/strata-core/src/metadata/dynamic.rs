@@ -1,26 +1,26 @@
-//! Dylib loading utilities for metadata providers.
+//! Dylib loading for C-ABI metadata plugins.
 //!
-//! Safety note:
-//! - This expects plugins compiled with the same Rust toolchain and compatible
-//!   dependency graph as the host (or a C-ABI shim).
-//! - The exported symbol **must** be named `register_plugin` and have the
-//!   signature shown below.
-//! - We keep the `Library` alive for the entire process lifetime via
-//!   `MetadataService::_libs` to avoid dangling vtables.
+//! A plugin exports `strata_plugin_entry_v1` (see `strata_abi::ffi`) returning
+//! a static `PluginApi`; only its `metadata` table is used here. Nothing but
+//! `repr(C)` data and C function pointers crosses the boundary, so plugins
+//! may be built out-of-tree with any toolchain.
 //!
-//! If you intend to support third-party plugins compiled out-of-tree,
-//! strongly consider a C-ABI surface in `strata-abi` (repr(C) vtable) rather
-//! than passing Rust trait objects across the boundary.
+//! Libraries are held by `MetadataService::libs` until
+//! `unload_metadata_plugins`, which drops the adapters first, so the
+//! negotiated `PluginApi` copy held by each adapter never dangles.
 
-use std::ffi::OsStr;
+use std::ffi::{CStr, CString, OsStr};
 use std::path::Path;
 
 use libloading::{Library, Symbol};
+use log::warn;
+use strata_abi::ffi::{
+    ERR_FAIL, FreeStringFn, PLUGIN_ENTRY_SYMBOL, PluginApi, PluginEntryFn, StrataString,
+};
+use strata_abi::metadata::{BackendMetadataProvider, ModelCoreInfo};
 
 use super::MetadataService;
 
-type RegisterFn = unsafe extern "C" fn(&mut MetadataService);
-
 #[inline]
 fn is_dylib(path: &Path) -> bool {
     match path.extension().and_then(OsStr::to_str) {
@@ -31,21 +31,80 @@ fn is_dylib(path: &Path) -> bool {
     }
 }
 
-/// Attempt to load a single dylib and call its `register_plugin` function.
-/// On success, the `lib` is retained by the service to keep the plugin alive.
-unsafe fn load_one(service: &mut MetadataService, path: &Path) -> Result<(), String> {
-    let lib = Library::new(path).map_err(|e| format!("dlopen {}: {e}", path.display()))?;
+/// `BackendMetadataProvider` over a plugin's `MetadataApi`.
+struct PluginMetadataProvider {
+    /// Plugin id from `PluginInfo`, for error messages.
+    id: String,
+    api: PluginApi,
+}
+
+// The plugin's tables are immutable statics and its entry points are
+// required to be thread-safe.
+unsafe impl Send for PluginMetadataProvider {}
+unsafe impl Sync for PluginMetadataProvider {}
 
-    // Convention: each plugin exports `register_plugin`.
-    let func: Symbol<RegisterFn> = lib
-        .get(b"register_plugin")
-        .map_err(|e| format!("dlsym(register_plugin) {}: {e}", path.display()))?;
+/// Copy a plugin-owned string, then release it with the plugin's `free`.
+unsafe fn take_string(s: StrataString, free: FreeStringFn) -> Option<String> {
+    if s.ptr.is_null() {
+        return None;
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(s.ptr as *const u8, s.len) };
+    let out = String::from_utf8_lossy(bytes).into_owned();
+    unsafe { free(s) };
+    Some(out)
+}
+
+impl BackendMetadataProvider for PluginMetadataProvider {
+    fn can_handle(&self, file: &Path) -> bool {
+        let Some(c_path) = file.to_str().and_then(|s| CString::new(s).ok()) else {
+            return false;
+        };
+        unsafe { (self.api.metadata.can_handle)(c_path.as_ptr()) }
+    }
+
+    fn collect(&self, file: &Path) -> Result<ModelCoreInfo, String> {
+        let c_path = file
+            .to_str()
+            .and_then(|s| CString::new(s).ok())
+            .ok_or_else(|| format!("path not representable for plugin: {}", file.display()))?;
+        let js = unsafe { (self.api.metadata.collect_json)(c_path.as_ptr()) };
+        let js = unsafe { take_string(js, self.api.metadata.free_string) }.ok_or_else(|| {
+            let err = unsafe {
+                self.api
+                    .error(std::ptr::null_mut(), ERR_FAIL, "collect_json")
+            };
+            format!("plugin {}: {err}", self.id)
+        })?;
+        serde_json::from_str(&js).map_err(|e| format!("plugin {}: bad metadata JSON: {e}", self.id))
+    }
+
+    fn priority(&self) -> i32 {
+        self.api.metadata.priority
+    }
+}
+
+/// Load a single dylib, check its ABI version and register its metadata
+/// provider. On success, the `lib` is retained by the service.
+unsafe fn load_one(service: &mut MetadataService, path: &Path) -> Result<(), String> {
+    let lib =
+        unsafe { Library::new(path) }.map_err(|e| format!("dlopen {}: {e}", path.display()))?;
 
-    // Allow plugin to register one or more providers.
-    func(service);
+    let entry: Symbol<PluginEntryFn> = unsafe { lib.get(PLUGIN_ENTRY_SYMBOL.as_bytes()) }
+        .map_err(|e| format!("dlsym({PLUGIN_ENTRY_SYMBOL}) {}: {e}", path.display()))?;
+    // Pointers inside stay valid for as long as `lib` is loaded, i.e. until
+    // the service drops this plugin's adapter along with it.
+    let api = unsafe { strata_abi::ffi::negotiate(entry()) }
+        .map_err(|e| format!("{}: {e}", path.display()))?;
+    let id = if api.info.id.is_null() {
+        path.display().to_string()
+    } else {
+        unsafe { CStr::from_ptr(api.info.id) }
+            .to_string_lossy()
+            .into_owned()
+    };
 
-    // Keep the library alive for the process lifetime.
-    service._libs.push(lib);
+    crate::logging::attach_plugin_logger(&api);
+    service.register_plugin(Box::new(PluginMetadataProvider { id, api }), lib);
     Ok(())
 }
 
@@ -64,11 +123,7 @@ pub(super) fn load_dir_into(service: &mut MetadataService, dir: &Path) -> Result
         unsafe {
             // Each plugin is isolated; we keep loading even if one fails.
             if let Err(e) = load_one(service, &path) {
-                eprintln!(
-                    "[metadata][warn] failed to load plugin {}: {}",
-                    path.display(),
-                    e
-                );
+                warn!("failed to load plugin {}: {e}", path.display());
             }
         }
     }
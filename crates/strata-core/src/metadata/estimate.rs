@@ -0,0 +1,346 @@
+//! Memory estimate for a model at given session parameters, and a
+//! "will it fit" verdict against the machine's RAM/VRAM.
+//!
+//! Figures are approximations of what llama.cpp allocates: weights as stored,
+//! a standard per-layer K/V cache, and a compute buffer dominated by the logits
+//! and (without flash attention) the attention score matrix.
+
+use serde::{Deserialize, Serialize};
+use strata_abi::metadata::ModelGeometry;
+use strata_abi::session::SessionParams;
+
+const GIB: u64 = 1 << 30;
+/// Fixed allowance for graph metadata, output buffers and allocator slack.
+const COMPUTE_OVERHEAD: u64 = 64 << 20;
+/// Suggested context sizes are multiples of this.
+const CTX_STEP: u32 = 256;
+
+/// Estimated bytes for one session.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MemoryEstimate {
+    /// Context the estimate was made for (0 resolved to the training context).
+    pub n_ctx: u32,
+    pub weights_bytes: u64,
+    pub kv_bytes: u64,
+    pub compute_bytes: u64,
+    pub total_bytes: u64,
+}
+
+/// Memory the host can give a model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryBudget {
+    /// Installed system RAM.
+    pub ram_bytes: u64,
+    /// VRAM of a GPU a backend can offload to (0 = none).
+    pub vram_bytes: u64,
+}
+
+impl MemoryBudget {
+    /// RAM left after reserving room for the OS and other apps
+    /// (an eighth of RAM, at least 1 GiB).
+    pub fn usable_ram(&self) -> u64 {
+        self.ram_bytes.saturating_sub((self.ram_bytes / 8).max(GIB))
+    }
+
+    /// Usable RAM plus VRAM (layers split across both).
+    pub fn usable_total(&self) -> u64 {
+        self.usable_ram() + self.vram_bytes
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FitVerdict {
+    /// Fits in RAM with headroom to spare.
+    Fits,
+    /// Fits only if part of the model is offloaded to the GPU.
+    NeedsGpuOffload,
+    /// Fits in installed RAM but eats the OS reserve; expect swapping.
+    Tight,
+    /// Larger than RAM and VRAM combined.
+    TooLarge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FitReport {
+    pub estimate: MemoryEstimate,
+    pub budget: MemoryBudget,
+    pub verdict: FitVerdict,
+    /// Largest context (multiple of 256, capped at the training context)
+    /// whose estimate fits the usable budget; `None` if even the weights don't.
+    pub max_n_ctx: Option<u32>,
+}
+
+/// Per-session costs: a fixed part and parts linear in `n_ctx`.
+struct CostModel {
+    /// K/V bytes per cached token across all layers.
+    kv_per_token: f64,
+    /// Compute-buffer bytes per context token (attention scores).
+    scores_per_token: f64,
+    /// Compute-buffer bytes independent of `n_ctx`.
+    compute_fixed: u64,
+}
+
+impl CostModel {
+    fn new(geom: &ModelGeometry, params: &SessionParams) -> Self {
+        let g = |v: u32| v as f64;
+        let n_ubatch = g(params.n_ubatch.max(1));
+
+        let kv_per_token = g(geom.n_layer)
+            * g(geom.n_head_kv)
+            * (g(geom.head_dim_k) * params.type_k.bits_per_element()
+                + g(geom.head_dim_v) * params.type_v.bits_per_element())
+            / 8.0;
+
+        // Without flash attention each ubatch materializes n_head × n_ctx f32 scores.
+        let scores_per_token = if params.flash_attn == Some(true) {
+            0.0
+        } else {
+            n_ubatch * g(geom.n_head) * 4.0
+        };
+
+        // Logits for a full ubatch plus a few n_embd-wide activations per token.
+        let activations = n_ubatch * (g(geom.n_vocab) + 8.0 * g(geom.n_embd)) * 4.0;
+
+        Self {
+            kv_per_token,
+            scores_per_token,
+            compute_fixed: activations as u64 + COMPUTE_OVERHEAD,
+        }
+    }
+
+    fn per_token(&self) -> f64 {
+        self.kv_per_token + self.scores_per_token
+    }
+}
+
+fn resolve_n_ctx(geom: &ModelGeometry, params: &SessionParams) -> u32 {
+    if params.n_ctx > 0 {
+        params.n_ctx
+    } else {
+        geom.n_ctx_train
+    }
+}
+
+/// Estimate the memory a session with `params` would take.
+pub fn estimate_memory(geom: &ModelGeometry, params: &SessionParams) -> MemoryEstimate {
+    let params = params.normalized();
+    let n_ctx = resolve_n_ctx(geom, &params);
+    let cost = CostModel::new(geom, &params);
+
+    let kv_bytes = (n_ctx as f64 * cost.kv_per_token) as u64;
+    let compute_bytes = cost.compute_fixed + (n_ctx as f64 * cost.scores_per_token) as u64;
+
+    MemoryEstimate {
+        n_ctx,
+        weights_bytes: geom.weights_bytes,
+        kv_bytes,
+        compute_bytes,
+        total_bytes: geom.weights_bytes + kv_bytes + compute_bytes,
+    }
+}
+
+/// Estimate `params` against `budget` and find the largest context that fits.
+pub fn check_fit(geom: &ModelGeometry, params: &SessionParams, budget: MemoryBudget) -> FitReport {
+    let estimate = estimate_memory(geom, params);
+    let total = estimate.total_bytes;
+
+    let verdict = if total <= budget.usable_ram() {
+        FitVerdict::Fits
+    } else if budget.vram_bytes > 0 && total <= budget.usable_total() {
+        FitVerdict::NeedsGpuOffload
+    } else if total <= budget.ram_bytes + budget.vram_bytes {
+        FitVerdict::Tight
+    } else {
+        FitVerdict::TooLarge
+    };
+
+    let cost = CostModel::new(geom, &params.normalized());
+    let max_n_ctx = budget
+        .usable_total()
+        .checked_sub(geom.weights_bytes + cost.compute_fixed)
+        .map(|room| {
+            let n = if cost.per_token() > 0.0 {
+                (room as f64 / cost.per_token()).min(u32::MAX as f64) as u32
+            } else {
+                u32::MAX
+            };
+            let cap = if geom.n_ctx_train > 0 {
+                geom.n_ctx_train
+            } else {
+                u32::MAX
+            };
+            n.min(cap) / CTX_STEP * CTX_STEP
+        })
+        .filter(|&n| n > 0);
+
+    FitReport {
+        estimate,
+        budget,
+        verdict,
+        max_n_ctx,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strata_abi::session::KvCacheType;
+
+    const MIB: u64 = 1 << 20;
+
+    /// Llama-2-7B shape with 4 GiB of weights.
+    fn llama7b() -> ModelGeometry {
+        ModelGeometry {
+            weights_bytes: 4 * GIB,
+            n_layer: 32,
+            n_embd: 4096,
+            n_head: 32,
+            n_head_kv: 32,
+            head_dim_k: 128,
+            head_dim_v: 128,
+            n_ctx_train: 4096,
+            n_vocab: 32000,
+        }
+    }
+
+    /// Logits and activations for the default ubatch of 16, plus the overhead.
+    const COMPUTE_FIXED: u64 = 16 * (32000 + 8 * 4096) * 4 + COMPUTE_OVERHEAD;
+
+    #[test]
+    fn known_geometry() {
+        let est = estimate_memory(&llama7b(), &SessionParams::default());
+        assert_eq!(est.n_ctx, 4096);
+        // 32 layers × 32 heads × (128 + 128) f16 values per token.
+        assert_eq!(est.kv_bytes, 2 * GIB);
+        // 16 × 32 f32 scores per context token without flash attention.
+        assert_eq!(est.compute_bytes, COMPUTE_FIXED + 8 * MIB);
+        assert_eq!(est.total_bytes, 4 * GIB + 2 * GIB + COMPUTE_FIXED + 8 * MIB);
+    }
+
+    #[test]
+    fn zero_ctx_uses_training_context() {
+        let geom = ModelGeometry {
+            n_ctx_train: 8192,
+            ..llama7b()
+        };
+        let params = SessionParams {
+            n_ctx: 0,
+            ..Default::default()
+        };
+        let est = estimate_memory(&geom, &params);
+        assert_eq!(est.n_ctx, 8192);
+        assert_eq!(est.kv_bytes, 4 * GIB);
+    }
+
+    #[test]
+    fn gqa_shrinks_kv() {
+        let geom = ModelGeometry {
+            n_head_kv: 8,
+            ..llama7b()
+        };
+        let est = estimate_memory(&geom, &SessionParams::default());
+        assert_eq!(est.kv_bytes, 512 * MIB);
+        // Scores scale with query heads, not KV heads.
+        assert_eq!(est.compute_bytes, COMPUTE_FIXED + 8 * MIB);
+    }
+
+    #[test]
+    fn quantized_kv() {
+        let params = SessionParams {
+            type_k: KvCacheType::Q8_0,
+            type_v: KvCacheType::Q8_0,
+            flash_attn: Some(true),
+            ..Default::default()
+        };
+        let est = estimate_memory(&llama7b(), &params);
+        // 8.5 bits per element: 34 bytes per block of 32.
+        assert_eq!(est.kv_bytes, 4096 * 32 * 32 * 272);
+
+        // Without flash attention the V cache falls back to f16.
+        let params = SessionParams {
+            flash_attn: Some(false),
+            ..params
+        };
+        let est = estimate_memory(&llama7b(), &params);
+        assert_eq!(est.kv_bytes, 4096 * 32 * 32 * (136 + 256));
+    }
+
+    #[test]
+    fn flash_attention_drops_score_buffer() {
+        let on = SessionParams {
+            flash_attn: Some(true),
+            ..Default::default()
+        };
+        let off = SessionParams {
+            flash_attn: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(
+            estimate_memory(&llama7b(), &on).compute_bytes,
+            COMPUTE_FIXED
+        );
+        assert_eq!(
+            estimate_memory(&llama7b(), &off).compute_bytes,
+            COMPUTE_FIXED + 8 * MIB
+        );
+    }
+
+    #[test]
+    fn max_ctx_rounds_down_and_caps_at_training_context() {
+        // 14 GiB usable: 10 GiB left after the weights.
+        let budget = MemoryBudget {
+            ram_bytes: 16 * GIB,
+            vram_bytes: 0,
+        };
+        let params = SessionParams::default();
+
+        let report = check_fit(&llama7b(), &params, budget);
+        assert_eq!(report.verdict, FitVerdict::Fits);
+        assert_eq!(report.max_n_ctx, Some(4096));
+
+        // Uncapped: (10 GiB - fixed compute) / (512 KiB KV + 2 KiB scores)
+        // is 20264 tokens, rounded down to a multiple of 256.
+        let uncapped = ModelGeometry {
+            n_ctx_train: 0,
+            ..llama7b()
+        };
+        let report = check_fit(&uncapped, &params, budget);
+        assert_eq!(report.max_n_ctx, Some(20224));
+    }
+
+    #[test]
+    fn verdicts() {
+        let geom = ModelGeometry {
+            weights_bytes: 20 * GIB,
+            ..llama7b()
+        };
+        let params = SessionParams::default();
+        let ram = |ram_gib, vram_gib| MemoryBudget {
+            ram_bytes: ram_gib * GIB,
+            vram_bytes: vram_gib * GIB,
+        };
+
+        assert_eq!(
+            check_fit(&geom, &params, ram(32, 0)).verdict,
+            FitVerdict::Fits
+        );
+        assert_eq!(
+            check_fit(&geom, &params, ram(16, 10)).verdict,
+            FitVerdict::NeedsGpuOffload
+        );
+        assert_eq!(
+            check_fit(&geom, &params, ram(23, 0)).verdict,
+            FitVerdict::Tight
+        );
+        assert_eq!(
+            check_fit(&geom, &params, ram(8, 0)),
+            FitReport {
+                estimate: estimate_memory(&geom, &params),
+                budget: ram(8, 0),
+                verdict: FitVerdict::TooLarge,
+                max_n_ctx: None,
+            }
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! Built-in GGUF metadata provider (header-only, no backend plugin needed).
+
+use std::path::Path;
+
+use strata_abi::backend::PromptFlavor;
+use strata_abi::metadata::{
+    BackendMetadataProvider, ModelCoreInfo, ModelGeometry, TensorTypeCount,
+};
+use strata_gguf::{GgufFile, file_type_label};
+
+/// Describes `.gguf` files (whole split sets included) straight from their
+/// headers; they run on the llama backend.
+pub struct GgufMetadataProvider;
+
+impl BackendMetadataProvider for GgufMetadataProvider {
+    fn can_handle(&self, file: &Path) -> bool {
+        file.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("gguf"))
+    }
+
+    fn collect(&self, file: &Path) -> Result<ModelCoreInfo, String> {
+        // Split models: metadata from the first file, tensors from all of them.
+        let gguf = GgufFile::open_split(file)?;
+
+        let chat_template = gguf
+            .chat_template()
+            .filter(|t| !t.is_empty())
+            .map(str::to_string);
+        let flavor = PromptFlavor::infer(chat_template.as_deref(), gguf.architecture());
+
+        let token_id = |key: &str| gguf.get_i64(key).and_then(|v| i32::try_from(v).ok());
+        let tensor_types = tensor_types(&gguf);
+        let quantization = gguf
+            .get_str("general.quantization")
+            .or_else(|| {
+                let code = gguf.get_u64("general.file_type")?;
+                file_type_label(u32::try_from(code).ok()?)
+            })
+            .map(str::to_string)
+            .or_else(|| tensor_types.first().map(|t| t.ggml_type.to_uppercase()));
+
+        Ok(ModelCoreInfo {
+            name: gguf.get_str("general.name").map(str::to_string),
+            family: gguf
+                .architecture()
+                .or_else(|| gguf.get_str("general.basename"))
+                .map(str::to_string),
+            backend: "llama".into(),
+            path: file.to_path_buf(),
+            file_type: "gguf".into(),
+            context_length: gguf.context_length().and_then(|n| u32::try_from(n).ok()),
+            vocab_size: gguf.vocab_size().and_then(|n| u32::try_from(n).ok()),
+            eos_token_id: token_id("tokenizer.ggml.eos_token_id"),
+            bos_token_id: token_id("tokenizer.ggml.bos_token_id"),
+            quantization,
+            chat_template,
+            prompt_flavor_hint: Some(flavor.as_str().to_string()),
+            geometry: geometry(&gguf, file),
+            bits_per_weight: gguf.bits_per_weight().map(|b| b as f32),
+            output_tensor_type: output_tensor_type(&gguf, &tensor_types),
+            tensor_types,
+            raw: gguf.flatten(),
+        })
+    }
+}
+
+/// Shape from the `<arch>.*` keys; weight bytes from the tensor table, or
+/// everything after the header when a tensor type is unknown.
+fn geometry(gguf: &GgufFile, file: &Path) -> Option<ModelGeometry> {
+    let d = gguf.model_dims()?;
+    let weights_bytes = gguf.tensor_data_size().unwrap_or_else(|| {
+        std::fs::metadata(file)
+            .map(|m| m.len().saturating_sub(gguf.data_offset))
+            .unwrap_or(0)
+    });
+    let n = |v: u64| u32::try_from(v).unwrap_or(u32::MAX);
+    Some(ModelGeometry {
+        weights_bytes,
+        n_layer: n(d.n_layer),
+        n_embd: n(d.n_embd),
+        n_head: n(d.n_head),
+        n_head_kv: n(d.n_head_kv),
+        head_dim_k: n(d.head_dim_k),
+        head_dim_v: n(d.head_dim_v),
+        n_ctx_train: n(d.n_ctx_train),
+        n_vocab: n(d.n_vocab),
+    })
+}
+
+fn tensor_types(gguf: &GgufFile) -> Vec<TensorTypeCount> {
+    gguf.tensor_type_histogram()
+        .into_iter()
+        .map(|s| TensorTypeCount {
+            ggml_type: s.ggml_type.name().to_string(),
+            n_tensors: u32::try_from(s.n_tensors).unwrap_or(u32::MAX),
+            n_elements: s.n_elements,
+            bytes: s.bytes,
+        })
+        .collect()
+}
+
+/// `output.weight`'s type, when it differs from the most common weight type.
+fn output_tensor_type(gguf: &GgufFile, types: &[TensorTypeCount]) -> Option<String> {
+    let out = gguf.output_tensor_type()?.name();
+    (types.first().map(|t| t.ggml_type.as_str()) != Some(out)).then(|| out.to_string())
+}
@@ -0,0 +1,88 @@
+//! Hugging Face-style model folders: `config.json` + weights + tokenizer files.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Tokenizer and generation sidecars shipped next to HF weights.
+const TOKENIZER_FILES: &[&str] = &[
+    "tokenizer.json",
+    "tokenizer_config.json",
+    "tokenizer.model",
+    "special_tokens_map.json",
+    "added_tokens.json",
+    "vocab.json",
+    "vocab.txt",
+    "merges.txt",
+    "chat_template.jinja",
+    "chat_template.json",
+    "generation_config.json",
+];
+
+/// Files making up one folder model.
+#[derive(Debug, Clone)]
+pub struct HfModelDir {
+    pub dir: PathBuf,
+    pub config: PathBuf,
+    /// Weight files, sorted ("model-00001-of-00002.safetensors", …).
+    pub weights: Vec<PathBuf>,
+    /// "safetensors" or "bin".
+    pub weight_format: &'static str,
+    /// Tokenizer/generation sidecars present, sorted.
+    pub tokenizer_files: Vec<PathBuf>,
+}
+
+impl HfModelDir {
+    /// Bytes of config, weights and tokenizer files.
+    pub fn total_size(&self) -> u64 {
+        std::iter::once(&self.config)
+            .chain(&self.weights)
+            .chain(&self.tokenizer_files)
+            .filter_map(|p| fs::metadata(p).ok())
+            .map(|m| m.len())
+            .sum()
+    }
+}
+
+/// Describe `dir` if it holds `config.json` and top-level weights
+/// (safetensors preferred, else `pytorch_model*.bin`).
+pub fn hf_model_dir(dir: &Path) -> Option<HfModelDir> {
+    let config = dir.join("config.json");
+    if !config.is_file() {
+        return None;
+    }
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file())
+        .collect();
+    files.sort();
+
+    let name = |p: &Path| {
+        p.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase()
+    };
+    let pick = |keep: &dyn Fn(&str) -> bool| -> Vec<PathBuf> {
+        files.iter().filter(|p| keep(&name(p))).cloned().collect()
+    };
+
+    let safetensors = pick(&|n| n.ends_with(".safetensors") && !n.starts_with("adapter_"));
+    let (weights, weight_format) = if !safetensors.is_empty() {
+        (safetensors, "safetensors")
+    } else {
+        let bin = pick(&|n| n.starts_with("pytorch_model") && n.ends_with(".bin"));
+        if bin.is_empty() {
+            return None;
+        }
+        (bin, "bin")
+    };
+
+    Some(HfModelDir {
+        dir: dir.to_path_buf(),
+        config,
+        weights,
+        weight_format,
+        tokenizer_files: pick(&|n| TOKENIZER_FILES.contains(&n)),
+    })
+}
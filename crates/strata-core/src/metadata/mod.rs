@@ -4,10 +4,15 @@
 //! - `MetadataService` lives in this parent module so child modules can access
 //!   its private fields without making them pub(crate).
 //! - `service.rs` implements the registry & public API.
-//! - `dynamic.rs` contains unsafe dylib utilities (kept small & isolated).
+//! - `dynamic.rs` loads C-ABI plugin dylibs and adapts their `MetadataApi` (unsafe, kept small & isolated).
 //! - `dto.rs` holds UI-facing DTO + mapping.
+//! - `gguf.rs` is the built-in header-only GGUF provider.
+//! - `safetensors.rs` is the built-in safetensors (+ HF sidecar files) provider.
+//! - `hf_dir.rs` recognizes Hugging Face model folders (config + weights + tokenizer).
+//! - `onnx.rs` is the built-in ONNX provider (protobuf walk in `onnx/proto.rs`).
+//! - `estimate.rs` turns model geometry into a memory estimate / fit verdict.
 //!
-//! NOTE: Dynamic plugins require ABI care. See docs in `dynamic.rs`.
+//! Providers resolve by `BackendMetadataProvider::priority`, highest first.
 
 use libloading::Library;
 use std::path::Path;
@@ -16,28 +21,64 @@ use strata_abi::metadata::{BackendMetadataProvider, ModelCoreInfo};
 /// In-process registry of metadata providers (static + dynamic).
 /// Private fields; only child modules may touch them.
 struct MetadataService {
-    providers: Vec<Box<dyn BackendMetadataProvider>>,
-    /// Keep libraries alive for the duration of the process to ensure any
-    /// provider vtables / function pointers remain valid.
-    _libs: Vec<Library>,
+    providers: Vec<Registered>,
+    /// Libraries of the plugin providers, kept loaded until `unload_plugins`
+    /// so their function pointers remain valid.
+    libs: Vec<Library>,
+}
+
+/// A provider and whether it came from a plugin library.
+struct Registered {
+    provider: Box<dyn BackendMetadataProvider>,
+    from_plugin: bool,
 }
 
 impl MetadataService {
     fn new() -> Self {
         Self {
             providers: Vec::new(),
-            _libs: Vec::new(),
+            libs: Vec::new(),
         }
     }
 
-    /// Append a provider to the registry (registration order == resolution order).
     fn register(&mut self, p: Box<dyn BackendMetadataProvider>) {
-        self.providers.push(p);
+        self.insert(Registered {
+            provider: p,
+            from_plugin: false,
+        });
+    }
+
+    /// Register a provider whose code lives in `lib`.
+    fn register_plugin(&mut self, p: Box<dyn BackendMetadataProvider>, lib: Library) {
+        self.insert(Registered {
+            provider: p,
+            from_plugin: true,
+        });
+        self.libs.push(lib);
+    }
+
+    /// Insert after every provider of equal or higher priority, so
+    /// resolution order is priority first, then registration order.
+    fn insert(&mut self, r: Registered) {
+        let priority = r.provider.priority();
+        let at = self
+            .providers
+            .partition_point(|q| q.provider.priority() >= priority);
+        self.providers.insert(at, r);
     }
 
-    /// Find the first provider that claims to handle this file and collect metadata.
+    /// Drop the plugin providers, then their libraries. Returns how many
+    /// libraries were released.
+    fn unload_plugins(&mut self) -> usize {
+        self.providers.retain(|r| !r.from_plugin);
+        let n = self.libs.len();
+        self.libs.clear();
+        n
+    }
+
+    /// Find the highest-priority provider that claims to handle this file and collect metadata.
     fn collect_for(&self, file: &Path) -> Result<ModelCoreInfo, String> {
-        for p in &self.providers {
+        for p in self.providers.iter().map(|r| &r.provider) {
             if p.can_handle(file) {
                 return p.collect(file);
             }
@@ -53,11 +94,28 @@ impl MetadataService {
 mod service;
 pub use service::{
     collect_model_metadata, load_metadata_plugins, register_backend_metadata_provider,
+    unload_metadata_plugins,
 };
 
 // UI DTOs + mapper.
 mod dto;
 pub use dto::{ModelMetaOut, to_ui_meta};
 
+// Memory estimate + fit check.
+mod estimate;
+pub use estimate::{
+    FitReport, FitVerdict, MemoryBudget, MemoryEstimate, check_fit, estimate_memory,
+};
+
+// Built-in providers (registered by the host at startup).
+mod gguf;
+mod hf_dir;
+mod onnx;
+mod safetensors;
+pub use gguf::GgufMetadataProvider;
+pub use hf_dir::{HfModelDir, hf_model_dir};
+pub use onnx::OnnxMetadataProvider;
+pub use safetensors::SafetensorsMetadataProvider;
+
 // Unsafe/dylib helpers are kept private to this module.
 mod dynamic;
@@ -0,0 +1,174 @@
+//! Built-in ONNX metadata provider (pure-Rust protobuf header walk).
+//!
+//! ONNX-specific facts go into `ModelCoreInfo::raw` under `onnx.*` so an ONNX
+//! backend can route on them:
+//! - `onnx.ir_version`, `onnx.opset` (default domain), `onnx.opset.<domain>`
+//! - `onnx.producer` ("name version"), `onnx.domain`, `onnx.model_version`
+//! - `onnx.inputs` / `onnx.outputs`: `name: dtype[dim, …]` joined by "; "
+//! - `onnx.initializers`, `onnx.initializer_bytes`, `onnx.external_data`
+//! - `onnx.meta.<key>` for each `metadata_props` entry
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use strata_abi::metadata::{BackendMetadataProvider, ModelCoreInfo, TensorTypeCount};
+
+mod proto;
+
+use proto::{Dim, OnnxModel, ValueInfo, data_type};
+
+/// Describes `.onnx` files; they run on an ONNX backend.
+pub struct OnnxMetadataProvider;
+
+impl BackendMetadataProvider for OnnxMetadataProvider {
+    fn can_handle(&self, file: &Path) -> bool {
+        file.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("onnx"))
+    }
+
+    fn collect(&self, file: &Path) -> Result<ModelCoreInfo, String> {
+        let f = File::open(file).map_err(|e| format!("open {}: {e}", file.display()))?;
+        let len = f
+            .metadata()
+            .map_err(|e| format!("stat {}: {e}", file.display()))?
+            .len();
+        let model = proto::read_model(BufReader::new(f), len)
+            .map_err(|e| format!("{}: {e}", file.display()))?;
+
+        let tensor_types = initializer_histogram(&model);
+        // Dims come from the file: saturate rather than overflow.
+        let n_params = tensor_types
+            .iter()
+            .map(|t| t.n_elements)
+            .fold(0u64, u64::saturating_add);
+        let weight_bytes = tensor_types
+            .iter()
+            .filter_map(|t| t.bytes)
+            .fold(0u64, u64::saturating_add);
+
+        let meta = |key: &str| {
+            model
+                .metadata_props
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.clone())
+        };
+
+        Ok(ModelCoreInfo {
+            name: Some(model.graph_name.clone())
+                .filter(|n| !n.is_empty())
+                .or_else(|| file.file_stem().map(|s| s.to_string_lossy().into_owned())),
+            family: meta("model_type").or_else(|| Some("onnx".into())),
+            backend: "onnx".into(),
+            path: file.to_path_buf(),
+            file_type: "onnx".into(),
+            context_length: None,
+            vocab_size: None,
+            eos_token_id: None,
+            bos_token_id: None,
+            quantization: tensor_types.first().map(|t| t.ggml_type.to_uppercase()),
+            chat_template: None,
+            prompt_flavor_hint: None,
+            geometry: None,
+            bits_per_weight: (n_params > 0)
+                .then(|| (weight_bytes as f64 * 8.0 / n_params as f64) as f32),
+            tensor_types,
+            output_tensor_type: None,
+            raw: raw_map(&model, weight_bytes),
+        })
+    }
+}
+
+/// Initializer counts, elements and bytes per data type, largest first.
+fn initializer_histogram(model: &OnnxModel) -> Vec<TensorTypeCount> {
+    let mut out: Vec<TensorTypeCount> = Vec::new();
+    for t in &model.initializers {
+        let (name, bits) = data_type(t.data_type);
+        let n_elements = t
+            .dims
+            .iter()
+            .try_fold(1u64, |acc, &d| acc.checked_mul(u64::try_from(d).ok()?))
+            .unwrap_or(0);
+        let bytes = bits.map(|b| (n_elements.saturating_mul(b)).div_ceil(8));
+        match out.iter_mut().find(|c| c.ggml_type == name) {
+            Some(c) => {
+                c.n_tensors = c.n_tensors.saturating_add(1);
+                c.n_elements = c.n_elements.saturating_add(n_elements);
+                c.bytes = c.bytes.zip(bytes).map(|(a, b)| a.saturating_add(b));
+            }
+            None => out.push(TensorTypeCount {
+                ggml_type: name.to_string(),
+                n_tensors: 1,
+                n_elements,
+                bytes,
+            }),
+        }
+    }
+    out.sort_by_key(|c| std::cmp::Reverse(c.n_elements));
+    out
+}
+
+fn raw_map(model: &OnnxModel, weight_bytes: u64) -> HashMap<String, String> {
+    let mut raw = HashMap::new();
+    raw.insert("onnx.ir_version".into(), model.ir_version.to_string());
+    for (domain, version) in &model.opset_import {
+        let key = match domain.as_str() {
+            "" | "ai.onnx" => "onnx.opset".to_string(),
+            d => format!("onnx.opset.{d}"),
+        };
+        raw.insert(key, version.to_string());
+    }
+    let producer = format!("{} {}", model.producer_name, model.producer_version);
+    if !producer.trim().is_empty() {
+        raw.insert("onnx.producer".into(), producer.trim().to_string());
+    }
+    if !model.domain.is_empty() {
+        raw.insert("onnx.domain".into(), model.domain.clone());
+    }
+    if model.model_version != 0 {
+        raw.insert("onnx.model_version".into(), model.model_version.to_string());
+    }
+    raw.insert("onnx.inputs".into(), describe_values(&model.inputs));
+    raw.insert("onnx.outputs".into(), describe_values(&model.outputs));
+    raw.insert(
+        "onnx.initializers".into(),
+        model.initializers.len().to_string(),
+    );
+    raw.insert("onnx.initializer_bytes".into(), weight_bytes.to_string());
+    raw.insert(
+        "onnx.external_data".into(),
+        model.initializers.iter().any(|t| t.external).to_string(),
+    );
+    for (k, v) in &model.metadata_props {
+        raw.insert(format!("onnx.meta.{k}"), v.clone());
+    }
+    raw
+}
+
+/// `input_ids: int64[batch, sequence]; attention_mask: int64[batch, sequence]`.
+fn describe_values(values: &[ValueInfo]) -> String {
+    values
+        .iter()
+        .map(|v| {
+            let dtype = data_type(v.elem_type).0;
+            match &v.shape {
+                None => format!("{}: {dtype}", v.name),
+                Some(dims) => {
+                    let dims: Vec<String> = dims
+                        .iter()
+                        .map(|d| match d {
+                            Dim::Value(n) => n.to_string(),
+                            Dim::Param(p) => p.clone(),
+                            Dim::Unknown => "?".into(),
+                        })
+                        .collect();
+                    format!("{}: {dtype}[{}]", v.name, dims.join(", "))
+                }
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -0,0 +1,521 @@
+//! Streaming reader for the parts of an ONNX `ModelProto` we describe.
+//!
+//! Protobuf is walked field by field over a seekable reader; tensor payloads
+//! (`raw_data`, typed data arrays) and node lists are skipped with seeks, so a
+//! multi-GB model with inline weights costs a few KiB of reads per tensor.
+//! Field numbers follow `onnx.proto` (IR v3+).
+
+use std::io::{Read, Seek};
+
+/// Longest string/bytes field we materialize (doc strings, metadata values).
+const MAX_STRING_LEN: u64 = 16 << 20;
+/// Messages nest model → graph → value_info → type → tensor_type → shape → dim.
+const MAX_DEPTH: usize = 16;
+
+#[derive(Debug, Default)]
+pub(super) struct OnnxModel {
+    pub ir_version: i64,
+    pub producer_name: String,
+    pub producer_version: String,
+    pub domain: String,
+    pub model_version: i64,
+    /// `(domain, version)`; "" is the default `ai.onnx` domain.
+    pub opset_import: Vec<(String, i64)>,
+    pub metadata_props: Vec<(String, String)>,
+    pub graph_name: String,
+    pub inputs: Vec<ValueInfo>,
+    pub outputs: Vec<ValueInfo>,
+    pub initializers: Vec<Initializer>,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct ValueInfo {
+    pub name: String,
+    /// `TensorProto.DataType`; 0 for non-tensor values.
+    pub elem_type: i32,
+    /// Fixed sizes or symbolic names; `None` when the rank is unknown.
+    pub shape: Option<Vec<Dim>>,
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum Dim {
+    Value(i64),
+    Param(String),
+    Unknown,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct Initializer {
+    pub data_type: i32,
+    pub dims: Vec<i64>,
+    /// Weights live in a separate file (`data_location = EXTERNAL`).
+    pub external: bool,
+}
+
+/// Protobuf wire types.
+const VARINT: u8 = 0;
+const I64: u8 = 1;
+const LEN: u8 = 2;
+const I32: u8 = 5;
+
+struct Wire<R> {
+    inner: R,
+    pos: u64,
+    depth: usize,
+}
+
+impl<R: Read + Seek> Wire<R> {
+    fn byte(&mut self) -> Result<u8, String> {
+        let mut b = [0u8; 1];
+        self.inner
+            .read_exact(&mut b)
+            .map_err(|e| format!("read at offset {}: {e}", self.pos))?;
+        self.pos += 1;
+        Ok(b[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.byte()?;
+            v |= u64::from(b & 0x7f) << shift;
+            if b & 0x80 == 0 {
+                return Ok(v);
+            }
+        }
+        Err(format!("varint too long at offset {}", self.pos))
+    }
+
+    /// `(field number, wire type)`.
+    fn key(&mut self) -> Result<(u64, u8), String> {
+        let k = self.varint()?;
+        Ok((k >> 3, (k & 7) as u8))
+    }
+
+    fn skip_bytes(&mut self, n: u64) -> Result<(), String> {
+        let n = i64::try_from(n).map_err(|_| format!("field length {n} too large"))?;
+        self.inner
+            .seek_relative(n)
+            .map_err(|e| format!("seek at offset {}: {e}", self.pos))?;
+        self.pos += n as u64;
+        Ok(())
+    }
+
+    fn skip(&mut self, wire_type: u8) -> Result<(), String> {
+        match wire_type {
+            VARINT => self.varint().map(drop),
+            I64 => self.skip_bytes(8),
+            LEN => {
+                let n = self.varint()?;
+                self.skip_bytes(n)
+            }
+            I32 => self.skip_bytes(4),
+            t => Err(format!("unsupported wire type {t} at offset {}", self.pos)),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let n = self.varint()?;
+        if n > MAX_STRING_LEN {
+            return Err(format!("string of {n} bytes at offset {}", self.pos));
+        }
+        let mut buf = Vec::new();
+        let got = (&mut self.inner)
+            .take(n)
+            .read_to_end(&mut buf)
+            .map_err(|e| format!("read at offset {}: {e}", self.pos))?;
+        self.pos += got as u64;
+        if (got as u64) < n {
+            return Err("unexpected end of file in string".into());
+        }
+        Ok(String::from_utf8_lossy(&buf).into_owned())
+    }
+
+    fn int(&mut self, wire_type: u8) -> Result<i64, String> {
+        if wire_type != VARINT {
+            return Err(format!("expected varint at offset {}", self.pos));
+        }
+        Ok(self.varint()? as i64)
+    }
+
+    /// Run `field` for each field of the length-delimited message at the cursor.
+    fn message(
+        &mut self,
+        mut field: impl FnMut(&mut Self, u64, u8) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let len = self.varint()?;
+        let end = self
+            .pos
+            .checked_add(len)
+            .ok_or("message length overflows")?;
+        self.fields(end, &mut field)
+    }
+
+    fn fields(
+        &mut self,
+        end: u64,
+        field: &mut impl FnMut(&mut Self, u64, u8) -> Result<(), String>,
+    ) -> Result<(), String> {
+        if self.depth >= MAX_DEPTH {
+            return Err("protobuf messages nested too deeply".into());
+        }
+        self.depth += 1;
+        while self.pos < end {
+            let (num, wt) = self.key()?;
+            field(self, num, wt)?;
+        }
+        self.depth -= 1;
+        if self.pos != end {
+            return Err(format!("field overruns its message at offset {}", self.pos));
+        }
+        Ok(())
+    }
+
+    /// Repeated int64: packed (LEN) or one varint per occurrence.
+    fn push_ints(&mut self, wire_type: u8, out: &mut Vec<i64>) -> Result<(), String> {
+        if wire_type != LEN {
+            out.push(self.int(wire_type)?);
+            return Ok(());
+        }
+        let len = self.varint()?;
+        let end = self
+            .pos
+            .checked_add(len)
+            .ok_or("packed field length overflows")?;
+        while self.pos < end {
+            out.push(self.varint()? as i64);
+        }
+        if self.pos != end {
+            return Err(format!(
+                "packed varint overruns its field at offset {}",
+                self.pos
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Parse the model header from a reader positioned at the start of a file
+/// of `len` bytes.
+pub(super) fn read_model<R: Read + Seek>(inner: R, len: u64) -> Result<OnnxModel, String> {
+    let mut w = Wire {
+        inner,
+        pos: 0,
+        depth: 0,
+    };
+    let mut m = OnnxModel::default();
+    w.fields(len, &mut |w, num, wt| {
+        match (num, wt) {
+            (1, _) => m.ir_version = w.int(wt)?,
+            (2, LEN) => m.producer_name = w.string()?,
+            (3, LEN) => m.producer_version = w.string()?,
+            (4, LEN) => m.domain = w.string()?,
+            (5, _) => m.model_version = w.int(wt)?,
+            (7, LEN) => read_graph(w, &mut m)?,
+            (8, LEN) => m.opset_import.push(read_opset(w)?),
+            (14, LEN) => m.metadata_props.push(read_string_pair(w)?),
+            _ => w.skip(wt)?,
+        }
+        Ok(())
+    })?;
+    if m.ir_version == 0 && m.opset_import.is_empty() {
+        return Err("not an ONNX model (no ir_version or opset_import)".into());
+    }
+    Ok(m)
+}
+
+fn read_graph<R: Read + Seek>(w: &mut Wire<R>, m: &mut OnnxModel) -> Result<(), String> {
+    w.message(|w, num, wt| {
+        match (num, wt) {
+            (2, LEN) => m.graph_name = w.string()?,
+            (5, LEN) => m.initializers.push(read_tensor(w)?),
+            (11, LEN) => m.inputs.push(read_value_info(w)?),
+            (12, LEN) => m.outputs.push(read_value_info(w)?),
+            _ => w.skip(wt)?,
+        }
+        Ok(())
+    })
+}
+
+/// `OperatorSetIdProto`.
+fn read_opset<R: Read + Seek>(w: &mut Wire<R>) -> Result<(String, i64), String> {
+    let (mut domain, mut version) = (String::new(), 0);
+    w.message(|w, num, wt| {
+        match (num, wt) {
+            (1, LEN) => domain = w.string()?,
+            (2, _) => version = w.int(wt)?,
+            _ => w.skip(wt)?,
+        }
+        Ok(())
+    })?;
+    Ok((domain, version))
+}
+
+/// `StringStringEntryProto`.
+fn read_string_pair<R: Read + Seek>(w: &mut Wire<R>) -> Result<(String, String), String> {
+    let (mut key, mut value) = (String::new(), String::new());
+    w.message(|w, num, wt| {
+        match (num, wt) {
+            (1, LEN) => key = w.string()?,
+            (2, LEN) => value = w.string()?,
+            _ => w.skip(wt)?,
+        }
+        Ok(())
+    })?;
+    Ok((key, value))
+}
+
+/// `TensorProto` header fields; data is skipped.
+fn read_tensor<R: Read + Seek>(w: &mut Wire<R>) -> Result<Initializer, String> {
+    let mut t = Initializer::default();
+    w.message(|w, num, wt| {
+        match (num, wt) {
+            (1, _) => w.push_ints(wt, &mut t.dims)?,
+            (2, _) => t.data_type = w.int(wt)? as i32,
+            (14, _) => t.external = w.int(wt)? == 1,
+            _ => w.skip(wt)?,
+        }
+        Ok(())
+    })?;
+    Ok(t)
+}
+
+/// `ValueInfoProto`; only tensor types are described.
+fn read_value_info<R: Read + Seek>(w: &mut Wire<R>) -> Result<ValueInfo, String> {
+    let mut v = ValueInfo::default();
+    w.message(|w, num, wt| {
+        match (num, wt) {
+            (1, LEN) => v.name = w.string()?,
+            (2, LEN) => read_type(w, &mut v)?,
+            _ => w.skip(wt)?,
+        }
+        Ok(())
+    })?;
+    Ok(v)
+}
+
+/// `TypeProto` → `tensor_type` → `{ elem_type, shape }`.
+fn read_type<R: Read + Seek>(w: &mut Wire<R>, v: &mut ValueInfo) -> Result<(), String> {
+    w.message(|w, num, wt| match (num, wt) {
+        (1, LEN) => w.message(|w, num, wt| {
+            match (num, wt) {
+                (1, _) => v.elem_type = w.int(wt)? as i32,
+                (2, LEN) => v.shape = Some(read_shape(w)?),
+                _ => w.skip(wt)?,
+            }
+            Ok(())
+        }),
+        _ => w.skip(wt),
+    })
+}
+
+/// `TensorShapeProto`: `dim { dim_value | dim_param }`.
+fn read_shape<R: Read + Seek>(w: &mut Wire<R>) -> Result<Vec<Dim>, String> {
+    let mut dims = Vec::new();
+    w.message(|w, num, wt| match (num, wt) {
+        (1, LEN) => {
+            let mut d = Dim::Unknown;
+            w.message(|w, num, wt| {
+                match (num, wt) {
+                    (1, _) => d = Dim::Value(w.int(wt)?),
+                    (2, LEN) => d = Dim::Param(w.string()?),
+                    _ => w.skip(wt)?,
+                }
+                Ok(())
+            })?;
+            dims.push(d);
+            Ok(())
+        }
+        _ => w.skip(wt),
+    })?;
+    Ok(dims)
+}
+
+/// `TensorProto.DataType` name and element size in bits (`None` for strings
+/// and undefined).
+pub(super) fn data_type(code: i32) -> (&'static str, Option<u64>) {
+    match code {
+        1 => ("float", Some(32)),
+        2 => ("uint8", Some(8)),
+        3 => ("int8", Some(8)),
+        4 => ("uint16", Some(16)),
+        5 => ("int16", Some(16)),
+        6 => ("int32", Some(32)),
+        7 => ("int64", Some(64)),
+        8 => ("string", None),
+        9 => ("bool", Some(8)),
+        10 => ("float16", Some(16)),
+        11 => ("double", Some(64)),
+        12 => ("uint32", Some(32)),
+        13 => ("uint64", Some(64)),
+        14 => ("complex64", Some(64)),
+        15 => ("complex128", Some(128)),
+        16 => ("bfloat16", Some(16)),
+        17 => ("float8e4m3fn", Some(8)),
+        18 => ("float8e4m3fnuz", Some(8)),
+        19 => ("float8e5m2", Some(8)),
+        20 => ("float8e5m2fnuz", Some(8)),
+        21 => ("uint4", Some(4)),
+        22 => ("int4", Some(4)),
+        23 => ("float4e2m1", Some(4)),
+        _ => ("undefined", None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn varint(mut v: u64) -> Vec<u8> {
+        let mut out = Vec::new();
+        while v >= 0x80 {
+            out.push(v as u8 | 0x80);
+            v >>= 7;
+        }
+        out.push(v as u8);
+        out
+    }
+
+    fn int_field(num: u64, v: u64) -> Vec<u8> {
+        let mut out = varint(num << 3 | u64::from(VARINT));
+        out.extend(varint(v));
+        out
+    }
+
+    fn len_field(num: u64, body: &[u8]) -> Vec<u8> {
+        let mut out = varint(num << 3 | u64::from(LEN));
+        out.extend(varint(body.len() as u64));
+        out.extend_from_slice(body);
+        out
+    }
+
+    fn read(bytes: &[u8]) -> Result<OnnxModel, String> {
+        read_model(Cursor::new(bytes), bytes.len() as u64)
+    }
+
+    /// `ir_version = 8` followed by a graph holding `graph`.
+    fn model(graph: &[u8]) -> Vec<u8> {
+        let mut out = int_field(1, 8);
+        out.extend(len_field(7, graph));
+        out
+    }
+
+    #[test]
+    fn packed_and_unpacked_dims() {
+        let mut packed = len_field(1, &[2, 3]);
+        packed.extend(int_field(2, 1));
+        let mut unpacked = int_field(1, 4);
+        unpacked.extend(int_field(1, 300));
+        unpacked.extend(int_field(2, 10));
+        unpacked.extend(int_field(14, 1));
+
+        let mut graph = len_field(2, b"g");
+        graph.extend(len_field(5, &packed));
+        graph.extend(len_field(5, &unpacked));
+        let m = read(&model(&graph)).unwrap();
+
+        assert_eq!(m.ir_version, 8);
+        assert_eq!(m.graph_name, "g");
+        assert_eq!(m.initializers.len(), 2);
+        assert_eq!(m.initializers[0].dims, [2, 3]);
+        assert_eq!(m.initializers[0].data_type, 1);
+        assert!(!m.initializers[0].external);
+        assert_eq!(m.initializers[1].dims, [4, 300]);
+        assert_eq!(m.initializers[1].data_type, 10);
+        assert!(m.initializers[1].external);
+    }
+
+    #[test]
+    fn input_shapes() {
+        let mut dims = len_field(1, &int_field(1, 1));
+        dims.extend(len_field(1, &len_field(2, b"seq")));
+        dims.extend(len_field(1, &[]));
+        let mut tensor_type = int_field(1, 7);
+        tensor_type.extend(len_field(2, &dims));
+        let mut input = len_field(1, b"ids");
+        input.extend(len_field(2, &len_field(1, &tensor_type)));
+
+        let m = read(&model(&len_field(11, &input))).unwrap();
+        let input = &m.inputs[0];
+        assert_eq!(input.name, "ids");
+        assert_eq!(input.elem_type, 7);
+        let shape = input.shape.as_ref().unwrap();
+        assert!(matches!(
+            shape.as_slice(),
+            [Dim::Value(1), Dim::Param(p), Dim::Unknown] if p == "seq"
+        ));
+    }
+
+    #[test]
+    fn packed_run_overrunning_its_length_is_an_error() {
+        // Declared 2 bytes, but the second varint takes two.
+        let mut tensor = vec![0x0a, 2, 3, 0x96, 0x01];
+        tensor.extend(int_field(2, 1));
+        let err = read(&model(&len_field(5, &tensor))).unwrap_err();
+        assert!(err.contains("packed varint overruns"), "{err}");
+    }
+
+    #[test]
+    fn truncated_varint_is_an_error() {
+        let err = read(&[0x08, 0x80]).unwrap_err();
+        assert!(err.contains("read at offset 2"), "{err}");
+        let err = read(&[0x80; 11]).unwrap_err();
+        assert!(err.contains("varint too long"), "{err}");
+    }
+
+    #[test]
+    fn field_length_past_eof_is_an_error() {
+        let mut bytes = int_field(1, 8);
+        bytes.extend([0x12, 16, b'a']);
+        let err = read(&bytes).unwrap_err();
+        assert!(err.contains("unexpected end of file"), "{err}");
+
+        // A skipped field seeks past the end instead of reading.
+        let mut bytes = int_field(1, 8);
+        bytes.extend([0x4a, 100, 0]);
+        let err = read(&bytes).unwrap_err();
+        assert!(err.contains("overruns"), "{err}");
+
+        // A graph claiming more bytes than the file holds.
+        let mut bytes = int_field(1, 8);
+        bytes.extend([0x3a, 50]);
+        bytes.extend(len_field(2, b"g"));
+        assert!(read(&bytes).is_err());
+    }
+
+    #[test]
+    fn nesting_is_bounded() {
+        fn nest<R: Read + Seek>(w: &mut Wire<R>) -> Result<(), String> {
+            w.message(|w, _, _| nest(w))
+        }
+
+        let mut body = Vec::new();
+        for _ in 0..MAX_DEPTH + 4 {
+            body = len_field(1, &body);
+        }
+        let len = body.len() as u64;
+        let mut w = Wire {
+            inner: Cursor::new(body),
+            pos: 0,
+            depth: 0,
+        };
+        let err = w
+            .fields(len, &mut |w: &mut Wire<_>, _, _| nest(w))
+            .unwrap_err();
+        assert!(err.contains("nested too deeply"), "{err}");
+    }
+
+    #[test]
+    fn non_onnx_files_are_rejected() {
+        let err = read(b"").unwrap_err();
+        assert!(err.contains("not an ONNX model"), "{err}");
+
+        let err = read(b"GGUF\x03\0\0\0").unwrap_err();
+        assert!(err.contains("unsupported wire type"), "{err}");
+
+        // Well-formed protobuf without any ONNX fields.
+        let err = read(&len_field(9, b"hello")).unwrap_err();
+        assert!(err.contains("not an ONNX model"), "{err}");
+    }
+}
@@ -0,0 +1,356 @@
+//! Built-in safetensors metadata provider.
+//!
+//! Reads only the JSON header of each file (tensor names, dtypes, shapes,
+//! byte ranges) plus the Hugging Face sidecars next to it: `config.json` for
+//! architecture and sizes, `tokenizer_config.json` / `chat_template.jinja` for
+//! the chat template. A shard of a `model-0000x-of-0000y.safetensors` set
+//! describes the whole set; an HF model folder (see [`super::hf_model_dir`])
+//! is described as one model, including `pytorch_model*.bin` folders whose
+//! weights can't be inspected.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use strata_abi::backend::PromptFlavor;
+use strata_abi::metadata::{
+    BackendMetadataProvider, ModelCoreInfo, ModelGeometry, TensorTypeCount,
+};
+
+use super::hf_dir::hf_model_dir;
+
+/// Largest header we accept; real headers are a few MiB at most.
+const MAX_HEADER_LEN: u64 = 100 << 20;
+
+/// Describes `.safetensors` files (single or sharded) and HF model folders;
+/// they run on a transformers-style backend.
+pub struct SafetensorsMetadataProvider;
+
+impl BackendMetadataProvider for SafetensorsMetadataProvider {
+    fn can_handle(&self, file: &Path) -> bool {
+        if file.is_dir() {
+            return hf_model_dir(file).is_some();
+        }
+        file.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("safetensors"))
+    }
+
+    fn collect(&self, file: &Path) -> Result<ModelCoreInfo, String> {
+        let (dir, files, file_type) = if file.is_dir() {
+            let hf = hf_model_dir(file)
+                .ok_or_else(|| format!("{}: not a model folder", file.display()))?;
+            let files = match hf.weight_format {
+                "safetensors" => folder_shards(&hf.weights)?,
+                _ => Vec::new(),
+            };
+            (file, files, hf.weight_format)
+        } else {
+            let dir = file.parent().unwrap_or(Path::new("."));
+            (dir, shard_set(file)?, "safetensors")
+        };
+
+        let mut tensors = Vec::new();
+        let mut raw = HashMap::new();
+        for f in &files {
+            let header = read_header(f)?;
+            tensors.extend(header.tensors);
+            raw.extend(header.metadata);
+        }
+
+        let config = read_json(&dir.join("config.json")).unwrap_or_default();
+        // Multimodal configs nest the language model's sizes under `text_config`.
+        let text = config
+            .get("text_config")
+            .and_then(Value::as_object)
+            .unwrap_or(&config);
+        let num = |keys: &[&str]| -> Option<u64> {
+            keys.iter()
+                .find_map(|k| text.get(*k).or_else(|| config.get(*k)))
+                .and_then(first_u64)
+        };
+
+        let chat_template = chat_template(dir);
+        let family = config
+            .get("model_type")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let flavor = PromptFlavor::infer(chat_template.as_deref(), family.as_deref());
+
+        let tensor_types = dtype_histogram(&tensors);
+        // Header values are untrusted: saturate rather than overflow.
+        let n_params = tensors
+            .iter()
+            .map(|t| t.n_elements)
+            .fold(0u64, u64::saturating_add);
+        let weights_bytes = tensors
+            .iter()
+            .map(|t| t.bytes)
+            .fold(0u64, u64::saturating_add);
+        let quantization = tensor_types
+            .first()
+            .map(|t| t.ggml_type.to_uppercase())
+            .or_else(|| {
+                config
+                    .get("torch_dtype")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+            });
+
+        let ctx_keys = [
+            "max_position_embeddings",
+            "n_positions",
+            "max_seq_len",
+            "seq_length",
+        ];
+        let u32_of = |v: u64| u32::try_from(v).ok();
+        let context_length = num(&ctx_keys).and_then(u32_of);
+        let vocab_size = num(&["vocab_size"]).and_then(u32_of);
+
+        raw.insert("safetensors.shards".into(), files.len().to_string());
+        raw.insert("safetensors.tensors".into(), tensors.len().to_string());
+        raw.insert("general.parameter_count".into(), n_params.to_string());
+        for (k, v) in &config {
+            if !v.is_object() && !v.is_array() {
+                raw.insert(format!("config.{k}"), json_scalar(v));
+            }
+        }
+        if let Some(a) = config
+            .get("architectures")
+            .and_then(Value::as_array)
+            .and_then(|a| a.first())
+            .and_then(Value::as_str)
+        {
+            raw.insert("config.architectures".into(), a.to_string());
+        }
+
+        let geometry = (|| {
+            let n_layer = num(&["num_hidden_layers", "n_layer"])?;
+            let n_embd = num(&["hidden_size", "n_embd"])?;
+            let n_head = num(&["num_attention_heads", "n_head"]).filter(|&n| n > 0)?;
+            let n_head_kv = num(&["num_key_value_heads"]).unwrap_or(n_head);
+            let head_dim = num(&["head_dim"]).unwrap_or(n_embd / n_head);
+            let n = |v: u64| u32::try_from(v).unwrap_or(u32::MAX);
+            Some(ModelGeometry {
+                weights_bytes,
+                n_layer: n(n_layer),
+                n_embd: n(n_embd),
+                n_head: n(n_head),
+                n_head_kv: n(n_head_kv),
+                head_dim_k: n(head_dim),
+                head_dim_v: n(head_dim),
+                n_ctx_train: context_length.unwrap_or(0),
+                n_vocab: vocab_size.unwrap_or(0),
+            })
+        })();
+
+        Ok(ModelCoreInfo {
+            name: config
+                .get("_name_or_path")
+                .and_then(Value::as_str)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .or_else(|| dir.file_name().map(|n| n.to_string_lossy().into_owned())),
+            family,
+            backend: "transformers".into(),
+            path: file.to_path_buf(),
+            file_type: file_type.into(),
+            context_length,
+            vocab_size,
+            eos_token_id: num(&["eos_token_id"]).and_then(|v| i32::try_from(v).ok()),
+            bos_token_id: num(&["bos_token_id"]).and_then(|v| i32::try_from(v).ok()),
+            quantization,
+            chat_template,
+            prompt_flavor_hint: Some(flavor.as_str().to_string()),
+            geometry,
+            bits_per_weight: (n_params > 0)
+                .then(|| (weights_bytes as f64 * 8.0 / n_params as f64) as f32),
+            tensor_types,
+            output_tensor_type: None,
+            raw,
+        })
+    }
+}
+
+struct Tensor {
+    dtype: String,
+    n_elements: u64,
+    bytes: u64,
+}
+
+struct Header {
+    tensors: Vec<Tensor>,
+    /// `__metadata__` string map.
+    metadata: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct TensorEntry {
+    dtype: String,
+    shape: Vec<u64>,
+    data_offsets: [u64; 2],
+}
+
+/// `u64` little-endian header length, then that many bytes of JSON.
+fn read_header(path: &Path) -> Result<Header, String> {
+    let mut f = File::open(path).map_err(|e| format!("open {}: {e}", path.display()))?;
+    let mut len = [0u8; 8];
+    f.read_exact(&mut len)
+        .map_err(|e| format!("{}: {e}", path.display()))?;
+    let len = u64::from_le_bytes(len);
+    if len > MAX_HEADER_LEN {
+        return Err(format!(
+            "{}: safetensors header claims {len} bytes",
+            path.display()
+        ));
+    }
+    let mut buf = Vec::new();
+    f.take(len)
+        .read_to_end(&mut buf)
+        .map_err(|e| format!("{}: {e}", path.display()))?;
+    if (buf.len() as u64) < len {
+        return Err(format!("{}: truncated safetensors header", path.display()));
+    }
+
+    let map: Map<String, Value> = serde_json::from_slice(&buf)
+        .map_err(|e| format!("{}: bad safetensors header: {e}", path.display()))?;
+    let mut header = Header {
+        tensors: Vec::with_capacity(map.len()),
+        metadata: HashMap::new(),
+    };
+    for (name, v) in map {
+        if name == "__metadata__" {
+            if let Value::Object(m) = v {
+                header.metadata = m.iter().map(|(k, v)| (k.clone(), json_scalar(v))).collect();
+            }
+            continue;
+        }
+        let t: TensorEntry = serde_json::from_value(v)
+            .map_err(|e| format!("{}: tensor {name:?}: {e}", path.display()))?;
+        header.tensors.push(Tensor {
+            dtype: t.dtype.to_ascii_lowercase(),
+            n_elements: t.shape.iter().fold(1u64, |a, &d| a.saturating_mul(d)),
+            bytes: t.data_offsets[1].saturating_sub(t.data_offsets[0]),
+        });
+    }
+    Ok(header)
+}
+
+/// Split `model-00002-of-00005.safetensors` into ("model", 2, 5, width 5).
+fn parse_shard_name(file: &Path) -> Option<(String, u32, u32, usize)> {
+    let stem = file.file_stem()?.to_str()?;
+    let (rest, total) = stem.rsplit_once("-of-")?;
+    let (prefix, index) = rest.rsplit_once('-')?;
+    if index.is_empty() || index.len() != total.len() {
+        return None;
+    }
+    let width = total.len();
+    let index: u32 = index.parse().ok()?;
+    let total: u32 = total.parse().ok()?;
+    (1..=total)
+        .contains(&index)
+        .then(|| (prefix.to_string(), index, total, width))
+}
+
+/// Every file of the shard set `file` belongs to, or just `file`.
+/// Errors if a sibling shard is missing.
+fn shard_set(file: &Path) -> Result<Vec<PathBuf>, String> {
+    let Some((prefix, _, total, width)) = parse_shard_name(file) else {
+        return Ok(vec![file.to_path_buf()]);
+    };
+    let dir = file.parent().unwrap_or(Path::new("."));
+    let files: Vec<PathBuf> = (1..=total)
+        .map(|i| {
+            dir.join(format!(
+                "{prefix}-{i:0width$}-of-{total:0width$}.safetensors"
+            ))
+        })
+        .collect();
+    let missing: Vec<String> = files
+        .iter()
+        .filter(|p| !p.is_file())
+        .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!(
+            "incomplete shard set ({} of {total} present); missing {}",
+            total as usize - missing.len(),
+            missing.join(", ")
+        ));
+    }
+    Ok(files)
+}
+
+/// A folder's safetensors files, checking any shard set among them is complete.
+fn folder_shards(weights: &[PathBuf]) -> Result<Vec<PathBuf>, String> {
+    if let Some(shard) = weights.iter().find(|w| parse_shard_name(w).is_some()) {
+        shard_set(shard)?;
+    }
+    Ok(weights.to_vec())
+}
+
+fn dtype_histogram(tensors: &[Tensor]) -> Vec<TensorTypeCount> {
+    let mut out: Vec<TensorTypeCount> = Vec::new();
+    for t in tensors {
+        match out.iter_mut().find(|c| c.ggml_type == t.dtype) {
+            Some(c) => {
+                c.n_tensors = c.n_tensors.saturating_add(1);
+                c.n_elements = c.n_elements.saturating_add(t.n_elements);
+                c.bytes = c.bytes.map(|b| b.saturating_add(t.bytes));
+            }
+            None => out.push(TensorTypeCount {
+                ggml_type: t.dtype.clone(),
+                n_tensors: 1,
+                n_elements: t.n_elements,
+                bytes: Some(t.bytes),
+            }),
+        }
+    }
+    out.sort_by_key(|c| std::cmp::Reverse(c.n_elements));
+    out
+}
+
+/// `tokenizer_config.json`'s `chat_template` (a string, or named templates
+/// where "default" wins), else a standalone `chat_template.jinja`.
+fn chat_template(dir: &Path) -> Option<String> {
+    let from_config = read_json(&dir.join("tokenizer_config.json")).and_then(|c| {
+        match c.get("chat_template")? {
+            Value::String(s) => Some(s.clone()),
+            Value::Array(named) => {
+                let template = |n: &Value| n.get("template")?.as_str().map(str::to_string);
+                named
+                    .iter()
+                    .find(|n| n.get("name").and_then(Value::as_str) == Some("default"))
+                    .or_else(|| named.first())
+                    .and_then(template)
+            }
+            _ => None,
+        }
+    });
+    from_config
+        .or_else(|| std::fs::read_to_string(dir.join("chat_template.jinja")).ok())
+        .filter(|t| !t.is_empty())
+}
+
+fn read_json(path: &Path) -> Option<Map<String, Value>> {
+    let bytes = std::fs::read(path).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// A number, or the first number of an array (e.g. several EOS ids).
+fn first_u64(v: &Value) -> Option<u64> {
+    match v {
+        Value::Array(a) => a.first().and_then(Value::as_u64),
+        v => v.as_u64(),
+    }
+}
+
+fn json_scalar(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    }
+}
@@ -17,15 +17,24 @@ pub fn register_backend_metadata_provider(p: Box<dyn BackendMetadataProvider>) {
     r.register(p);
 }
 
-/// Collect metadata for the given model file using the first provider that can handle it.
+/// Collect metadata for the given model file using the highest-priority provider that can handle it.
 pub fn collect_model_metadata(path: &Path) -> Result<ModelCoreInfo, String> {
     let r = registry().read().expect("metadata registry poisoned");
     r.collect_for(path)
 }
 
-/// Load dynamic plugins from a directory.
-/// Call this once at app startup (after registry init).
+/// Load C-ABI plugins (`strata_plugin_entry_v1`) from a directory and register
+/// their metadata providers.
+/// Call once at app startup, and again after `unload_metadata_plugins`.
 pub fn load_metadata_plugins(dir: &Path) -> Result<(), String> {
     let mut r = registry().write().expect("metadata registry poisoned");
     super::dynamic::load_dir_into(&mut *r, dir)
 }
+
+/// Drop the providers `load_metadata_plugins` registered and unload their
+/// libraries, so plugin files can be replaced; load them again afterwards.
+/// Returns how many libraries were released.
+pub fn unload_metadata_plugins() -> usize {
+    let mut r = registry().write().expect("metadata registry poisoned");
+    r.unload_plugins()
+}
@@ -0,0 +1,41 @@
+//! Host-side tools the engine can call on the model's behalf.
+
+use serde_json::Value as Json;
+
+/// A function the model may call. Registered on an `LLMEngine` and advertised
+/// to the chat template as `tools`.
+pub trait Tool: Send {
+    /// Name the model uses to call this tool.
+    fn name(&self) -> &str;
+
+    /// One-line description shown to the model.
+    fn description(&self) -> &str;
+
+    /// JSON Schema of the arguments object.
+    fn parameters(&self) -> Json;
+
+    /// Run the tool; the returned text becomes the `tool` turn's content.
+    fn call(&mut self, arguments: &Json) -> Result<String, String>;
+}
+
+/// OpenAI/HF-style definition (`{"type": "function", "function": {...}}`) for templates.
+pub fn tool_definition(tool: &dyn Tool) -> Json {
+    serde_json::json!({
+        "type": "function",
+        "function": {
+            "name": tool.name(),
+            "description": tool.description(),
+            "parameters": tool.parameters(),
+        }
+    })
+}
+
+/// Output of a tool-enabled chat.
+#[derive(Debug, Clone)]
+pub struct ToolChatOutcome {
+    /// The model's final (non-tool) answer.
+    pub reply: String,
+    /// Turns appended during the exchange: assistant tool calls, tool results,
+    /// and the final assistant turn.
+    pub turns: Vec<strata_abi::backend::ChatTurn>,
+}
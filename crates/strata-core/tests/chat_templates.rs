@@ -0,0 +1,318 @@
+//! Real `tokenizer.chat_template` strings rendered through the core Jinja
+//! renderer, compared with the prompt HF's `apply_chat_template` produces.
+
+use serde_json::json;
+use strata_abi::backend::{ChatTurn, Role, ToolCall};
+use strata_core::format::chat_template::{ChatTemplateInputs, render_chat_template};
+
+const QWEN25: &str = include_str!("chat_templates/qwen2.5.jinja");
+const QWEN3: &str = include_str!("chat_templates/qwen3.jinja");
+const LLAMA31: &str = include_str!("chat_templates/llama3.1.jinja");
+const MISTRAL: &str = include_str!("chat_templates/mistral-instruct-v0.2.jinja");
+const GEMMA2: &str = include_str!("chat_templates/gemma2.jinja");
+const DEEPSEEK_R1: &str = include_str!("chat_templates/deepseek-r1.jinja");
+
+const QWEN_TOOLS_HEADER: &str = "# Tools\n\nYou may call one or more functions to assist with the user query.\n\nYou are provided with function signatures within <tools></tools> XML tags:\n<tools>\n{\"type\": \"function\", \"function\": {\"name\": \"get_weather\", \"description\": \"Get the weather\", \"parameters\": {\"type\": \"object\", \"properties\": {\"city\": {\"type\": \"string\"}}, \"required\": [\"city\"]}}}\n</tools>\n\nFor each function call, return a json object with function name and arguments within <tool_call></tool_call> XML tags:\n<tool_call>\n{\"name\": <function-name>, \"arguments\": <args-json-object>}\n</tool_call><|im_end|>\n";
+
+fn tools() -> serde_json::Value {
+    json!([{
+        "type": "function",
+        "function": {
+            "name": "get_weather",
+            "description": "Get the weather",
+            "parameters": {
+                "type": "object",
+                "properties": { "city": { "type": "string" } },
+                "required": ["city"]
+            }
+        }
+    }])
+}
+
+/// user asks, assistant calls `get_weather`, the tool answers.
+fn tool_exchange(user: &str) -> Vec<ChatTurn> {
+    let mut call = ChatTurn::assistant("");
+    call.tool_calls = vec![ToolCall {
+        id: Some("call_1".into()),
+        name: "get_weather".into(),
+        arguments: r#"{"city":"Paris"}"#.into(),
+    }];
+    let mut result = ChatTurn::new(Role::Tool, r#"{"temp": 18}"#);
+    result.tool_call_id = Some("call_1".into());
+    result.name = Some("get_weather".into());
+    vec![ChatTurn::user(user), call, result]
+}
+
+fn render(template: &str, messages: &[ChatTurn], bos: &str, eos: &str) -> Result<String, String> {
+    render_chat_template(
+        template,
+        &ChatTemplateInputs {
+            messages,
+            add_generation_prompt: true,
+            bos_token: bos,
+            eos_token: eos,
+            ..Default::default()
+        },
+    )
+}
+
+#[test]
+fn qwen25_default_system() {
+    let out = render(QWEN25, &[ChatTurn::user("Hi")], "", "<|im_end|>").unwrap();
+    assert_eq!(
+        out,
+        "<|im_start|>system\nYou are Qwen, created by Alibaba Cloud. You are a helpful assistant.<|im_end|>\n\
+         <|im_start|>user\nHi<|im_end|>\n\
+         <|im_start|>assistant\n"
+    );
+}
+
+#[test]
+fn qwen25_tools() {
+    let mut messages = vec![ChatTurn::system("You are a weather bot.")];
+    messages.extend(tool_exchange("Weather in Paris?"));
+    let tools = tools();
+    let out = render_chat_template(
+        QWEN25,
+        &ChatTemplateInputs {
+            messages: &messages,
+            add_generation_prompt: true,
+            tools: Some(&tools),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let expected = format!(
+        "<|im_start|>system\nYou are a weather bot.\n\n{QWEN_TOOLS_HEADER}\
+         <|im_start|>user\nWeather in Paris?<|im_end|>\n\
+         <|im_start|>assistant\n<tool_call>\n{{\"name\": \"get_weather\", \"arguments\": {{\"city\": \"Paris\"}}}}\n</tool_call><|im_end|>\n\
+         <|im_start|>user\n<tool_response>\n{{\"temp\": 18}}\n</tool_response><|im_end|>\n\
+         <|im_start|>assistant\n"
+    );
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn qwen3_tools() {
+    let messages = tool_exchange("Weather in Paris?");
+    let tools = tools();
+    let out = render_chat_template(
+        QWEN3,
+        &ChatTemplateInputs {
+            messages: &messages,
+            add_generation_prompt: true,
+            tools: Some(&tools),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let expected = format!(
+        "<|im_start|>system\n{QWEN_TOOLS_HEADER}\
+         <|im_start|>user\nWeather in Paris?<|im_end|>\n\
+         <|im_start|>assistant\n<tool_call>\n{{\"name\": \"get_weather\", \"arguments\": {{\"city\": \"Paris\"}}}}\n</tool_call><|im_end|>\n\
+         <|im_start|>user\n<tool_response>\n{{\"temp\": 18}}\n</tool_response><|im_end|>\n\
+         <|im_start|>assistant\n"
+    );
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn qwen3_enable_thinking() {
+    let messages = [
+        ChatTurn::user("Hi"),
+        ChatTurn::assistant("<think>\nplanning\n</think>\n\nHello!"),
+        ChatTurn::user("Bye"),
+    ];
+    // Reasoning of earlier turns is dropped from history.
+    let history = "<|im_start|>user\nHi<|im_end|>\n\
+                   <|im_start|>assistant\nHello!<|im_end|>\n\
+                   <|im_start|>user\nBye<|im_end|>\n\
+                   <|im_start|>assistant\n";
+
+    let default = render(QWEN3, &messages, "", "<|im_end|>").unwrap();
+    assert_eq!(default, history);
+
+    for (enable, suffix) in [(true, ""), (false, "<think>\n\n</think>\n\n")] {
+        let extra = json!({ "enable_thinking": enable });
+        let out = render_chat_template(
+            QWEN3,
+            &ChatTemplateInputs {
+                messages: &messages,
+                add_generation_prompt: true,
+                extra: Some(&extra),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            format!("{history}{suffix}"),
+            "enable_thinking={enable}"
+        );
+    }
+}
+
+#[test]
+fn llama31_tools_in_user_message() {
+    let messages = [
+        ChatTurn::system("You are helpful."),
+        ChatTurn::user("Weather in Paris?"),
+    ];
+    let tools = tools();
+    let out = render_chat_template(
+        LLAMA31,
+        &ChatTemplateInputs {
+            messages: &messages,
+            add_generation_prompt: true,
+            bos_token: "<|begin_of_text|>",
+            eos_token: "<|eot_id|>",
+            tools: Some(&tools),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    let tool_json = r#"{
+    "type": "function",
+    "function": {
+        "name": "get_weather",
+        "description": "Get the weather",
+        "parameters": {
+            "type": "object",
+            "properties": {
+                "city": {
+                    "type": "string"
+                }
+            },
+            "required": [
+                "city"
+            ]
+        }
+    }
+}"#;
+    let expected = format!(
+        "<|begin_of_text|><|start_header_id|>system<|end_header_id|>\n\n\
+         Environment: ipython\n\
+         Cutting Knowledge Date: December 2023\n\
+         Today Date: 26 Jul 2024\n\n\
+         You are helpful.<|eot_id|>\
+         <|start_header_id|>user<|end_header_id|>\n\n\
+         Given the following functions, please respond with a JSON for a function call \
+         with its proper arguments that best answers the given prompt.\n\n\
+         Respond in the format {{\"name\": function name, \"parameters\": dictionary of argument name and its value}}.\
+         Do not use variables.\n\n\
+         {tool_json}\n\n\
+         Weather in Paris?<|eot_id|>\
+         <|start_header_id|>assistant<|end_header_id|>\n\n"
+    );
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn llama31_tool_call_history() {
+    let messages = tool_exchange("  Weather in Paris?  ");
+    let out = render(LLAMA31, &messages, "<|begin_of_text|>", "<|eot_id|>").unwrap();
+    // `| trim` binds to the content only; a string tool result is `iterable`,
+    // so it goes through `tojson`.
+    assert_eq!(
+        out,
+        "<|begin_of_text|><|start_header_id|>system<|end_header_id|>\n\n\
+         Cutting Knowledge Date: December 2023\n\
+         Today Date: 26 Jul 2024\n\n<|eot_id|>\
+         <|start_header_id|>user<|end_header_id|>\n\nWeather in Paris?<|eot_id|>\
+         <|start_header_id|>assistant<|end_header_id|>\n\n\
+         {\"name\": \"get_weather\", \"parameters\": {\"city\": \"Paris\"}}<|eot_id|>\
+         <|start_header_id|>ipython<|end_header_id|>\n\n\"{\\\"temp\\\": 18}\"<|eot_id|>\
+         <|start_header_id|>assistant<|end_header_id|>\n\n"
+    );
+}
+
+#[test]
+fn mistral_alternating_roles() {
+    let messages = [
+        ChatTurn::user("Hi"),
+        ChatTurn::assistant("Hello"),
+        ChatTurn::user("Bye"),
+    ];
+    let out = render(MISTRAL, &messages, "<s>", "</s>").unwrap();
+    assert_eq!(out, "<s>[INST] Hi [/INST]Hello</s>[INST] Bye [/INST]");
+}
+
+#[test]
+fn mistral_raises_on_bad_role_order() {
+    let err = render(
+        MISTRAL,
+        &[ChatTurn::user("a"), ChatTurn::user("b")],
+        "<s>",
+        "</s>",
+    )
+    .unwrap_err();
+    assert!(
+        err.contains("Conversation roles must alternate user/assistant/user/assistant/..."),
+        "{err}"
+    );
+
+    let err = render(
+        MISTRAL,
+        &[ChatTurn::system("s"), ChatTurn::user("a")],
+        "<s>",
+        "</s>",
+    )
+    .unwrap_err();
+    assert!(err.contains("Conversation roles must alternate"), "{err}");
+}
+
+#[test]
+fn gemma2_model_role_and_trim() {
+    let messages = [
+        ChatTurn::user(" Hi "),
+        ChatTurn::assistant("Hello"),
+        ChatTurn::user("Bye"),
+    ];
+    let out = render(GEMMA2, &messages, "<bos>", "<eos>").unwrap();
+    assert_eq!(
+        out,
+        "<bos><start_of_turn>user\nHi<end_of_turn>\n\
+         <start_of_turn>model\nHello<end_of_turn>\n\
+         <start_of_turn>user\nBye<end_of_turn>\n\
+         <start_of_turn>model\n"
+    );
+
+    let err = render(
+        GEMMA2,
+        &[ChatTurn::system("s"), ChatTurn::user("a")],
+        "<bos>",
+        "<eos>",
+    )
+    .unwrap_err();
+    assert!(err.contains("System role not supported"), "{err}");
+}
+
+#[test]
+fn deepseek_r1_strips_past_reasoning() {
+    let messages = [
+        ChatTurn::system("Be brief."),
+        ChatTurn::user("2+2?"),
+        ChatTurn::assistant("<think>\nadd\n</think>\n\n4"),
+        ChatTurn::user("3+3?"),
+    ];
+    let bos = "<｜begin▁of▁sentence｜>";
+    let out = render(DEEPSEEK_R1, &messages, bos, "<｜end▁of▁sentence｜>").unwrap();
+    assert_eq!(
+        out,
+        "<｜begin▁of▁sentence｜>Be brief.\
+         <｜User｜>2+2?<｜Assistant｜>\n\n4<｜end▁of▁sentence｜>\
+         <｜User｜>3+3?<｜Assistant｜><think>\n"
+    );
+
+    let no_prompt = render_chat_template(
+        DEEPSEEK_R1,
+        &ChatTemplateInputs {
+            messages: &messages[..2],
+            add_generation_prompt: false,
+            bos_token: bos,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(no_prompt, "<｜begin▁of▁sentence｜>Be brief.<｜User｜>2+2?");
+}
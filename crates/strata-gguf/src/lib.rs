@@ -0,0 +1,541 @@
+//! Pure-Rust reader for GGUF (v2/v3) file headers.
+//!
+//! Reads the metadata key/values (typed, arrays included), the tensor table
+//! (names, shapes, ggml types, byte sizes) and the data alignment without
+//! touching tensor data or linking llama.cpp. Both byte orders are accepted.
+//!
+//! Parsing is bounded by the input: header counts and lengths are never
+//! trusted for allocation, so arbitrary bytes fail cleanly with an error.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+mod ftype;
+mod reader;
+pub mod split;
+pub mod tensor;
+pub mod value;
+
+pub use ftype::file_type_label;
+pub use split::{SplitName, check_split_set};
+pub use tensor::{GgmlType, TensorInfo, TensorTypeStats};
+pub use value::{GgufValue, GgufValueType};
+
+use reader::{MAX_PREALLOC, Reader};
+
+pub const GGUF_MAGIC: [u8; 4] = *b"GGUF";
+/// Alignment used when `general.alignment` is absent.
+pub const DEFAULT_ALIGNMENT: u64 = 32;
+/// Tensors have at most this many dimensions (`GGML_MAX_DIMS`).
+pub const MAX_DIMS: u32 = 4;
+
+/// Layer and attention sizes read from the `<arch>.*` keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ModelDims {
+    pub n_layer: u64,
+    pub n_embd: u64,
+    /// Query heads (largest per-layer value when given as an array).
+    pub n_head: u64,
+    /// Key/value heads; equals `n_head` without GQA.
+    pub n_head_kv: u64,
+    /// Per-head K and V widths.
+    pub head_dim_k: u64,
+    pub head_dim_v: u64,
+    pub n_ctx_train: u64,
+    pub n_vocab: u64,
+}
+
+/// Parsed GGUF header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GgufFile {
+    pub version: u32,
+    pub big_endian: bool,
+    /// Metadata in file order.
+    pub metadata: Vec<(String, GgufValue)>,
+    pub tensors: Vec<TensorInfo>,
+    /// `general.alignment`, or [`DEFAULT_ALIGNMENT`].
+    pub alignment: u64,
+    /// Absolute file offset where tensor data starts.
+    pub data_offset: u64,
+}
+
+/// True if the file starts with the GGUF magic.
+pub fn is_gguf(path: &Path) -> bool {
+    let mut magic = [0u8; 4];
+    File::open(path)
+        .and_then(|mut f| f.read_exact(&mut magic))
+        .is_ok_and(|()| magic == GGUF_MAGIC)
+}
+
+impl GgufFile {
+    /// Read the header of the GGUF file at `path`.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let f = File::open(path).map_err(|e| format!("open {}: {e}", path.display()))?;
+        Self::read(BufReader::new(f)).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Read a model that may be split across files (see [`split`]), from
+    /// any of its files: metadata comes from the first file, `tensors` from
+    /// all of them (offsets stay relative to each file's data). Fails if the
+    /// set is incomplete. Unsplit files read as with [`GgufFile::open`].
+    pub fn open_split(path: &Path) -> Result<Self, String> {
+        let mut files = split::open_set(path)?.into_iter().map(|(_, g)| g);
+        let mut model = files.next().ok_or("empty split set")?;
+        for part in files {
+            model.tensors.extend(part.tensors);
+        }
+        Ok(model)
+    }
+
+    /// `(split.no, split.count)` for a file of a split model (`no` is 0-based).
+    pub fn split(&self) -> Option<(u64, u64)> {
+        let count = self.get_u64(split::KEY_SPLIT_COUNT).filter(|&n| n > 1)?;
+        Some((self.get_u64(split::KEY_SPLIT_NO).unwrap_or(0), count))
+    }
+
+    /// Parse a header held in memory (tensor data may be absent).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        Self::read(bytes)
+    }
+
+    /// Parse a header from any reader positioned at the start of the file.
+    pub fn read<R: Read>(r: R) -> Result<Self, String> {
+        let mut r = Reader::new(r);
+
+        let magic = r.magic()?;
+        if magic != GGUF_MAGIC {
+            return Err(format!("not a GGUF file (magic {magic:02x?})"));
+        }
+
+        // The version field tells us the byte order: a big-endian v3 file
+        // reads as 0x0300_0000 little-endian.
+        let mut version = r.u32()?;
+        if !(2..=3).contains(&version) && (2..=3).contains(&version.swap_bytes()) {
+            version = version.swap_bytes();
+            r.set_big_endian(true);
+        }
+        match version {
+            2 | 3 => {}
+            1 => return Err("GGUF v1 is not supported (re-convert the model)".into()),
+            v => return Err(format!("unsupported GGUF version {v}")),
+        }
+
+        let n_tensors = r.u64()?;
+        let n_kv = r.u64()?;
+
+        let mut metadata = Vec::with_capacity((n_kv as usize).min(MAX_PREALLOC));
+        for _ in 0..n_kv {
+            let key = r.string()?;
+            let ty = r.value_type()?;
+            let value = r.value(ty).map_err(|e| format!("key {key:?}: {e}"))?;
+            metadata.push((key, value));
+        }
+
+        let mut tensors = Vec::with_capacity((n_tensors as usize).min(MAX_PREALLOC));
+        for _ in 0..n_tensors {
+            let name = r.string()?;
+            let n_dims = r.u32()?;
+            if n_dims > MAX_DIMS {
+                return Err(format!(
+                    "tensor {name:?}: {n_dims} dimensions (max {MAX_DIMS})"
+                ));
+            }
+            let dims = (0..n_dims)
+                .map(|_| r.u64())
+                .collect::<Result<Vec<_>, _>>()?;
+            let ggml_type = GgmlType::from_code(r.u32()?);
+            let offset = r.u64()?;
+            tensors.push(TensorInfo {
+                name,
+                dims,
+                ggml_type,
+                offset,
+            });
+        }
+
+        let alignment = match metadata.iter().find(|(k, _)| k == "general.alignment") {
+            None => DEFAULT_ALIGNMENT,
+            Some((_, v)) => match v.as_u64() {
+                Some(a) if a.is_power_of_two() => a,
+                _ => return Err(format!("invalid general.alignment {v}")),
+            },
+        };
+        let data_offset = r.pos().div_ceil(alignment) * alignment;
+
+        Ok(Self {
+            version,
+            big_endian: r.big_endian(),
+            metadata,
+            tensors,
+            alignment,
+            data_offset,
+        })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&GgufValue> {
+        self.metadata.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(GgufValue::as_str)
+    }
+
+    pub fn get_u64(&self, key: &str) -> Option<u64> {
+        self.get(key).and_then(GgufValue::as_u64)
+    }
+
+    pub fn get_i64(&self, key: &str) -> Option<i64> {
+        self.get(key).and_then(GgufValue::as_i64)
+    }
+
+    /// `general.architecture` (e.g. "llama", "qwen3").
+    pub fn architecture(&self) -> Option<&str> {
+        self.get_str("general.architecture")
+    }
+
+    /// Architecture-scoped key, e.g. `arch_u64("context_length")` reads
+    /// `llama.context_length` for a llama model.
+    pub fn arch_u64(&self, suffix: &str) -> Option<u64> {
+        let arch = self.architecture()?;
+        self.get_u64(&format!("{arch}.{suffix}"))
+    }
+
+    /// Scalar `<arch>.<suffix>`, or the largest entry when it is stored
+    /// per layer as an array.
+    fn arch_u64_or_max(&self, suffix: &str) -> Option<u64> {
+        let arch = self.architecture()?;
+        match self.get(&format!("{arch}.{suffix}"))? {
+            GgufValue::Array { items, .. } => items.iter().filter_map(GgufValue::as_u64).max(),
+            v => v.as_u64(),
+        }
+    }
+
+    /// Layer/attention geometry; `None` unless block count, embedding width
+    /// and head count are all present.
+    pub fn model_dims(&self) -> Option<ModelDims> {
+        let n_layer = self.arch_u64("block_count")?;
+        let n_embd = self.arch_u64("embedding_length")?;
+        let n_head = self
+            .arch_u64_or_max("attention.head_count")
+            .filter(|&n| n > 0)?;
+        let n_head_kv = self
+            .arch_u64_or_max("attention.head_count_kv")
+            .unwrap_or(n_head);
+        let head_dim_k = self
+            .arch_u64("attention.key_length")
+            .unwrap_or(n_embd / n_head);
+        let head_dim_v = self
+            .arch_u64("attention.value_length")
+            .unwrap_or(head_dim_k);
+        Some(ModelDims {
+            n_layer,
+            n_embd,
+            n_head,
+            n_head_kv,
+            head_dim_k,
+            head_dim_v,
+            n_ctx_train: self.context_length().unwrap_or(0),
+            n_vocab: self.vocab_size().unwrap_or(0),
+        })
+    }
+
+    /// Training context length.
+    pub fn context_length(&self) -> Option<u64> {
+        self.arch_u64("context_length")
+    }
+
+    /// Vocabulary size: the token list length, else `<arch>.vocab_size`.
+    pub fn vocab_size(&self) -> Option<u64> {
+        self.get("tokenizer.ggml.tokens")
+            .and_then(GgufValue::as_array)
+            .map(|t| t.len() as u64)
+            .or_else(|| self.arch_u64("vocab_size"))
+    }
+
+    /// Default chat template (`tokenizer.chat_template`).
+    pub fn chat_template(&self) -> Option<&str> {
+        self.get_str("tokenizer.chat_template")
+    }
+
+    /// Total weight count over all tensors.
+    pub fn parameter_count(&self) -> u64 {
+        self.tensors
+            .iter()
+            .filter_map(TensorInfo::n_elements)
+            .fold(0u64, u64::saturating_add)
+    }
+
+    /// Total tensor data bytes; `None` if any tensor's size is unknown.
+    pub fn tensor_data_size(&self) -> Option<u64> {
+        self.tensors
+            .iter()
+            .try_fold(0u64, |acc, t| acc.checked_add(t.byte_size()?))
+    }
+
+    /// Average storage bits per weight over all tensors; `None` if any
+    /// tensor's size is unknown.
+    pub fn bits_per_weight(&self) -> Option<f64> {
+        let n = self.parameter_count();
+        (n > 0).then_some(self.tensor_data_size()? as f64 * 8.0 / n as f64)
+    }
+
+    /// Tensor counts, weights and bytes per ggml type, largest share first.
+    pub fn tensor_type_histogram(&self) -> Vec<TensorTypeStats> {
+        let mut out: Vec<TensorTypeStats> = Vec::new();
+        for t in &self.tensors {
+            let n_elements = t.n_elements().unwrap_or(u64::MAX);
+            let bytes = t.byte_size();
+            match out.iter_mut().find(|s| s.ggml_type == t.ggml_type) {
+                Some(s) => {
+                    s.n_tensors += 1;
+                    s.n_elements = s.n_elements.saturating_add(n_elements);
+                    s.bytes = s.bytes.zip(bytes).map(|(a, b)| a.saturating_add(b));
+                }
+                None => out.push(TensorTypeStats {
+                    ggml_type: t.ggml_type,
+                    n_tensors: 1,
+                    n_elements,
+                    bytes,
+                }),
+            }
+        }
+        out.sort_by_key(|s| std::cmp::Reverse(s.n_elements));
+        out
+    }
+
+    /// Type of the output projection (`output.weight`), which quantization
+    /// mixes often keep at higher precision than the rest.
+    pub fn output_tensor_type(&self) -> Option<GgmlType> {
+        self.tensors
+            .iter()
+            .find(|t| t.name == "output.weight")
+            .map(|t| t.ggml_type)
+    }
+
+    /// Metadata rendered as strings (see [`GgufValue`]'s `Display`), for
+    /// flat key/value views.
+    pub fn flatten(&self) -> HashMap<String, String> {
+        self.metadata
+            .iter()
+            .map(|(k, v)| (k.clone(), v.to_string()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Little-endian GGUF header builder.
+    struct Header(Vec<u8>);
+
+    impl Header {
+        fn new(version: u32, n_tensors: u64, n_kv: u64) -> Self {
+            let mut b = GGUF_MAGIC.to_vec();
+            b.extend_from_slice(&version.to_le_bytes());
+            b.extend_from_slice(&n_tensors.to_le_bytes());
+            b.extend_from_slice(&n_kv.to_le_bytes());
+            Self(b)
+        }
+
+        fn string(mut self, s: &str) -> Self {
+            self.0.extend_from_slice(&(s.len() as u64).to_le_bytes());
+            self.0.extend_from_slice(s.as_bytes());
+            self
+        }
+
+        fn u32(mut self, v: u32) -> Self {
+            self.0.extend_from_slice(&v.to_le_bytes());
+            self
+        }
+
+        fn u64(mut self, v: u64) -> Self {
+            self.0.extend_from_slice(&v.to_le_bytes());
+            self
+        }
+
+        fn kv_u32(self, key: &str, v: u32) -> Self {
+            self.string(key).u32(4).u32(v)
+        }
+
+        fn kv_str(self, key: &str, v: &str) -> Self {
+            self.string(key).u32(8).string(v)
+        }
+
+        fn tensor(self, name: &str, dims: &[u64], ggml_type: u32, offset: u64) -> Self {
+            let mut h = self.string(name).u32(dims.len() as u32);
+            for &d in dims {
+                h = h.u64(d);
+            }
+            h.u32(ggml_type).u64(offset)
+        }
+    }
+
+    fn sample() -> Vec<u8> {
+        Header::new(3, 1, 2)
+            .kv_str("general.architecture", "llama")
+            .kv_u32("llama.context_length", 4096)
+            .tensor("token_embd.weight", &[64, 8], 0, 0)
+            .0
+    }
+
+    #[test]
+    fn parses_a_v3_header() {
+        let bytes = sample();
+        let f = GgufFile::from_bytes(&bytes).unwrap();
+        assert_eq!(f.version, 3);
+        assert!(!f.big_endian);
+        assert_eq!(f.architecture(), Some("llama"));
+        assert_eq!(f.context_length(), Some(4096));
+        assert_eq!(f.tensors.len(), 1);
+        assert_eq!(f.tensors[0].dims, [64, 8]);
+        assert_eq!(f.tensors[0].ggml_type, GgmlType::F32);
+        assert_eq!(f.parameter_count(), 512);
+        assert_eq!(f.tensor_data_size(), Some(2048));
+        assert_eq!(f.alignment, DEFAULT_ALIGNMENT);
+        assert_eq!(f.data_offset, (bytes.len() as u64).div_ceil(32) * 32);
+    }
+
+    #[test]
+    fn v2_and_v3_share_a_layout() {
+        let mut v2 = sample();
+        v2[4..8].copy_from_slice(&2u32.to_le_bytes());
+        let v2 = GgufFile::from_bytes(&v2).unwrap();
+        let v3 = GgufFile::from_bytes(&sample()).unwrap();
+        assert_eq!(v2.version, 2);
+        assert_eq!(v2.metadata, v3.metadata);
+        assert_eq!(v2.tensors, v3.tensors);
+        assert_eq!(v2.data_offset, v3.data_offset);
+    }
+
+    #[test]
+    fn other_versions_are_rejected() {
+        let err = GgufFile::from_bytes(&Header::new(1, 0, 0).0).unwrap_err();
+        assert!(err.starts_with("GGUF v1 is not supported"), "{err}");
+        let err = GgufFile::from_bytes(&Header::new(4, 0, 0).0).unwrap_err();
+        assert_eq!(err, "unsupported GGUF version 4");
+        assert!(GgufFile::from_bytes(&Header::new(0, 0, 0).0).is_err());
+    }
+
+    #[test]
+    fn bad_magic_is_rejected() {
+        let mut bytes = sample();
+        bytes[0] = b'X';
+        let err = GgufFile::from_bytes(&bytes).unwrap_err();
+        assert!(err.starts_with("not a GGUF file"), "{err}");
+    }
+
+    #[test]
+    fn big_endian_header_is_detected() {
+        let mut b = GGUF_MAGIC.to_vec();
+        b.extend_from_slice(&3u32.to_be_bytes());
+        b.extend_from_slice(&0u64.to_be_bytes());
+        b.extend_from_slice(&1u64.to_be_bytes());
+        b.extend_from_slice(&4u64.to_be_bytes());
+        b.extend_from_slice(b"name");
+        b.extend_from_slice(&4u32.to_be_bytes());
+        b.extend_from_slice(&7u32.to_be_bytes());
+        let f = GgufFile::from_bytes(&b).unwrap();
+        assert_eq!(f.version, 3);
+        assert!(f.big_endian);
+        assert_eq!(f.get_u64("name"), Some(7));
+    }
+
+    #[test]
+    fn every_truncation_of_the_header_fails() {
+        let bytes = sample();
+        for len in 0..bytes.len() {
+            assert!(
+                GgufFile::from_bytes(&bytes[..len]).is_err(),
+                "parsed a header cut at {len} of {} bytes",
+                bytes.len()
+            );
+        }
+    }
+
+    #[test]
+    fn truncated_value_names_its_key() {
+        let bytes = Header::new(3, 0, 1)
+            .string("general.name")
+            .u32(8)
+            .u64(100)
+            .0;
+        let err = GgufFile::from_bytes(&bytes).unwrap_err();
+        assert!(
+            err.starts_with("key \"general.name\": string at offset"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn huge_counts_fail_without_allocating() {
+        // Counts far beyond what the input holds must hit EOF on the
+        // first entry, not reserve memory up front.
+        let err = GgufFile::from_bytes(&Header::new(3, 0, u64::MAX).0).unwrap_err();
+        assert!(err.starts_with("read at offset 24:"), "{err}");
+        let err = GgufFile::from_bytes(&Header::new(3, u64::MAX, 0).0).unwrap_err();
+        assert!(err.starts_with("read at offset 24:"), "{err}");
+    }
+
+    #[test]
+    fn huge_array_count_is_rejected() {
+        let bytes = Header::new(3, 0, 1)
+            .string("tokenizer.ggml.tokens")
+            .u32(9)
+            .u32(8)
+            .u64(u64::MAX)
+            .0;
+        let err = GgufFile::from_bytes(&bytes).unwrap_err();
+        assert!(
+            err.starts_with("key \"tokenizer.ggml.tokens\": array of"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn unknown_value_type_is_rejected() {
+        let bytes = Header::new(3, 0, 1).string("k").u32(13).u32(0).0;
+        let err = GgufFile::from_bytes(&bytes).unwrap_err();
+        assert_eq!(err, "unknown GGUF value type 13");
+    }
+
+    #[test]
+    fn too_many_dimensions_are_rejected() {
+        let bytes = Header::new(3, 1, 0).tensor("t", &[1, 1, 1, 1, 1], 0, 0).0;
+        let err = GgufFile::from_bytes(&bytes).unwrap_err();
+        assert_eq!(err, "tensor \"t\": 5 dimensions (max 4)");
+    }
+
+    #[test]
+    fn unknown_tensor_type_is_kept() {
+        let bytes = Header::new(3, 1, 0).tensor("t", &[32], 200, 0).0;
+        let f = GgufFile::from_bytes(&bytes).unwrap();
+        assert_eq!(f.tensors[0].ggml_type, GgmlType::Unknown(200));
+        assert_eq!(f.tensor_data_size(), None);
+    }
+
+    #[test]
+    fn custom_alignment_moves_the_data_offset() {
+        let bytes = Header::new(3, 0, 1).kv_u32("general.alignment", 64).0;
+        let f = GgufFile::from_bytes(&bytes).unwrap();
+        assert_eq!(f.alignment, 64);
+        assert_eq!(f.data_offset, 64);
+
+        let bytes = Header::new(3, 0, 1).kv_u32("general.alignment", 8).0;
+        let f = GgufFile::from_bytes(&bytes).unwrap();
+        assert_eq!(f.data_offset, (bytes.len() as u64).div_ceil(8) * 8);
+    }
+
+    #[test]
+    fn invalid_alignment_is_rejected() {
+        for bad in [0, 3, 48] {
+            let bytes = Header::new(3, 0, 1).kv_u32("general.alignment", bad).0;
+            let err = GgufFile::from_bytes(&bytes).unwrap_err();
+            assert_eq!(err, format!("invalid general.alignment {bad}"));
+        }
+        let bytes = Header::new(3, 0, 1).kv_str("general.alignment", "32").0;
+        assert!(GgufFile::from_bytes(&bytes).is_err());
+    }
+}
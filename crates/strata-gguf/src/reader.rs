@@ -0,0 +1,299 @@
+//! Byte-level reader for GGUF headers.
+//!
+//! Every length and count in a GGUF header is untrusted. Nothing here
+//! preallocates from a count; strings are read through `Read::take`, so a
+//! bogus length runs into EOF instead of a huge allocation. Hard caps keep a
+//! corrupt header from pulling the tensor data of a real file into memory.
+
+use std::io::Read;
+
+use crate::value::{GgufValue, GgufValueType};
+
+/// Arrays of arrays are legal but never nested deeply in practice.
+const MAX_ARRAY_DEPTH: usize = 8;
+/// Longest string we accept (chat templates are the largest in practice).
+const MAX_STRING_LEN: u64 = 64 << 20;
+/// Most items we accept in one array (vocabularies/merges stay far below).
+const MAX_ARRAY_LEN: u64 = 1 << 24;
+/// Cap for up-front `Vec` reservations driven by header counts.
+pub(crate) const MAX_PREALLOC: usize = 4096;
+
+pub(crate) struct Reader<R> {
+    inner: R,
+    big_endian: bool,
+    /// Bytes consumed so far (for the tensor-data offset).
+    pos: u64,
+}
+
+impl<R: Read> Reader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        Self {
+            inner,
+            big_endian: false,
+            pos: 0,
+        }
+    }
+
+    pub(crate) fn set_big_endian(&mut self, big_endian: bool) {
+        self.big_endian = big_endian;
+    }
+
+    pub(crate) fn big_endian(&self) -> bool {
+        self.big_endian
+    }
+
+    pub(crate) fn pos(&self) -> u64 {
+        self.pos
+    }
+
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut buf = [0u8; N];
+        self.inner
+            .read_exact(&mut buf)
+            .map_err(|e| format!("read at offset {}: {e}", self.pos))?;
+        self.pos += N as u64;
+        Ok(buf)
+    }
+
+    pub(crate) fn magic(&mut self) -> Result<[u8; 4], String> {
+        self.bytes::<4>()
+    }
+}
+
+macro_rules! read_num {
+    ($($name:ident -> $t:ty),* $(,)?) => {
+        impl<R: Read> Reader<R> {
+            $(
+                pub(crate) fn $name(&mut self) -> Result<$t, String> {
+                    let b = self.bytes::<{ std::mem::size_of::<$t>() }>()?;
+                    Ok(if self.big_endian {
+                        <$t>::from_be_bytes(b)
+                    } else {
+                        <$t>::from_le_bytes(b)
+                    })
+                }
+            )*
+        }
+    };
+}
+
+read_num! {
+    u8 -> u8, i8 -> i8, u16 -> u16, i16 -> i16, u32 -> u32, i32 -> i32,
+    u64 -> u64, i64 -> i64, f32 -> f32, f64 -> f64,
+}
+
+impl<R: Read> Reader<R> {
+    /// `u64` length followed by that many (UTF-8, lossily decoded) bytes.
+    pub(crate) fn string(&mut self) -> Result<String, String> {
+        let len = self.u64()?;
+        if len > MAX_STRING_LEN {
+            return Err(format!(
+                "string at offset {} claims {len} bytes",
+                self.pos - 8
+            ));
+        }
+        let mut buf = Vec::new();
+        let got = (&mut self.inner)
+            .take(len)
+            .read_to_end(&mut buf)
+            .map_err(|e| format!("read string at offset {}: {e}", self.pos))?;
+        self.pos += got as u64;
+        if (got as u64) < len {
+            return Err(format!(
+                "string at offset {} claims {len} bytes, file ends after {got}",
+                self.pos - got as u64
+            ));
+        }
+        Ok(match String::from_utf8(buf) {
+            Ok(s) => s,
+            Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+        })
+    }
+
+    pub(crate) fn value_type(&mut self) -> Result<GgufValueType, String> {
+        let code = self.u32()?;
+        GgufValueType::from_code(code).ok_or_else(|| format!("unknown GGUF value type {code}"))
+    }
+
+    pub(crate) fn value(&mut self, ty: GgufValueType) -> Result<GgufValue, String> {
+        self.value_at_depth(ty, 0)
+    }
+
+    fn value_at_depth(&mut self, ty: GgufValueType, depth: usize) -> Result<GgufValue, String> {
+        Ok(match ty {
+            GgufValueType::U8 => GgufValue::U8(self.u8()?),
+            GgufValueType::I8 => GgufValue::I8(self.i8()?),
+            GgufValueType::U16 => GgufValue::U16(self.u16()?),
+            GgufValueType::I16 => GgufValue::I16(self.i16()?),
+            GgufValueType::U32 => GgufValue::U32(self.u32()?),
+            GgufValueType::I32 => GgufValue::I32(self.i32()?),
+            GgufValueType::F32 => GgufValue::F32(self.f32()?),
+            GgufValueType::Bool => match self.u8()? {
+                0 => GgufValue::Bool(false),
+                1 => GgufValue::Bool(true),
+                b => return Err(format!("invalid bool byte {b}")),
+            },
+            GgufValueType::String => GgufValue::String(self.string()?),
+            GgufValueType::U64 => GgufValue::U64(self.u64()?),
+            GgufValueType::I64 => GgufValue::I64(self.i64()?),
+            GgufValueType::F64 => GgufValue::F64(self.f64()?),
+            GgufValueType::Array => {
+                if depth >= MAX_ARRAY_DEPTH {
+                    return Err("GGUF arrays nested too deeply".into());
+                }
+                let item_type = self.value_type()?;
+                let n = self.u64()?;
+                if n > MAX_ARRAY_LEN {
+                    return Err(format!("array of {n} items is too large"));
+                }
+                let mut items = Vec::with_capacity((n as usize).min(MAX_PREALLOC));
+                for _ in 0..n {
+                    items.push(self.value_at_depth(item_type, depth + 1)?);
+                }
+                GgufValue::Array { item_type, items }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reader(bytes: &[u8]) -> Reader<&[u8]> {
+        Reader::new(bytes)
+    }
+
+    fn string_bytes(len: u64, body: &[u8]) -> Vec<u8> {
+        let mut b = len.to_le_bytes().to_vec();
+        b.extend_from_slice(body);
+        b
+    }
+
+    #[test]
+    fn numbers_follow_byte_order() {
+        let bytes = [0x01, 0x02, 0x03, 0x04];
+        assert_eq!(reader(&bytes).u32().unwrap(), 0x0403_0201);
+        let mut r = reader(&bytes);
+        r.set_big_endian(true);
+        assert_eq!(r.u32().unwrap(), 0x0102_0304);
+        assert_eq!(r.pos(), 4);
+    }
+
+    #[test]
+    fn truncated_number_reports_offset() {
+        let mut r = reader(&[0u8; 6]);
+        r.u32().unwrap();
+        let err = r.u32().unwrap_err();
+        assert!(err.starts_with("read at offset 4:"), "{err}");
+    }
+
+    #[test]
+    fn string_reads_declared_length() {
+        let bytes = string_bytes(5, b"hello world");
+        let mut r = reader(&bytes);
+        assert_eq!(r.string().unwrap(), "hello");
+        assert_eq!(r.pos(), 13);
+    }
+
+    #[test]
+    fn string_past_eof_fails() {
+        let bytes = string_bytes(10, b"abc");
+        let err = reader(&bytes).string().unwrap_err();
+        assert_eq!(err, "string at offset 8 claims 10 bytes, file ends after 3");
+    }
+
+    #[test]
+    fn huge_string_length_is_rejected_before_reading() {
+        let bytes = string_bytes(u64::MAX, b"abc");
+        let err = reader(&bytes).string().unwrap_err();
+        assert!(err.contains(&format!("claims {} bytes", u64::MAX)), "{err}");
+
+        let bytes = string_bytes(MAX_STRING_LEN + 1, b"");
+        assert!(reader(&bytes).string().is_err());
+    }
+
+    #[test]
+    fn invalid_utf8_is_decoded_lossily() {
+        let bytes = string_bytes(3, &[b'a', 0xff, b'b']);
+        assert_eq!(reader(&bytes).string().unwrap(), "a\u{fffd}b");
+    }
+
+    #[test]
+    fn unknown_value_type_is_an_error() {
+        let bytes = 13u32.to_le_bytes();
+        assert_eq!(
+            reader(&bytes).value_type().unwrap_err(),
+            "unknown GGUF value type 13"
+        );
+        let bytes = u32::MAX.to_le_bytes();
+        assert!(reader(&bytes).value_type().is_err());
+    }
+
+    #[test]
+    fn bool_must_be_zero_or_one() {
+        assert_eq!(
+            reader(&[1]).value(GgufValueType::Bool).unwrap(),
+            GgufValue::Bool(true)
+        );
+        assert_eq!(
+            reader(&[2]).value(GgufValueType::Bool).unwrap_err(),
+            "invalid bool byte 2"
+        );
+    }
+
+    #[test]
+    fn array_of_scalars() {
+        let mut bytes = 4u32.to_le_bytes().to_vec(); // u32 items
+        bytes.extend_from_slice(&2u64.to_le_bytes());
+        bytes.extend_from_slice(&7u32.to_le_bytes());
+        bytes.extend_from_slice(&9u32.to_le_bytes());
+        assert_eq!(
+            reader(&bytes).value(GgufValueType::Array).unwrap(),
+            GgufValue::Array {
+                item_type: GgufValueType::U32,
+                items: vec![GgufValue::U32(7), GgufValue::U32(9)],
+            }
+        );
+    }
+
+    #[test]
+    fn huge_array_count_is_rejected() {
+        let mut bytes = 0u32.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&(MAX_ARRAY_LEN + 1).to_le_bytes());
+        let err = reader(&bytes).value(GgufValueType::Array).unwrap_err();
+        assert!(err.contains("too large"), "{err}");
+    }
+
+    #[test]
+    fn array_count_beyond_input_hits_eof() {
+        // Within the cap, but the data isn't there: fails on the first
+        // missing item rather than allocating for the count.
+        let mut bytes = 10u32.to_le_bytes().to_vec(); // u64 items
+        bytes.extend_from_slice(&MAX_ARRAY_LEN.to_le_bytes());
+        bytes.extend_from_slice(&1u64.to_le_bytes());
+        let err = reader(&bytes).value(GgufValueType::Array).unwrap_err();
+        assert!(err.starts_with("read at offset 20:"), "{err}");
+    }
+
+    #[test]
+    fn array_with_unknown_item_type_fails() {
+        let mut bytes = 99u32.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&1u64.to_le_bytes());
+        assert_eq!(
+            reader(&bytes).value(GgufValueType::Array).unwrap_err(),
+            "unknown GGUF value type 99"
+        );
+    }
+
+    #[test]
+    fn deeply_nested_arrays_are_rejected() {
+        let mut bytes = Vec::new();
+        for _ in 0..=MAX_ARRAY_DEPTH {
+            bytes.extend_from_slice(&9u32.to_le_bytes()); // array of arrays
+            bytes.extend_from_slice(&1u64.to_le_bytes());
+        }
+        let err = reader(&bytes).value(GgufValueType::Array).unwrap_err();
+        assert_eq!(err, "GGUF arrays nested too deeply");
+    }
+}
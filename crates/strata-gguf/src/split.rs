@@ -0,0 +1,193 @@
+//! Split GGUF sets (`gguf-split`): `<prefix>-00001-of-00003.gguf`, …
+//!
+//! llama.cpp loads a set from its first file and derives the others from the
+//! name, so the naming below is exactly `llama_split_path`'s. Each file also
+//! records `split.no` (0-based) and `split.count`; only the first carries the
+//! model's full metadata.
+
+use std::path::{Path, PathBuf};
+
+use crate::GgufFile;
+
+pub const KEY_SPLIT_NO: &str = "split.no";
+pub const KEY_SPLIT_COUNT: &str = "split.count";
+
+/// Position of a file within a split set, from its name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitName {
+    /// Path up to (not including) `-00001-of-00003.gguf`.
+    pub prefix: PathBuf,
+    /// 1-based, as in the file name.
+    pub index: u32,
+    pub count: u32,
+}
+
+impl SplitName {
+    /// Parse `<prefix>-NNNNN-of-MMMMM.gguf`; `None` for other names and
+    /// single-file "sets".
+    pub fn parse(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        let stem = name
+            .strip_suffix(".gguf")
+            .or_else(|| name.strip_suffix(".GGUF"))?;
+        let (rest, count) = stem.rsplit_once("-of-")?;
+        let (prefix, index) = rest.rsplit_once('-')?;
+        let digits = |s: &str| s.len() == 5 && s.bytes().all(|b| b.is_ascii_digit());
+        if prefix.is_empty() || !digits(index) || !digits(count) {
+            return None;
+        }
+        let index: u32 = index.parse().ok()?;
+        let count: u32 = count.parse().ok()?;
+        if count < 2 || index == 0 || index > count {
+            return None;
+        }
+        Some(Self {
+            prefix: path.with_file_name(prefix),
+            index,
+            count,
+        })
+    }
+
+    /// Path of file `index` (1-based) in this set.
+    pub fn path_of(&self, index: u32) -> PathBuf {
+        split_path(&self.prefix, index, self.count)
+    }
+
+    /// The file llama.cpp is pointed at.
+    pub fn first(&self) -> PathBuf {
+        self.path_of(1)
+    }
+
+    /// Every file of the set, in order.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        (1..=self.count).map(|i| self.path_of(i)).collect()
+    }
+
+    /// Files of the set that don't exist on disk.
+    pub fn missing(&self) -> Vec<PathBuf> {
+        self.paths().into_iter().filter(|p| !p.is_file()).collect()
+    }
+}
+
+/// `<prefix>-{index:05}-of-{count:05}.gguf` (`llama_split_path`).
+pub fn split_path(prefix: &Path, index: u32, count: u32) -> PathBuf {
+    let mut name = prefix.as_os_str().to_owned();
+    name.push(format!("-{index:05}-of-{count:05}.gguf"));
+    PathBuf::from(name)
+}
+
+/// Check that the set `path` belongs to is complete and consistent: every
+/// named file exists and its header's `split.no`/`split.count` match its
+/// name. Returns the set's files (just `path` when it isn't split).
+pub fn check_split_set(path: &Path) -> Result<Vec<PathBuf>, String> {
+    Ok(open_set(path)?.into_iter().map(|(p, _)| p).collect())
+}
+
+/// Headers of every file in the set `path` belongs to, first file first.
+pub(crate) fn open_set(path: &Path) -> Result<Vec<(PathBuf, GgufFile)>, String> {
+    let Some(split) = SplitName::parse(path) else {
+        // Unconventional name: trust the header.
+        let gguf = GgufFile::open(path)?;
+        let count = gguf.get_u64(KEY_SPLIT_COUNT).unwrap_or(1);
+        if count > 1 {
+            return Err(format!(
+                "{} is part of a {count}-file split model, but its name doesn't follow \
+                 the <name>-00001-of-{count:05}.gguf pattern needed to find the other files",
+                path.display()
+            ));
+        }
+        return Ok(vec![(path.to_path_buf(), gguf)]);
+    };
+
+    let missing = split.missing();
+    if !missing.is_empty() {
+        let names: Vec<String> = missing
+            .iter()
+            .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+            .collect();
+        return Err(format!(
+            "Incomplete split model: {} of {} files missing ({})",
+            missing.len(),
+            split.count,
+            names.join(", ")
+        ));
+    }
+
+    split
+        .paths()
+        .into_iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let gguf = GgufFile::open(&p)?;
+            let no = gguf.get_u64(KEY_SPLIT_NO);
+            let count = gguf.get_u64(KEY_SPLIT_COUNT);
+            if no != Some(i as u64) || count != Some(u64::from(split.count)) {
+                return Err(format!(
+                    "{}: header says split {} of {}, expected {} of {}",
+                    p.display(),
+                    no.map_or("?".into(), |n| (n + 1).to_string()),
+                    count.map_or("?".into(), |n| n.to_string()),
+                    i + 1,
+                    split.count
+                ));
+            }
+            Ok((p, gguf))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(name: &str) -> Option<SplitName> {
+        SplitName::parse(Path::new(name))
+    }
+
+    #[test]
+    fn parses_a_split_name() {
+        let s = parse("models/llama-7b-q4_k-00002-of-00003.gguf").unwrap();
+        assert_eq!(s.prefix, Path::new("models/llama-7b-q4_k"));
+        assert_eq!((s.index, s.count), (2, 3));
+        assert_eq!(
+            s.first(),
+            Path::new("models/llama-7b-q4_k-00001-of-00003.gguf")
+        );
+        assert_eq!(s.paths().len(), 3);
+        assert!(parse("m-00001-of-00002.GGUF").is_some());
+    }
+
+    #[test]
+    fn split_path_round_trips() {
+        let p = split_path(Path::new("dir/model"), 7, 12);
+        assert_eq!(p, Path::new("dir/model-00007-of-00012.gguf"));
+        let s = SplitName::parse(&p).unwrap();
+        assert_eq!(s.path_of(7), p);
+    }
+
+    #[test]
+    fn index_zero_is_rejected() {
+        assert_eq!(parse("model-00000-of-00003.gguf"), None);
+    }
+
+    #[test]
+    fn index_past_count_is_rejected() {
+        assert_eq!(parse("model-00004-of-00003.gguf"), None);
+    }
+
+    #[test]
+    fn single_file_sets_are_rejected() {
+        assert_eq!(parse("model-00001-of-00001.gguf"), None);
+        assert_eq!(parse("model-00000-of-00000.gguf"), None);
+    }
+
+    #[test]
+    fn other_names_are_rejected() {
+        assert_eq!(parse("model.gguf"), None);
+        assert_eq!(parse("-00001-of-00002.gguf"), None);
+        assert_eq!(parse("model-0001-of-00002.gguf"), None);
+        assert_eq!(parse("model-00001-of-2.gguf"), None);
+        assert_eq!(parse("model-0000a-of-00002.gguf"), None);
+        assert_eq!(parse("model-00001-of-00002.bin"), None);
+    }
+}
@@ -0,0 +1,293 @@
+//! Tensor table entries and ggml element types.
+
+/// ggml tensor element type (`ggml_type`). Codes ggml has retired or that
+/// this crate doesn't know yet come back as `Unknown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GgmlType {
+    F32,
+    F16,
+    Q4_0,
+    Q4_1,
+    Q5_0,
+    Q5_1,
+    Q8_0,
+    Q8_1,
+    Q2K,
+    Q3K,
+    Q4K,
+    Q5K,
+    Q6K,
+    Q8K,
+    Iq2Xxs,
+    Iq2Xs,
+    Iq3Xxs,
+    Iq1S,
+    Iq4Nl,
+    Iq3S,
+    Iq2S,
+    Iq4Xs,
+    I8,
+    I16,
+    I32,
+    I64,
+    F64,
+    Iq1M,
+    Bf16,
+    /// Repacked Q4_0/IQ4_NL layouts, retired from ggml but present in older files.
+    Q4_0_4_4,
+    Q4_0_4_8,
+    Q4_0_8_8,
+    Tq1_0,
+    Tq2_0,
+    Iq4Nl4_4,
+    Iq4Nl4_8,
+    Iq4Nl8_8,
+    Mxfp4,
+    Unknown(u32),
+}
+
+impl GgmlType {
+    pub fn from_code(code: u32) -> Self {
+        match code {
+            0 => Self::F32,
+            1 => Self::F16,
+            2 => Self::Q4_0,
+            3 => Self::Q4_1,
+            6 => Self::Q5_0,
+            7 => Self::Q5_1,
+            8 => Self::Q8_0,
+            9 => Self::Q8_1,
+            10 => Self::Q2K,
+            11 => Self::Q3K,
+            12 => Self::Q4K,
+            13 => Self::Q5K,
+            14 => Self::Q6K,
+            15 => Self::Q8K,
+            16 => Self::Iq2Xxs,
+            17 => Self::Iq2Xs,
+            18 => Self::Iq3Xxs,
+            19 => Self::Iq1S,
+            20 => Self::Iq4Nl,
+            21 => Self::Iq3S,
+            22 => Self::Iq2S,
+            23 => Self::Iq4Xs,
+            24 => Self::I8,
+            25 => Self::I16,
+            26 => Self::I32,
+            27 => Self::I64,
+            28 => Self::F64,
+            29 => Self::Iq1M,
+            30 => Self::Bf16,
+            31 => Self::Q4_0_4_4,
+            32 => Self::Q4_0_4_8,
+            33 => Self::Q4_0_8_8,
+            34 => Self::Tq1_0,
+            35 => Self::Tq2_0,
+            36 => Self::Iq4Nl4_4,
+            37 => Self::Iq4Nl4_8,
+            38 => Self::Iq4Nl8_8,
+            39 => Self::Mxfp4,
+            other => Self::Unknown(other),
+        }
+    }
+
+    /// ggml's name for the type (`ggml_type_name`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::F32 => "f32",
+            Self::F16 => "f16",
+            Self::Q4_0 => "q4_0",
+            Self::Q4_1 => "q4_1",
+            Self::Q5_0 => "q5_0",
+            Self::Q5_1 => "q5_1",
+            Self::Q8_0 => "q8_0",
+            Self::Q8_1 => "q8_1",
+            Self::Q2K => "q2_K",
+            Self::Q3K => "q3_K",
+            Self::Q4K => "q4_K",
+            Self::Q5K => "q5_K",
+            Self::Q6K => "q6_K",
+            Self::Q8K => "q8_K",
+            Self::Iq2Xxs => "iq2_xxs",
+            Self::Iq2Xs => "iq2_xs",
+            Self::Iq3Xxs => "iq3_xxs",
+            Self::Iq1S => "iq1_s",
+            Self::Iq4Nl => "iq4_nl",
+            Self::Iq3S => "iq3_s",
+            Self::Iq2S => "iq2_s",
+            Self::Iq4Xs => "iq4_xs",
+            Self::I8 => "i8",
+            Self::I16 => "i16",
+            Self::I32 => "i32",
+            Self::I64 => "i64",
+            Self::F64 => "f64",
+            Self::Iq1M => "iq1_m",
+            Self::Bf16 => "bf16",
+            Self::Q4_0_4_4 => "q4_0_4x4",
+            Self::Q4_0_4_8 => "q4_0_4x8",
+            Self::Q4_0_8_8 => "q4_0_8x8",
+            Self::Tq1_0 => "tq1_0",
+            Self::Tq2_0 => "tq2_0",
+            Self::Iq4Nl4_4 => "iq4_nl_4x4",
+            Self::Iq4Nl4_8 => "iq4_nl_4x8",
+            Self::Iq4Nl8_8 => "iq4_nl_8x8",
+            Self::Mxfp4 => "mxfp4",
+            Self::Unknown(_) => "unknown",
+        }
+    }
+
+    /// `(elements per block, bytes per block)`; `None` for unknown types.
+    pub fn block_layout(&self) -> Option<(u64, u64)> {
+        Some(match self {
+            Self::F32 => (1, 4),
+            Self::F16 => (1, 2),
+            Self::Q4_0 => (32, 18),
+            Self::Q4_1 => (32, 20),
+            Self::Q5_0 => (32, 22),
+            Self::Q5_1 => (32, 24),
+            Self::Q8_0 => (32, 34),
+            Self::Q8_1 => (32, 36),
+            Self::Q2K => (256, 84),
+            Self::Q3K => (256, 110),
+            Self::Q4K => (256, 144),
+            Self::Q5K => (256, 176),
+            Self::Q6K => (256, 210),
+            Self::Q8K => (256, 292),
+            Self::Iq2Xxs => (256, 66),
+            Self::Iq2Xs => (256, 74),
+            Self::Iq3Xxs => (256, 98),
+            Self::Iq1S => (256, 50),
+            Self::Iq4Nl => (32, 18),
+            Self::Iq3S => (256, 110),
+            Self::Iq2S => (256, 82),
+            Self::Iq4Xs => (256, 136),
+            Self::I8 => (1, 1),
+            Self::I16 => (1, 2),
+            Self::I32 => (1, 4),
+            Self::I64 => (1, 8),
+            Self::F64 => (1, 8),
+            Self::Iq1M => (256, 56),
+            Self::Bf16 => (1, 2),
+            Self::Q4_0_4_4 | Self::Q4_0_4_8 | Self::Q4_0_8_8 => (32, 18),
+            Self::Tq1_0 => (256, 54),
+            Self::Tq2_0 => (256, 66),
+            Self::Iq4Nl4_4 | Self::Iq4Nl4_8 | Self::Iq4Nl8_8 => (32, 18),
+            Self::Mxfp4 => (32, 17),
+            Self::Unknown(_) => return None,
+        })
+    }
+
+    /// True for block-quantized types (everything but plain floats and ints).
+    pub fn is_quantized(&self) -> bool {
+        !matches!(
+            self,
+            Self::F32
+                | Self::F16
+                | Self::Bf16
+                | Self::F64
+                | Self::I8
+                | Self::I16
+                | Self::I32
+                | Self::I64
+                | Self::Unknown(_)
+        )
+    }
+
+    /// Storage cost in bits per weight.
+    pub fn bits_per_weight(&self) -> Option<f64> {
+        self.block_layout()
+            .map(|(elems, bytes)| bytes as f64 * 8.0 / elems as f64)
+    }
+}
+
+/// One entry of the tensor table (the data itself is not read).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TensorInfo {
+    pub name: String,
+    /// Dimensions, innermost first (ggml `ne` order).
+    pub dims: Vec<u64>,
+    pub ggml_type: GgmlType,
+    /// Offset of the tensor's data, relative to [`crate::GgufFile::data_offset`].
+    pub offset: u64,
+}
+
+impl TensorInfo {
+    /// Element count (`None` on overflow).
+    pub fn n_elements(&self) -> Option<u64> {
+        self.dims
+            .iter()
+            .try_fold(1u64, |acc, &d| acc.checked_mul(d))
+    }
+
+    /// Bytes of tensor data; `None` for unknown types or rows that don't
+    /// divide into whole blocks.
+    pub fn byte_size(&self) -> Option<u64> {
+        let (block, bytes) = self.ggml_type.block_layout()?;
+        let row = *self.dims.first().unwrap_or(&1);
+        if !row.is_multiple_of(block) {
+            return None;
+        }
+        (self.n_elements()? / block).checked_mul(bytes)
+    }
+}
+
+/// Tensors of one ggml type, summed over the tensor table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TensorTypeStats {
+    pub ggml_type: GgmlType,
+    pub n_tensors: u64,
+    pub n_elements: u64,
+    /// `None` if any tensor's size is unknown.
+    pub bytes: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tensor(dims: &[u64], ggml_type: GgmlType) -> TensorInfo {
+        TensorInfo {
+            name: "t".into(),
+            dims: dims.to_vec(),
+            ggml_type,
+            offset: 0,
+        }
+    }
+
+    #[test]
+    fn unknown_codes_are_kept() {
+        assert_eq!(GgmlType::from_code(0), GgmlType::F32);
+        assert_eq!(GgmlType::from_code(39), GgmlType::Mxfp4);
+        assert_eq!(GgmlType::from_code(1000), GgmlType::Unknown(1000));
+        assert_eq!(GgmlType::Unknown(1000).block_layout(), None);
+        assert_eq!(GgmlType::Unknown(1000).bits_per_weight(), None);
+    }
+
+    #[test]
+    fn byte_size_counts_whole_blocks() {
+        assert_eq!(
+            tensor(&[4096, 32], GgmlType::F16).byte_size(),
+            Some(262_144)
+        );
+        assert_eq!(tensor(&[256, 2], GgmlType::Q4K).byte_size(), Some(288));
+        assert_eq!(tensor(&[64], GgmlType::Q8_0).byte_size(), Some(68));
+        assert_eq!(tensor(&[], GgmlType::F32).byte_size(), Some(4));
+    }
+
+    #[test]
+    fn partial_blocks_have_no_size() {
+        assert_eq!(tensor(&[100], GgmlType::Q4_0).byte_size(), None);
+        assert_eq!(tensor(&[32], GgmlType::Unknown(99)).byte_size(), None);
+    }
+
+    #[test]
+    fn overflowing_dims_have_no_size() {
+        let t = tensor(&[u64::MAX, 2], GgmlType::F32);
+        assert_eq!(t.n_elements(), None);
+        assert_eq!(t.byte_size(), None);
+
+        let t = tensor(&[u64::MAX / 2], GgmlType::F32);
+        assert_eq!(t.n_elements(), Some(u64::MAX / 2));
+        assert_eq!(t.byte_size(), None);
+    }
+}
@@ -0,0 +1,239 @@
+//! Typed GGUF metadata values.
+
+use std::fmt;
+
+/// Arrays longer than this are summarized rather than listed by `Display`.
+const DISPLAY_MAX_ITEMS: usize = 32;
+
+/// On-disk value type tag (`gguf_type`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GgufValueType {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    F32,
+    Bool,
+    String,
+    Array,
+    U64,
+    I64,
+    F64,
+}
+
+impl GgufValueType {
+    pub fn from_code(code: u32) -> Option<Self> {
+        Some(match code {
+            0 => Self::U8,
+            1 => Self::I8,
+            2 => Self::U16,
+            3 => Self::I16,
+            4 => Self::U32,
+            5 => Self::I32,
+            6 => Self::F32,
+            7 => Self::Bool,
+            8 => Self::String,
+            9 => Self::Array,
+            10 => Self::U64,
+            11 => Self::I64,
+            12 => Self::F64,
+            _ => return None,
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::U8 => "u8",
+            Self::I8 => "i8",
+            Self::U16 => "u16",
+            Self::I16 => "i16",
+            Self::U32 => "u32",
+            Self::I32 => "i32",
+            Self::F32 => "f32",
+            Self::Bool => "bool",
+            Self::String => "str",
+            Self::Array => "arr",
+            Self::U64 => "u64",
+            Self::I64 => "i64",
+            Self::F64 => "f64",
+        }
+    }
+}
+
+/// One metadata value, as stored in the file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GgufValue {
+    U8(u8),
+    I8(i8),
+    U16(u16),
+    I16(i16),
+    U32(u32),
+    I32(i32),
+    F32(f32),
+    Bool(bool),
+    String(String),
+    Array {
+        item_type: GgufValueType,
+        items: Vec<GgufValue>,
+    },
+    U64(u64),
+    I64(i64),
+    F64(f64),
+}
+
+impl GgufValue {
+    pub fn value_type(&self) -> GgufValueType {
+        match self {
+            Self::U8(_) => GgufValueType::U8,
+            Self::I8(_) => GgufValueType::I8,
+            Self::U16(_) => GgufValueType::U16,
+            Self::I16(_) => GgufValueType::I16,
+            Self::U32(_) => GgufValueType::U32,
+            Self::I32(_) => GgufValueType::I32,
+            Self::F32(_) => GgufValueType::F32,
+            Self::Bool(_) => GgufValueType::Bool,
+            Self::String(_) => GgufValueType::String,
+            Self::Array { .. } => GgufValueType::Array,
+            Self::U64(_) => GgufValueType::U64,
+            Self::I64(_) => GgufValueType::I64,
+            Self::F64(_) => GgufValueType::F64,
+        }
+    }
+
+    /// Any integer that fits in `i64` (writers disagree on signedness).
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Self::U8(v) => Some(v.into()),
+            Self::I8(v) => Some(v.into()),
+            Self::U16(v) => Some(v.into()),
+            Self::I16(v) => Some(v.into()),
+            Self::U32(v) => Some(v.into()),
+            Self::I32(v) => Some(v.into()),
+            Self::U64(v) => i64::try_from(v).ok(),
+            Self::I64(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Any non-negative integer.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Self::U64(v) => Some(v),
+            _ => self.as_i64().and_then(|v| u64::try_from(v).ok()),
+        }
+    }
+
+    /// Floats, and integers widened to `f64`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::F32(v) => Some(v.into()),
+            Self::F64(v) => Some(v),
+            _ => self.as_i64().map(|v| v as f64),
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Self::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[GgufValue]> {
+        match self {
+            Self::Array { items, .. } => Some(items),
+            _ => None,
+        }
+    }
+}
+
+/// Scalars print plainly and strings verbatim; arrays print as `[a, b, …]`
+/// up to a small length and as `[<type>; N]` beyond it.
+impl fmt::Display for GgufValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::U8(v) => write!(f, "{v}"),
+            Self::I8(v) => write!(f, "{v}"),
+            Self::U16(v) => write!(f, "{v}"),
+            Self::I16(v) => write!(f, "{v}"),
+            Self::U32(v) => write!(f, "{v}"),
+            Self::I32(v) => write!(f, "{v}"),
+            Self::F32(v) => write!(f, "{v}"),
+            Self::Bool(v) => write!(f, "{v}"),
+            Self::String(s) => f.write_str(s),
+            Self::U64(v) => write!(f, "{v}"),
+            Self::I64(v) => write!(f, "{v}"),
+            Self::F64(v) => write!(f, "{v}"),
+            Self::Array { item_type, items } => {
+                if items.len() > DISPLAY_MAX_ITEMS {
+                    return write!(f, "[{}; {}]", item_type.name(), items.len());
+                }
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    match item {
+                        Self::String(s) => write!(f, "{s:?}")?,
+                        other => write!(f, "{other}")?,
+                    }
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_codes_round_trip() {
+        for code in 0..=12 {
+            let ty = GgufValueType::from_code(code).unwrap();
+            assert_eq!(ty as u32, code);
+        }
+        assert_eq!(GgufValueType::from_code(13), None);
+        assert_eq!(GgufValueType::from_code(u32::MAX), None);
+    }
+
+    #[test]
+    fn integers_convert_across_signedness() {
+        assert_eq!(GgufValue::I32(4096).as_u64(), Some(4096));
+        assert_eq!(GgufValue::I32(-1).as_u64(), None);
+        assert_eq!(GgufValue::U64(u64::MAX).as_u64(), Some(u64::MAX));
+        assert_eq!(GgufValue::U64(u64::MAX).as_i64(), None);
+        assert_eq!(GgufValue::U8(7).as_i64(), Some(7));
+        assert_eq!(GgufValue::F32(1.0).as_u64(), None);
+        assert_eq!(GgufValue::U16(3).as_f64(), Some(3.0));
+        assert_eq!(GgufValue::Bool(true).as_i64(), None);
+    }
+
+    #[test]
+    fn long_arrays_display_as_a_summary() {
+        let short = GgufValue::Array {
+            item_type: GgufValueType::String,
+            items: vec![GgufValue::String("a".into()), GgufValue::String("b".into())],
+        };
+        assert_eq!(short.to_string(), r#"["a", "b"]"#);
+
+        let long = GgufValue::Array {
+            item_type: GgufValueType::I32,
+            items: vec![GgufValue::I32(0); DISPLAY_MAX_ITEMS + 1],
+        };
+        assert_eq!(
+            long.to_string(),
+            format!("[{}; {}]", GgufValueType::I32.name(), DISPLAY_MAX_ITEMS + 1)
+        );
+    }
+}
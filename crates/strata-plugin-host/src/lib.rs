@@ -0,0 +1,229 @@
+//! Wire protocol between the app and `strata-plugin-host`, the process that
+//! loads a runtime plugin out of process so a native crash in the plugin
+//! (e.g. inside llama.cpp) only takes down the host.
+//!
+//! Transport: the app listens on a loopback TCP port and spawns the host
+//! with `--plugin <dylib> --connect <addr> [--log-level <n>]` and a one-time
+//! token in [`ENV_TOKEN`]. The host connects and sends a [`Hello`] carrying
+//! the token, then serves one [`Request`] → [`Response`] at a time. A socket
+//! is used rather than stdio so stray native output can't corrupt frames.
+//!
+//! Logging: the host installs itself as the plugin's log sink and writes each
+//! record to stderr as one JSON [`LogLine`] for the app to re-emit. Other
+//! stderr lines (llama.cpp before the sink is set, panics) are passed on as
+//! plain text.
+//!
+//! Framing: every message is a little-endian `u32` byte length followed by
+//! that many bytes of JSON. `Request::EvaluateMedia` is followed by one raw
+//! (non-JSON) frame per image.
+//!
+//! Requests mirror `strata_abi::ffi::LlmApi`/`MetadataApi` one to one. The
+//! host applies each call's error convention (status code, negated value,
+//! null result) and answers a failure with the plugin's [`StrataError`], so
+//! the client never has to ask for the error text separately.
+
+use std::io::{self, Read, Write};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use strata_abi::error::StrataError;
+
+/// Environment variable carrying the handshake token to the host.
+pub const ENV_TOKEN: &str = "STRATA_PLUGIN_HOST_TOKEN";
+
+/// Largest frame either side accepts (images are sent as raw frames).
+pub const MAX_FRAME: usize = 256 << 20;
+
+/// First message from the host.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hello {
+    /// Must equal the token passed in [`ENV_TOKEN`].
+    pub token: String,
+    /// The loaded plugin, or why it could not be loaded (the host then exits).
+    pub plugin: Result<PluginDesc, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginDesc {
+    pub abi_version: u32,
+    pub id: String,
+    pub semver: String,
+    /// `strata_abi::ffi::CAP_*` bits after negotiation.
+    pub capabilities: u64,
+}
+
+/// Host-side session handle (the plugin's session pointer never leaves the host).
+pub type SessionId = u64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Request {
+    // MetadataApi
+    CanHandle {
+        path: String,
+    },
+    CollectJson {
+        path: String,
+    },
+
+    // LlmApi
+    /// Replies `Session`.
+    CreateSession {
+        path: String,
+        params_json: String,
+    },
+    DestroySession {
+        session: SessionId,
+    },
+    Tokenize {
+        session: SessionId,
+        text: String,
+    },
+    Evaluate {
+        session: SessionId,
+        tokens: Vec<i32>,
+        n_past: i32,
+    },
+    SampleJson {
+        session: SessionId,
+        sampling_json: String,
+    },
+    DecodeToken {
+        session: SessionId,
+        token: i32,
+    },
+    Detokenize {
+        session: SessionId,
+        tokens: Vec<i32>,
+        remove_special: bool,
+        unparse_special: bool,
+    },
+    FormatChatJson {
+        session: SessionId,
+        turns_json: String,
+        add_assistant: bool,
+    },
+    ClearKvCache {
+        session: SessionId,
+    },
+    KvLenHint {
+        session: SessionId,
+    },
+    ContextWindowHint {
+        session: SessionId,
+    },
+    StopStringsJson {
+        session: SessionId,
+    },
+    PromptFlavor {
+        session: SessionId,
+    },
+    LoadProjector {
+        session: SessionId,
+        path: String,
+    },
+    MediaMarker {
+        session: SessionId,
+    },
+    /// Followed by `n_images` raw frames.
+    EvaluateMedia {
+        session: SessionId,
+        prompt: String,
+        n_images: usize,
+        n_past: i32,
+    },
+    LoadLora {
+        session: SessionId,
+        path: String,
+    },
+    SetLora {
+        session: SessionId,
+        id: i32,
+        scale: f32,
+    },
+    RemoveLora {
+        session: SessionId,
+        id: i32,
+    },
+    LoraListJson {
+        session: SessionId,
+    },
+    SetControlVectorsJson {
+        session: SessionId,
+        vectors_json: String,
+    },
+    MemoryReportJson {
+        session: SessionId,
+    },
+    TakePerfJson {
+        session: SessionId,
+    },
+
+    /// Destroy all sessions and exit.
+    Shutdown,
+}
+
+/// Result of the mirrored call: `Unit` for calls returning a status code,
+/// `Int` for ids, n_past and hints, `Text` for plugin strings, `Ints` for
+/// token arrays.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reply {
+    Unit,
+    Bool(bool),
+    Int(i32),
+    Text(String),
+    Ints(Vec<i32>),
+    Session(SessionId),
+}
+
+/// A plugin log record (`strata_abi::ffi::LogFn`) as written to stderr.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogLine {
+    /// `strata_abi::ffi::LOG_*`.
+    pub level: i32,
+    pub target: String,
+    pub message: String,
+}
+
+/// `Err` is the plugin's error for a failed call, or a protocol-level failure
+/// (unknown session, bad request) as `ErrorCode::Failed`.
+pub type Response = Result<Reply, StrataError>;
+
+pub fn write_frame<W: Write>(w: &mut W, payload: &[u8]) -> io::Result<()> {
+    if payload.len() > MAX_FRAME {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("frame of {} bytes exceeds {MAX_FRAME}", payload.len()),
+        ));
+    }
+    w.write_all(&(payload.len() as u32).to_le_bytes())?;
+    w.write_all(payload)
+}
+
+pub fn read_frame<R: Read>(r: &mut R) -> io::Result<Vec<u8>> {
+    let mut len = [0u8; 4];
+    r.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_FRAME {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {len} bytes exceeds {MAX_FRAME}"),
+        ));
+    }
+    let mut buf = vec![0u8; len];
+    r.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+/// Write `msg` as one JSON frame and flush.
+pub fn send<W: Write, T: Serialize>(w: &mut W, msg: &T) -> io::Result<()> {
+    let bytes = serde_json::to_vec(msg).map_err(io::Error::other)?;
+    write_frame(w, &bytes)?;
+    w.flush()
+}
+
+/// Read one JSON frame as `T`.
+pub fn recv<R: Read, T: DeserializeOwned>(r: &mut R) -> io::Result<T> {
+    let bytes = read_frame(r)?;
+    serde_json::from_slice(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
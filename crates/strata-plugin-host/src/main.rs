@@ -0,0 +1,449 @@
+//! `strata-plugin-host --plugin <dylib> --connect <addr> [--log-level <n>]`
+//!
+//! Loads one runtime plugin and serves its C API over a loopback socket
+//! (protocol in the library crate). Plugin log records at or below
+//! `--log-level` (`LOG_*`, default `LOG_INFO`) go to stderr as JSON lines.
+//! Exits when the app disconnects or sends `Shutdown`.
+
+use std::collections::HashMap;
+use std::ffi::{CStr, CString, c_char, c_void};
+use std::io::{BufReader, BufWriter};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use libloading::Library;
+use strata_abi::error::{ErrorCode, StrataError};
+use strata_abi::ffi::{
+    ByteSlice, CAP_CONTROL_VECTORS, CAP_LORA, CAP_MEMORY_REPORT, CAP_SESSION_PARAMS, CAP_VISION,
+    ERR_FAIL, ERR_OK, FreeStringFn, LOG_INFO, PLUGIN_ENTRY_SYMBOL, PluginApi, PluginEntryFn,
+    StrataString, negotiate,
+};
+use strata_plugin_host::{
+    ENV_TOKEN, Hello, LogLine, PluginDesc, Reply, Request, Response, SessionId, read_frame, recv,
+    send,
+};
+
+struct Args {
+    plugin: PathBuf,
+    connect: String,
+    log_level: i32,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut plugin = None;
+    let mut connect = None;
+    let mut log_level = LOG_INFO;
+    let mut it = std::env::args().skip(1);
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--plugin" => plugin = it.next().map(PathBuf::from),
+            "--connect" => connect = it.next(),
+            "--log-level" => {
+                log_level = it
+                    .next()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or("--log-level needs a LOG_* number")?
+            }
+            other => return Err(format!("unknown argument {other:?}")),
+        }
+    }
+    Ok(Args {
+        plugin: plugin.ok_or("missing --plugin <path>")?,
+        connect: connect.ok_or("missing --connect <addr>")?,
+        log_level,
+    })
+}
+
+/// `LogFn` for the plugin: one `LogLine` per record on stderr.
+unsafe extern "C" fn log_to_stderr(
+    _user: *mut c_void,
+    level: i32,
+    target: *const c_char,
+    message: *const c_char,
+) {
+    if target.is_null() || message.is_null() {
+        return;
+    }
+    let line = LogLine {
+        level,
+        target: unsafe { CStr::from_ptr(target) }
+            .to_string_lossy()
+            .into_owned(),
+        message: unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned(),
+    };
+    if let Ok(json) = serde_json::to_string(&line) {
+        eprintln!("{json}");
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("[plugin-host] {e}");
+            return ExitCode::from(2);
+        }
+    };
+    let token = std::env::var(ENV_TOKEN).unwrap_or_default();
+
+    let stream = match TcpStream::connect(&args.connect) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("[plugin-host] connect {}: {e}", args.connect);
+            return ExitCode::FAILURE;
+        }
+    };
+    let _ = stream.set_nodelay(true);
+    let mut reader = BufReader::new(match stream.try_clone() {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("[plugin-host] clone socket: {e}");
+            return ExitCode::FAILURE;
+        }
+    });
+    let mut writer = BufWriter::new(stream);
+
+    let loaded = load_plugin(&args.plugin);
+    if let Ok((plugin, _)) = &loaded
+        && let Some(set_logger) = plugin.api.llm.set_logger
+    {
+        unsafe { set_logger(Some(log_to_stderr), std::ptr::null_mut(), args.log_level) };
+    }
+    let hello = Hello {
+        token,
+        plugin: loaded
+            .as_ref()
+            .map(|(_, desc)| desc.clone())
+            .map_err(Clone::clone),
+    };
+    if let Err(e) = send(&mut writer, &hello) {
+        eprintln!("[plugin-host] handshake: {e}");
+        return ExitCode::FAILURE;
+    }
+    let Ok((plugin, _)) = loaded else {
+        return ExitCode::FAILURE;
+    };
+
+    let mut host = Host {
+        api: plugin.api,
+        sessions: HashMap::new(),
+        next_id: 1,
+    };
+    // A read error means the app went away: clean up and exit.
+    while let Ok(req) = recv::<_, Request>(&mut reader) {
+        if matches!(req, Request::Shutdown) {
+            break;
+        }
+        let images = match &req {
+            Request::EvaluateMedia { n_images, .. } => {
+                match (0..*n_images).map(|_| read_frame(&mut reader)).collect() {
+                    Ok(v) => v,
+                    Err(_) => break,
+                }
+            }
+            _ => Vec::new(),
+        };
+        let resp = host.handle(req, &images);
+        if send(&mut writer, &resp).is_err() {
+            break;
+        }
+    }
+    host.destroy_all();
+    drop(plugin);
+    ExitCode::SUCCESS
+}
+
+struct LoadedPlugin {
+    _lib: Library,
+    /// Negotiated tables; valid while `_lib` stays loaded, i.e. until `main` returns.
+    api: PluginApi,
+}
+
+fn load_plugin(path: &Path) -> Result<(LoadedPlugin, PluginDesc), String> {
+    let lib = unsafe { Library::new(path) }.map_err(|e| format!("load {}: {e}", path.display()))?;
+    let entry: libloading::Symbol<PluginEntryFn> =
+        unsafe { lib.get(PLUGIN_ENTRY_SYMBOL.as_bytes()) }
+            .map_err(|e| format!("missing symbol {PLUGIN_ENTRY_SYMBOL}: {e}"))?;
+    let api = unsafe { negotiate(entry()) }?;
+    let c_str = |p: *const c_char| {
+        if p.is_null() {
+            String::new()
+        } else {
+            unsafe { CStr::from_ptr(p) }.to_string_lossy().into_owned()
+        }
+    };
+    let desc = PluginDesc {
+        abi_version: api.info.abi_version,
+        id: c_str(api.info.id),
+        semver: c_str(api.info.semver),
+        capabilities: api.info.capabilities,
+    };
+    Ok((LoadedPlugin { _lib: lib, api }, desc))
+}
+
+struct Host {
+    api: PluginApi,
+    sessions: HashMap<SessionId, *mut c_void>,
+    next_id: SessionId,
+}
+
+fn cstring(s: &str) -> Result<CString, StrataError> {
+    CString::new(s)
+        .map_err(|_| StrataError::new(ErrorCode::InvalidArg, "string contains interior NUL"))
+}
+
+impl Host {
+    fn session(&self, id: SessionId) -> Result<*mut c_void, String> {
+        self.sessions
+            .get(&id)
+            .copied()
+            .ok_or_else(|| format!("unknown session {id}"))
+    }
+
+    /// Copy a plugin string out (null reads as empty) and free it.
+    fn take(&self, s: StrataString, free: FreeStringFn) -> String {
+        if s.ptr.is_null() {
+            return String::new();
+        }
+        let bytes = unsafe { std::slice::from_raw_parts(s.ptr as *const u8, s.len) };
+        let out = String::from_utf8_lossy(bytes).into_owned();
+        unsafe { free(s) };
+        out
+    }
+
+    fn text(&self, s: StrataString) -> Reply {
+        Reply::Text(self.take(s, self.api.llm.free_string))
+    }
+
+    /// `Text`, or the plugin's error for `what` if it returned null.
+    fn text_or_error(&self, session: *mut c_void, s: StrataString, what: &str) -> Response {
+        if s.ptr.is_null() {
+            return Err(unsafe { self.api.error(session, ERR_FAIL, what) });
+        }
+        Ok(self.text(s))
+    }
+
+    /// `Unit`, or the plugin's error for a status call that returned `rc`.
+    fn status(&self, session: *mut c_void, rc: i32, what: &str) -> Response {
+        if rc == ERR_OK {
+            Ok(Reply::Unit)
+        } else {
+            Err(unsafe { self.api.error(session, rc, what) })
+        }
+    }
+
+    /// `Int`, or the plugin's error for a value call that returned `v < 0`.
+    fn value(&self, session: *mut c_void, v: i32, what: &str) -> Response {
+        if v >= 0 {
+            Ok(Reply::Int(v))
+        } else {
+            Err(unsafe { self.api.error(session, v, what) })
+        }
+    }
+
+    fn handle(&mut self, req: Request, images: &[Vec<u8>]) -> Response {
+        let llm = &self.api.llm;
+        let md = &self.api.metadata;
+        Ok(match req {
+            Request::CanHandle { path } => {
+                Reply::Bool(unsafe { (md.can_handle)(cstring(&path)?.as_ptr()) })
+            }
+            Request::CollectJson { path } => {
+                let s = unsafe { (md.collect_json)(cstring(&path)?.as_ptr()) };
+                Reply::Text(self.take(s, md.free_string))
+            }
+            Request::CreateSession { path, params_json } => {
+                let (cpath, cparams) = (cstring(&path)?, cstring(&params_json)?);
+                let ptr = if self.api.supports(CAP_SESSION_PARAMS) {
+                    unsafe { (llm.create_session_with_params)(cpath.as_ptr(), cparams.as_ptr()) }
+                } else {
+                    unsafe { (llm.create_session)(cpath.as_ptr()) }
+                };
+                if ptr.is_null() {
+                    return Err(unsafe {
+                        self.api
+                            .error(std::ptr::null_mut(), ERR_FAIL, "create_session")
+                    });
+                }
+                let id = self.next_id;
+                self.next_id += 1;
+                self.sessions.insert(id, ptr);
+                Reply::Session(id)
+            }
+            Request::DestroySession { session } => {
+                if let Some(ptr) = self.sessions.remove(&session) {
+                    unsafe { (llm.destroy_session)(ptr) };
+                }
+                Reply::Unit
+            }
+            Request::Tokenize { session, text } => {
+                let s = self.session(session)?;
+                let arr = unsafe { (llm.tokenize_utf8)(s, cstring(&text)?.as_ptr()) };
+                if arr.ptr.is_null() {
+                    return Err(unsafe { self.api.error(s, ERR_FAIL, "tokenize") });
+                }
+                let v = unsafe { std::slice::from_raw_parts(arr.ptr, arr.len) }.to_vec();
+                unsafe { (llm.free_ints)(arr) };
+                Reply::Ints(v)
+            }
+            Request::Evaluate {
+                session,
+                tokens,
+                n_past,
+            } => {
+                let s = self.session(session)?;
+                let rc = unsafe { (llm.evaluate)(s, tokens.as_ptr(), tokens.len(), n_past) };
+                self.status(s, rc, "evaluate")?
+            }
+            Request::SampleJson {
+                session,
+                sampling_json,
+            } => {
+                let s = self.session(session)?;
+                let tok = unsafe { (llm.sample_json)(s, cstring(&sampling_json)?.as_ptr()) };
+                self.value(s, tok, "sample")?
+            }
+            Request::DecodeToken { session, token } => {
+                let s = self.session(session)?;
+                self.text_or_error(s, unsafe { (llm.decode_token)(s, token) }, "decode_token")?
+            }
+            Request::Detokenize {
+                session,
+                tokens,
+                remove_special,
+                unparse_special,
+            } => {
+                let s = self.session(session)?;
+                let text = unsafe {
+                    (llm.detokenize_utf8)(
+                        s,
+                        tokens.as_ptr(),
+                        tokens.len(),
+                        remove_special,
+                        unparse_special,
+                    )
+                };
+                self.text_or_error(s, text, "detokenize")?
+            }
+            Request::FormatChatJson {
+                session,
+                turns_json,
+                add_assistant,
+            } => {
+                let s = self.session(session)?;
+                let text = unsafe {
+                    (llm.format_chat_json)(s, cstring(&turns_json)?.as_ptr(), add_assistant)
+                };
+                self.text_or_error(s, text, "format_chat_json")?
+            }
+            Request::ClearKvCache { session } => {
+                unsafe { (llm.clear_kv_cache)(self.session(session)?) };
+                Reply::Unit
+            }
+            Request::KvLenHint { session } => {
+                Reply::Int(unsafe { (llm.kv_len_hint)(self.session(session)?) })
+            }
+            Request::ContextWindowHint { session } => {
+                Reply::Int(unsafe { (llm.context_window_hint)(self.session(session)?) })
+            }
+            Request::StopStringsJson { session } => {
+                self.text(unsafe { (llm.stop_strings_json)(self.session(session)?) })
+            }
+            Request::PromptFlavor { session } => {
+                self.text(unsafe { (llm.prompt_flavor)(self.session(session)?) })
+            }
+            Request::LoadProjector { session, path } => {
+                self.api.require(CAP_VISION, "image input")?;
+                let s = self.session(session)?;
+                let rc = unsafe { (llm.load_projector)(s, cstring(&path)?.as_ptr()) };
+                self.status(s, rc, "load_projector")?
+            }
+            Request::MediaMarker { session } => {
+                self.api.require(CAP_VISION, "image input")?;
+                self.text(unsafe { (llm.media_marker)(self.session(session)?) })
+            }
+            Request::EvaluateMedia {
+                session,
+                prompt,
+                n_images: _,
+                n_past,
+            } => {
+                self.api.require(CAP_VISION, "image input")?;
+                let s = self.session(session)?;
+                let slices: Vec<ByteSlice> = images
+                    .iter()
+                    .map(|img| ByteSlice {
+                        ptr: img.as_ptr(),
+                        len: img.len(),
+                    })
+                    .collect();
+                let n = unsafe {
+                    (llm.evaluate_media)(
+                        s,
+                        cstring(&prompt)?.as_ptr(),
+                        slices.as_ptr(),
+                        slices.len(),
+                        n_past,
+                    )
+                };
+                self.value(s, n, "evaluate_media")?
+            }
+            Request::LoadLora { session, path } => {
+                self.api.require(CAP_LORA, "LoRA adapters")?;
+                let s = self.session(session)?;
+                let id = unsafe { (llm.load_lora)(s, cstring(&path)?.as_ptr()) };
+                self.value(s, id, "load_lora")?
+            }
+            Request::SetLora { session, id, scale } => {
+                self.api.require(CAP_LORA, "LoRA adapters")?;
+                let s = self.session(session)?;
+                self.status(s, unsafe { (llm.set_lora)(s, id, scale) }, "set_lora")?
+            }
+            Request::RemoveLora { session, id } => {
+                self.api.require(CAP_LORA, "LoRA adapters")?;
+                let s = self.session(session)?;
+                self.status(s, unsafe { (llm.remove_lora)(s, id) }, "remove_lora")?
+            }
+            Request::LoraListJson { session } => {
+                self.api.require(CAP_LORA, "LoRA adapters")?;
+                self.text(unsafe { (llm.lora_list_json)(self.session(session)?) })
+            }
+            Request::SetControlVectorsJson {
+                session,
+                vectors_json,
+            } => {
+                if !self.api.supports(CAP_CONTROL_VECTORS) && vectors_json == "[]" {
+                    return Ok(Reply::Unit);
+                }
+                self.api.require(CAP_CONTROL_VECTORS, "control vectors")?;
+                let s = self.session(session)?;
+                let rc =
+                    unsafe { (llm.set_control_vectors_json)(s, cstring(&vectors_json)?.as_ptr()) };
+                self.status(s, rc, "set_control_vectors")?
+            }
+            Request::MemoryReportJson { session } => {
+                self.api.require(CAP_MEMORY_REPORT, "memory reports")?;
+                self.text(unsafe { (llm.memory_report_json)(self.session(session)?) })
+            }
+            Request::TakePerfJson { session } => {
+                let take = llm.take_perf_json.ok_or_else(|| {
+                    StrataError::new(
+                        ErrorCode::Unsupported,
+                        "this runtime plugin does not report performance counters",
+                    )
+                })?;
+                self.text(unsafe { take(self.session(session)?) })
+            }
+            Request::Shutdown => Reply::Unit,
+        })
+    }
+
+    fn destroy_all(&mut self) {
+        for (_, ptr) in self.sessions.drain() {
+            unsafe { (self.api.llm.destroy_session)(ptr) };
+        }
+    }
+}